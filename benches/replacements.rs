@@ -0,0 +1,52 @@
+//! Compares the single-pass replacement engine with applying one regex per
+//! rule. Run with `cargo bench --bench replacements`.
+
+use regex::Regex;
+use std::time::{Duration, Instant};
+use tjvox::replacements::ReplacementEngine;
+
+const RULES: usize = 500;
+const ITERATIONS: u32 = 200;
+
+fn time<F: FnMut() -> String>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        std::hint::black_box(f());
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let pairs: Vec<(String, String)> = (0..RULES)
+        .map(|i| (format!("phrase number {}", i), format!("<{}>", i)))
+        .collect();
+    let text = "so this is a dictated paragraph with phrase number 42 and \
+                phrase number 317 plus some ordinary words period "
+        .repeat(20);
+
+    let sequential: Vec<(Regex, String)> = pairs
+        .iter()
+        .map(|(k, v)| {
+            let re = Regex::new(&format!(r"(?i)\b{}\b", regex::escape(k))).unwrap();
+            (re, v.clone())
+        })
+        .collect();
+    let engine = ReplacementEngine::from_pairs(pairs);
+
+    let per_rule = time(|| {
+        let mut result = text.clone();
+        for (re, replacement) in &sequential {
+            result = re.replace_all(&result, replacement.as_str()).to_string();
+        }
+        result
+    });
+    let single_pass = time(|| engine.apply(&text));
+
+    println!("{} rules, {} chars of text", RULES, text.len());
+    println!("  one regex per rule: {:>10.1?}", per_rule);
+    println!("  single pass:        {:>10.1?}", single_pass);
+    println!(
+        "  speedup:            {:>10.1}x",
+        per_rule.as_secs_f64() / single_pass.as_secs_f64()
+    );
+}
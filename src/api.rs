@@ -0,0 +1,338 @@
+//! Localhost HTTP control API (`api` build feature). Each request is
+//! forwarded to the daemon's Unix socket, so it runs through the same
+//! handlers as `tjvox toggle`, `tjvox status` and `tjvox history`.
+//!
+//! - `POST /toggle`, `/push-start`, `/push-stop`: control recording
+//! - `GET` or `POST /status`: current state and last error
+//! - `GET /history?limit=N`: recent history entries as JSON
+
+#[cfg(any(feature = "api", test))]
+use serde_json::json;
+
+/// Entries returned by `/history` without a `limit`
+#[cfg(any(feature = "api", test))]
+const DEFAULT_HISTORY_LIMIT: u32 = 20;
+
+/// Host names a request without `token` may address. Checking them stops a
+/// DNS-rebinding page, which reaches localhost under its own name and sends
+/// no Origin on same-origin requests.
+#[cfg(any(feature = "api", test))]
+const LOOPBACK_HOSTS: [&str; 3] = ["127.0.0.1", "localhost", "[::1]"];
+
+/// The parts of an HTTP request the API looks at.
+#[cfg(any(feature = "api", test))]
+#[derive(Debug, PartialEq, Eq)]
+struct Request {
+    method: String,
+    path: String,
+    query: Option<String>,
+    host: Option<String>,
+    authorization: Option<String>,
+    /// Sent by browsers, which any web page can make post to localhost
+    has_origin: bool,
+}
+
+/// How a request is answered.
+#[cfg(any(feature = "api", test))]
+#[derive(Debug, PartialEq, Eq)]
+enum Route {
+    /// Forward this command to the daemon socket
+    Command(String),
+    /// Answer directly with a status code and message
+    Reply(u16, String),
+}
+
+/// Serve the API on `listen` until the task is aborted. With `token` set,
+/// every request needs an `Authorization: Bearer <token>` header; without
+/// it, only requests addressed to a loopback name are answered.
+#[cfg(feature = "api")]
+pub async fn serve(
+    listen: &str,
+    token: Option<String>,
+) -> anyhow::Result<tokio::task::JoinHandle<()>> {
+    use anyhow::Context;
+    use tracing::info;
+
+    let listener = tokio::net::TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to bind HTTP API to {}", listen))?;
+    let port = listener.local_addr()?.port();
+    info!("Serving HTTP API at http://{}", listen);
+
+    Ok(crate::http::serve(listener, "HTTP API", move |raw| {
+        let token = token.clone();
+        async move {
+            let cors = token.is_some();
+            let (status, body) = match parse_request(&raw) {
+                Some(request) => match route(&request, token.as_deref(), port) {
+                    Route::Command(cmd) => match crate::socket::send_command(&cmd).await {
+                        Ok(reply) => reply_body(&reply),
+                        Err(e) => (503, error_body(&format!("{:#}", e))),
+                    },
+                    Route::Reply(status, message) => (status, error_body(&message)),
+                },
+                None => (400, error_body("bad request")),
+            };
+            response(status, &body, cors)
+        }
+    }))
+}
+
+/// Request line and relevant headers of a raw request, `None` if malformed.
+#[cfg(any(feature = "api", test))]
+fn parse_request(raw: &[u8]) -> Option<Request> {
+    let text = String::from_utf8_lossy(raw);
+    let mut lines = text.split("\r\n");
+    let mut parts = lines.next()?.split_whitespace();
+    let (method, target) = (parts.next()?, parts.next()?);
+    parts.next().filter(|version| version.starts_with("HTTP/"))?;
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, Some(query.to_string())),
+        None => (target, None),
+    };
+
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query,
+        host: None,
+        authorization: None,
+        has_origin: false,
+    };
+    for line in lines.take_while(|line| !line.is_empty()) {
+        let (name, value) = line.split_once(':')?;
+        if name.eq_ignore_ascii_case("host") {
+            request.host = Some(value.trim().to_string());
+        } else if name.eq_ignore_ascii_case("authorization") {
+            request.authorization = Some(value.trim().to_string());
+        } else if name.eq_ignore_ascii_case("origin") {
+            request.has_origin = true;
+        }
+    }
+    Some(request)
+}
+
+/// Decide how to answer `request` to the API on `port`, checking `token`
+/// or, without one, the Host header first.
+#[cfg(any(feature = "api", test))]
+fn route(request: &Request, token: Option<&str>, port: u16) -> Route {
+    match token {
+        // CORS preflight, so dashboards on other origins can send the token
+        Some(_) if request.method == "OPTIONS" => return Route::Reply(204, String::new()),
+        Some(token) => {
+            let bearer = request
+                .authorization
+                .as_deref()
+                .and_then(|value| value.strip_prefix("Bearer "));
+            if bearer.map(str::trim) != Some(token) {
+                return Route::Reply(401, "missing or wrong token".to_string());
+            }
+        }
+        // Without a token any web page could post to localhost
+        None if request.has_origin => {
+            return Route::Reply(403, "set api.token to call the API from a browser".to_string());
+        }
+        None if !is_loopback_host(request.host.as_deref(), port) => {
+            return Route::Reply(403, "requests must be addressed to localhost".to_string());
+        }
+        None => {}
+    }
+
+    let method = request.method.as_str();
+    match request.path.as_str() {
+        path @ ("/toggle" | "/push-start" | "/push-stop") if method == "POST" => {
+            Route::Command(path[1..].to_string())
+        }
+        "/status" if method == "GET" || method == "POST" => {
+            Route::Command("status-json".to_string())
+        }
+        "/history" if method == "GET" => {
+            let limit = request
+                .query
+                .iter()
+                .flat_map(|query| query.split('&'))
+                .find_map(|pair| pair.strip_prefix("limit="));
+            match limit.map(str::parse::<u32>) {
+                None => Route::Command(format!("history {}", DEFAULT_HISTORY_LIMIT)),
+                Some(Ok(limit)) => Route::Command(format!("history {}", limit)),
+                Some(Err(_)) => Route::Reply(400, "limit must be a number".to_string()),
+            }
+        }
+        "/toggle" | "/push-start" | "/push-stop" | "/status" | "/history" => {
+            Route::Reply(405, "method not allowed".to_string())
+        }
+        _ => Route::Reply(404, "not found".to_string()),
+    }
+}
+
+/// Whether `host` is a loopback name with the API's port.
+#[cfg(any(feature = "api", test))]
+fn is_loopback_host(host: Option<&str>, port: u16) -> bool {
+    let Some((name, host_port)) = host.and_then(|host| host.rsplit_once(':')) else {
+        return false;
+    };
+    host_port.parse::<u16>().ok() == Some(port) && LOOPBACK_HOSTS.iter().any(|h| name.eq_ignore_ascii_case(h))
+}
+
+/// Status and JSON body for a socket reply: `ok: <state>` becomes
+/// `{"status": ...}`, `error: ...` a 409, and status or history JSON passes
+/// through.
+#[cfg(any(feature = "api", test))]
+fn reply_body(reply: &str) -> (u16, String) {
+    if let Some(status) = reply.strip_prefix("ok: ") {
+        (200, json!({ "status": status }).to_string())
+    } else if let Some(message) = reply.strip_prefix("error: ") {
+        (409, error_body(message))
+    } else {
+        (200, reply.to_string())
+    }
+}
+
+#[cfg(any(feature = "api", test))]
+fn error_body(message: &str) -> String {
+    if message.is_empty() {
+        return String::new();
+    }
+    json!({ "error": message }).to_string()
+}
+
+#[cfg(any(feature = "api", test))]
+fn response(status: u16, body: &str, cors: bool) -> String {
+    let reason = match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        _ => "Service Unavailable",
+    };
+    let cors = if cors {
+        "Access-Control-Allow-Origin: *\r\n\
+         Access-Control-Allow-Methods: GET, POST\r\n\
+         Access-Control-Allow-Headers: Authorization\r\n"
+    } else {
+        ""
+    };
+    format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        cors,
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PORT: u16 = 9465;
+
+    fn request(raw: &str) -> Request {
+        parse_request(raw.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_api_parse_request() {
+        let req = request(
+            "GET /history?limit=5 HTTP/1.1\r\nHost: localhost\r\nauthorization: Bearer abc\r\n\r\n",
+        );
+        assert_eq!(req.method, "GET");
+        assert_eq!(req.path, "/history");
+        assert_eq!(req.query.as_deref(), Some("limit=5"));
+        assert_eq!(req.host.as_deref(), Some("localhost"));
+        assert_eq!(req.authorization.as_deref(), Some("Bearer abc"));
+        assert!(!req.has_origin);
+
+        assert!(parse_request(b"").is_none());
+        assert!(parse_request(b"GET /status\r\n\r\n").is_none());
+        assert!(parse_request(b"GET /status HTTP/1.1\r\nbroken header\r\n\r\n").is_none());
+    }
+
+    #[test]
+    fn test_api_routes() {
+        let command = |cmd: &str| Route::Command(cmd.to_string());
+        let status = |raw: &str| match route(&request(raw), None, PORT) {
+            Route::Reply(status, _) => status,
+            Route::Command(cmd) => panic!("unexpected command {}", cmd),
+        };
+
+        assert_eq!(route(&request("POST /toggle HTTP/1.1\r\nHost: 127.0.0.1:9465\r\n\r\n"), None, PORT), command("toggle"));
+        assert_eq!(
+            route(&request("POST /push-stop HTTP/1.1\r\nHost: 127.0.0.1:9465\r\n\r\n"), None, PORT),
+            command("push-stop")
+        );
+        assert_eq!(
+            route(&request("GET /status HTTP/1.1\r\nHost: 127.0.0.1:9465\r\n\r\n"), None, PORT),
+            command("status-json")
+        );
+        assert_eq!(route(&request("GET /history HTTP/1.1\r\nHost: 127.0.0.1:9465\r\n\r\n"), None, PORT), command("history 20"));
+        assert_eq!(
+            route(&request("GET /history?x=1&limit=3 HTTP/1.1\r\nHost: 127.0.0.1:9465\r\n\r\n"), None, PORT),
+            command("history 3")
+        );
+        assert_eq!(status("GET /history?limit=all HTTP/1.1\r\nHost: 127.0.0.1:9465\r\n\r\n"), 400);
+        assert_eq!(status("GET /toggle HTTP/1.1\r\nHost: 127.0.0.1:9465\r\n\r\n"), 405);
+        assert_eq!(status("POST /quit HTTP/1.1\r\nHost: 127.0.0.1:9465\r\n\r\n"), 404);
+        assert_eq!(
+            status("POST /toggle HTTP/1.1\r\nHost: 127.0.0.1:9465\r\nOrigin: https://example.com\r\n\r\n"),
+            403
+        );
+    }
+
+    #[test]
+    fn test_api_host() {
+        let toggle = |host: &str| {
+            route(&request(&format!("POST /toggle HTTP/1.1\r\nHost: {}\r\n\r\n", host)), None, PORT)
+        };
+        for host in ["127.0.0.1:9465", "localhost:9465", "LocalHost:9465", "[::1]:9465"] {
+            assert_eq!(toggle(host), Route::Command("toggle".to_string()), "{}", host);
+        }
+        // A rebound DNS name, another port, or no Host at all
+        for host in ["evil.example:9465", "127.0.0.1:80", "localhost", "127.0.0.1.evil.example:9465"] {
+            assert!(matches!(toggle(host), Route::Reply(403, _)), "{}", host);
+        }
+        let no_host = request("GET /history HTTP/1.1\r\n\r\n");
+        assert!(matches!(route(&no_host, None, PORT), Route::Reply(403, _)));
+
+        // The token protects other names, e.g. behind a reverse proxy
+        let proxied = request("POST /toggle HTTP/1.1\r\nHost: vox.lan\r\nAuthorization: Bearer s3cret\r\n\r\n");
+        assert_eq!(route(&proxied, Some("s3cret"), PORT), Route::Command("toggle".to_string()));
+    }
+
+    #[test]
+    fn test_api_token() {
+        let token = Some("s3cret");
+        let authorized = request("POST /toggle HTTP/1.1\r\nAuthorization: Bearer s3cret\r\n\r\n");
+        assert_eq!(route(&authorized, token, PORT), Route::Command("toggle".to_string()));
+
+        let wrong = request("POST /toggle HTTP/1.1\r\nAuthorization: Bearer nope\r\n\r\n");
+        assert!(matches!(route(&wrong, token, PORT), Route::Reply(401, _)));
+        let missing = request("POST /toggle HTTP/1.1\r\n\r\n");
+        assert!(matches!(route(&missing, token, PORT), Route::Reply(401, _)));
+
+        // Browsers may call the API once a token is required
+        let preflight = request("OPTIONS /toggle HTTP/1.1\r\nOrigin: https://example.com\r\n\r\n");
+        assert_eq!(route(&preflight, token, PORT), Route::Reply(204, String::new()));
+    }
+
+    #[test]
+    fn test_api_reply_body() {
+        assert_eq!(reply_body("ok: recording"), (200, r#"{"status":"recording"}"#.to_string()));
+        assert_eq!(
+            reply_body("error: history is disabled"),
+            (409, r#"{"error":"history is disabled"}"#.to_string())
+        );
+        assert_eq!(reply_body("[]"), (200, "[]".to_string()));
+
+        let ok = response(200, "[]", true);
+        assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(ok.contains("Access-Control-Allow-Origin: *\r\n"));
+        assert!(ok.ends_with("\r\n\r\n[]"));
+        assert!(!response(404, "", false).contains("Access-Control"));
+    }
+}
@@ -1,21 +1,190 @@
 use anyhow::Result;
 use hound::WavWriter;
 use std::io::BufWriter;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use tracing::{error, info};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use crate::config::AudioConfig;
+use crate::config::{AudioConfig, MonoMode, ResampleQuality};
+
+/// Upper bound on how long `start` waits for the PipeWire stream to reach the
+/// streaming state before carrying on anyway.
+const STREAM_READY_TIMEOUT_MS: u64 = 2000;
+
+/// How often the capture thread checks whether it should stop and whether
+/// audio is still arriving
+const CAPTURE_WATCHDOG_INTERVAL_MS: u64 = 100;
+
+/// A streaming capture that delivers no audio for this long has lost its
+/// source, e.g. because the microphone was unplugged
+const CAPTURE_STALL_MS: u64 = 2000;
+
+/// How long a capture that lost its source waits for one to reappear
+/// before the recording fails
+const RECONNECT_WAIT_MS: u64 = 3000;
+
+/// Quietest level shown on the mic-test meter; anything below reads as silence
+pub const METER_FLOOR_DBFS: f32 = -60.0;
+
+/// Samples at or above this magnitude count as clipped
+const CLIP_LEVEL: f32 = 0.999;
+
+/// Counts samples at full scale during a capture, to spot input gain set
+/// so high that the signal is distorted.
+#[derive(Default)]
+pub struct ClipCounter {
+    total: AtomicU64,
+    clipped: AtomicU64,
+}
+
+impl ClipCounter {
+    /// Count a buffer of captured samples, interleaved or not.
+    fn add(&self, samples: &[f32]) {
+        let clipped = samples.iter().filter(|s| s.abs() >= CLIP_LEVEL).count();
+        self.total.fetch_add(samples.len() as u64, Ordering::Relaxed);
+        self.clipped.fetch_add(clipped as u64, Ordering::Relaxed);
+    }
+
+    fn reset(&self) {
+        self.total.store(0, Ordering::Relaxed);
+        self.clipped.store(0, Ordering::Relaxed);
+    }
+
+    /// Fraction of samples that were clipped, 0 when nothing was captured.
+    pub fn fraction(&self) -> f32 {
+        let total = self.total.load(Ordering::Relaxed);
+        if total == 0 {
+            return 0.0;
+        }
+        self.clipped.load(Ordering::Relaxed) as f32 / total as f32
+    }
+}
+
+/// WAV writer shared with the capture thread; `None` while not recording
+type SharedWavWriter = Arc<Mutex<Option<WavWriter<BufWriter<std::fs::File>>>>>;
+
+/// Samples of an `audio.in_memory` recording, filled by the capture thread
+type SharedSamples = Arc<Mutex<Vec<f32>>>;
+
+/// A finished recording.
+#[derive(Debug, Clone)]
+pub enum RecordedAudio {
+    /// WAV file in `audio.temp_dir`
+    File(PathBuf),
+    /// Mono samples kept in memory with `audio.in_memory`
+    Memory {
+        samples: Arc<Vec<f32>>,
+        sample_rate: u32,
+    },
+}
+
+impl RecordedAudio {
+    /// Delete the recording's file; in-memory recordings have nothing to remove.
+    pub async fn remove(&self) {
+        if let RecordedAudio::File(path) = self {
+            tokio::fs::remove_file(path).await.ok();
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            RecordedAudio::File(path) => Some(path),
+            RecordedAudio::Memory { .. } => None,
+        }
+    }
+}
+
+impl std::fmt::Display for RecordedAudio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordedAudio::File(path) => write!(f, "{}", path.display()),
+            RecordedAudio::Memory { samples, sample_rate } => write!(
+                f,
+                "in-memory recording ({:.1}s)",
+                samples.len() as f64 / (*sample_rate).max(1) as f64
+            ),
+        }
+    }
+}
+
+/// Why the capture thread ended on its own while recording.
+#[derive(Default)]
+struct CaptureFailure {
+    reason: Mutex<Option<String>>,
+    notify: tokio::sync::Notify,
+}
+
+impl CaptureFailure {
+    fn set(&self, reason: String) {
+        if let Ok(mut guard) = self.reason.lock() {
+            *guard = Some(reason);
+        }
+        self.notify.notify_one();
+    }
+
+    fn take(&self) -> Option<String> {
+        self.reason.lock().ok()?.take()
+    }
+}
+
+/// The error `stop` returns when the capture ended before the recording
+/// did, with whatever was recorded up to that point.
+#[derive(Debug)]
+pub struct CaptureLost {
+    pub reason: String,
+    /// The audio captured before the failure, if there was any
+    pub partial: Option<RecordedAudio>,
+}
+
+impl std::fmt::Display for CaptureLost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Audio capture stopped before the recording ended: {}", self.reason)
+    }
+}
+
+impl std::error::Error for CaptureLost {}
+
+/// Mono samples from a sample sink and the rate they were captured at,
+/// which is the device's own rate in a recording with `audio.capture_native`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleChunk {
+    pub rate: u32,
+    pub samples: Vec<f32>,
+}
+
+impl SampleChunk {
+    /// The samples at `rate`, converted if they were captured at another.
+    /// Chunks arrive every few milliseconds, so the fast resampler is used.
+    pub fn into_rate(self, rate: u32) -> Vec<f32> {
+        if self.rate == rate {
+            return self.samples;
+        }
+        crate::transcription::resample(&self.samples, self.rate, rate, ResampleQuality::Fast)
+    }
+}
+
+/// How a capture whose stream got going came to an end.
+enum CaptureEnd {
+    /// `running` was cleared
+    Stopped,
+    /// The stream failed or stopped delivering audio while still running
+    Lost(String),
+}
 
 pub struct AudioRecorder {
     config: AudioConfig,
     recording_path: PathBuf,
     running: Arc<AtomicBool>,
     thread: Option<std::thread::JoinHandle<()>>,
-    writer: Arc<Mutex<Option<WavWriter<BufWriter<std::fs::File>>>>>,
+    failure: Arc<CaptureFailure>,
+    writer: SharedWavWriter,
+    memory: SharedSamples,
     amplitude_tx: Option<std::sync::mpsc::Sender<f32>>,
+    sample_tx: Option<std::sync::mpsc::Sender<SampleChunk>>,
+    clips: Arc<ClipCounter>,
 }
 
 impl AudioRecorder {
@@ -26,62 +195,204 @@ impl AudioRecorder {
         let recording_id = Uuid::new_v4().to_string();
         let recording_path =
             PathBuf::from(&config.temp_dir).join(format!("recording_{}.wav", recording_id));
-        std::fs::create_dir_all(&config.temp_dir)?;
+        if !config.in_memory {
+            std::fs::create_dir_all(&config.temp_dir)?;
+        }
 
         Ok(Self {
             config: config.clone(),
             recording_path,
             running: Arc::new(AtomicBool::new(false)),
             thread: None,
+            failure: Arc::new(CaptureFailure::default()),
             writer: Arc::new(Mutex::new(None)),
+            memory: Arc::new(Mutex::new(Vec::new())),
             amplitude_tx,
+            sample_tx: None,
+            clips: Arc::new(ClipCounter::default()),
         })
     }
 
-    pub async fn start(&mut self) -> Result<PathBuf> {
+    /// Also send the captured mono samples to `sample_tx` as they arrive,
+    /// tagged with their rate.
+    pub fn with_sample_sink(mut self, sample_tx: std::sync::mpsc::Sender<SampleChunk>) -> Self {
+        self.sample_tx = Some(sample_tx);
+        self
+    }
+
+    /// Start recording. Returns the WAV file being written, or `None` with
+    /// `audio.in_memory`.
+    pub async fn start(&mut self) -> Result<Option<PathBuf>> {
+        if self.config.in_memory {
+            info!("Starting in-memory audio recording");
+            self.memory
+                .lock()
+                .map_err(|_| anyhow::anyhow!("Sample buffer mutex poisoned"))?
+                .clear();
+            self.clips.reset();
+            let memory = self.memory.clone();
+            self.spawn_capture(None, Some(memory)).await?;
+            return Ok(None);
+        }
+
         info!(
             "Starting audio recording to: {}",
             self.recording_path.display()
         );
 
         let path = self.recording_path.clone();
-        let sample_rate = self.config.sample_rate;
-        let channels = self.config.channels as u32;
-
-        // Create WAV writer (F32 at requested sample rate)
-        let spec = hound::WavSpec {
-            channels: channels as u16,
-            sample_rate,
-            bits_per_sample: 32,
-            sample_format: hound::SampleFormat::Float,
+
+        // With capture_native the rate is only known once PipeWire has
+        // negotiated the format, so the capture thread creates the writer
+        let native_wav = if self.config.capture_native {
+            Some(path)
+        } else {
+            // Create WAV writer (mono at requested sample rate; multi-channel
+            // capture is mixed down in the capture thread)
+            let spec = wav_spec(self.config.sample_rate, self.config.bit_depth);
+            let wav_writer = WavWriter::create(&path, spec)?;
+            *self.writer.lock().map_err(|_| anyhow::anyhow!("Writer mutex poisoned"))? = Some(wav_writer);
+            None
         };
-        let wav_writer = WavWriter::create(&path, spec)?;
-        *self.writer.lock().map_err(|_| anyhow::anyhow!("Writer mutex poisoned"))? = Some(wav_writer);
 
+        self.clips.reset();
+        self.spawn_capture(native_wav, None).await?;
+        Ok(Some(self.recording_path.clone()))
+    }
+
+    /// Open the capture stream without writing a WAV file; only the
+    /// amplitude channel receives data. Used by `tjvox mic-test` and the
+    /// overlay meter.
+    pub async fn start_monitor(&mut self) -> Result<()> {
+        info!("Starting audio level monitor");
+        self.spawn_capture(None, None).await
+    }
+
+    /// Stop a capture started with `start_monitor`.
+    pub fn stop_monitor(&mut self) {
+        self.abort_capture();
+    }
+
+    /// Resolves if the capture fails while recording and could not
+    /// reconnect; `stop` then returns the reason.
+    pub fn capture_lost(&self) -> impl std::future::Future<Output = ()> + 'static {
+        let failure = self.failure.clone();
+        async move { failure.notify.notified().await }
+    }
+
+    /// Spawn the PipeWire capture thread and wait for the stream to start.
+    /// `native_wav` records to that path at whatever rate is negotiated,
+    /// `memory` collects the samples instead of writing a file. If the
+    /// stream is lost while running, the thread reconnects once when a
+    /// source comes back.
+    async fn spawn_capture(
+        &mut self,
+        native_wav: Option<PathBuf>,
+        memory: Option<SharedSamples>,
+    ) -> Result<()> {
+        let config = self.config.clone();
         self.running.store(true, Ordering::SeqCst);
         let running = self.running.clone();
         let writer = self.writer.clone();
         let amp_tx = self.amplitude_tx.clone();
+        let sample_tx = self.sample_tx.clone();
+        let clips = self.clips.clone();
+        let (ready_tx, ready_rx) = tokio::sync::oneshot::channel();
+        let failure = Arc::new(CaptureFailure::default());
+        self.failure = failure.clone();
 
         let thread = std::thread::spawn(move || {
-            if let Err(e) =
-                run_pipewire_capture(running, writer, amp_tx, sample_rate, channels)
-            {
-                error!("Audio capture error: {}", e);
+            let capture = |ready_tx| {
+                let wav = WavTarget {
+                    writer: writer.clone(),
+                    native_path: native_wav.clone(),
+                    memory: memory.clone(),
+                };
+                run_pipewire_capture(
+                    running.clone(),
+                    wav,
+                    amp_tx.clone(),
+                    sample_tx.clone(),
+                    clips.clone(),
+                    &config,
+                    ready_tx,
+                )
+            };
+            let reason = match capture(ready_tx) {
+                Ok(CaptureEnd::Stopped) => return,
+                // A native-rate recording cannot resume, the new stream
+                // could negotiate a different rate
+                Ok(CaptureEnd::Lost(reason))
+                    if native_wav.is_none() && wait_for_source(&config, &running) =>
+                {
+                    warn!("Audio capture lost ({}), reconnecting", reason);
+                    let (ready_tx, _) = tokio::sync::oneshot::channel();
+                    match capture(ready_tx) {
+                        Ok(CaptureEnd::Stopped) => return,
+                        Ok(CaptureEnd::Lost(reason)) => reason,
+                        Err(e) => e.to_string(),
+                    }
+                }
+                Ok(CaptureEnd::Lost(reason)) => reason,
+                Err(e) => {
+                    error!("Audio capture error: {}", e);
+                    e.to_string()
+                }
+            };
+            if running.load(Ordering::SeqCst) {
+                error!("Audio capture stopped while recording: {}", reason);
+                failure.set(reason);
             }
         });
 
         self.thread = Some(thread);
 
-        // Brief delay for PipeWire to connect
-        tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
+        // Wait until PipeWire reports the stream as streaming
+        let ready = tokio::time::timeout(
+            tokio::time::Duration::from_millis(STREAM_READY_TIMEOUT_MS),
+            ready_rx,
+        )
+        .await;
+
+        match ready {
+            Ok(Ok(Ok(()))) => {}
+            Ok(Ok(Err(e))) => {
+                self.abort_capture();
+                return Err(anyhow::anyhow!("PipeWire stream error: {}", e));
+            }
+            Ok(Err(_)) => {
+                self.abort_capture();
+                return Err(anyhow::anyhow!("Audio capture failed to start"));
+            }
+            Err(_) => {
+                warn!(
+                    "PipeWire stream not streaming after {}ms, continuing anyway",
+                    STREAM_READY_TIMEOUT_MS
+                );
+            }
+        }
+
+        Ok(())
+    }
 
-        Ok(self.recording_path.clone())
+    /// Stop the capture thread after a failed start and drop the WAV writer.
+    fn abort_capture(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        if let Ok(mut guard) = self.writer.lock() {
+            guard.take();
+        }
     }
 
-    pub async fn stop(&mut self) -> Result<PathBuf> {
+    /// End the recording and return it. A capture that ended on its own is
+    /// reported as a [`CaptureLost`] error.
+    pub async fn stop(&mut self) -> Result<RecordedAudio> {
         info!("Stopping audio recording");
 
+        // Nothing but `running` ends a healthy capture thread
+        let ended_early = self.thread.as_ref().is_some_and(|t| t.is_finished());
         self.running.store(false, Ordering::SeqCst);
 
         if let Some(thread) = self.thread.take() {
@@ -90,6 +401,37 @@ impl AudioRecorder {
                 .map_err(|_| anyhow::anyhow!("Recording thread panicked"))?;
         }
 
+        let failure = self
+            .failure
+            .take()
+            .or_else(|| ended_early.then(|| "the capture thread exited".to_string()));
+        if let Some(reason) = failure {
+            let partial = self.finish().await.ok();
+            return Err(CaptureLost { reason, partial }.into());
+        }
+        self.finish().await
+    }
+
+    /// Close the recording and hand it over, failing if it is empty.
+    async fn finish(&mut self) -> Result<RecordedAudio> {
+        if self.config.in_memory {
+            let samples = std::mem::take(
+                &mut *self
+                    .memory
+                    .lock()
+                    .map_err(|_| anyhow::anyhow!("Sample buffer mutex poisoned"))?,
+            );
+            if samples.is_empty() {
+                return Err(anyhow::anyhow!("Recording is empty"));
+            }
+            let audio = RecordedAudio::Memory {
+                samples: Arc::new(samples),
+                sample_rate: self.config.sample_rate,
+            };
+            info!("Recording kept as {}", audio);
+            return Ok(audio);
+        }
+
         // Finalize WAV
         if let Ok(mut guard) = self.writer.lock() {
             if let Some(writer) = guard.take() {
@@ -111,7 +453,19 @@ impl AudioRecorder {
             metadata.len()
         );
 
-        Ok(self.recording_path.clone())
+        Ok(RecordedAudio::File(self.recording_path.clone()))
+    }
+
+    /// Whether the last recording clipped more than
+    /// `audio.clip_warning_fraction` of its samples.
+    pub fn is_clipping(&self) -> bool {
+        let threshold = self.config.clip_warning_fraction;
+        let fraction = self.clips.fraction();
+        if threshold > 0.0 && fraction > threshold {
+            warn!("Input is clipping: {:.2}% of samples at full scale", fraction * 100.0);
+            return true;
+        }
+        false
     }
 
     pub async fn cleanup(&self) -> Result<()> {
@@ -122,13 +476,235 @@ impl AudioRecorder {
     }
 }
 
+/// The recording file, either already open or, with `native_path`, created
+/// by the capture thread once the negotiated rate is known. With `memory`
+/// the samples are collected there instead.
+struct WavTarget {
+    writer: SharedWavWriter,
+    native_path: Option<PathBuf>,
+    memory: Option<SharedSamples>,
+}
+
+/// Mono WAV at `sample_rate`: 16-bit integer PCM, or 32-bit float for any
+/// other `bit_depth`. Multi-channel capture is mixed down before writing.
+fn wav_spec(sample_rate: u32, bit_depth: u16) -> hound::WavSpec {
+    let (bits_per_sample, sample_format) = match bit_depth {
+        16 => (16, hound::SampleFormat::Int),
+        _ => (32, hound::SampleFormat::Float),
+    };
+    hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample,
+        sample_format,
+    }
+}
+
+/// Write one sample in the writer's format, converting to 16-bit PCM if needed.
+fn write_wav_sample<W: std::io::Write + std::io::Seek>(
+    writer: &mut WavWriter<W>,
+    sample: f32,
+) -> hound::Result<()> {
+    match writer.spec().sample_format {
+        hound::SampleFormat::Float => writer.write_sample(sample),
+        hound::SampleFormat::Int => {
+            writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16)
+        }
+    }
+}
+
+/// Convert an RMS level to dBFS, clamped to `METER_FLOOR_DBFS`.
+pub fn rms_to_dbfs(rms: f32) -> f32 {
+    if rms <= 0.0 {
+        return METER_FLOOR_DBFS;
+    }
+    (20.0 * rms.log10()).clamp(METER_FLOOR_DBFS, 0.0)
+}
+
+/// Render an RMS level as an ASCII meter of `width` cells on a dBFS scale,
+/// e.g. `[########------------] -30.0 dBFS`.
+pub fn level_meter(rms: f32, width: usize) -> String {
+    let db = rms_to_dbfs(rms);
+    let filled = (((db - METER_FLOOR_DBFS) / -METER_FLOOR_DBFS) * width as f32).round() as usize;
+    let filled = filled.min(width);
+    format!(
+        "[{}{}] {:6.1} dBFS",
+        "#".repeat(filled),
+        "-".repeat(width - filled),
+        db
+    )
+}
+
+/// Put `ms` of silence in front of a clip, giving whisper a lead-in so the
+/// first phoneme is not dropped. The clip itself is left unchanged.
+pub fn prepend_silence(samples: Vec<f32>, sample_rate: u32, ms: u32) -> Vec<f32> {
+    let pad = samples_for_ms(sample_rate, ms);
+    if pad == 0 {
+        return samples;
+    }
+    let mut padded = Vec::with_capacity(pad + samples.len());
+    padded.resize(pad, 0.0);
+    padded.extend_from_slice(&samples);
+    padded
+}
+
+/// Number of samples in `ms` of audio at `sample_rate`.
+fn samples_for_ms(sample_rate: u32, ms: u32) -> usize {
+    (sample_rate as u64 * ms as u64 / 1000) as usize
+}
+
+/// Drop up to `*remaining` samples from the front of a buffer, counting them
+/// off, so `audio.trim_start_ms` is cut exactly across capture callbacks.
+fn skip_leading<'a>(samples: &'a [f32], remaining: &mut usize) -> &'a [f32] {
+    let skip = (*remaining).min(samples.len());
+    *remaining -= skip;
+    &samples[skip..]
+}
+
+/// Mix interleaved multi-channel samples down to mono.
+/// `weights` is only used for `MonoMode::Weighted`; missing weights count as 0.
+pub fn downmix(samples: &[f32], channels: usize, mode: MonoMode, weights: &[f32]) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+
+    samples
+        .chunks(channels)
+        .map(|frame| match mode {
+            MonoMode::Average => frame.iter().sum::<f32>() / frame.len() as f32,
+            MonoMode::Left => frame[0],
+            MonoMode::Right => frame.get(1).copied().unwrap_or(frame[0]),
+            MonoMode::Weighted => frame
+                .iter()
+                .zip(weights)
+                .map(|(sample, weight)| sample * weight)
+                .sum(),
+        })
+        .collect()
+}
+
+/// Node name of the source created by PipeWire's echo-cancel module, from
+/// `(node.name, media.class)` pairs; the module's default name wins.
+fn echo_cancel_source(nodes: &[(String, String)]) -> Option<&str> {
+    let sources: Vec<&str> = nodes
+        .iter()
+        .filter(|(name, class)| {
+            class == "Audio/Source" && (name.contains("echo-cancel") || name.contains("echo_cancel"))
+        })
+        .map(|(name, _)| name.as_str())
+        .collect();
+    sources
+        .iter()
+        .find(|&&name| name == "echo-cancel-source")
+        .or(sources.first())
+        .copied()
+}
+
+/// `(node.name, media.class)` of every PipeWire node, read with one
+/// registry roundtrip.
+fn list_pipewire_nodes(
+    mainloop: &pipewire::main_loop::MainLoop,
+    core: &pipewire::core::Core,
+) -> Result<Vec<(String, String)>> {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    let registry = core
+        .get_registry()
+        .map_err(|e| anyhow::anyhow!("Failed to get PipeWire registry: {:?}", e))?;
+    let nodes = Rc::new(RefCell::new(Vec::new()));
+    let done = Rc::new(Cell::new(false));
+
+    // Answered after every existing global has been announced
+    let pending = core
+        .sync(0)
+        .map_err(|e| anyhow::anyhow!("Failed to query PipeWire nodes: {:?}", e))?;
+    let done_signal = done.clone();
+    let quit_loop = mainloop.clone();
+    let _core_listener = core
+        .add_listener_local()
+        .done(move |id, seq| {
+            if id == pipewire::core::PW_ID_CORE && seq == pending {
+                done_signal.set(true);
+                quit_loop.quit();
+            }
+        })
+        .register();
+    let found = nodes.clone();
+    let _registry_listener = registry
+        .add_listener_local()
+        .global(move |global| {
+            if global.type_ != pipewire::types::ObjectType::Node {
+                return;
+            }
+            let Some(props) = global.props else {
+                return;
+            };
+            if let (Some(name), Some(class)) = (
+                props.get(*pipewire::keys::NODE_NAME),
+                props.get(*pipewire::keys::MEDIA_CLASS),
+            ) {
+                found.borrow_mut().push((name.to_string(), class.to_string()));
+            }
+        })
+        .register();
+
+    while !done.get() {
+        mainloop.run();
+    }
+    Ok(nodes.take())
+}
+
+/// Whether `nodes` has a source to record from: the echo-cancel source with
+/// `echo_cancel`, otherwise any audio source.
+fn has_capture_source(nodes: &[(String, String)], echo_cancel: bool) -> bool {
+    if echo_cancel {
+        return echo_cancel_source(nodes).is_some();
+    }
+    nodes.iter().any(|(_, class)| class == "Audio/Source")
+}
+
+/// After the capture lost its source, wait up to `RECONNECT_WAIT_MS` for
+/// PipeWire to offer one again. Gives up early once `running` is cleared.
+fn wait_for_source(config: &AudioConfig, running: &AtomicBool) -> bool {
+    let list_nodes = || -> Result<Vec<(String, String)>> {
+        let mainloop = pipewire::main_loop::MainLoop::new(None)
+            .map_err(|e| anyhow::anyhow!("Failed to create PipeWire main loop: {:?}", e))?;
+        let context = pipewire::context::Context::new(&mainloop)
+            .map_err(|e| anyhow::anyhow!("Failed to create PipeWire context: {:?}", e))?;
+        let core = context
+            .connect(None)
+            .map_err(|e| anyhow::anyhow!("Failed to connect to PipeWire: {:?}", e))?;
+        list_pipewire_nodes(&mainloop, &core)
+    };
+
+    let deadline = Instant::now() + Duration::from_millis(RECONNECT_WAIT_MS);
+    while running.load(Ordering::SeqCst) && Instant::now() < deadline {
+        match list_nodes() {
+            Ok(nodes) if has_capture_source(&nodes, config.echo_cancel) => return true,
+            Ok(_) => debug!("No capture source yet"),
+            Err(e) => debug!("{}", e),
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+    false
+}
+
 fn run_pipewire_capture(
     running: Arc<AtomicBool>,
-    writer: Arc<Mutex<Option<WavWriter<BufWriter<std::fs::File>>>>>,
+    wav: WavTarget,
     amplitude_tx: Option<std::sync::mpsc::Sender<f32>>,
-    sample_rate: u32,
-    channels: u32,
-) -> Result<()> {
+    sample_tx: Option<std::sync::mpsc::Sender<SampleChunk>>,
+    clips: Arc<ClipCounter>,
+    config: &AudioConfig,
+    ready_tx: tokio::sync::oneshot::Sender<std::result::Result<(), String>>,
+) -> Result<CaptureEnd> {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    let sample_rate = config.sample_rate;
+    let channels = config.channels as u32;
+
     pipewire::init();
 
     let mainloop = pipewire::main_loop::MainLoop::new(None)
@@ -139,43 +715,150 @@ fn run_pipewire_capture(
         .connect(None)
         .map_err(|e| anyhow::anyhow!("Failed to connect to PipeWire: {:?}", e))?;
 
-    let props = pipewire::properties::properties! {
+    let mut props = pipewire::properties::properties! {
         *pipewire::keys::MEDIA_TYPE => "Audio",
-        *pipewire::keys::MEDIA_CATEGORY => "Capture",
-        *pipewire::keys::MEDIA_ROLE => "Communication",
+        *pipewire::keys::MEDIA_CATEGORY => config.media_category.as_str(),
+        *pipewire::keys::MEDIA_ROLE => config.media_role.as_str(),
     };
+    if config.echo_cancel {
+        match list_pipewire_nodes(&mainloop, &core) {
+            Ok(nodes) => match echo_cancel_source(&nodes) {
+                Some(source) => {
+                    info!("Recording from echo-cancelled source {}", source);
+                    props.insert(*pipewire::keys::TARGET_OBJECT, source);
+                }
+                None => warn!(
+                    "audio.echo_cancel is set but PipeWire has no echo-cancel source; \
+                     load libpipewire-module-echo-cancel. Recording from the default source"
+                ),
+            },
+            Err(e) => warn!("{}; recording from the default source", e),
+        }
+    }
 
     let stream = pipewire::stream::Stream::new(&core, "tjvox-capture", props)
         .map_err(|e| anyhow::anyhow!("Failed to create PipeWire stream: {:?}", e))?;
 
-    // Build format parameters: F32LE at specified sample rate and channels
-    let audio_params = build_audio_params(sample_rate, channels)?;
+    // Build format parameters: F32LE at specified sample rate and channels,
+    // leaving the rate to the device when recording at its native rate
+    let pinned_rate = wav.native_path.is_none().then_some(sample_rate);
+    let audio_params = build_audio_params(pinned_rate, channels)?;
     let pod = pipewire::spa::pod::Pod::from_bytes(&audio_params)
         .ok_or_else(|| anyhow::anyhow!("Failed to create SPA pod from audio params"))?;
 
-    // Window size for amplitude computation (50ms)
-    let window_samples = (sample_rate as usize / 20) * channels as usize;
+    // Window size for amplitude computation (50ms of mono samples)
+    let window_samples = sample_rate as usize / 20;
 
     struct CaptureState {
-        writer: Arc<Mutex<Option<WavWriter<BufWriter<std::fs::File>>>>>,
+        writer: SharedWavWriter,
         amp_buffer: Vec<f32>,
         amplitude_tx: Option<std::sync::mpsc::Sender<f32>>,
+        sample_tx: Option<std::sync::mpsc::Sender<SampleChunk>>,
+        clips: Arc<ClipCounter>,
         window_samples: usize,
+        /// Negotiated capture rate, `sample_rate` until PipeWire reports one
+        rate: u32,
+        channels: usize,
+        mono_mode: MonoMode,
+        channel_weights: Vec<f32>,
+        /// Recording path whose writer is created once the rate is known
+        native_wav: Option<PathBuf>,
+        bit_depth: u16,
+        memory: Option<SharedSamples>,
+        trim_start_ms: u32,
+        /// Interleaved samples still to drop for `audio.trim_start_ms`
+        trim_remaining: usize,
     }
 
     let state = CaptureState {
-        writer,
+        writer: wav.writer,
         amp_buffer: Vec::with_capacity(window_samples * 2),
         amplitude_tx,
+        sample_tx,
+        clips,
         window_samples,
+        rate: sample_rate,
+        channels: channels as usize,
+        mono_mode: config.mono_mode,
+        channel_weights: config.channel_weights.clone(),
+        native_wav: wav.native_path,
+        bit_depth: config.bit_depth,
+        memory: wav.memory,
+        trim_start_ms: config.trim_start_ms,
+        trim_remaining: samples_for_ms(sample_rate, config.trim_start_ms) * channels as usize,
     };
 
     // Get raw pointer for quitting from callback (safe: same thread)
     let raw_mainloop = mainloop.as_raw_ptr();
     let running_check = running.clone();
+    let mut ready_tx = Some(ready_tx);
+
+    // When audio last arrived, from the moment the stream is streaming
+    let last_audio: Rc<Cell<Option<Instant>>> = Rc::new(Cell::new(None));
+    // Why the stream ended while still running
+    let lost: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
 
+    let streaming = last_audio.clone();
+    let stream_lost = lost.clone();
+    let quit_loop = mainloop.clone();
+    let audio_seen = last_audio.clone();
     let _listener = stream
         .add_local_listener_with_user_data(state)
+        .state_changed(move |_stream, _state, _old, new| {
+            match &new {
+                pipewire::stream::StreamState::Streaming => streaming.set(Some(Instant::now())),
+                pipewire::stream::StreamState::Error(e) => {
+                    stream_lost.replace(Some(format!("PipeWire stream error: {}", e)));
+                    quit_loop.quit();
+                }
+                pipewire::stream::StreamState::Unconnected if streaming.get().is_some() => {
+                    stream_lost.replace(Some("the capture stream was disconnected".to_string()));
+                    quit_loop.quit();
+                }
+                _ => {}
+            }
+            let signal = match new {
+                pipewire::stream::StreamState::Streaming => Ok(()),
+                pipewire::stream::StreamState::Error(e) => Err(e),
+                _ => return,
+            };
+            if let Some(tx) = ready_tx.take() {
+                let _ = tx.send(signal);
+            }
+        })
+        .param_changed(|_stream, state, id, param| {
+            let Some(param) = param else {
+                return;
+            };
+            if id != pipewire::spa::param::ParamType::Format.as_raw() {
+                return;
+            }
+            let mut format = pipewire::spa::param::audio::AudioInfoRaw::new();
+            if format.parse(param).is_err() {
+                return;
+            }
+            let rate = format.rate();
+            info!("Capture format negotiated: {} Hz, {} channel(s)", rate, format.channels());
+            if rate == 0 {
+                return;
+            }
+            state.rate = rate;
+            state.window_samples = (rate as usize / 20).max(1);
+            if format.channels() > 0 {
+                state.channels = format.channels() as usize;
+            }
+            state.trim_remaining = samples_for_ms(rate, state.trim_start_ms) * state.channels;
+            if let Some(path) = state.native_wav.take() {
+                match WavWriter::create(&path, wav_spec(rate, state.bit_depth)) {
+                    Ok(wav_writer) => {
+                        if let Ok(mut guard) = state.writer.lock() {
+                            *guard = Some(wav_writer);
+                        }
+                    }
+                    Err(e) => error!("Failed to create {}: {}", path.display(), e),
+                }
+            }
+        })
         .process(move |stream, state| {
             if !running_check.load(Ordering::Relaxed) {
                 unsafe { pipewire::sys::pw_main_loop_quit(raw_mainloop); }
@@ -183,6 +866,7 @@ fn run_pipewire_capture(
             }
 
             if let Some(mut buffer) = stream.dequeue_buffer() {
+                audio_seen.set(Some(Instant::now()));
                 let datas = buffer.datas_mut();
                 if let Some(d) = datas.first_mut() {
                     let chunk = d.chunk();
@@ -203,15 +887,50 @@ fn run_pipewire_capture(
                             )
                         };
 
+                        // Whole frames are trimmed, so channels stay aligned
+                        let samples = skip_leading(samples, &mut state.trim_remaining);
+                        if samples.is_empty() {
+                            return;
+                        }
+                        // Any channel at full scale clips, even if the mix does not
+                        state.clips.add(samples);
+
+                        // Mix interleaved frames down to mono
+                        let mixed;
+                        let samples = if state.channels > 1 {
+                            mixed = downmix(
+                                samples,
+                                state.channels,
+                                state.mono_mode,
+                                &state.channel_weights,
+                            );
+                            mixed.as_slice()
+                        } else {
+                            samples
+                        };
+
                         // Write to WAV
                         if let Ok(mut guard) = state.writer.try_lock() {
                             if let Some(ref mut w) = *guard {
                                 for &sample in samples {
-                                    let _ = w.write_sample(sample);
+                                    let _ = write_wav_sample(w, sample);
                                 }
                             }
                         }
 
+                        if let Some(ref memory) = state.memory {
+                            if let Ok(mut buffer) = memory.lock() {
+                                buffer.extend_from_slice(samples);
+                            }
+                        }
+
+                        if let Some(ref tx) = state.sample_tx {
+                            let _ = tx.send(SampleChunk {
+                                rate: state.rate,
+                                samples: samples.to_vec(),
+                            });
+                        }
+
                         // Compute amplitude (RMS per window)
                         state.amp_buffer.extend_from_slice(samples);
                         while state.amp_buffer.len() >= state.window_samples {
@@ -242,51 +961,83 @@ fn run_pipewire_capture(
         )
         .map_err(|e| anyhow::anyhow!("Failed to connect PipeWire stream: {:?}", e))?;
 
+    // `process` stops being called when the source goes away, so stopping
+    // and noticing a stalled stream cannot rely on it
+    let watchdog_running = running.clone();
+    let watchdog_quit = mainloop.clone();
+    let watchdog_audio = last_audio.clone();
+    let stalled = lost.clone();
+    let watchdog = mainloop.loop_().add_timer(move |_| {
+        if !watchdog_running.load(Ordering::Relaxed) {
+            watchdog_quit.quit();
+            return;
+        }
+        let stall = Duration::from_millis(CAPTURE_STALL_MS);
+        if watchdog_audio.get().is_some_and(|at| at.elapsed() >= stall) {
+            stalled.replace(Some(format!("no audio from the source for {} ms", CAPTURE_STALL_MS)));
+            watchdog_quit.quit();
+        }
+    });
+    let interval = Duration::from_millis(CAPTURE_WATCHDOG_INTERVAL_MS);
+    watchdog
+        .update_timer(Some(interval), Some(interval))
+        .into_result()
+        .map_err(|e| anyhow::anyhow!("Failed to start the capture watchdog: {}", e))?;
+
     info!("PipeWire audio capture started");
     mainloop.run();
     info!("PipeWire audio capture stopped");
 
-    Ok(())
+    match lost.take() {
+        // Failures before the stream was streaming are start errors
+        Some(reason) if last_audio.get().is_none() => Err(anyhow::anyhow!(reason)),
+        Some(reason) if running.load(Ordering::SeqCst) => Ok(CaptureEnd::Lost(reason)),
+        _ => Ok(CaptureEnd::Stopped),
+    }
 }
 
-fn build_audio_params(sample_rate: u32, channels: u32) -> Result<Vec<u8>> {
+fn build_audio_params(sample_rate: Option<u32>, channels: u32) -> Result<Vec<u8>> {
     use pipewire::spa::pod::serialize::PodSerializer;
     use pipewire::spa::pod::{Object, Property, PropertyFlags, Value};
     use pipewire::spa::sys;
     use pipewire::spa::utils::Id;
 
+    let mut properties = vec![
+        Property {
+            key: sys::SPA_FORMAT_mediaType,
+            flags: PropertyFlags::empty(),
+            value: Value::Id(Id(sys::SPA_MEDIA_TYPE_audio)),
+        },
+        Property {
+            key: sys::SPA_FORMAT_mediaSubtype,
+            flags: PropertyFlags::empty(),
+            value: Value::Id(Id(sys::SPA_MEDIA_SUBTYPE_raw)),
+        },
+        Property {
+            key: sys::SPA_FORMAT_AUDIO_format,
+            flags: PropertyFlags::empty(),
+            value: Value::Id(Id(sys::SPA_AUDIO_FORMAT_F32_LE)),
+        },
+        Property {
+            key: sys::SPA_FORMAT_AUDIO_channels,
+            flags: PropertyFlags::empty(),
+            value: Value::Int(channels as i32),
+        },
+    ];
+    if let Some(rate) = sample_rate {
+        properties.push(Property {
+            key: sys::SPA_FORMAT_AUDIO_rate,
+            flags: PropertyFlags::empty(),
+            value: Value::Int(rate as i32),
+        });
+    }
+
     let bytes = PodSerializer::serialize(
         std::io::Cursor::new(Vec::new()),
         &Value::Object(Object {
             type_: pipewire::spa::utils::SpaTypes::ObjectParamFormat.as_raw(),
             id: pipewire::spa::param::ParamType::EnumFormat.as_raw(),
-            properties: vec![
-                Property {
-                    key: sys::SPA_FORMAT_mediaType,
-                    flags: PropertyFlags::empty(),
-                    value: Value::Id(Id(sys::SPA_MEDIA_TYPE_audio)),
-                },
-                Property {
-                    key: sys::SPA_FORMAT_mediaSubtype,
-                    flags: PropertyFlags::empty(),
-                    value: Value::Id(Id(sys::SPA_MEDIA_SUBTYPE_raw)),
-                },
-                Property {
-                    key: sys::SPA_FORMAT_AUDIO_format,
-                    flags: PropertyFlags::empty(),
-                    value: Value::Id(Id(sys::SPA_AUDIO_FORMAT_F32_LE)),
-                },
-                Property {
-                    key: sys::SPA_FORMAT_AUDIO_rate,
-                    flags: PropertyFlags::empty(),
-                    value: Value::Int(sample_rate as i32),
-                },
-                Property {
-                    key: sys::SPA_FORMAT_AUDIO_channels,
-                    flags: PropertyFlags::empty(),
-                    value: Value::Int(channels as i32),
-                },
-            ],
+            properties,
         }),
     )
     .map_err(|e| anyhow::anyhow!("Failed to serialize audio params: {:?}", e))?
@@ -295,3 +1046,219 @@ fn build_audio_params(sample_rate: u32, channels: u32) -> Result<Vec<u8>> {
 
     Ok(bytes)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEREO: [f32; 4] = [0.2, 0.6, -0.4, 0.0];
+
+    #[test]
+    fn test_downmix_average() {
+        let mono = downmix(&STEREO, 2, MonoMode::Average, &[]);
+        assert_eq!(mono.len(), 2);
+        assert!((mono[0] - 0.4).abs() < 1e-6);
+        assert!((mono[1] + 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_echo_cancel_source() {
+        let node = |name: &str, class: &str| (name.to_string(), class.to_string());
+        let mut nodes = vec![
+            node("alsa_input.usb-mic", "Audio/Source"),
+            node("echo-cancel-capture", "Stream/Input/Audio"),
+            node("echo-cancel-sink", "Audio/Sink"),
+        ];
+        assert_eq!(echo_cancel_source(&nodes), None);
+
+        nodes.push(node("alsa_input.usb-mic.echo_cancel", "Audio/Source"));
+        assert_eq!(echo_cancel_source(&nodes), Some("alsa_input.usb-mic.echo_cancel"));
+        nodes.push(node("echo-cancel-source", "Audio/Source"));
+        assert_eq!(echo_cancel_source(&nodes), Some("echo-cancel-source"));
+    }
+
+    #[test]
+    fn test_has_capture_source() {
+        let node = |name: &str, class: &str| (name.to_string(), class.to_string());
+        let mut nodes = vec![node("alsa_output.speakers", "Audio/Sink")];
+        assert!(!has_capture_source(&nodes, false));
+
+        nodes.push(node("alsa_input.usb-mic", "Audio/Source"));
+        assert!(has_capture_source(&nodes, false));
+        assert!(!has_capture_source(&nodes, true));
+        nodes.push(node("echo-cancel-source", "Audio/Source"));
+        assert!(has_capture_source(&nodes, true));
+    }
+
+    #[tokio::test]
+    async fn test_stop_reports_lost_capture() {
+        let mut config = crate::config::Config::default().audio;
+        config.in_memory = true;
+        let mut recorder = AudioRecorder::new(&config, None).unwrap();
+        recorder.memory.lock().unwrap().extend_from_slice(&[0.1; 1600]);
+        recorder.failure.set("PipeWire stream error: device removed".to_string());
+
+        // Completes at once, as the daemon's select sees it
+        recorder.capture_lost().await;
+        let err = recorder.stop().await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Audio capture stopped before the recording ended: PipeWire stream error: device removed"
+        );
+        // What was recorded before the failure is handed back
+        let lost = err.downcast::<CaptureLost>().unwrap();
+        match lost.partial {
+            Some(RecordedAudio::Memory { samples, .. }) => assert_eq!(samples.len(), 1600),
+            other => panic!("unexpected partial audio: {:?}", other),
+        }
+
+        // A capture thread that ended without a reason still counts
+        recorder.thread = Some(std::thread::spawn(|| {}));
+        while !recorder.thread.as_ref().unwrap().is_finished() {
+            std::thread::yield_now();
+        }
+        let err = recorder.stop().await.unwrap_err();
+        assert!(err.to_string().ends_with("the capture thread exited"));
+        assert!(err.downcast::<CaptureLost>().unwrap().partial.is_none());
+    }
+
+    #[test]
+    fn test_downmix_left() {
+        assert_eq!(downmix(&STEREO, 2, MonoMode::Left, &[]), vec![0.2, -0.4]);
+    }
+
+    #[test]
+    fn test_downmix_right() {
+        assert_eq!(downmix(&STEREO, 2, MonoMode::Right, &[]), vec![0.6, 0.0]);
+    }
+
+    #[test]
+    fn test_downmix_weighted() {
+        let mono = downmix(&STEREO, 2, MonoMode::Weighted, &[0.5, 1.0]);
+        assert!((mono[0] - 0.7).abs() < 1e-6);
+        assert!((mono[1] + 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_downmix_mono_passthrough() {
+        assert_eq!(downmix(&STEREO, 1, MonoMode::Right, &[]), STEREO.to_vec());
+    }
+
+    #[test]
+    fn test_rms_to_dbfs() {
+        assert_eq!(rms_to_dbfs(1.0), 0.0);
+        assert!((rms_to_dbfs(0.1) + 20.0).abs() < 1e-4);
+        assert_eq!(rms_to_dbfs(0.0), METER_FLOOR_DBFS);
+        assert_eq!(rms_to_dbfs(1e-9), METER_FLOOR_DBFS);
+    }
+
+    #[test]
+    fn test_sample_chunk_into_rate() {
+        let chunk = SampleChunk {
+            rate: 16000,
+            samples: vec![0.25; 160],
+        };
+        assert_eq!(chunk.clone().into_rate(16000), chunk.samples);
+
+        // 10ms captured at a native 48 kHz is 10ms at 16 kHz
+        let native = SampleChunk {
+            rate: 48000,
+            samples: vec![0.25; 480],
+        };
+        let converted = native.into_rate(16000);
+        assert_eq!(converted.len(), 160);
+        assert!(converted.iter().all(|s| (s - 0.25).abs() < 1e-3));
+    }
+
+    #[test]
+    fn test_skip_leading() {
+        let mut remaining = samples_for_ms(16000, 1);
+        assert_eq!(remaining, 16);
+        let buffer = [0.5f32; 10];
+        assert!(skip_leading(&buffer, &mut remaining).is_empty());
+        assert_eq!(skip_leading(&buffer, &mut remaining).len(), 4);
+        assert_eq!(remaining, 0);
+        assert_eq!(skip_leading(&buffer, &mut remaining).len(), 10);
+    }
+
+    #[test]
+    fn test_prepend_silence() {
+        let clip: Vec<f32> = (1..=8).map(|i| i as f32 / 10.0).collect();
+
+        let padded = prepend_silence(clip.clone(), 16000, 250);
+        assert_eq!(padded.len(), 4000 + clip.len());
+        assert!(padded[..4000].iter().all(|&s| s == 0.0));
+        // The speech itself reaches whisper untouched, only later
+        assert_eq!(&padded[4000..], clip.as_slice());
+
+        assert_eq!(prepend_silence(clip.clone(), 16000, 0), clip);
+    }
+
+    #[tokio::test]
+    async fn test_recorded_audio_in_memory() {
+        let audio = RecordedAudio::Memory {
+            samples: Arc::new(vec![0.0; 24000]),
+            sample_rate: 16000,
+        };
+        assert_eq!(audio.to_string(), "in-memory recording (1.5s)");
+        assert_eq!(audio.path(), None);
+        // Nothing on disk to remove
+        audio.remove().await;
+
+        let file = RecordedAudio::File(PathBuf::from("/tmp/tjvox/recording.wav"));
+        assert_eq!(file.to_string(), "/tmp/tjvox/recording.wav");
+        assert_eq!(file.path(), Some(Path::new("/tmp/tjvox/recording.wav")));
+    }
+
+    #[test]
+    fn test_wav_16_bit_round_trip() {
+        use crate::config::ResampleQuality;
+
+        let dir = tempfile::tempdir().unwrap();
+        let samples = [0.0, 0.5, -0.25, 1.0, -1.0, 1.5];
+        let mut sizes = Vec::new();
+        for bit_depth in [16, 32] {
+            let path = dir.path().join(format!("{}.wav", bit_depth));
+            let mut writer = WavWriter::create(&path, wav_spec(16000, bit_depth)).unwrap();
+            for &sample in samples.iter().cycle().take(1600) {
+                write_wav_sample(&mut writer, sample).unwrap();
+            }
+            writer.finalize().unwrap();
+            sizes.push(std::fs::metadata(&path).unwrap().len());
+
+            let audio = RecordedAudio::File(path);
+            let quality = ResampleQuality::High;
+            let loaded =
+                crate::transcription::load_audio(&audio, MonoMode::Average, &[], quality).unwrap();
+            assert_eq!(loaded.len(), 1600);
+            for (got, want) in loaded.iter().zip(samples.iter().cycle()) {
+                // Out-of-range input is clipped when stored as 16-bit PCM
+                let want = if bit_depth == 16 { want.clamp(-1.0, 1.0) } else { *want };
+                assert!((got - want).abs() < 1.0 / 16384.0, "{} != {}", got, want);
+            }
+        }
+        assert!(sizes[0] < sizes[1] / 2 + 64);
+    }
+
+    #[test]
+    fn test_clip_counter() {
+        let clips = ClipCounter::default();
+        assert_eq!(clips.fraction(), 0.0);
+
+        clips.add(&[0.5, -0.2, 1.0, -1.0]);
+        clips.add(&[0.1; 4]);
+        assert_eq!(clips.fraction(), 0.25);
+
+        clips.reset();
+        clips.add(&[0.998, -0.5]);
+        assert_eq!(clips.fraction(), 0.0);
+    }
+
+    #[test]
+    fn test_level_meter() {
+        assert_eq!(level_meter(0.0, 10), "[----------]  -60.0 dBFS");
+        assert_eq!(level_meter(1.0, 10), "[##########]    0.0 dBFS");
+        assert_eq!(level_meter(0.001, 4), "[----]  -60.0 dBFS");
+        assert_eq!(level_meter(0.031_622_776, 4), "[##--]  -30.0 dBFS");
+    }
+}
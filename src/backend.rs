@@ -0,0 +1,140 @@
+use std::path::Path;
+
+/// whisper.cpp acceleration backend selected at compile time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Cpu,
+    Cuda,
+    Vulkan,
+    Metal,
+    Hipblas,
+}
+
+impl Backend {
+    /// Backend this binary was built with. GPU features take precedence over
+    /// each other in the order CUDA, HIP, Vulkan, Metal, as whisper.cpp
+    /// only uses one of them.
+    pub fn compiled() -> Self {
+        if cfg!(feature = "cuda") {
+            Backend::Cuda
+        } else if cfg!(feature = "hipblas") {
+            Backend::Hipblas
+        } else if cfg!(feature = "vulkan") {
+            Backend::Vulkan
+        } else if cfg!(feature = "metal") {
+            Backend::Metal
+        } else {
+            Backend::Cpu
+        }
+    }
+
+    pub fn is_gpu(self) -> bool {
+        self != Backend::Cpu
+    }
+
+    /// Look for a device this backend can run on. Returns a short
+    /// description of what was found, or `None` if nothing usable exists.
+    pub fn detect_device(self) -> Option<String> {
+        self.detect_device_in(Path::new("/"))
+    }
+
+    fn detect_device_in(self, root: &Path) -> Option<String> {
+        match self {
+            Backend::Cpu => Some("CPU".to_string()),
+            Backend::Cuda => {
+                let gpus = root.join("proc/driver/nvidia/gpus");
+                let count = std::fs::read_dir(&gpus).map(|d| d.count()).unwrap_or(0);
+                if count > 0 {
+                    Some(format!("{} NVIDIA GPU(s)", count))
+                } else if root.join("dev/nvidia0").exists() {
+                    Some("/dev/nvidia0".to_string())
+                } else {
+                    None
+                }
+            }
+            Backend::Hipblas => root
+                .join("dev/kfd")
+                .exists()
+                .then(|| "/dev/kfd".to_string()),
+            Backend::Vulkan => std::fs::read_dir(root.join("dev/dri"))
+                .ok()?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| name.starts_with("renderD"))
+                .min()
+                .map(|name| format!("/dev/dri/{}", name)),
+            Backend::Metal => cfg!(target_os = "macos").then(|| "Metal".to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Backend::Cpu => write!(f, "CPU"),
+            Backend::Cuda => write!(f, "CUDA"),
+            Backend::Vulkan => write!(f, "Vulkan"),
+            Backend::Metal => write!(f, "Metal"),
+            Backend::Hipblas => write!(f, "HIP (ROCm)"),
+        }
+    }
+}
+
+/// Check that a GPU backend has a device to run on before loading a model,
+/// so a missing driver is reported instead of silently falling back to the
+/// CPU. Returns whether whisper should use the GPU.
+pub fn resolve_gpu(backend: Backend, use_gpu: bool, device: Option<&str>) -> Result<bool, String> {
+    if !use_gpu || !backend.is_gpu() {
+        return Ok(false);
+    }
+    match device {
+        Some(_) => Ok(true),
+        None => Err(format!(
+            "tjvox was built with the {} backend but no {} device was found. \
+             Check the GPU driver, or set transcription.use_gpu = false to run on the CPU",
+            backend, backend
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_gpu() {
+        assert_eq!(resolve_gpu(Backend::Cpu, true, None), Ok(false));
+        assert_eq!(resolve_gpu(Backend::Cuda, false, None), Ok(false));
+        assert_eq!(resolve_gpu(Backend::Cuda, true, Some("/dev/nvidia0")), Ok(true));
+
+        let err = resolve_gpu(Backend::Vulkan, true, None).unwrap_err();
+        assert!(err.contains("Vulkan"));
+        assert!(err.contains("use_gpu = false"));
+    }
+
+    #[test]
+    fn test_detect_device() {
+        let root = std::env::temp_dir().join(format!("tjvox-backend-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("dev/dri")).unwrap();
+
+        assert_eq!(Backend::Cpu.detect_device_in(&root).as_deref(), Some("CPU"));
+        assert_eq!(Backend::Cuda.detect_device_in(&root), None);
+        assert_eq!(Backend::Vulkan.detect_device_in(&root), None);
+
+        std::fs::write(root.join("dev/dri/card0"), "").unwrap();
+        std::fs::write(root.join("dev/dri/renderD129"), "").unwrap();
+        std::fs::write(root.join("dev/dri/renderD128"), "").unwrap();
+        assert_eq!(
+            Backend::Vulkan.detect_device_in(&root).as_deref(),
+            Some("/dev/dri/renderD128")
+        );
+
+        std::fs::write(root.join("dev/nvidia0"), "").unwrap();
+        assert_eq!(
+            Backend::Cuda.detect_device_in(&root).as_deref(),
+            Some("/dev/nvidia0")
+        );
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,232 @@
+use crate::output::{parse_paste_keys, PasteChord};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::{debug, info, warn};
+
+/// A spoken phrase and the keys it presses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoiceCommand {
+    pub phrase: String,
+    /// The key sequence as written in `commands.toml`, e.g. `"ctrl+a"`
+    pub keys: String,
+    pub chords: Vec<PasteChord>,
+}
+
+/// Outcome of matching an utterance against the configured commands.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CommandMatch<'a> {
+    Run(&'a VoiceCommand),
+    /// The utterance started with the command prefix but the rest is not a
+    /// known command; holds the rest, normalized
+    Unknown(String),
+}
+
+/// Spoken phrases mapped to key sequences, unlike replacements which insert
+/// text. Phrases match whole utterances, ignoring case and punctuation.
+pub struct CommandEngine {
+    commands: Vec<VoiceCommand>,
+    /// Command index by normalized phrase
+    by_phrase: HashMap<String, usize>,
+}
+
+impl CommandEngine {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            info!("Commands file not found at {:?}, creating default", path);
+            Self::create_default(path)?;
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read commands file: {:?}", path))?;
+
+        let table: toml::Value = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse commands file: {:?}", path))?;
+
+        let commands = table
+            .get("commands")
+            .and_then(|v| v.as_table())
+            .cloned()
+            .unwrap_or_default();
+
+        let mut pairs = Vec::new();
+        for (phrase, value) in &commands {
+            match value.as_str() {
+                Some(keys) if !normalize(phrase).is_empty() => {
+                    pairs.push((phrase.clone(), keys.to_string()));
+                }
+                _ => warn!("Ignoring command {:?}: expected a key sequence string", phrase),
+            }
+        }
+
+        let engine = Self::from_pairs(pairs);
+        info!("Loaded {} voice commands from {:?}", engine.len(), path);
+        Ok(engine)
+    }
+
+    /// Build an engine from `(phrase, keys)` pairs, skipping entries whose key
+    /// sequence does not parse.
+    pub fn from_pairs<K, V>(pairs: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut commands = Vec::new();
+        let mut by_phrase = HashMap::new();
+        for (phrase, keys) in pairs {
+            let (phrase, keys) = (phrase.as_ref(), keys.as_ref());
+            let chords = match parse_paste_keys(keys) {
+                Ok(chords) => chords,
+                Err(e) => {
+                    warn!("Ignoring command {:?}: {}", phrase, e);
+                    continue;
+                }
+            };
+            debug!("Loaded command: {:?} -> {:?}", phrase, keys);
+            by_phrase.entry(normalize(phrase)).or_insert(commands.len());
+            commands.push(VoiceCommand {
+                phrase: phrase.to_string(),
+                keys: keys.to_string(),
+                chords,
+            });
+        }
+        Self { commands, by_phrase }
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// The command whose phrase is the whole utterance.
+    pub fn lookup(&self, utterance: &str) -> Option<&VoiceCommand> {
+        self.by_phrase
+            .get(&normalize(utterance))
+            .map(|&i| &self.commands[i])
+    }
+
+    /// Match `text` as a command. Text starting with `prefix` is always
+    /// treated as a command; otherwise a whole-utterance match only counts
+    /// in `command_mode`, and anything else is left for dictation.
+    pub fn match_text(
+        &self,
+        text: &str,
+        prefix: Option<&str>,
+        command_mode: bool,
+    ) -> Option<CommandMatch<'_>> {
+        let words = normalize(text);
+        if let Some(prefix) = prefix.map(normalize).filter(|p| !p.is_empty()) {
+            let rest = words
+                .strip_prefix(&prefix)
+                .filter(|rest| rest.is_empty() || rest.starts_with(' '));
+            if let Some(rest) = rest {
+                let rest = rest.trim_start();
+                return Some(match self.by_phrase.get(rest) {
+                    Some(&i) => CommandMatch::Run(&self.commands[i]),
+                    None => CommandMatch::Unknown(rest.to_string()),
+                });
+            }
+        }
+        if command_mode {
+            return self.by_phrase.get(&words).map(|&i| CommandMatch::Run(&self.commands[i]));
+        }
+        None
+    }
+
+    /// Write the built-in commands to `path`, creating its directory.
+    pub fn create_default(path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let default = r#"# Spoken phrase = keys to press, in the output.paste_keys syntax
+[commands]
+"new line" = "enter"
+"select all" = "ctrl+a"
+"undo" = "ctrl+z"
+"redo" = "ctrl+shift+z"
+"copy that" = "ctrl+c"
+"cut that" = "ctrl+x"
+"paste that" = "ctrl+v"
+"delete that" = "backspace"
+"tab key" = "tab"
+"escape" = "escape"
+"#;
+
+        std::fs::write(path, default)?;
+        Ok(())
+    }
+}
+
+/// Lowercase words of `text` joined by single spaces, without punctuation,
+/// so "Select all." and "select  all" compare equal.
+fn normalize(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine() -> CommandEngine {
+        CommandEngine::from_pairs([
+            ("select all", "ctrl+a"),
+            ("new line", "enter"),
+            ("undo", "ctrl+z"),
+            ("broken", "ctrl+nope"),
+        ])
+    }
+
+    #[test]
+    fn test_command_lookup_normalizes() {
+        let engine = engine();
+        assert_eq!(engine.len(), 3);
+        assert_eq!(engine.lookup("Select all.").unwrap().keys, "ctrl+a");
+        assert_eq!(engine.lookup("  NEW   line!").unwrap().keys, "enter");
+        assert!(engine.lookup("select all of it").is_none());
+        assert!(engine.lookup("broken").is_none());
+    }
+
+    #[test]
+    fn test_command_match_modes() {
+        let engine = engine();
+        // Plain dictation is left alone unless command mode is on
+        assert_eq!(engine.match_text("Undo.", None, false), None);
+        assert!(matches!(
+            engine.match_text("Undo.", None, true),
+            Some(CommandMatch::Run(cmd)) if cmd.phrase == "undo"
+        ));
+        assert_eq!(engine.match_text("Undo the change.", None, true), None);
+
+        // The prefix forces command handling, even for unknown phrases
+        assert!(matches!(
+            engine.match_text("Command, select all.", Some("command"), false),
+            Some(CommandMatch::Run(cmd)) if cmd.keys == "ctrl+a"
+        ));
+        assert_eq!(
+            engine.match_text("Command fly away", Some("command"), false),
+            Some(CommandMatch::Unknown("fly away".to_string()))
+        );
+        // The prefix must be whole words
+        assert_eq!(engine.match_text("Commander undo", Some("command"), false), None);
+        assert_eq!(engine.match_text("Commander undo", Some(" "), true), None);
+    }
+
+    #[test]
+    fn test_command_default_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tjvox/commands.toml");
+        let engine = CommandEngine::load(&path).unwrap();
+        assert!(path.exists());
+        assert_eq!(engine.lookup("select all").unwrap().keys, "ctrl+a");
+        assert_eq!(engine.lookup("delete that").unwrap().keys, "backspace");
+        assert!(!engine.is_empty());
+    }
+}
@@ -1,6 +1,7 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use tracing::debug;
 
@@ -21,6 +22,14 @@ pub struct Config {
     pub input: InputConfig,
     #[serde(default)]
     pub llm: LlmConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub api: ApiConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -29,10 +38,125 @@ pub struct AudioConfig {
     pub channels: u8,
     pub format: String,
     pub temp_dir: String,
+    /// How multi-channel input is mixed down to mono
+    #[serde(default)]
+    pub mono_mode: MonoMode,
+    /// Per-channel weights for `mono_mode = "weighted"`
+    #[serde(default)]
+    pub channel_weights: Vec<f32>,
+    /// Reject any `sample_rate` other than whisper's native 16 kHz
+    #[serde(default)]
+    pub strict_sample_rate: bool,
+    /// PipeWire `media.role` of the capture stream
+    #[serde(default = "default_media_role")]
+    pub media_role: String,
+    /// PipeWire `media.category` of the capture stream
+    #[serde(default = "default_media_category")]
+    pub media_category: String,
+    /// Warn after a recording when more than this fraction of samples hit
+    /// full scale. 0 disables the warning.
+    #[serde(default = "default_clip_warning_fraction")]
+    pub clip_warning_fraction: f32,
+    /// Silence put in front of each recording before transcription, so
+    /// whisper does not miss the first phoneme
+    #[serde(default)]
+    pub preroll_ms: u32,
+    /// Captured audio dropped from the start of each recording, removing the
+    /// click or pop some devices produce when the stream connects
+    #[serde(default)]
+    pub trim_start_ms: u32,
+    /// Record at the device's own rate instead of asking PipeWire for
+    /// `sample_rate`, and convert to 16 kHz with `resample_quality` when
+    /// transcribing. Live monitors (mic test, wake word) keep `sample_rate`.
+    #[serde(default)]
+    pub capture_native: bool,
+    /// Resampler used when audio is not at whisper's 16 kHz
+    #[serde(default)]
+    pub resample_quality: ResampleQuality,
+    /// Keep dictations in memory and transcribe them from there instead of
+    /// writing a WAV file to `temp_dir`
+    #[serde(default)]
+    pub in_memory: bool,
+    /// Bits per sample of recorded WAV files: 16 (integer PCM, half the
+    /// size) or 32 (float)
+    #[serde(default = "default_bit_depth")]
+    pub bit_depth: u16,
+    /// Record from PipeWire's echo-cancelled source when one exists, so
+    /// audio playing from the speakers is not transcribed
+    #[serde(default)]
+    pub echo_cancel: bool,
+}
+
+fn default_media_role() -> String {
+    "Communication".to_string()
+}
+
+fn default_media_category() -> String {
+    "Capture".to_string()
+}
+
+fn default_bit_depth() -> u16 {
+    16
+}
+
+fn default_clip_warning_fraction() -> f32 {
+    0.001
+}
+
+/// Sample rate whisper models expect; anything else is resampled on load.
+pub const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+impl AudioConfig {
+    /// Whether recordings will need resampling before transcription.
+    pub fn needs_resampling(&self) -> bool {
+        self.sample_rate != WHISPER_SAMPLE_RATE
+    }
+}
+
+/// Stereo-to-mono mixing strategy for capture and WAV loading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MonoMode {
+    /// Mean of all channels
+    #[default]
+    Average,
+    /// First channel only
+    Left,
+    /// Second channel only
+    Right,
+    /// Weighted sum using `channel_weights`
+    Weighted,
+}
+
+/// Conversion used when audio has to be resampled to 16 kHz.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ResampleQuality {
+    /// Linear interpolation; cheap, but aliases when downsampling
+    Fast,
+    /// Windowed-sinc low-pass interpolation
+    #[default]
+    High,
+}
+
+/// Speech-to-text engine used for dictation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptionEngine {
+    #[default]
+    Whisper,
+    /// Vosk (Kaldi) models; much lighter than whisper but without
+    /// punctuation. Needs the `vosk` build feature.
+    Vosk,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TranscriptionConfig {
+    #[serde(default)]
+    pub engine: TranscriptionEngine,
+    /// Unpacked Vosk model directory for `engine = "vosk"`
+    #[serde(default)]
+    pub vosk_model: Option<String>,
     pub model: String,
     #[serde(default = "default_models_dir")]
     pub models_dir: String,
@@ -40,6 +164,145 @@ pub struct TranscriptionConfig {
     pub threads: Option<u32>,
     #[serde(default)]
     pub remove_filler_words: bool,
+    /// Punctuate at pauses between segments, for models that leave
+    /// punctuation out: a comma after `pause_comma_ms`, a sentence break
+    /// after `pause_period_ms`
+    #[serde(default)]
+    pub auto_punctuate: bool,
+    #[serde(default = "default_pause_comma_ms")]
+    pub pause_comma_ms: u64,
+    #[serde(default = "default_pause_period_ms")]
+    pub pause_period_ms: u64,
+    /// Results that are just one of these phrases (ignoring case and
+    /// punctuation) are treated as no speech
+    #[serde(default = "default_junk_phrases")]
+    pub junk_phrases: Vec<String>,
+    /// Treat results with no words at all, such as ".", as no speech
+    #[serde(default = "default_true")]
+    pub drop_punctuation_only: bool,
+    /// Don't feed previous segment text back as decoder context (whisper's default)
+    #[serde(default = "default_true")]
+    pub no_context: bool,
+    /// Force the whole clip to be decoded as a single segment
+    #[serde(default)]
+    pub single_segment: bool,
+    /// Split segments longer than this many characters (0 = no limit).
+    /// Turns on whisper's token timestamps, which the split relies on.
+    #[serde(default)]
+    pub max_segment_len: i32,
+    /// Candidates sampled by the greedy decoder, keeping the most likely;
+    /// more is slower but can fix misheard words
+    #[serde(default = "default_best_of")]
+    pub best_of: i32,
+    /// Model overrides keyed by language code (e.g. `de = "large-v3-turbo"`)
+    #[serde(default)]
+    pub language_models: HashMap<String, String>,
+    /// Run whisper on the GPU when the binary has a GPU backend compiled in
+    #[serde(default = "default_true")]
+    pub use_gpu: bool,
+    /// Proxy for model downloads (e.g. `http://proxy:3128`). Without it the
+    /// standard `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY` variables are used.
+    #[serde(default)]
+    pub download_proxy: Option<String>,
+    /// Give up if the model server cannot be reached within this many seconds
+    #[serde(default = "default_download_connect_timeout_secs")]
+    pub download_connect_timeout_secs: u64,
+    /// Give up if a model download receives no data for this many seconds
+    #[serde(default = "default_download_read_timeout_secs")]
+    pub download_read_timeout_secs: u64,
+    /// Recordings that can wait while another one is transcribed; further
+    /// recordings are refused until the queue drains. 0 disables queueing.
+    #[serde(default = "default_max_queued")]
+    pub max_queued: usize,
+    /// Larger model that re-transcribes results below `min_confidence`
+    #[serde(default)]
+    pub escalate_model: Option<String>,
+    /// Mean token probability (0–1) below which `escalate_model` is used
+    #[serde(default = "default_min_confidence")]
+    pub min_confidence: f32,
+    /// Treat results as no speech when whisper's mean no-speech probability
+    /// (0–1) is above this, even if it decoded some text. 1 disables it.
+    #[serde(default = "default_no_speech_threshold")]
+    pub no_speech_threshold: f32,
+    /// Press the keys of a `commands_file` entry instead of typing when a
+    /// whole utterance is one of its phrases
+    #[serde(default)]
+    pub command_mode: bool,
+    /// Utterances starting with this phrase are always run as commands,
+    /// e.g. "command select all", whether or not `command_mode` is on
+    #[serde(default)]
+    pub command_prefix: Option<String>,
+    /// Spoken phrase → key sequence mapping used by command mode
+    #[serde(default = "default_commands_file")]
+    pub commands_file: String,
+}
+
+impl TranscriptionConfig {
+    /// Whether any utterance can be run as a voice command.
+    pub fn commands_enabled(&self) -> bool {
+        self.command_mode || self.command_prefix.is_some()
+    }
+
+    /// Model to use for the given language, falling back to `model`.
+    pub fn model_for_language(&self, language: Option<&str>) -> &str {
+        language
+            .and_then(|lang| self.language_models.get(lang))
+            .map(String::as_str)
+            .unwrap_or(&self.model)
+    }
+}
+
+fn default_download_connect_timeout_secs() -> u64 {
+    15
+}
+
+fn default_download_read_timeout_secs() -> u64 {
+    60
+}
+
+fn default_junk_phrases() -> Vec<String> {
+    ["you", "thank you", "thanks for watching", "thank you for watching"]
+        .map(String::from)
+        .to_vec()
+}
+
+fn default_best_of() -> i32 {
+    1
+}
+
+/// Upper bound for `transcription.best_of`; each candidate is a full decode
+pub const MAX_BEST_OF: i32 = 8;
+
+fn default_max_queued() -> usize {
+    3
+}
+
+fn default_min_confidence() -> f32 {
+    0.6
+}
+
+fn default_no_speech_threshold() -> f32 {
+    0.8
+}
+
+fn default_pause_comma_ms() -> u64 {
+    300
+}
+
+fn default_pause_period_ms() -> u64 {
+    800
+}
+
+fn default_commands_file() -> String {
+    dirs::config_dir()
+        .unwrap_or_else(|| {
+            std::env::var("HOME")
+                .map(|h| std::path::PathBuf::from(h).join(".config"))
+                .unwrap_or_else(|_| std::path::PathBuf::from("/tmp"))
+        })
+        .join("tjvox/commands.toml")
+        .to_string_lossy()
+        .to_string()
 }
 
 fn default_models_dir() -> String {
@@ -77,6 +340,48 @@ pub struct WhisperConfig {
     pub mode: WhisperMode,
 }
 
+/// How synthetic keystrokes are delivered to the focused window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputBackend {
+    /// ydotoold via /dev/uinput (works on KDE Plasma)
+    #[default]
+    Ydotool,
+    /// Wayland `zwp_virtual_keyboard_v1` protocol via wtype (wlroots compositors)
+    VirtualKeyboard,
+}
+
+/// A way to read and write the clipboard, tried in `output.clipboard_tools` order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClipboardTool {
+    /// wl-clipboard-rs in-process (wlroots data-control protocol)
+    Native,
+    /// `wl-copy`/`wl-paste` (standard Wayland data device, works on KDE)
+    WlClipboard,
+    /// `xclip`, for X11 sessions and XWayland-only setups
+    Xclip,
+}
+
+impl std::fmt::Display for ClipboardTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClipboardTool::Native => write!(f, "native"),
+            ClipboardTool::WlClipboard => write!(f, "wl-clipboard"),
+            ClipboardTool::Xclip => write!(f, "xclip"),
+        }
+    }
+}
+
+impl std::fmt::Display for OutputBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputBackend::Ydotool => write!(f, "ydotool"),
+            OutputBackend::VirtualKeyboard => write!(f, "virtual_keyboard"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OutputConfig {
     pub delay_ms: u64,
@@ -86,12 +391,88 @@ pub struct OutputConfig {
     pub append_trailing_space: bool,
     #[serde(default = "default_output_method")]
     pub method: String,
+    /// Destinations applied in order, e.g. `["type", "file:/path/log.txt"]`,
+    /// with at most one output method. Empty means just `method`.
+    #[serde(default)]
+    pub sinks: Vec<String>,
+    #[serde(default)]
+    pub backend: OutputBackend,
+    /// Never route text through the clipboard; always type it directly.
+    /// Clipboard-history daemons keep everything that is copied, so this
+    /// keeps dictated secrets out of them.
+    #[serde(default)]
+    pub sensitive: bool,
+    /// Window classes (case-insensitive substring match) treated as sensitive,
+    /// e.g. a password manager.
+    #[serde(default)]
+    pub sensitive_apps: Vec<String>,
+    /// Window classes (case-insensitive substring match) where Enter sends a
+    /// message; newlines are typed there as Shift+Enter instead.
+    #[serde(default = "default_shift_enter_apps")]
+    pub shift_enter_apps: Vec<String>,
+    /// Window classes (case-insensitive substring match) where the `auto`
+    /// and `paste` methods type instead, because a paste there can act on
+    /// its own, e.g. a browser navigating from the address bar
+    #[serde(default)]
+    pub type_apps: Vec<String>,
+    /// How newlines are output per window class (case-insensitive substring,
+    /// the longest match wins): `"keep"`, `"join"` (space), `"remove"`, or
+    /// keys pressed instead, e.g. `"escape o"`
+    #[serde(default)]
+    pub newline_strategy: HashMap<String, String>,
+    /// Truncate output beyond this many characters (history keeps the full text)
+    #[serde(default)]
+    pub max_output_chars: Option<usize>,
+    /// Append a "… [truncated]" marker when output is truncated
+    #[serde(default = "default_true")]
+    pub truncation_marker: bool,
+    /// Extra attempts when reading or writing the clipboard fails
+    #[serde(default = "default_clipboard_retries")]
+    pub clipboard_retries: u32,
+    /// Clipboard tools in the order they are tried
+    #[serde(default = "default_clipboard_tools")]
+    pub clipboard_tools: Vec<ClipboardTool>,
+    /// Leave the transcription on the clipboard after pasting instead of
+    /// restoring what was there before
+    #[serde(default)]
+    pub keep_on_clipboard: bool,
+    /// Keys pressed by the `paste` method instead of Ctrl+V, e.g.
+    /// `"shift+insert"` or `"ctrl+v ctrl+v"`
+    #[serde(default)]
+    pub paste_keys: Option<String>,
+    /// Focus the window that was active when recording started again before
+    /// output, for dictations started from a launcher or panel (KWin, Hyprland)
+    #[serde(default)]
+    pub refocus_previous: bool,
+    /// Command used to start ydotoold when it is not running, e.g.
+    /// `"ydotoold --socket-path /run/user/1000/.ydotool_socket"`
+    #[serde(default)]
+    pub ydotoold_cmd: Option<String>,
+    /// Start ydotoold with the daemon and stop it on shutdown, instead of
+    /// starting it detached on first use
+    #[serde(default)]
+    pub manage_ydotoold: bool,
 }
 
 fn default_paste_delay() -> u64 {
     50
 }
 
+fn default_shift_enter_apps() -> Vec<String> {
+    ["slack", "discord", "element", "signal", "telegram", "teams", "whatsapp"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_clipboard_tools() -> Vec<ClipboardTool> {
+    vec![ClipboardTool::Native, ClipboardTool::WlClipboard]
+}
+
+fn default_clipboard_retries() -> u32 {
+    2
+}
+
 fn default_output_method() -> String {
     "auto".to_string()
 }
@@ -100,6 +481,12 @@ fn default_output_method() -> String {
 pub struct UiConfig {
     pub show_notifications: bool,
     pub notification_timeout_ms: u64,
+    /// Include diagnostic details (e.g. the output method used) in notifications
+    #[serde(default)]
+    pub verbose: bool,
+    /// Show a notification with the percentage while transcription runs
+    #[serde(default)]
+    pub progress_notifications: bool,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -114,6 +501,46 @@ pub struct OverlayConfig {
     pub position: String,
     #[serde(default = "default_overlay_opacity")]
     pub opacity: f64,
+    /// Briefly show the current model and whisper mode when the overlay appears
+    #[serde(default)]
+    pub show_model: bool,
+    /// Waveform gain applied to the mic RMS level
+    #[serde(default = "default_overlay_gain")]
+    pub gain: f32,
+    /// Normalize the waveform to the recent peak level instead of a fixed gain
+    #[serde(default)]
+    pub auto_gain: bool,
+    /// Show the partial transcript instead of dots while transcribing
+    #[serde(default)]
+    pub show_partial: bool,
+    /// How quickly bars rise towards a louder level (0–1, 1 = instant)
+    #[serde(default = "default_overlay_attack")]
+    pub attack: f32,
+    /// How quickly bars fall towards a quieter level (0–1, 1 = instant)
+    #[serde(default = "default_overlay_decay")]
+    pub decay: f32,
+    /// Exponent applied to bar levels; below 1 boosts quiet speech, 1 is linear
+    #[serde(default = "default_overlay_compression")]
+    pub compression: f32,
+    /// Show the overlay automatically while recording and transcribing;
+    /// when off it only appears via `tjvox show-overlay`
+    #[serde(default = "default_true")]
+    pub auto_show: bool,
+    /// What the bars show while recording
+    #[serde(default)]
+    pub visualizer: Visualizer,
+}
+
+/// Overlay visualization of the microphone input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Visualizer {
+    /// Recent loudness, newest in the middle
+    #[default]
+    Amplitude,
+    /// Frequency spectrum of the latest samples, mirrored with the lowest
+    /// frequencies in the middle
+    Spectrum,
 }
 
 fn default_true() -> bool {
@@ -136,6 +563,22 @@ fn default_overlay_opacity() -> f64 {
     0.85
 }
 
+fn default_overlay_gain() -> f32 {
+    8.0
+}
+
+fn default_overlay_attack() -> f32 {
+    0.6
+}
+
+fn default_overlay_decay() -> f32 {
+    0.15
+}
+
+fn default_overlay_compression() -> f32 {
+    0.5
+}
+
 impl Default for OverlayConfig {
     fn default() -> Self {
         Self {
@@ -144,6 +587,15 @@ impl Default for OverlayConfig {
             height: 50,
             position: "bottom-center".to_string(),
             opacity: 0.85,
+            show_model: false,
+            gain: default_overlay_gain(),
+            auto_gain: false,
+            show_partial: false,
+            attack: default_overlay_attack(),
+            decay: default_overlay_decay(),
+            compression: default_overlay_compression(),
+            auto_show: true,
+            visualizer: Visualizer::default(),
         }
     }
 }
@@ -152,8 +604,18 @@ impl Default for OverlayConfig {
 pub struct ReplacementsConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
+    /// One rules file, or a list merged in order so a personal file can
+    /// add to or override a shared one
     #[serde(default = "default_replacements_file")]
-    pub file: String,
+    pub file: ReplacementFiles,
+    /// Remove spaces before punctuation and collapse repeated spaces, so
+    /// "word comma" becomes "word," rather than "word ,"
+    #[serde(default = "default_true")]
+    pub normalize_spacing: bool,
+    /// Capitalize the word after sentence-ending punctuation, including
+    /// punctuation inserted by replacement rules
+    #[serde(default)]
+    pub smart_formatting: bool,
 }
 
 impl Default for ReplacementsConfig {
@@ -161,11 +623,41 @@ impl Default for ReplacementsConfig {
         Self {
             enabled: true,
             file: default_replacements_file(),
+            normalize_spacing: true,
+            smart_formatting: false,
+        }
+    }
+}
+
+/// `replacements.file`: a single path or a list of paths.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ReplacementFiles {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl ReplacementFiles {
+    /// All files in the order their rules are merged
+    pub fn paths(&self) -> Vec<PathBuf> {
+        match self {
+            Self::One(path) => vec![PathBuf::from(path)],
+            Self::Many(paths) => paths.iter().map(PathBuf::from).collect(),
         }
     }
+
+    /// The last file, whose rules win; the one `replacements-edit` and
+    /// `replacements-reset` work on
+    pub fn personal(&self) -> Option<PathBuf> {
+        self.paths().pop()
+    }
+}
+
+fn default_replacements_file() -> ReplacementFiles {
+    ReplacementFiles::One(default_replacements_path())
 }
 
-fn default_replacements_file() -> String {
+fn default_replacements_path() -> String {
     dirs::config_dir()
         .unwrap_or_else(|| {
             std::env::var("HOME")
@@ -181,6 +673,7 @@ fn default_replacements_file() -> String {
 pub struct HistoryConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
+    /// Number of entries to keep; 0 keeps everything
     #[serde(default = "default_max_entries")]
     pub max_entries: u32,
 }
@@ -198,10 +691,160 @@ fn default_max_entries() -> u32 {
     1000
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+/// Which edge of the push-to-talk key starts recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PttTrigger {
+    /// Press starts, release stops (hold to talk)
+    #[default]
+    Down,
+    /// Presses are ignored; each release starts or stops recording
+    Up,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct InputConfig {
     #[serde(default)]
     pub ptt_key: Option<String>,
+    /// Key edge that starts a push-to-talk recording
+    #[serde(default)]
+    pub ptt_trigger: PttTrigger,
+    /// Two push-to-talk taps within this window latch continuous recording
+    /// (0 disables double-tap)
+    #[serde(default)]
+    pub double_tap_ms: u64,
+    /// Phrase that starts dictation hands-free (needs the `wake-word` feature).
+    /// Unset keeps the microphone closed while idle.
+    #[serde(default)]
+    pub wake_word: Option<String>,
+    /// Whisper model used to listen for the wake word
+    #[serde(default = "default_wake_word_model")]
+    pub wake_word_model: String,
+    /// Silence after speech that ends a wake-word dictation
+    #[serde(default = "default_wake_word_silence_ms")]
+    pub wake_word_silence_ms: u64,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            ptt_key: None,
+            ptt_trigger: PttTrigger::Down,
+            double_tap_ms: 0,
+            wake_word: None,
+            wake_word_model: default_wake_word_model(),
+            wake_word_silence_ms: default_wake_word_silence_ms(),
+        }
+    }
+}
+
+fn default_wake_word_model() -> String {
+    "tiny".to_string()
+}
+
+fn default_wake_word_silence_ms() -> u64 {
+    1500
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct HooksConfig {
+    /// Command run after each successful output. `{text}` is replaced with
+    /// the transcription as a single argument; the text is also sent on stdin.
+    #[serde(default)]
+    pub post_transcription: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct DaemonConfig {
+    /// Pause after each output before another recording may start; toggles
+    /// during the pause are held and start the recording once it ends
+    #[serde(default)]
+    pub cooldown_ms: u64,
+    /// Exit after this long without a recording (0 keeps running)
+    #[serde(default)]
+    pub idle_exit_ms: u64,
+    /// What bare `tjvox` (no subcommand) starts
+    #[serde(default)]
+    pub default_command: DefaultCommand,
+    /// Directories `transcribe` socket requests may read audio from; empty
+    /// allows any file the daemon's user can read
+    #[serde(default)]
+    pub transcribe_dirs: Vec<String>,
+}
+
+impl DaemonConfig {
+    /// Whether a `transcribe` request may read `path`, which must already be
+    /// canonical so `..` and symlinks cannot escape the allowed directories.
+    pub fn transcribe_path_allowed(&self, path: &std::path::Path) -> bool {
+        self.transcribe_dirs.is_empty()
+            || self.transcribe_dirs.iter().any(|dir| {
+                std::path::Path::new(dir)
+                    .canonicalize()
+                    .is_ok_and(|dir| path.starts_with(dir))
+            })
+    }
+}
+
+/// Mode started by `tjvox` without a subcommand. Defaults to the GUI in
+/// builds with the `gui` feature and to the headless daemon otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DefaultCommand {
+    #[cfg_attr(feature = "gui", default)]
+    Gui,
+    #[cfg_attr(not(feature = "gui"), default)]
+    Daemon,
+}
+
+/// Prometheus-style counters served over HTTP (needs the `metrics` feature)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MetricsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Address of the endpoint; keep it on localhost unless the counters
+    /// should be reachable from other machines
+    #[serde(default = "default_metrics_listen")]
+    pub listen: String,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: default_metrics_listen(),
+        }
+    }
+}
+
+fn default_metrics_listen() -> String {
+    "127.0.0.1:9464".to_string()
+}
+
+/// HTTP control API on localhost (needs the `api` feature)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApiConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Loopback address to serve on
+    #[serde(default = "default_api_listen")]
+    pub listen: String,
+    /// Required as `Authorization: Bearer <token>` when set
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: default_api_listen(),
+            token: None,
+        }
+    }
+}
+
+fn default_api_listen() -> String {
+    "127.0.0.1:9465".to_string()
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -214,10 +857,25 @@ pub struct LlmConfig {
     pub api_key: String,
     #[serde(default = "default_llm_model")]
     pub model: String,
+    /// System prompt, or `file:/path/prompt.txt` to read it from a file
     #[serde(default = "default_llm_prompt")]
     pub prompt: String,
     #[serde(default = "default_llm_timeout_ms")]
     pub timeout_ms: u64,
+    /// After a timeout, send the request once more with this longer
+    /// deadline before falling back to the raw text (0 = no retry)
+    #[serde(default)]
+    pub timeout_retry_ms: u64,
+    /// Example corrections sent ahead of the dictation as earlier turns
+    #[serde(default)]
+    pub examples: Vec<LlmExample>,
+}
+
+/// A few-shot example: dictated `input` and the correction expected for it
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct LlmExample {
+    pub input: String,
+    pub output: String,
 }
 
 fn default_llm_endpoint() -> String {
@@ -236,6 +894,9 @@ fn default_llm_timeout_ms() -> u64 {
     5000
 }
 
+/// Longest `llm.timeout_ms` / `llm.timeout_retry_ms`, for large local models
+pub const MAX_LLM_TIMEOUT_MS: u64 = 600_000;
+
 impl Default for LlmConfig {
     fn default() -> Self {
         Self {
@@ -245,6 +906,8 @@ impl Default for LlmConfig {
             model: default_llm_model(),
             prompt: default_llm_prompt(),
             timeout_ms: default_llm_timeout_ms(),
+            timeout_retry_ms: 0,
+            examples: Vec::new(),
         }
     }
 }
@@ -260,13 +923,49 @@ impl Default for Config {
                     .join("tjvox")
                     .to_string_lossy()
                     .to_string(),
+                mono_mode: MonoMode::Average,
+                channel_weights: Vec::new(),
+                strict_sample_rate: false,
+                media_role: default_media_role(),
+                media_category: default_media_category(),
+                clip_warning_fraction: default_clip_warning_fraction(),
+                preroll_ms: 0,
+                trim_start_ms: 0,
+                capture_native: false,
+                in_memory: false,
+                bit_depth: default_bit_depth(),
+                resample_quality: ResampleQuality::High,
+                echo_cancel: false,
             },
             transcription: TranscriptionConfig {
+                engine: TranscriptionEngine::Whisper,
+                vosk_model: None,
                 model: "base".to_string(),
                 models_dir: default_models_dir(),
                 language: Some("en".to_string()),
                 threads: None,
                 remove_filler_words: false,
+                auto_punctuate: false,
+                pause_comma_ms: default_pause_comma_ms(),
+                pause_period_ms: default_pause_period_ms(),
+                junk_phrases: default_junk_phrases(),
+                drop_punctuation_only: true,
+                no_context: true,
+                single_segment: false,
+                max_segment_len: 0,
+                best_of: default_best_of(),
+                language_models: HashMap::new(),
+                use_gpu: true,
+                download_proxy: None,
+                download_connect_timeout_secs: default_download_connect_timeout_secs(),
+                download_read_timeout_secs: default_download_read_timeout_secs(),
+                max_queued: default_max_queued(),
+                escalate_model: None,
+                min_confidence: default_min_confidence(),
+                no_speech_threshold: default_no_speech_threshold(),
+                command_mode: false,
+                command_prefix: None,
+                commands_file: default_commands_file(),
             },
             whisper: WhisperConfig {
                 mode: WhisperMode::Cold,
@@ -276,36 +975,97 @@ impl Default for Config {
                 paste_delay_ms: 50,
                 append_trailing_space: true,
                 method: "auto".to_string(),
+                backend: OutputBackend::Ydotool,
+                sensitive: false,
+                sensitive_apps: Vec::new(),
+                shift_enter_apps: default_shift_enter_apps(),
+                type_apps: Vec::new(),
+                newline_strategy: HashMap::new(),
+                sinks: Vec::new(),
+                max_output_chars: None,
+                truncation_marker: true,
+                clipboard_retries: default_clipboard_retries(),
+                clipboard_tools: default_clipboard_tools(),
+                keep_on_clipboard: false,
+                paste_keys: None,
+                refocus_previous: false,
+                ydotoold_cmd: None,
+                manage_ydotoold: false,
             },
             ui: UiConfig {
                 show_notifications: true,
                 notification_timeout_ms: 3000,
+                verbose: false,
+                progress_notifications: false,
             },
             overlay: OverlayConfig::default(),
             replacements: ReplacementsConfig::default(),
             history: HistoryConfig::default(),
             input: InputConfig::default(),
             llm: LlmConfig::default(),
+            hooks: HooksConfig::default(),
+            daemon: DaemonConfig::default(),
+            metrics: MetricsConfig::default(),
+            api: ApiConfig::default(),
+        }
+    }
+}
+
+/// Admin-provided settings that the user's config is layered over
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/tjvox/config.toml";
+
+/// Recursively overlay `top` onto `base`: tables are merged key by key, any
+/// other value (including arrays) in `top` replaces the one in `base`.
+fn merge_toml(base: &mut toml::Value, top: toml::Value) {
+    match (base, top) {
+        (toml::Value::Table(base), toml::Value::Table(top)) => {
+            for (key, value) in top {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
         }
+        (base, top) => *base = top,
     }
 }
 
 impl Config {
+    /// Load the configuration from built-in defaults, then
+    /// `/etc/tjvox/config.toml`, then the user's file at `path`; later layers
+    /// override earlier ones field by field.
     pub async fn load(path: &Path) -> Result<Self> {
+        Self::load_layered(Path::new(SYSTEM_CONFIG_PATH), path).await
+    }
+
+    async fn load_layered(system_path: &Path, path: &Path) -> Result<Self> {
         // Validate config path
         Self::validate_config_path(path)?;
-        
-        if !path.exists() {
+
+        let system_exists = system_path.exists();
+        if !path.exists() && !system_exists {
             debug!("Config file not found at {:?}, creating default", path);
             let config = Self::default();
             config.save(path).await?;
             return Ok(config);
         }
 
-        let content = fs::read_to_string(path).await?;
-        let config: Config = toml::from_str(&content)?;
+        let mut merged = toml::Value::try_from(Self::default())?;
+        for layer in [system_path, path] {
+            if !layer.exists() {
+                continue;
+            }
+            debug!("Loading config layer {:?}", layer);
+            let content = fs::read_to_string(layer).await?;
+            let value: toml::Value = toml::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", layer.display()))?;
+            merge_toml(&mut merged, value);
+        }
+        let config: Config = merged.try_into()?;
         
-        // Validate loaded config values
+        // Validate the merged config values
         config.validate()?;
         
         Ok(config)
@@ -335,32 +1095,284 @@ impl Config {
         if self.audio.channels == 0 {
             return Err(TjvoxError::Config("channels must be greater than 0".to_string()).into());
         }
-        
-        // Validate temp_dir doesn't contain path traversal
-        if self.audio.temp_dir.contains("..") {
+        if self.audio.strict_sample_rate && self.audio.needs_resampling() {
+            return Err(TjvoxError::Config(format!(
+                "sample_rate is {} Hz but whisper needs {} Hz (strict_sample_rate is set)",
+                self.audio.sample_rate, WHISPER_SAMPLE_RATE
+            )).into());
+        }
+        if self.audio.strict_sample_rate && self.audio.capture_native {
             return Err(TjvoxError::Config(
-                "temp_dir cannot contain path traversal sequences".to_string()
+                "capture_native cannot be combined with strict_sample_rate".to_string()
             ).into());
         }
-        
-        // Validate output config
-        if self.output.paste_delay_ms > 10000 {
+        if self.audio.in_memory && self.audio.capture_native {
             return Err(TjvoxError::Config(
-                "paste_delay_ms cannot exceed 10000ms".to_string()
+                "in_memory records at sample_rate and cannot be combined with capture_native"
+                    .to_string(),
             ).into());
         }
-        
-        // Validate overlay config
-        if self.overlay.width < 50 || self.overlay.width > 1000 {
+        if !matches!(self.audio.bit_depth, 16 | 32) {
+            return Err(TjvoxError::Config(format!(
+                "bit_depth must be 16 or 32, got {}",
+                self.audio.bit_depth
+            )).into());
+        }
+        if self.audio.media_role.trim().is_empty() || self.audio.media_category.trim().is_empty() {
             return Err(TjvoxError::Config(
-                "overlay width must be between 50 and 1000".to_string()
+                "media_role and media_category must not be empty".to_string(),
             ).into());
         }
-        if self.overlay.height < 20 || self.overlay.height > 200 {
+        if self.audio.preroll_ms > 2000 {
+            return Err(TjvoxError::Config(
+                "preroll_ms must be at most 2000".to_string(),
+            ).into());
+        }
+        if self.audio.trim_start_ms > 1000 {
+            return Err(TjvoxError::Config(
+                "trim_start_ms must be at most 1000".to_string(),
+            ).into());
+        }
+        if !(0.0..=1.0).contains(&self.audio.clip_warning_fraction) {
+            return Err(TjvoxError::Config(
+                "clip_warning_fraction must be between 0 and 1".to_string(),
+            ).into());
+        }
+        if self.audio.mono_mode == MonoMode::Weighted
+            && self.audio.channel_weights.len() != self.audio.channels as usize
+        {
+            return Err(TjvoxError::Config(format!(
+                "mono_mode = \"weighted\" needs one channel_weights entry per channel ({})",
+                self.audio.channels
+            )).into());
+        }
+        
+        // Validate temp_dir doesn't contain path traversal
+        if self.audio.temp_dir.contains("..") {
+            return Err(TjvoxError::Config(
+                "temp_dir cannot contain path traversal sequences".to_string()
+            ).into());
+        }
+        
+        if self.transcription.download_connect_timeout_secs == 0
+            || self.transcription.download_read_timeout_secs == 0
+        {
+            return Err(TjvoxError::Config(
+                "download timeouts must be greater than 0".to_string()
+            ).into());
+        }
+        if self.transcription.max_segment_len < 0 {
+            return Err(TjvoxError::Config(
+                "max_segment_len must not be negative (0 disables it)".to_string()
+            ).into());
+        }
+        if self.transcription.pause_comma_ms > self.transcription.pause_period_ms {
+            return Err(TjvoxError::Config(
+                "pause_comma_ms must not be longer than pause_period_ms".to_string()
+            ).into());
+        }
+        if !(1..=MAX_BEST_OF).contains(&self.transcription.best_of) {
+            return Err(TjvoxError::Config(format!(
+                "best_of must be between 1 and {}",
+                MAX_BEST_OF
+            )).into());
+        }
+        if let Some(ref proxy) = self.transcription.download_proxy {
+            reqwest::Proxy::all(proxy.as_str()).map_err(|e| {
+                TjvoxError::Config(format!("Invalid download_proxy '{}': {}", proxy, e))
+            })?;
+        }
+        if self.llm.prompt.trim() == "file:" {
+            return Err(TjvoxError::Config(
+                "llm.prompt 'file:' needs a path".to_string()
+            ).into());
+        }
+        if self.llm.examples.iter().any(|e| e.input.trim().is_empty() || e.output.trim().is_empty()) {
+            return Err(TjvoxError::Config(
+                "llm.examples entries need both input and output".to_string()
+            ).into());
+        }
+        if self.transcription.engine == TranscriptionEngine::Vosk
+            && !self.transcription.vosk_model.as_deref().is_some_and(|m| !m.trim().is_empty())
+        {
+            return Err(TjvoxError::Config(
+                "engine = \"vosk\" needs transcription.vosk_model (a Vosk model directory)".to_string()
+            ).into());
+        }
+        if !(0.0..=1.0).contains(&self.transcription.min_confidence) {
+            return Err(TjvoxError::Config(
+                "min_confidence must be between 0 and 1".to_string()
+            ).into());
+        }
+        if !(0.0..=1.0).contains(&self.transcription.no_speech_threshold) {
+            return Err(TjvoxError::Config(
+                "no_speech_threshold must be between 0 and 1".to_string()
+            ).into());
+        }
+        if self
+            .transcription
+            .command_prefix
+            .as_deref()
+            .is_some_and(|p| !p.chars().any(char::is_alphanumeric))
+        {
+            return Err(TjvoxError::Config(
+                "command_prefix must contain a word".to_string()
+            ).into());
+        }
+        if self.transcription.escalate_model.as_deref().is_some_and(|m| m.trim().is_empty()) {
+            return Err(TjvoxError::Config(
+                "escalate_model must not be empty".to_string()
+            ).into());
+        }
+        if self.transcription.max_queued > 20 {
+            return Err(TjvoxError::Config(
+                "max_queued must be at most 20".to_string()
+            ).into());
+        }
+        for (lang, model) in &self.transcription.language_models {
+            if model.is_empty() {
+                return Err(TjvoxError::Config(
+                    format!("language_models entry for '{}' has an empty model name", lang)
+                ).into());
+            }
+        }
+
+        // Validate output config
+        if self.output.paste_delay_ms > 10000 {
+            return Err(TjvoxError::Config(
+                "paste_delay_ms cannot exceed 10000ms".to_string()
+            ).into());
+        }
+        if self.output.max_output_chars == Some(0) {
+            return Err(TjvoxError::Config(
+                "max_output_chars must be greater than 0".to_string()
+            ).into());
+        }
+        let mut method_sinks = 0;
+        for sink in &self.output.sinks {
+            let sink = crate::output::parse_sink(sink)
+                .map_err(|e| TjvoxError::Config(format!("Invalid output sink: {}", e)))?;
+            if matches!(sink, crate::output::OutputSink::Method(_)) {
+                method_sinks += 1;
+            }
+        }
+        if method_sinks > 1 {
+            return Err(TjvoxError::Config(
+                "output.sinks may contain at most one output method".to_string()
+            ).into());
+        }
+        if let Some(ref keys) = self.output.paste_keys {
+            crate::output::parse_paste_keys(keys)
+                .map_err(|e| TjvoxError::Config(format!("Invalid output.paste_keys: {}", e)))?;
+        }
+        for (class, strategy) in &self.output.newline_strategy {
+            crate::output::NewlineStrategy::parse(strategy).map_err(|e| {
+                TjvoxError::Config(format!("Invalid output.newline_strategy for '{}': {}", class, e))
+            })?;
+        }
+        if self.output.clipboard_tools.is_empty() {
+            return Err(TjvoxError::Config(
+                "clipboard_tools must list at least one tool".to_string()
+            ).into());
+        }
+        if self.output.clipboard_retries > 10 {
+            return Err(TjvoxError::Config(
+                "clipboard_retries cannot exceed 10".to_string()
+            ).into());
+        }
+        
+        if self.input.double_tap_ms > 1000 {
+            return Err(TjvoxError::Config(
+                "double_tap_ms cannot exceed 1000ms".to_string()
+            ).into());
+        }
+        if let Some(ref phrase) = self.input.wake_word {
+            if phrase.split_whitespace().next().is_none() {
+                return Err(TjvoxError::Config(
+                    "wake_word cannot be empty; remove it to disable the wake word".to_string()
+                ).into());
+            }
+            if self.input.wake_word_model.is_empty() {
+                return Err(TjvoxError::Config(
+                    "wake_word_model cannot be empty".to_string()
+                ).into());
+            }
+            if self.input.wake_word_silence_ms < 300 || self.input.wake_word_silence_ms > 10000 {
+                return Err(TjvoxError::Config(
+                    "wake_word_silence_ms must be between 300 and 10000".to_string()
+                ).into());
+            }
+        }
+
+        // Validate hooks
+        if let Some(ref cmd) = self.hooks.post_transcription {
+            crate::hooks::build_args(cmd, "").map_err(|e| {
+                TjvoxError::Config(format!("hooks.post_transcription: {}", e))
+            })?;
+        }
+
+        if let Some(ref cmd) = self.output.ydotoold_cmd {
+            match crate::hooks::split_command(cmd) {
+                Ok(args) if args.is_empty() => {
+                    return Err(TjvoxError::Config("output.ydotoold_cmd is empty".to_string()).into());
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    return Err(TjvoxError::Config(format!("output.ydotoold_cmd: {}", e)).into());
+                }
+            }
+        }
+
+        if self.metrics.enabled && self.metrics.listen.parse::<std::net::SocketAddr>().is_err() {
+            return Err(TjvoxError::Config(format!(
+                "metrics.listen must be an address such as 127.0.0.1:9464, got '{}'",
+                self.metrics.listen
+            )).into());
+        }
+
+        if self.api.enabled {
+            match self.api.listen.parse::<std::net::SocketAddr>() {
+                Ok(addr) if addr.ip().is_loopback() => {}
+                _ => {
+                    return Err(TjvoxError::Config(format!(
+                        "api.listen must be a loopback address such as 127.0.0.1:9465, got '{}'",
+                        self.api.listen
+                    )).into());
+                }
+            }
+            if self.api.token.as_deref().is_some_and(|token| token.trim().is_empty()) {
+                return Err(TjvoxError::Config("api.token must not be empty".to_string()).into());
+            }
+        }
+
+        // Validate overlay config
+        if self.overlay.width < 50 || self.overlay.width > 1000 {
+            return Err(TjvoxError::Config(
+                "overlay width must be between 50 and 1000".to_string()
+            ).into());
+        }
+        if self.overlay.height < 20 || self.overlay.height > 200 {
             return Err(TjvoxError::Config(
                 "overlay height must be between 20 and 200".to_string()
             ).into());
         }
+        if !(self.overlay.gain > 0.0 && self.overlay.gain <= 100.0) {
+            return Err(TjvoxError::Config(
+                "overlay gain must be greater than 0 and at most 100".to_string()
+            ).into());
+        }
+        for (name, value) in [
+            ("attack", self.overlay.attack),
+            ("decay", self.overlay.decay),
+            ("compression", self.overlay.compression),
+        ] {
+            if !(value > 0.0 && value <= 1.0) {
+                return Err(TjvoxError::Config(format!(
+                    "overlay {} must be greater than 0 and at most 1",
+                    name
+                )).into());
+            }
+        }
         if self.overlay.opacity < 0.0 || self.overlay.opacity > 1.0 {
             return Err(TjvoxError::Config(
                 "overlay opacity must be between 0.0 and 1.0".to_string()
@@ -379,24 +1391,45 @@ impl Config {
                     "LLM model cannot be empty when enabled".to_string()
                 ).into());
             }
-            if self.llm.timeout_ms < 1000 || self.llm.timeout_ms > 30000 {
-                return Err(TjvoxError::Config(
-                    "LLM timeout_ms must be between 1000 and 30000".to_string()
-                ).into());
+            if self.llm.timeout_ms < 1000 || self.llm.timeout_ms > MAX_LLM_TIMEOUT_MS {
+                return Err(TjvoxError::Config(format!(
+                    "LLM timeout_ms must be between 1000 and {}",
+                    MAX_LLM_TIMEOUT_MS
+                )).into());
+            }
+            let retry = self.llm.timeout_retry_ms;
+            if retry > 0 && (retry <= self.llm.timeout_ms || retry > MAX_LLM_TIMEOUT_MS) {
+                return Err(TjvoxError::Config(format!(
+                    "LLM timeout_retry_ms must be 0 or longer than timeout_ms, up to {}",
+                    MAX_LLM_TIMEOUT_MS
+                )).into());
             }
         }
 
-        // Validate history config
-        if self.history.max_entries == 0 {
+        if self.replacements.file.paths().is_empty() {
             return Err(TjvoxError::Config(
-                "max_entries must be greater than 0".to_string()
+                "replacements.file must name at least one file".to_string()
             ).into());
         }
+
+        // Validate history config
+        // max_entries == 0 means unlimited history
         if self.history.max_entries > 100000 {
             return Err(TjvoxError::Config(
                 "max_entries cannot exceed 100000".to_string()
             ).into());
         }
+
+        if self.daemon.cooldown_ms > 10000 {
+            return Err(TjvoxError::Config(
+                "daemon.cooldown_ms must be at most 10000".to_string()
+            ).into());
+        }
+        if self.daemon.idle_exit_ms > 0 && self.daemon.idle_exit_ms < 60000 {
+            return Err(TjvoxError::Config(
+                "daemon.idle_exit_ms must be 0 (disabled) or at least 60000".to_string()
+            ).into());
+        }
         
         Ok(())
     }
@@ -445,6 +1478,50 @@ mod tests {
         assert_eq!(loaded.transcription.model, config.transcription.model);
     }
 
+    #[tokio::test]
+    async fn test_config_layered_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let system_path = temp_dir.path().join("system.toml");
+        let user_path = temp_dir.path().join("user.toml");
+
+        std::fs::write(
+            &system_path,
+            "[transcription]\nmodel = \"small\"\nlanguage = \"de\"\n\n[output]\nsinks = [\"type\", \"stdout\"]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &user_path,
+            "[transcription]\nmodel = \"tiny\"\n\n[output]\nsinks = [\"type\"]\n",
+        )
+        .unwrap();
+
+        let config = Config::load_layered(&system_path, &user_path).await.unwrap();
+        // User value wins, the admin's other fields in the same table remain
+        assert_eq!(config.transcription.model, "tiny");
+        assert_eq!(config.transcription.language.as_deref(), Some("de"));
+        // Arrays are replaced, not concatenated
+        assert_eq!(config.output.sinks, vec!["type".to_string()]);
+        // Fields set nowhere come from the defaults
+        assert_eq!(config.audio.sample_rate, 16000);
+    }
+
+    #[tokio::test]
+    async fn test_config_layered_load_without_user_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let system_path = temp_dir.path().join("system.toml");
+        let user_path = temp_dir.path().join("user.toml");
+        std::fs::write(&system_path, "[whisper]\nmode = \"hot\"\n").unwrap();
+
+        let config = Config::load_layered(&system_path, &user_path).await.unwrap();
+        assert_eq!(config.whisper.mode, WhisperMode::Hot);
+        // A default user file would shadow every admin setting
+        assert!(!user_path.exists());
+
+        // The merged result is validated
+        std::fs::write(&user_path, "[audio]\nsample_rate = 0\n").unwrap();
+        assert!(Config::load_layered(&system_path, &user_path).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_config_validation_invalid_sample_rate() {
         let mut config = Config::default();
@@ -473,6 +1550,567 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[tokio::test]
+    async fn test_config_validation_max_output_chars() {
+        let mut config = Config::default();
+        config.output.max_output_chars = Some(0);
+        assert!(config.validate().is_err());
+
+        config.output.max_output_chars = Some(500);
+        assert!(config.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_output_sinks() {
+        let mut config = Config::default();
+        assert!(config.output.sinks.is_empty());
+        config.output.sinks = vec!["type".to_string(), "file:/tmp/tjvox.log".to_string()];
+        assert!(config.validate().is_ok());
+        config.output.sinks.push("printer".to_string());
+        assert!(config.validate().is_err());
+        // Two methods would type the text twice
+        config.output.sinks = vec!["type".to_string(), "clipboard".to_string()];
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_clipboard_retries() {
+        let mut config = Config::default();
+        assert_eq!(config.output.clipboard_retries, 2);
+        config.output.clipboard_retries = 0;
+        assert!(config.validate().is_ok());
+        config.output.clipboard_retries = 50;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_keep_on_clipboard() {
+        assert!(!Config::default().output.keep_on_clipboard);
+        let output: OutputConfig =
+            toml::from_str("delay_ms = 100\nkeep_on_clipboard = true").unwrap();
+        assert!(output.keep_on_clipboard);
+    }
+
+    #[tokio::test]
+    async fn test_config_clipboard_tools() {
+        let mut config = Config::default();
+        assert_eq!(
+            config.output.clipboard_tools,
+            [ClipboardTool::Native, ClipboardTool::WlClipboard]
+        );
+
+        let parse = |tools: &str| {
+            toml::from_str::<OutputConfig>(&format!("delay_ms = 100\nclipboard_tools = {}", tools))
+        };
+        let output = parse(r#"["xclip", "wl-clipboard"]"#).unwrap();
+        assert_eq!(output.clipboard_tools, [ClipboardTool::Xclip, ClipboardTool::WlClipboard]);
+        assert!(parse(r#"["pbcopy"]"#).is_err());
+
+        config.output.clipboard_tools.clear();
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_paste_keys() {
+        let mut config = Config::default();
+        config.output.paste_keys = Some("shift+insert".to_string());
+        assert!(config.validate().is_ok());
+        config.output.paste_keys = Some("ctrl+paste".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_newline_strategy() {
+        let output: OutputConfig = toml::from_str(
+            "delay_ms = 100\n[newline_strategy]\nkitty = \"join\"\ngvim = \"escape o\"\n",
+        )
+        .unwrap();
+        assert_eq!(output.newline_strategy["gvim"], "escape o");
+
+        let mut config = Config {
+            output,
+            ..Config::default()
+        };
+        assert!(config.validate().is_ok());
+        config.output.newline_strategy.insert("konsole".to_string(), "newline".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_model_for_language() {
+        let config: TranscriptionConfig = toml::from_str(
+            "model = \"small\"\nlanguage = \"en\"\n\n[language_models]\nde = \"large-v3-turbo\"",
+        )
+        .unwrap();
+        assert_eq!(config.model_for_language(Some("de")), "large-v3-turbo");
+        assert_eq!(config.model_for_language(Some("en")), "small");
+        assert_eq!(config.model_for_language(None), "small");
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_language_models() {
+        let mut config = Config::default();
+        config
+            .transcription
+            .language_models
+            .insert("de".to_string(), String::new());
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_download() {
+        let mut config = Config::default();
+        config.transcription.download_proxy = Some("http://proxy.example:3128".to_string());
+        assert!(config.validate().is_ok());
+        config.transcription.download_proxy = Some("http://[::1".to_string());
+        assert!(config.validate().is_err());
+
+        config.transcription.download_proxy = None;
+        config.transcription.download_read_timeout_secs = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_llm_examples() {
+        let config: LlmConfig = toml::from_str(
+            r#"
+prompt = "file:~/.config/tjvox/prompt.txt"
+examples = [
+    { input = "send to bob at example dot com", output = "Send to bob@example.com." },
+]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.examples.len(), 1);
+        assert_eq!(config.examples[0].output, "Send to bob@example.com.");
+        assert_eq!(config.timeout_ms, 5000);
+
+        let mut config = Config::default();
+        config.llm.prompt = "file:".to_string();
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.llm.examples.push(LlmExample {
+            input: "hello".to_string(),
+            output: String::new(),
+        });
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_clip_warning() {
+        let mut config = Config::default();
+        config.audio.clip_warning_fraction = 0.0;
+        assert!(config.validate().is_ok());
+        config.audio.clip_warning_fraction = 1.5;
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_bit_depth() {
+        let mut config = Config::default();
+        assert_eq!(config.audio.bit_depth, 16);
+        config.audio.bit_depth = 32;
+        assert!(config.validate().is_ok());
+
+        config.audio.bit_depth = 24;
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_preroll() {
+        let mut config = Config::default();
+        config.audio.preroll_ms = 300;
+        assert!(config.validate().is_ok());
+        config.audio.preroll_ms = 5000;
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_trim_start() {
+        let mut config = Config::default();
+        assert_eq!(config.audio.trim_start_ms, 0);
+        config.audio.trim_start_ms = 50;
+        assert!(config.validate().is_ok());
+        config.audio.trim_start_ms = 1500;
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_engine() {
+        let config: TranscriptionConfig =
+            toml::from_str("model = \"base\"\nengine = \"vosk\"").unwrap();
+        assert_eq!(config.engine, TranscriptionEngine::Vosk);
+
+        let mut config = Config::default();
+        assert_eq!(config.transcription.engine, TranscriptionEngine::Whisper);
+        config.transcription.engine = TranscriptionEngine::Vosk;
+        assert!(config.validate().is_err());
+        config.transcription.vosk_model = Some("/opt/vosk-model-small-en-us-0.15".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_escalation() {
+        let mut config = Config::default();
+        config.transcription.escalate_model = Some("large-v3-turbo".to_string());
+        config.transcription.min_confidence = 0.75;
+        assert!(config.validate().is_ok());
+
+        config.transcription.min_confidence = 1.2;
+        assert!(config.validate().is_err());
+
+        config.transcription.min_confidence = 0.75;
+        config.transcription.escalate_model = Some(" ".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_no_speech_threshold() {
+        let mut config = Config::default();
+        assert_eq!(config.transcription.no_speech_threshold, 0.8);
+        config.transcription.no_speech_threshold = 1.0;
+        assert!(config.validate().is_ok());
+
+        config.transcription.no_speech_threshold = -0.1;
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_command_prefix() {
+        let mut config = Config::default();
+        assert!(!config.transcription.commands_enabled());
+        assert!(config.transcription.commands_file.ends_with("tjvox/commands.toml"));
+
+        config.transcription.command_prefix = Some("computer".to_string());
+        assert!(config.validate().is_ok());
+        assert!(config.transcription.commands_enabled());
+
+        config.transcription.command_prefix = Some(" , ".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_max_queued() {
+        let mut config = Config::default();
+        assert_eq!(config.transcription.max_queued, 3);
+        config.transcription.max_queued = 0;
+        assert!(config.validate().is_ok());
+        config.transcription.max_queued = 21;
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_cooldown() {
+        let mut config = Config::default();
+        assert_eq!(config.daemon.cooldown_ms, 0);
+        config.daemon.cooldown_ms = 300;
+        assert!(config.validate().is_ok());
+        config.daemon.cooldown_ms = 10001;
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_idle_exit() {
+        let mut config = Config::default();
+        assert_eq!(config.daemon.idle_exit_ms, 0);
+        config.daemon.idle_exit_ms = 30 * 60 * 1000;
+        assert!(config.validate().is_ok());
+        config.daemon.idle_exit_ms = 5000;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_transcribe_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let inside = dir.path().join("memo.wav");
+        std::fs::write(&inside, b"").unwrap();
+        let inside = inside.canonicalize().unwrap();
+
+        let mut daemon = DaemonConfig::default();
+        assert!(daemon.transcribe_path_allowed(&inside));
+        assert!(daemon.transcribe_path_allowed(std::path::Path::new("/etc/passwd")));
+
+        daemon.transcribe_dirs = vec![dir.path().to_string_lossy().to_string()];
+        assert!(daemon.transcribe_path_allowed(&inside));
+        assert!(!daemon.transcribe_path_allowed(std::path::Path::new("/etc/passwd")));
+
+        daemon.transcribe_dirs = vec!["/nonexistent/tjvox".to_string()];
+        assert!(!daemon.transcribe_path_allowed(&inside));
+    }
+
+    #[test]
+    fn test_config_default_command() {
+        let expected = if cfg!(feature = "gui") {
+            DefaultCommand::Gui
+        } else {
+            DefaultCommand::Daemon
+        };
+        assert_eq!(Config::default().daemon.default_command, expected);
+
+        let daemon: DaemonConfig = toml::from_str("default_command = \"daemon\"").unwrap();
+        assert_eq!(daemon.default_command, DefaultCommand::Daemon);
+        assert!(toml::from_str::<DaemonConfig>("default_command = \"tray\"").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_hooks() {
+        let mut config = Config::default();
+        config.hooks.post_transcription = Some("logger 'unterminated".to_string());
+        assert!(config.validate().is_err());
+
+        config.hooks.post_transcription = Some("logger -t tjvox {text}".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_metrics() {
+        let mut config = Config::default();
+        config.metrics.enabled = true;
+        assert_eq!(config.metrics.listen, "127.0.0.1:9464");
+        assert!(config.validate().is_ok());
+
+        config.metrics.listen = "localhost".to_string();
+        assert!(config.validate().is_err());
+        config.metrics.enabled = false;
+        assert!(config.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_api() {
+        let mut config = Config::default();
+        config.api.enabled = true;
+        assert_eq!(config.api.listen, "127.0.0.1:9465");
+        assert!(config.validate().is_ok());
+
+        config.api.listen = "[::1]:8080".to_string();
+        assert!(config.validate().is_ok());
+        config.api.listen = "0.0.0.0:9465".to_string();
+        assert!(config.validate().is_err());
+        config.api.listen = "localhost:9465".to_string();
+        assert!(config.validate().is_err());
+
+        config.api.listen = default_api_listen();
+        config.api.token = Some(" ".to_string());
+        assert!(config.validate().is_err());
+        config.api.token = Some("s3cret".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_ydotoold_cmd() {
+        let mut config = Config::default();
+        config.output.ydotoold_cmd = Some("ydotoold --socket-perm 0660".to_string());
+        assert!(config.validate().is_ok());
+
+        config.output.ydotoold_cmd = Some("  ".to_string());
+        assert!(config.validate().is_err());
+
+        config.output.ydotoold_cmd = Some("ydotoold 'unterminated".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_channel_weights() {
+        let mut config = Config::default();
+        config.audio.channels = 2;
+        config.audio.mono_mode = MonoMode::Weighted;
+        assert!(config.validate().is_err());
+
+        config.audio.channel_weights = vec![1.0, 0.0];
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_mono_mode_parse() {
+        let config: AudioConfig = toml::from_str(
+            "sample_rate = 16000\nchannels = 2\nformat = \"wav\"\ntemp_dir = \"/tmp\"\nmono_mode = \"right\"",
+        )
+        .unwrap();
+        assert_eq!(config.mono_mode, MonoMode::Right);
+        assert!(config.channel_weights.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_overlay_gain() {
+        let mut config = Config::default();
+        assert_eq!(config.overlay.gain, 8.0);
+        config.overlay.gain = 0.0;
+        assert!(config.validate().is_err());
+        config.overlay.gain = 250.0;
+        assert!(config.validate().is_err());
+        config.overlay.gain = 2.5;
+        assert!(config.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_overlay_smoothing() {
+        let mut config = Config::default();
+        assert_eq!(config.overlay.attack, 0.6);
+        assert_eq!(config.overlay.decay, 0.15);
+        assert_eq!(config.overlay.compression, 0.5);
+
+        config.overlay.attack = 1.0;
+        config.overlay.compression = 1.0;
+        assert!(config.validate().is_ok());
+        config.overlay.decay = 0.0;
+        assert!(config.validate().is_err());
+        config.overlay.decay = 0.15;
+        config.overlay.attack = 1.5;
+        assert!(config.validate().is_err());
+        config.overlay.attack = 0.6;
+        config.overlay.compression = -0.5;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_overlay_visualizer() {
+        assert_eq!(OverlayConfig::default().visualizer, Visualizer::Amplitude);
+        let config: OverlayConfig = toml::from_str("visualizer = \"spectrum\"").unwrap();
+        assert_eq!(config.visualizer, Visualizer::Spectrum);
+        assert!(toml::from_str::<OverlayConfig>("visualizer = \"bars\"").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_unlimited_history() {
+        let mut config = Config::default();
+        config.history.max_entries = 0;
+        assert!(config.validate().is_ok());
+        config.history.max_entries = 100001;
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_double_tap() {
+        let mut config = Config::default();
+        assert_eq!(config.input.double_tap_ms, 0);
+        config.input.double_tap_ms = 5000;
+        assert!(config.validate().is_err());
+        config.input.double_tap_ms = 300;
+        assert!(config.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_wake_word() {
+        let mut config = Config::default();
+        assert!(config.input.wake_word.is_none());
+        config.input.wake_word = Some("  ".to_string());
+        assert!(config.validate().is_err());
+        config.input.wake_word = Some("hey vox".to_string());
+        assert!(config.validate().is_ok());
+        config.input.wake_word_silence_ms = 50;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_decoder_flags_parse() {
+        let config: TranscriptionConfig = toml::from_str(
+            "model = \"base\"\nno_context = false\nsingle_segment = true",
+        )
+        .unwrap();
+        assert!(!config.no_context);
+        assert!(config.single_segment);
+
+        let config: TranscriptionConfig = toml::from_str("model = \"base\"").unwrap();
+        assert!(config.no_context);
+        assert!(!config.single_segment);
+        assert_eq!(config.max_segment_len, 0);
+    }
+
+    #[test]
+    fn test_config_validation_max_segment_len() {
+        let mut config = Config::default();
+        config.transcription.max_segment_len = 42;
+        assert!(config.validate().is_ok());
+
+        config.transcription.max_segment_len = -1;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_validation_pauses() {
+        let mut config = Config::default();
+        config.transcription.auto_punctuate = true;
+        assert!(config.validate().is_ok());
+
+        config.transcription.pause_comma_ms = 1000;
+        assert!(config.validate().is_err());
+        config.transcription.pause_period_ms = 1000;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_replacement_files() {
+        let config: ReplacementsConfig = toml::from_str(r#"file = "/a.toml""#).unwrap();
+        assert_eq!(config.file.paths(), vec![PathBuf::from("/a.toml")]);
+
+        let config: ReplacementsConfig =
+            toml::from_str(r#"file = ["/team.toml", "/mine.toml"]"#).unwrap();
+        assert_eq!(
+            config.file.paths(),
+            vec![PathBuf::from("/team.toml"), PathBuf::from("/mine.toml")]
+        );
+        assert_eq!(config.file.personal(), Some(PathBuf::from("/mine.toml")));
+
+        let mut config = Config::default();
+        config.replacements.file = ReplacementFiles::Many(Vec::new());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_validation_best_of() {
+        let mut config = Config::default();
+        assert_eq!(config.transcription.best_of, 1);
+        config.transcription.best_of = 5;
+        assert!(config.validate().is_ok());
+
+        config.transcription.best_of = 0;
+        assert!(config.validate().is_err());
+        config.transcription.best_of = MAX_BEST_OF + 1;
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_config_validation_strict_sample_rate() {
+        let mut config = Config::default();
+        config.audio.sample_rate = 44100;
+        assert!(config.audio.needs_resampling());
+        assert!(config.validate().is_ok());
+
+        config.audio.strict_sample_rate = true;
+        assert!(config.validate().is_err());
+
+        config.audio.sample_rate = WHISPER_SAMPLE_RATE;
+        assert!(!config.audio.needs_resampling());
+        assert!(config.validate().is_ok());
+
+        config.audio.capture_native = true;
+        assert!(config.validate().is_err());
+
+        config.audio.strict_sample_rate = false;
+        assert!(config.validate().is_ok());
+        config.audio.in_memory = true;
+        assert!(config.validate().is_err());
+        config.audio.capture_native = false;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_audio_media_role_defaults() {
+        let config: AudioConfig = toml::from_str(
+            "sample_rate = 16000\nchannels = 1\nformat = \"wav\"\ntemp_dir = \"/tmp/tjvox\"",
+        )
+        .unwrap();
+        assert_eq!(config.media_role, "Communication");
+        assert_eq!(config.media_category, "Capture");
+
+        let mut config = Config::default();
+        config.audio.media_role = "Production".to_string();
+        assert!(config.validate().is_ok());
+        config.audio.media_role = " ".to_string();
+        assert!(config.validate().is_err());
+    }
+
     #[tokio::test]
     async fn test_config_validation_path_traversal() {
         let mut config = Config::default();
@@ -486,6 +2124,18 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_output_backend_parse() {
+        let config: OutputConfig =
+            toml::from_str("delay_ms = 100\nbackend = \"virtual_keyboard\"").unwrap();
+        assert_eq!(config.backend, OutputBackend::VirtualKeyboard);
+
+        let config: OutputConfig = toml::from_str("delay_ms = 100").unwrap();
+        assert_eq!(config.backend, OutputBackend::Ydotool);
+
+        assert!(toml::from_str::<OutputConfig>("delay_ms = 100\nbackend = \"xdotool\"").is_err());
+    }
+
     #[tokio::test]
     async fn test_llm_config_defaults() {
         let config = LlmConfig::default();
@@ -533,6 +2183,22 @@ mod tests {
         let mut config = Config::default();
         config.llm.enabled = true;
         config.llm.timeout_ms = 60000;
+        assert!(config.validate().is_ok());
+        config.llm.timeout_ms = MAX_LLM_TIMEOUT_MS + 1;
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_llm_validation_timeout_retry() {
+        let mut config = Config::default();
+        config.llm.enabled = true;
+        assert_eq!(config.llm.timeout_retry_ms, 0);
+        config.llm.timeout_retry_ms = 20000;
+        assert!(config.validate().is_ok());
+
+        config.llm.timeout_retry_ms = config.llm.timeout_ms;
+        assert!(config.validate().is_err());
+        config.llm.timeout_retry_ms = MAX_LLM_TIMEOUT_MS + 1;
         assert!(config.validate().is_err());
     }
 
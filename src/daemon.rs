@@ -1,21 +1,36 @@
-use anyhow::Result;
-use tokio::fs;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use tokio::signal::unix::{signal, SignalKind};
 use tracing::{error, info, warn};
 
-use crate::audio::AudioRecorder;
-use crate::config::{Config, WhisperMode};
+use crate::audio::{AudioRecorder, CaptureLost, RecordedAudio, SampleChunk};
+use crate::commands::{CommandEngine, CommandMatch};
+use crate::config::{AudioConfig, Config, OutputBackend, TranscriptionConfig, WhisperMode};
+use crate::error::TjvoxError;
+use crate::executor::TranscriptionExecutor;
 use crate::history::HistoryStore;
+use crate::metrics::{ErrorKind, Metrics};
 use crate::llm::LlmProcessor;
-use crate::output::OutputManager;
-use crate::replacements::ReplacementEngine;
-use crate::socket::{SocketCommand, SocketServer};
-use crate::transcription::TranscriptionService;
+use crate::output::{FocusTarget, ManagedYdotoold, OutputManager, OutputMethod};
+use crate::pidfile::{self, PidLock};
+use crate::ptt::arbiter::{InputAction, InputArbiter, InputEvent, Phase};
+use crate::replacements::{is_junk, ReplacementEngine};
+use crate::socket::{SessionOptions, SocketCommand, SocketRequest, SocketServer};
+use crate::transcription::{
+    create_transcriber, DownloadCallback, PartialCallback, ProgressCallback, Transcriber,
+    Transcription,
+};
 use crate::ui::UiManager;
 
 #[cfg(feature = "gui")]
 use crate::messages::{DaemonMsg, GuiMsg, RecordingState};
 
+/// Minimum change in percent between transcription progress updates
+const PROGRESS_STEP: i32 = 10;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum DaemonState {
     Idle,
@@ -24,6 +39,152 @@ pub enum DaemonState {
     Typing,
 }
 
+/// File name, inside `audio.temp_dir`, of the recording kept after a failure
+const RETAINED_RECORDING: &str = "last_failed.wav";
+
+/// A finished recording waiting to be transcribed.
+struct QueuedRecording {
+    audio: RecordedAudio,
+    duration_ms: u64,
+    /// Window to focus again before output (`output.refocus_previous`)
+    focus: Option<FocusTarget>,
+    /// Overrides from the command that started the recording
+    options: SessionOptions,
+}
+
+/// The last recording whose transcription or output failed, with its text
+/// if transcription got that far.
+struct RetainedRecording {
+    audio: RecordedAudio,
+    duration_ms: u64,
+    text: Option<String>,
+    options: SessionOptions,
+}
+
+/// A recording being transcribed in the background while the daemon keeps
+/// accepting input.
+struct InFlight {
+    recording: QueuedRecording,
+    started: std::time::Instant,
+    handle: tokio::task::JoinHandle<Dictated>,
+}
+
+/// Outcome of a dictation job, with the model it used after any
+/// `language_models` switch.
+struct Dictated {
+    model: String,
+    transcription: Result<Transcription>,
+}
+
+/// Recreates the transcriber for another model. Runs on the transcription
+/// thread, so the event loop never waits for a model to load.
+struct ModelLoader {
+    transcription: TranscriptionConfig,
+    audio: AudioConfig,
+    on_download: DownloadCallback,
+    /// Load the new model right away (hot mode)
+    load: bool,
+}
+
+impl ModelLoader {
+    /// Replace `transcriber` with one for `model`. If that fails the old one
+    /// is kept, unloaded.
+    async fn switch(mut self, transcriber: &mut Box<dyn Transcriber>, model: String) {
+        // Unload current model so next transcription loads the new one
+        transcriber.unload_model();
+        self.transcription.model = model;
+        match create_transcriber(&self.transcription, &self.audio) {
+            Ok(mut t) => {
+                t.set_download_callback(self.on_download);
+                *transcriber = t;
+                // If hot mode, load new model immediately
+                if self.load {
+                    if let Err(e) = transcriber.load_model().await {
+                        error!("Failed to load new model: {}", e);
+                    }
+                }
+            }
+            Err(e) => {
+                error!("Failed to create transcriber for new model: {}", e);
+            }
+        }
+    }
+}
+
+/// How a dictation job picks its model from `language_models`.
+struct ModelChoice {
+    /// Configured language; detected from the audio when `None`
+    language: Option<String>,
+    models: HashMap<String, String>,
+    base_model: String,
+    /// Set up for the model currently in use
+    loader: ModelLoader,
+}
+
+impl ModelChoice {
+    /// Resolve the session language and switch `transcriber` to its
+    /// `language_models` entry, or back to the base model. Returns the
+    /// language and the model now in use.
+    async fn apply(
+        self,
+        transcriber: &mut Box<dyn Transcriber>,
+        audio: &RecordedAudio,
+    ) -> (Option<String>, String) {
+        let current = self.loader.transcription.model.clone();
+        let language = match self.language {
+            Some(lang) => Some(lang),
+            None => match transcriber.detect_language(audio).await {
+                Ok(lang) => lang,
+                Err(e) => {
+                    warn!("Language detection failed, keeping current model: {}", e);
+                    return (None, current);
+                }
+            },
+        };
+
+        let model = match language.as_deref().and_then(|lang| self.models.get(lang)) {
+            Some(model) => model.clone(),
+            None => self.base_model,
+        };
+        if model != current {
+            info!("Using model '{}' for language {:?}", model, language);
+            self.loader.switch(transcriber, model.clone()).await;
+        }
+        (language, model)
+    }
+}
+
+/// Audio files being transcribed for `transcribe` requests or from the GUI.
+/// They hold the transcriber outside `in_flight`, so model and mode changes
+/// wait for them too instead of waiting for the lock on the event loop.
+#[derive(Default)]
+struct FileJobs {
+    running: AtomicUsize,
+    /// Woken when a job ends, so the daemon can apply what waited for it
+    finished: tokio::sync::Notify,
+}
+
+impl FileJobs {
+    /// Count a job as running until the returned guard is dropped.
+    fn start(self: &Arc<Self>) -> FileJob {
+        self.running.fetch_add(1, Ordering::SeqCst);
+        FileJob(self.clone())
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst) > 0
+    }
+}
+
+struct FileJob(Arc<FileJobs>);
+
+impl Drop for FileJob {
+    fn drop(&mut self) {
+        self.0.running.fetch_sub(1, Ordering::SeqCst);
+        self.0.finished.notify_one();
+    }
+}
+
 impl std::fmt::Display for DaemonState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -39,30 +200,124 @@ pub struct Daemon {
     config: Config,
     state: DaemonState,
     recorder: Option<AudioRecorder>,
-    transcriber: TranscriptionService,
+    /// Shared with the background transcription task
+    transcriber: Arc<tokio::sync::Mutex<Box<dyn Transcriber>>>,
+    /// Thread the transcriber runs on, away from the daemon's runtime
+    executor: TranscriptionExecutor,
+    /// Finished recordings waiting for the transcriber, oldest first
+    queue: VecDeque<QueuedRecording>,
+    in_flight: Option<InFlight>,
+    file_jobs: Arc<FileJobs>,
+    retained: Option<RetainedRecording>,
+    /// Model selected by the user; `language_models` overrides switch away from it
+    base_model: String,
+    /// Model selected while a transcription held the transcriber, switched
+    /// to once it finishes instead of blocking the event loop on the lock
+    pending_model: Option<String>,
     ui: UiManager,
     whisper_mode: WhisperMode,
     amplitude_tx: Option<std::sync::mpsc::Sender<f32>>,
+    /// Captured samples for the overlay spectrum (`overlay.visualizer`)
+    overlay_sample_tx: Option<std::sync::mpsc::Sender<SampleChunk>>,
     replacement_engine: Option<ReplacementEngine>,
+    /// Voice commands, loaded when `command_mode` or `command_prefix` is set
+    command_engine: Option<CommandEngine>,
     llm_processor: Option<LlmProcessor>,
-    history: Option<HistoryStore>,
+    /// Runtime switch for LLM post-processing (starts from `llm.enabled`)
+    llm_enabled: bool,
+    /// Shared so saves can run off the daemon's task while the database is locked
+    history: Option<Arc<std::sync::Mutex<HistoryStore>>>,
     recording_start: Option<std::time::Instant>,
+    /// Window focused when the current recording started
+    focus_target: Option<FocusTarget>,
+    /// Overrides for the current recording, from `toggle` or `push-start` flags
+    recording_options: SessionOptions,
+    /// ydotoold started by the daemon (`output.manage_ydotoold`)
+    ydotoold: Option<ManagedYdotoold>,
+    metrics: Arc<Metrics>,
+    /// HTTP endpoint serving `metrics` (`metrics.enabled`)
+    metrics_server: Option<tokio::task::JoinHandle<()>>,
+    /// Localhost HTTP control API (`api.enabled`)
+    api_server: Option<tokio::task::JoinHandle<()>>,
+    /// Last recording or finished transcription, for `daemon.idle_exit_ms`
+    last_activity: std::time::Instant,
+    /// Failure of the last dictation, shown by `status` until one succeeds
+    last_error: Option<(std::time::Instant, String)>,
+    /// End of the `daemon.cooldown_ms` pause after the last output
+    cooldown_until: Option<std::time::Instant>,
+    /// Start input received during the cooldown, replayed when it ends
+    deferred_start: Option<(InputEvent, SessionOptions)>,
+    /// Whether dictations are typed; when off they only go to history
+    output_enabled: bool,
+    /// Paste keystroke set with `set-paste-keys`, replacing `output.paste_keys`
+    paste_keys_override: Option<String>,
+    input_arbiter: InputArbiter,
+    /// Held for the daemon's lifetime; dropping it removes the PID file
+    _pid_lock: PidLock,
     #[cfg(feature = "gui")]
     gui_rx: Option<async_channel::Receiver<GuiMsg>>,
     #[cfg(feature = "gui")]
     daemon_txs: Vec<async_channel::Sender<DaemonMsg>>,
+    /// Overlay shown on request via `show-overlay`, independent of recording
+    #[cfg(feature = "gui")]
+    overlay_pinned: bool,
+    /// Mic capture feeding the pinned overlay's meter while not recording
+    #[cfg(feature = "gui")]
+    overlay_meter: Option<AudioRecorder>,
+    #[cfg(feature = "wake-word")]
+    wake_word: Option<crate::wake_word::WakeWordListener>,
+}
+
+/// Show model downloads as notifications, so a first transcription that
+/// waits for the download does not look like a hang.
+fn download_notifier(ui: &UiManager) -> DownloadCallback {
+    let ui = ui.clone();
+    Arc::new(move |event| {
+        let ui = ui.clone();
+        tokio::spawn(async move {
+            let _ = ui.show_notification("TJvox", &event.to_string()).await;
+        });
+    })
 }
 
 impl Daemon {
     pub async fn new(config: Config) -> Result<Self> {
+        // Lock and write PID file in user-private runtime directory
+        let pid_lock = PidLock::acquire(&pidfile::pid_file_path())?;
+        Self::build(config, pid_lock)
+    }
+
+    fn build(config: Config, pid_lock: PidLock) -> Result<Self> {
         let ui = UiManager::with_config(&config.ui);
-        let transcriber = TranscriptionService::new(&config.transcription)?;
+        let mut transcriber = create_transcriber(&config.transcription, &config.audio)?;
+        transcriber.set_download_callback(download_notifier(&ui));
         let whisper_mode = config.whisper.mode;
 
+        // A recording kept by an earlier run can still be retried
+        let retained_path = Path::new(&config.audio.temp_dir).join(RETAINED_RECORDING);
+        let retained = retained_path.exists().then(|| {
+            info!("Found failed recording {}; `tjvox retry-last` re-transcribes it", retained_path.display());
+            RetainedRecording {
+                audio: RecordedAudio::File(retained_path),
+                duration_ms: 0,
+                text: None,
+                options: SessionOptions::default(),
+            }
+        });
+
+        if config.audio.needs_resampling() && !config.audio.capture_native {
+            warn!(
+                "audio.sample_rate is {} Hz; whisper needs {} Hz, so every recording is \
+                 resampled before transcription. Set sample_rate = {} to avoid the extra work.",
+                config.audio.sample_rate,
+                crate::config::WHISPER_SAMPLE_RATE,
+                crate::config::WHISPER_SAMPLE_RATE
+            );
+        }
+
         // Load replacement engine if enabled
         let replacement_engine = if config.replacements.enabled {
-            let path = std::path::PathBuf::from(&config.replacements.file);
-            match ReplacementEngine::load(&path) {
+            match ReplacementEngine::load(&config.replacements.file.paths()) {
                 Ok(engine) => Some(engine),
                 Err(e) => {
                     warn!("Failed to load replacements: {}", e);
@@ -73,6 +328,20 @@ impl Daemon {
             None
         };
 
+        // Load voice commands if command mode can trigger
+        let command_engine = if config.transcription.commands_enabled() {
+            let path = std::path::PathBuf::from(&config.transcription.commands_file);
+            match CommandEngine::load(&path) {
+                Ok(engine) => Some(engine),
+                Err(e) => {
+                    warn!("Failed to load voice commands: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         // Build LLM processor if enabled
         let llm_processor = if config.llm.enabled {
             match LlmProcessor::new(&config.llm) {
@@ -96,7 +365,7 @@ impl Daemon {
                 })
                 .join("tjvox/history.db");
             match HistoryStore::open(&db_path, config.history.max_entries) {
-                Ok(store) => Some(store),
+                Ok(store) => Some(Arc::new(std::sync::Mutex::new(store))),
                 Err(e) => {
                     warn!("Failed to open history store: {}", e);
                     None
@@ -106,50 +375,95 @@ impl Daemon {
             None
         };
 
-        // Write PID file to user-private runtime directory
-        let pid = std::process::id();
-        let uid = unsafe { libc::getuid() };
-        let pid_file = std::path::PathBuf::from(format!("/run/user/{}/tjvox.pid", uid));
-        fs::write(&pid_file, pid.to_string()).await?;
+        let llm_enabled = config.llm.enabled;
+        let input_arbiter =
+            InputArbiter::new(std::time::Duration::from_millis(config.input.double_tap_ms));
 
         Ok(Self {
+            _pid_lock: pid_lock,
+            base_model: config.transcription.model.clone(),
+            pending_model: None,
             config,
             state: DaemonState::Idle,
             recorder: None,
-            transcriber,
+            transcriber: Arc::new(tokio::sync::Mutex::new(transcriber)),
+            executor: TranscriptionExecutor::new()?,
+            queue: VecDeque::new(),
+            in_flight: None,
+            file_jobs: Arc::new(FileJobs::default()),
+            retained,
             ui,
             whisper_mode,
             amplitude_tx: None,
+            overlay_sample_tx: None,
             replacement_engine,
+            command_engine,
             llm_processor,
+            llm_enabled,
             history,
             recording_start: None,
+            focus_target: None,
+            recording_options: SessionOptions::default(),
+            ydotoold: None,
+            metrics: Arc::new(Metrics::default()),
+            metrics_server: None,
+            api_server: None,
+            last_activity: std::time::Instant::now(),
+            last_error: None,
+            cooldown_until: None,
+            deferred_start: None,
+            output_enabled: true,
+            paste_keys_override: None,
+            input_arbiter,
             #[cfg(feature = "gui")]
             gui_rx: None,
             #[cfg(feature = "gui")]
             daemon_txs: Vec::new(),
+            #[cfg(feature = "gui")]
+            overlay_pinned: false,
+            #[cfg(feature = "gui")]
+            overlay_meter: None,
+            #[cfg(feature = "wake-word")]
+            wake_word: None,
         })
     }
 
+    /// Start with typing turned off, so dictations are only saved to
+    /// history (`tjvox daemon --no-output`).
+    pub fn with_output(mut self, enabled: bool) -> Self {
+        if !enabled && self.history.is_none() {
+            warn!("Output is off but history is disabled; dictations will not be kept");
+        }
+        self.output_enabled = enabled;
+        self
+    }
+
     #[cfg(feature = "gui")]
     pub fn with_channels(
         mut self,
         gui_rx: async_channel::Receiver<GuiMsg>,
         daemon_txs: Vec<async_channel::Sender<DaemonMsg>>,
         amplitude_tx: Option<std::sync::mpsc::Sender<f32>>,
+        overlay_sample_tx: Option<std::sync::mpsc::Sender<SampleChunk>>,
     ) -> Self {
         self.gui_rx = Some(gui_rx);
         self.daemon_txs = daemon_txs;
         self.amplitude_tx = amplitude_tx;
+        self.overlay_sample_tx = overlay_sample_tx;
         self
     }
 
-    async fn prewarm_if_hot(&mut self) {
+    /// Pre-warm the model on the transcription thread in hot mode. Dictations
+    /// started meanwhile wait for it there.
+    fn prewarm_if_hot(&self) {
         if self.whisper_mode == WhisperMode::Hot {
             info!("Hot mode: pre-warming whisper model on startup");
-            if let Err(e) = self.transcriber.prewarm().await {
-                warn!("Failed to pre-warm model: {}", e);
-            }
+            let transcriber = self.transcriber.clone();
+            self.executor.spawn(async move {
+                if let Err(e) = transcriber.lock().await.prewarm().await {
+                    warn!("Failed to pre-warm model: {}", e);
+                }
+            });
         }
     }
 
@@ -174,6 +488,66 @@ impl Daemon {
     #[cfg(not(feature = "gui"))]
     fn notify_state(&self) {}
 
+    /// Forward partial transcripts to the GUI when the overlay displays them.
+    #[cfg(feature = "gui")]
+    fn partial_transcript_callback(&self) -> Option<PartialCallback> {
+        if !self.config.overlay.show_partial || self.daemon_txs.is_empty() {
+            return None;
+        }
+        let txs = self.daemon_txs.clone();
+        Some(Box::new(move |text: &str| {
+            for tx in &txs {
+                let _ = tx.try_send(DaemonMsg::PartialTranscript(text.to_string()));
+            }
+        }))
+    }
+
+    #[cfg(not(feature = "gui"))]
+    fn partial_transcript_callback(&self) -> Option<PartialCallback> {
+        None
+    }
+
+    /// Report transcription progress to the overlay and, if enabled, as a
+    /// notification. Updates are limited to every `PROGRESS_STEP` percent.
+    #[cfg(feature = "gui")]
+    fn progress_callback(&self) -> Option<ProgressCallback> {
+        let txs = self.daemon_txs.clone();
+        let ui = self.progress_ui();
+        if txs.is_empty() && ui.is_none() {
+            return None;
+        }
+        let mut last_reported = 0;
+        Some(Box::new(move |progress: i32| {
+            if !progress_milestone(&mut last_reported, progress) {
+                return;
+            }
+            for tx in &txs {
+                let _ = tx.try_send(DaemonMsg::TranscriptionProgress(progress as u8));
+            }
+            if let Some(ref ui) = ui {
+                ui.show_progress("TJvox", progress);
+            }
+        }))
+    }
+
+    #[cfg(not(feature = "gui"))]
+    fn progress_callback(&self) -> Option<ProgressCallback> {
+        let ui = self.progress_ui()?;
+        let mut last_reported = 0;
+        Some(Box::new(move |progress: i32| {
+            if progress_milestone(&mut last_reported, progress) {
+                ui.show_progress("TJvox", progress);
+            }
+        }))
+    }
+
+    fn progress_ui(&self) -> Option<UiManager> {
+        self.config
+            .ui
+            .progress_notifications
+            .then(|| self.ui.clone())
+    }
+
     #[cfg(feature = "gui")]
     fn notify_whisper_mode(&self) {
         self.broadcast(DaemonMsg::WhisperModeChanged(self.whisper_mode));
@@ -193,8 +567,18 @@ impl Daemon {
             .show_notification("TJvox", "Daemon started. Send SIGUSR1 to toggle.")
             .await?;
 
+        if self.config.output.manage_ydotoold && self.config.output.backend == OutputBackend::Ydotool {
+            match ManagedYdotoold::start(&self.config.output).await {
+                Ok(ydotoold) => self.ydotoold = ydotoold,
+                Err(e) => warn!("Failed to start ydotoold: {}", e),
+            }
+        }
+
+        self.start_metrics_server().await;
+        self.start_api_server().await;
+
         // Pre-warm model if hot mode
-        self.prewarm_if_hot().await;
+        self.prewarm_if_hot();
 
         // Notify GUI of initial state
         self.notify_state();
@@ -202,10 +586,11 @@ impl Daemon {
         {
             self.notify_whisper_mode();
             self.notify_model_changed();
+            self.broadcast(DaemonMsg::LlmChanged(self.llm_enabled));
         }
 
         // Start socket server for IPC
-        let socket_server = match SocketServer::bind().await {
+        let mut socket_server = match SocketServer::bind().await {
             Ok(server) => Some(server),
             Err(e) => {
                 warn!("Failed to start socket server: {}", e);
@@ -237,18 +622,84 @@ impl Daemon {
             }
         };
 
+        let mut wake_rx = self.start_wake_word().await;
+
         let mut sigusr1 = signal(SignalKind::user_defined1())?;
         let mut sigterm = signal(SignalKind::terminate())?;
         let mut sigint = signal(SignalKind::interrupt())?;
+        let mut sigtstp = signal(SignalKind::from_raw(libc::SIGTSTP))?;
+        let mut sigcont = signal(SignalKind::from_raw(libc::SIGCONT))?;
 
         loop {
-            // Helper future for socket accept
-            let socket_accept = async {
-                if let Some(ref server) = socket_server {
-                    server.accept().await
-                } else {
+            // Next command from a socket client
+            let socket_request = async {
+                match socket_server.as_mut() {
+                    Some(server) => match server.next_request().await {
+                        Some(request) => request,
+                        None => std::future::pending().await,
+                    },
                     // Never resolves if no socket server
-                    std::future::pending().await
+                    None => std::future::pending().await,
+                }
+            };
+
+            // Fires when a short push-to-talk tap was not followed by a second tap
+            let tap_deadline = self.input_arbiter.deadline();
+            let tap_timeout = async move {
+                match tap_deadline {
+                    Some(at) => tokio::time::sleep_until(tokio::time::Instant::from_std(at)).await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            // Fires when the pause after an output is over
+            let cooldown_until = self.cooldown_until;
+            let cooldown_over = async move {
+                match cooldown_until {
+                    Some(at) => tokio::time::sleep_until(tokio::time::Instant::from_std(at)).await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            // Fires when the audio capture fails in the middle of a recording
+            let capture_lost = self.recorder.as_ref().map(AudioRecorder::capture_lost);
+            let capture_lost = async move {
+                match capture_lost {
+                    Some(lost) => lost.await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            // Fires once the daemon has been idle for `daemon.idle_exit_ms`
+            let idle_deadline = self.idle_deadline();
+            let idle_exit = async move {
+                match idle_deadline {
+                    Some(at) => tokio::time::sleep_until(tokio::time::Instant::from_std(at)).await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            // Completes when the background transcription finishes
+            let in_flight = &mut self.in_flight;
+            let transcription_done = async move {
+                match in_flight {
+                    Some(job) => (&mut job.handle).await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            // Completes when a file transcription gives the transcriber back
+            let file_jobs = self.file_jobs.clone();
+            let file_job_done = async move { file_jobs.finished.notified().await };
+
+            // Wake word and the silence after it, as push-to-talk input
+            let wake_recv = async {
+                match wake_rx.as_mut() {
+                    Some(rx) => match rx.recv().await {
+                        Some(event) => event,
+                        None => std::future::pending().await,
+                    },
+                    None => std::future::pending().await,
                 }
             };
 
@@ -270,6 +721,28 @@ impl Daemon {
                         _ = sigusr1.recv() => {
                             self.handle_toggle().await;
                         }
+                        _ = tap_timeout => {
+                            self.dispatch_input(InputEvent::TapTimeout).await;
+                        }
+                        event = wake_recv => {
+                            self.dispatch_input(event).await;
+                        }
+                        result = transcription_done => {
+                            self.finish_transcription(result).await;
+                        }
+                        _ = file_job_done => {
+                            self.finish_file_job().await;
+                        }
+                        _ = cooldown_over => {
+                            self.end_cooldown().await;
+                        }
+                        _ = capture_lost => {
+                            self.handle_capture_lost().await;
+                        }
+                        _ = idle_exit => {
+                            info!("No recording for {} ms, exiting", self.config.daemon.idle_exit_ms);
+                            break;
+                        }
                         _ = sigterm.recv() => {
                             info!("Received SIGTERM, shutting down...");
                             break;
@@ -278,12 +751,16 @@ impl Daemon {
                             info!("Received SIGINT, shutting down...");
                             break;
                         }
-                        result = socket_accept => {
-                            if let Ok((cmd, stream)) = result {
-                                if self.handle_socket_command(cmd, stream).await {
-                                    info!("Quit requested via socket");
-                                    break;
-                                }
+                        _ = sigtstp.recv() => {
+                            self.suspend().await;
+                        }
+                        _ = sigcont.recv() => {
+                            self.resume().await;
+                        }
+                        request = socket_request => {
+                            if self.handle_socket_request(request).await {
+                                info!("Quit requested via socket");
+                                break;
                             }
                         }
                         evt = ptt_recv => {
@@ -297,10 +774,16 @@ impl Daemon {
                                     self.handle_toggle().await;
                                 }
                                 Ok(GuiMsg::SetWhisperMode(mode)) => {
-                                    self.set_whisper_mode(mode).await;
+                                    self.set_whisper_mode(mode);
                                 }
                                 Ok(GuiMsg::SetModel(model)) => {
-                                    self.set_model(model).await;
+                                    self.set_model(model);
+                                }
+                                Ok(GuiMsg::SetLlmEnabled(enabled)) => {
+                                    self.set_llm_enabled(enabled);
+                                }
+                                Ok(GuiMsg::TranscribeFile(path)) => {
+                                    self.spawn_gui_file_transcription(path);
                                 }
                                 Ok(GuiMsg::Quit) => {
                                     info!("Quit requested from GUI");
@@ -319,6 +802,28 @@ impl Daemon {
                         _ = sigusr1.recv() => {
                             self.handle_toggle().await;
                         }
+                        _ = tap_timeout => {
+                            self.dispatch_input(InputEvent::TapTimeout).await;
+                        }
+                        event = wake_recv => {
+                            self.dispatch_input(event).await;
+                        }
+                        result = transcription_done => {
+                            self.finish_transcription(result).await;
+                        }
+                        _ = file_job_done => {
+                            self.finish_file_job().await;
+                        }
+                        _ = cooldown_over => {
+                            self.end_cooldown().await;
+                        }
+                        _ = capture_lost => {
+                            self.handle_capture_lost().await;
+                        }
+                        _ = idle_exit => {
+                            info!("No recording for {} ms, exiting", self.config.daemon.idle_exit_ms);
+                            break;
+                        }
                         _ = sigterm.recv() => {
                             info!("Received SIGTERM, shutting down...");
                             break;
@@ -327,12 +832,16 @@ impl Daemon {
                             info!("Received SIGINT, shutting down...");
                             break;
                         }
-                        result = socket_accept => {
-                            if let Ok((cmd, stream)) = result {
-                                if self.handle_socket_command(cmd, stream).await {
-                                    info!("Quit requested via socket");
-                                    break;
-                                }
+                        _ = sigtstp.recv() => {
+                            self.suspend().await;
+                        }
+                        _ = sigcont.recv() => {
+                            self.resume().await;
+                        }
+                        request = socket_request => {
+                            if self.handle_socket_request(request).await {
+                                info!("Quit requested via socket");
+                                break;
                             }
                         }
                         msg = gui_rx.recv() => {
@@ -341,10 +850,16 @@ impl Daemon {
                                     self.handle_toggle().await;
                                 }
                                 Ok(GuiMsg::SetWhisperMode(mode)) => {
-                                    self.set_whisper_mode(mode).await;
+                                    self.set_whisper_mode(mode);
                                 }
                                 Ok(GuiMsg::SetModel(model)) => {
-                                    self.set_model(model).await;
+                                    self.set_model(model);
+                                }
+                                Ok(GuiMsg::SetLlmEnabled(enabled)) => {
+                                    self.set_llm_enabled(enabled);
+                                }
+                                Ok(GuiMsg::TranscribeFile(path)) => {
+                                    self.spawn_gui_file_transcription(path);
                                 }
                                 Ok(GuiMsg::Quit) => {
                                     info!("Quit requested from GUI");
@@ -368,6 +883,28 @@ impl Daemon {
                 _ = sigusr1.recv() => {
                     self.handle_toggle().await;
                 }
+                _ = tap_timeout => {
+                    self.dispatch_input(InputEvent::TapTimeout).await;
+                }
+                event = wake_recv => {
+                    self.dispatch_input(event).await;
+                }
+                result = transcription_done => {
+                    self.finish_transcription(result).await;
+                }
+                _ = file_job_done => {
+                    self.finish_file_job().await;
+                }
+                _ = cooldown_over => {
+                    self.end_cooldown().await;
+                }
+                _ = capture_lost => {
+                    self.handle_capture_lost().await;
+                }
+                _ = idle_exit => {
+                    info!("No recording for {} ms, exiting", self.config.daemon.idle_exit_ms);
+                    break;
+                }
                 _ = sigterm.recv() => {
                     info!("Received SIGTERM, shutting down...");
                     break;
@@ -376,9 +913,16 @@ impl Daemon {
                     info!("Received SIGINT, shutting down...");
                     break;
                 }
-                result = socket_accept => {
-                    if let Ok((cmd, stream)) = result {
-                        self.handle_socket_command(cmd, stream).await;
+                _ = sigtstp.recv() => {
+                    self.suspend().await;
+                }
+                _ = sigcont.recv() => {
+                    self.resume().await;
+                }
+                request = socket_request => {
+                    if self.handle_socket_request(request).await {
+                        info!("Quit requested via socket");
+                        break;
                     }
                 }
                 evt = ptt_recv => {
@@ -393,6 +937,28 @@ impl Daemon {
                 _ = sigusr1.recv() => {
                     self.handle_toggle().await;
                 }
+                _ = tap_timeout => {
+                    self.dispatch_input(InputEvent::TapTimeout).await;
+                }
+                event = wake_recv => {
+                    self.dispatch_input(event).await;
+                }
+                result = transcription_done => {
+                    self.finish_transcription(result).await;
+                }
+                _ = file_job_done => {
+                    self.finish_file_job().await;
+                }
+                _ = cooldown_over => {
+                    self.end_cooldown().await;
+                }
+                _ = capture_lost => {
+                    self.handle_capture_lost().await;
+                }
+                _ = idle_exit => {
+                    info!("No recording for {} ms, exiting", self.config.daemon.idle_exit_ms);
+                    break;
+                }
                 _ = sigterm.recv() => {
                     info!("Received SIGTERM, shutting down...");
                     break;
@@ -401,9 +967,16 @@ impl Daemon {
                     info!("Received SIGINT, shutting down...");
                     break;
                 }
-                result = socket_accept => {
-                    if let Ok((cmd, stream)) = result {
-                        self.handle_socket_command(cmd, stream).await;
+                _ = sigtstp.recv() => {
+                    self.suspend().await;
+                }
+                _ = sigcont.recv() => {
+                    self.resume().await;
+                }
+                request = socket_request => {
+                    if self.handle_socket_request(request).await {
+                        info!("Quit requested via socket");
+                        break;
                     }
                 }
             }
@@ -413,37 +986,273 @@ impl Daemon {
         Ok(())
     }
 
+    /// Serve the metrics counters over HTTP if `metrics.enabled` is set.
+    #[cfg(feature = "metrics")]
+    async fn start_metrics_server(&mut self) {
+        if !self.config.metrics.enabled {
+            return;
+        }
+        match crate::metrics::serve(&self.config.metrics.listen, self.metrics.clone()).await {
+            Ok(handle) => self.metrics_server = Some(handle),
+            Err(e) => warn!("Failed to start metrics endpoint: {:#}", e),
+        }
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    async fn start_metrics_server(&mut self) {
+        if self.config.metrics.enabled {
+            warn!("metrics.enabled is set but tjvox was built without the metrics feature");
+        }
+    }
+
+    /// Serve the HTTP control API if `api.enabled` is set. Requests are
+    /// forwarded to the control socket, so they share its handlers.
+    #[cfg(feature = "api")]
+    async fn start_api_server(&mut self) {
+        if !self.config.api.enabled {
+            return;
+        }
+        match crate::api::serve(&self.config.api.listen, self.config.api.token.clone()).await {
+            Ok(handle) => self.api_server = Some(handle),
+            Err(e) => warn!("Failed to start HTTP API: {:#}", e),
+        }
+    }
+
+    #[cfg(not(feature = "api"))]
+    async fn start_api_server(&mut self) {
+        if self.config.api.enabled {
+            warn!("api.enabled is set but tjvox was built without the api feature");
+        }
+    }
+
+    /// Start listening for `input.wake_word`. Wake-word events arrive as
+    /// push-to-talk input so the arbiter rules apply to them too.
+    #[cfg(feature = "wake-word")]
+    async fn start_wake_word(&mut self) -> Option<tokio::sync::mpsc::Receiver<InputEvent>> {
+        self.config.input.wake_word.as_ref()?;
+        match crate::wake_word::WakeWordListener::start(&self.config).await {
+            Ok((listener, rx)) => {
+                self.wake_word = Some(listener);
+                Some(rx)
+            }
+            Err(e) => {
+                warn!("Failed to start wake-word listener: {}", e);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(feature = "wake-word"))]
+    async fn start_wake_word(&mut self) -> Option<tokio::sync::mpsc::Receiver<InputEvent>> {
+        if self.config.input.wake_word.is_some() {
+            warn!("input.wake_word is set but tjvox was built without the wake-word feature");
+        }
+        None
+    }
+
+    /// SIGTSTP (Ctrl+Z): end a recording cleanly before stopping, so the
+    /// capture thread and WAV writer are not frozen mid-write. The partial
+    /// recording is discarded.
+    async fn suspend(&mut self) {
+        self.discard_recording("suspended").await;
+        info!("Suspending");
+        // Handling SIGTSTP replaced the default stop, so stop for real
+        unsafe {
+            libc::raise(libc::SIGSTOP);
+        }
+    }
+
+    /// SIGCONT: return to idle. A recording still open here was frozen by
+    /// SIGSTOP and is dropped rather than resumed with a gap.
+    async fn resume(&mut self) {
+        info!("Resumed");
+        self.discard_recording("resumed after the process was stopped").await;
+        self.notify_state();
+    }
+
+    async fn discard_recording(&mut self, reason: &str) {
+        let Some(mut recorder) = self.recorder.take() else {
+            return;
+        };
+        warn!("Discarding the current recording: {}", reason);
+        self.recording_start = None;
+        self.focus_target = None;
+        if let Err(e) = recorder.stop().await {
+            warn!("Recording stopped with: {}", e);
+        }
+        recorder.cleanup().await.ok();
+        self.settle_state();
+        let _ = self
+            .ui
+            .show_notification("TJvox", &format!("Recording cancelled: tjvox was {}", reason))
+            .await;
+    }
+
+    /// The capture failed mid-recording and did not recover: end the
+    /// recording now with the error `stop` reports instead of at the next
+    /// toggle, so the missing audio is not mistaken for silence.
+    async fn handle_capture_lost(&mut self) {
+        if let Err(e) = self.stop_recording().await {
+            error!("Recording failed: {}", e);
+            self.record_failure(ErrorKind::Recording, &e);
+            let _ = self.ui.show_error("TJvox", &e.to_string()).await;
+            self.settle_state();
+        }
+    }
+
     async fn handle_toggle(&mut self) {
+        self.dispatch_input(InputEvent::Toggle).await;
+    }
+
+    /// Recording can start while earlier recordings are transcribed, until
+    /// the queue is full.
+    fn input_phase(&self) -> Phase {
         match self.state {
-            DaemonState::Idle => {
-                if let Err(e) = self.start_recording().await {
+            DaemonState::Recording => Phase::Recording,
+            DaemonState::Typing => Phase::Busy,
+            DaemonState::Idle | DaemonState::Transcribing if self.queue_full() => Phase::Busy,
+            DaemonState::Idle | DaemonState::Transcribing => Phase::Idle,
+        }
+    }
+
+    /// When the daemon should exit for lack of use, if `daemon.idle_exit_ms`
+    /// is set and nothing is being recorded, transcribed or typed.
+    fn idle_deadline(&self) -> Option<std::time::Instant> {
+        let idle_exit_ms = self.config.daemon.idle_exit_ms;
+        if idle_exit_ms == 0 || self.state != DaemonState::Idle || self.recorder.is_some() {
+            return None;
+        }
+        Some(self.last_activity + std::time::Duration::from_millis(idle_exit_ms))
+    }
+
+    /// Whether another recording would exceed `transcription.max_queued`.
+    fn queue_full(&self) -> bool {
+        self.in_flight.is_some() && self.queue.len() >= self.config.transcription.max_queued
+    }
+
+    /// Derive the state from the work in progress; an active recording takes
+    /// precedence over background transcription.
+    fn settle_state(&mut self) {
+        let state = if self.recorder.is_some() {
+            DaemonState::Recording
+        } else if self.in_flight.is_some() || !self.queue.is_empty() {
+            DaemonState::Transcribing
+        } else {
+            DaemonState::Idle
+        };
+        if state != self.state {
+            self.state = state;
+            self.notify_state();
+        }
+    }
+
+    /// Route toggle and push-to-talk input through the arbiter so mixed
+    /// input methods cannot stop each other's recordings by accident.
+    async fn dispatch_input(&mut self, event: InputEvent) {
+        self.dispatch_session_input(event, SessionOptions::default()).await;
+    }
+
+    /// Like `dispatch_input`, with overrides for a recording this starts.
+    async fn dispatch_session_input(&mut self, event: InputEvent, options: SessionOptions) {
+        if self.defer_during_cooldown(event, options) {
+            return;
+        }
+        let phase = self.input_phase();
+        let action = self
+            .input_arbiter
+            .handle(event, phase, std::time::Instant::now());
+
+        match action {
+            InputAction::Start => {
+                if let Err(e) = self.start_recording(options).await {
                     error!("Failed to start recording: {}", e);
+                    self.record_failure(ErrorKind::Recording, &e);
                     let _ = self.ui.show_error("TJvox", &e.to_string()).await;
-                    self.state = DaemonState::Idle;
-                    self.notify_state();
+                    self.settle_state();
                 }
             }
-            DaemonState::Recording => {
-                if let Err(e) = self.stop_and_transcribe().await {
-                    error!("Failed to transcribe: {}", e);
+            InputAction::Stop => {
+                if let Err(e) = self.stop_recording().await {
+                    error!("Failed to stop recording: {}", e);
+                    self.record_failure(ErrorKind::Recording, &e);
                     let _ = self.ui.show_error("TJvox", &e.to_string()).await;
-                    self.state = DaemonState::Idle;
-                    self.notify_state();
+                    self.settle_state();
+                }
+            }
+            InputAction::Latch => {
+                info!("Push-to-talk latched, recording until the next press");
+                let _ = self
+                    .ui
+                    .show_notification("TJvox", "Continuous recording, press again to stop")
+                    .await;
+            }
+            InputAction::Ignore => {
+                info!("{:?} ignored during {} state", event, self.state);
+                if self.queue_full() && matches!(event, InputEvent::Toggle | InputEvent::PushStart) {
+                    let _ = self
+                        .ui
+                        .show_notification("TJvox", "Still transcribing earlier recordings, try again shortly")
+                        .await;
                 }
             }
-            _ => {
-                warn!("Toggle received during {} state, ignoring", self.state);
+        }
+
+        #[cfg(feature = "gui")]
+        self.sync_overlay_meter().await;
+    }
+
+    /// Hold input that would start a recording until the cooldown after the
+    /// last output is over, so the next paste cannot land in the middle of
+    /// the previous one. Releasing push-to-talk first cancels the held start.
+    fn defer_during_cooldown(&mut self, event: InputEvent, options: SessionOptions) -> bool {
+        if self.cooldown_until.is_none() || self.recorder.is_some() {
+            return false;
+        }
+        match event {
+            InputEvent::Toggle | InputEvent::PushStop if self.deferred_start.is_some() => {
+                info!("{:?} during cooldown cancels the held start", event);
+                self.deferred_start = None;
+                true
+            }
+            InputEvent::Toggle | InputEvent::PushStart => {
+                info!("{:?} during cooldown, recording starts when it ends", event);
+                self.deferred_start = Some((event, options));
+                true
             }
+            InputEvent::PushStop | InputEvent::TapTimeout => false,
+        }
+    }
+
+    /// The cooldown is over: start the recording requested during it.
+    async fn end_cooldown(&mut self) {
+        self.cooldown_until = None;
+        if let Some((event, options)) = self.deferred_start.take() {
+            self.dispatch_session_input(event, options).await;
         }
     }
 
-    async fn start_recording(&mut self) -> Result<()> {
+    async fn start_recording(&mut self, options: SessionOptions) -> Result<()> {
         info!("Starting recording");
+        if options != SessionOptions::default() {
+            info!("Session overrides: {:?}", options);
+        }
+        self.recording_options = options;
+        #[cfg(feature = "gui")]
+        if let Some(mut meter) = self.overlay_meter.take() {
+            meter.stop_monitor();
+        }
+        // Before the overlay shows, in case the compositor focuses it
+        self.focus_target = if self.config.output.refocus_previous {
+            crate::output::capture_focus().await
+        } else {
+            None
+        };
         self.state = DaemonState::Recording;
         self.recording_start = Some(std::time::Instant::now());
+        self.last_activity = std::time::Instant::now();
         self.notify_state();
 
-        let mut recorder = AudioRecorder::new(&self.config.audio, self.amplitude_tx.clone())?;
+        let mut recorder = self.new_recorder()?;
         recorder.start().await?;
         self.recorder = Some(recorder);
 
@@ -456,30 +1265,400 @@ impl Daemon {
         Ok(())
     }
 
-    async fn stop_and_transcribe(&mut self) -> Result<()> {
-        info!("Stopping recording and transcribing");
-        self.state = DaemonState::Transcribing;
-        self.notify_state();
-        self.ui
-            .show_notification("TJvox", "Transcribing...")
-            .await?;
-
-        let audio_path = match self.recorder.as_mut() {
-            Some(recorder) => recorder.stop().await?,
-            None => {
-                self.state = DaemonState::Idle;
-                self.notify_state();
-                return Err(anyhow::anyhow!("No active recorder"));
-            }
+    /// Stop the current recording and queue it for transcription.
+    async fn stop_recording(&mut self) -> Result<()> {
+        info!("Stopping recording");
+        let Some(mut recorder) = self.recorder.take() else {
+            return Err(anyhow::anyhow!("No active recorder"));
         };
-
-        // Transcribe using whisper-rs (model loads if not already loaded)
-        let text = self.transcriber.transcribe(&audio_path).await?;
-
+        let duration_ms = self
+            .recording_start
+            .take()
+            .map(|start| start.elapsed().as_millis() as u64)
+            .unwrap_or(0);
+        let audio = match recorder.stop().await {
+            Ok(audio) => audio,
+            Err(e) => {
+                let e = match e.downcast::<CaptureLost>() {
+                    Ok(CaptureLost {
+                        reason,
+                        partial: Some(audio),
+                    }) => self.retain_lost_capture(reason, audio, duration_ms).await,
+                    Ok(lost) => lost.into(),
+                    Err(e) => e,
+                };
+                recorder.cleanup().await.ok();
+                return Err(e);
+            }
+        };
+
+        if recorder.is_clipping() {
+            let _ = self
+                .ui
+                .show_notification("TJvox", "Input is clipping, lower your mic gain")
+                .await;
+        }
+
+        let focus = self.focus_target.take();
+        let options = std::mem::take(&mut self.recording_options);
+        self.queue.push_back(QueuedRecording {
+            audio,
+            duration_ms,
+            focus,
+            options,
+        });
+        if self.in_flight.is_some() {
+            info!("Transcriber busy, {} recording(s) queued", self.queue.len());
+        }
+        self.settle_state();
+        self.ui
+            .show_notification("TJvox", "Transcribing...")
+            .await?;
+        self.start_next_transcription().await;
+        Ok(())
+    }
+
+    /// Keep the audio recorded before a lost capture for `retry-last` and
+    /// return the error to report, naming where it was kept.
+    async fn retain_lost_capture(
+        &mut self,
+        reason: String,
+        audio: RecordedAudio,
+        duration_ms: u64,
+    ) -> anyhow::Error {
+        let recording = QueuedRecording {
+            audio,
+            duration_ms,
+            focus: self.focus_target.take(),
+            options: std::mem::take(&mut self.recording_options),
+        };
+        let lost = CaptureLost {
+            reason,
+            partial: None,
+        };
+        match self.retain_recording(recording, None).await {
+            Some(RecordedAudio::File(path)) => anyhow::anyhow!(
+                "{}\nPartial recording kept at {}; run `tjvox retry-last` to transcribe it",
+                lost,
+                path.display()
+            ),
+            Some(RecordedAudio::Memory { .. }) => anyhow::anyhow!(
+                "{}\nPartial recording kept in memory; run `tjvox retry-last` to transcribe it",
+                lost
+            ),
+            None => lost.into(),
+        }
+    }
+
+    /// Start transcribing the oldest queued recording unless the transcriber
+    /// is busy with another recording or an audio file.
+    async fn start_next_transcription(&mut self) {
+        if self.transcriber_busy() {
+            return;
+        }
+        let Some(recording) = self.queue.pop_front() else {
+            return;
+        };
+
+        // Detecting the language and loading its model take a while, so the
+        // per-language model is picked in the job as well
+        let language = self
+            .config
+            .transcription
+            .language
+            .clone()
+            .filter(|lang| lang != "auto");
+        let choice = (!self.config.transcription.language_models.is_empty()).then(|| ModelChoice {
+            language: language.clone(),
+            models: self.config.transcription.language_models.clone(),
+            base_model: self.base_model.clone(),
+            loader: self.model_loader(),
+        });
+        let model = self.config.transcription.model.clone();
+
+        // Transcribe using whisper-rs (model loads if not already loaded)
+        let transcriber = self.transcriber.clone();
+        let audio = recording.audio.clone();
+        let on_partial = self.partial_transcript_callback();
+        let on_progress = self.progress_callback();
+        let handle = self.executor.spawn(async move {
+            let mut transcriber = transcriber.lock().await;
+            let (language, model) = match choice {
+                Some(choice) => choice.apply(&mut transcriber, &audio).await,
+                None => (language, model),
+            };
+            let transcription = transcriber
+                .transcribe_detailed(&audio, on_partial, on_progress)
+                .await
+                .map(|mut transcription| {
+                    transcription.language = language.or(transcription.language.take());
+                    transcription
+                });
+            Dictated {
+                model,
+                transcription,
+            }
+        });
+
+        self.in_flight = Some(InFlight {
+            recording,
+            started: std::time::Instant::now(),
+            handle,
+        });
+    }
+
+    /// Output a finished transcription, then continue with the next queued
+    /// recording. Results are delivered in recording order.
+    async fn finish_transcription(
+        &mut self,
+        result: std::result::Result<Dictated, tokio::task::JoinError>,
+    ) {
+        let Some(job) = self.in_flight.take() else {
+            return;
+        };
+
+        let result = match result {
+            Ok(dictated) => {
+                if dictated.model != self.config.transcription.model {
+                    self.config.transcription.model = dictated.model;
+                    #[cfg(feature = "gui")]
+                    self.notify_model_changed();
+                }
+                dictated.transcription
+            }
+            Err(e) => Err(anyhow::anyhow!("Transcription task failed: {}", e)),
+        };
+        if let Ok(ref transcription) = result {
+            self.metrics
+                .record_transcription(transcription.duration_ms, job.started.elapsed());
+        }
+        let delivered = match result {
+            Ok(transcription) if self.run_voice_command(&transcription).await => Ok(()),
+            Ok(transcription) => match self.prepare_text(transcription, &job).await {
+                Some(text) if !self.output_enabled => {
+                    let preview: String = text.chars().take(50).collect();
+                    let _ = self
+                        .ui
+                        .show_notification("TJvox", &format!("Saved to history: {}", preview))
+                        .await;
+                    Ok(())
+                }
+                Some(text) => self
+                    .type_output(&text, job.recording.focus.as_ref())
+                    .await
+                    .map_err(|e| (e, Some(text))),
+                None => {
+                    self.metrics.record_no_speech();
+                    Ok(())
+                }
+            },
+            Err(e) => Err((e, None)),
+        };
+
+        match delivered {
+            Ok(()) => {
+                self.last_error = None;
+                // Cleanup
+                job.recording.audio.remove().await;
+            }
+            Err((e, text)) => {
+                error!("Failed to transcribe: {}", e);
+                let kind = if text.is_some() {
+                    ErrorKind::Output
+                } else {
+                    ErrorKind::Transcription
+                };
+                self.record_failure(kind, &e);
+                let message = match self.retain_recording(job.recording, text).await {
+                    Some(RecordedAudio::File(path)) => format!(
+                        "{}\nRecording kept at {}; run `tjvox retry-last` to try again",
+                        e,
+                        path.display()
+                    ),
+                    Some(RecordedAudio::Memory { .. }) => format!(
+                        "{}\nRecording kept in memory; run `tjvox retry-last` to try again",
+                        e
+                    ),
+                    None => e.to_string(),
+                };
+                let _ = self.ui.show_error("TJvox", &message).await;
+            }
+        }
+
+        self.resume_transcriber().await;
+
+        self.last_activity = std::time::Instant::now();
+        self.settle_state();
+        if self.state == DaemonState::Idle {
+            let _ = self.ui.show_notification("TJvox", "Ready").await;
+        }
+    }
+
+    /// Continue with what waited for the transcriber once an audio file is
+    /// transcribed.
+    async fn finish_file_job(&mut self) {
+        self.resume_transcriber().await;
+        self.settle_state();
+    }
+
+    /// Whether a recording or an audio file is being transcribed. The
+    /// transcriber is locked meanwhile, so the event loop must not wait for it.
+    fn transcriber_busy(&self) -> bool {
+        self.in_flight.is_some() || self.file_jobs.is_running()
+    }
+
+    /// Once nothing holds the transcriber, switch to a model picked in the
+    /// meantime and start the next queued recording. In cold mode the model
+    /// is unloaded when nothing is left to transcribe.
+    async fn resume_transcriber(&mut self) {
+        if self.transcriber_busy() {
+            return;
+        }
+        if let Some(model) = self.pending_model.take() {
+            self.switch_model(model);
+        }
+        self.start_next_transcription().await;
+        if !self.transcriber_busy() && self.whisper_mode == WhisperMode::Cold {
+            self.spawn_unload();
+        }
+    }
+
+    /// Unload the model on the transcription thread, after the jobs already
+    /// waiting there.
+    fn spawn_unload(&self) {
+        let transcriber = self.transcriber.clone();
+        self.executor.spawn(async move { transcriber.lock().await.unload_model() });
+    }
+
+    /// Run a model switch or load on the transcription thread without
+    /// waiting for it. The tray shows the model as loading until it is done.
+    fn spawn_model_job<F>(&self, job: F)
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        #[cfg(feature = "gui")]
+        self.broadcast(DaemonMsg::ModelLoading);
+        let done = self.executor.spawn(job);
+        #[cfg(feature = "gui")]
+        {
+            let txs = self.daemon_txs.clone();
+            let msg = DaemonMsg::ModelChanged(self.config.transcription.model.clone());
+            tokio::spawn(async move {
+                let _ = done.await;
+                for tx in &txs {
+                    let _ = tx.try_send(msg.clone());
+                }
+            });
+        }
+        #[cfg(not(feature = "gui"))]
+        drop(done);
+    }
+
+    /// Everything the transcription thread needs to switch to another model.
+    fn model_loader(&self) -> ModelLoader {
+        ModelLoader {
+            transcription: self.config.transcription.clone(),
+            audio: self.config.audio.clone(),
+            on_download: download_notifier(&self.ui),
+            load: self.whisper_mode == WhisperMode::Hot,
+        }
+    }
+
+    /// Press the keys of the voice command `result` names, if any. Returns
+    /// whether the transcription was taken as a command, in which case it is
+    /// neither typed nor saved to history.
+    async fn run_voice_command(&mut self, result: &Transcription) -> bool {
+        let transcription = &self.config.transcription;
+        let Some(engine) = self.command_engine.as_ref().filter(|_| self.output_enabled) else {
+            return false;
+        };
+        if result.is_no_speech(transcription.no_speech_threshold) {
+            return false;
+        }
+        let matched = engine.match_text(
+            &result.text,
+            transcription.command_prefix.as_deref(),
+            transcription.command_mode,
+        );
+        let command = match matched {
+            None => return false,
+            Some(CommandMatch::Unknown(phrase)) => {
+                info!("Unknown voice command: {:?}", phrase);
+                let _ = self
+                    .ui
+                    .show_notification("TJvox", &format!("Unknown command: {}", phrase))
+                    .await;
+                return true;
+            }
+            Some(CommandMatch::Run(command)) => command.clone(),
+        };
+
+        info!("Running voice command {:?} ({})", command.phrase, command.keys);
+        if self.recorder.is_none() {
+            self.state = DaemonState::Typing;
+            self.notify_state();
+        }
+        let pressed = match OutputManager::new(&self.config.output) {
+            Ok(output) => output.press_keys(&command.chords).await,
+            Err(e) => Err(e),
+        };
+        match pressed {
+            Ok(()) => {
+                let message = if self.config.ui.verbose {
+                    format!("Command: {} ({})", command.phrase, command.keys)
+                } else {
+                    format!("Command: {}", command.phrase)
+                };
+                let _ = self.ui.show_notification("TJvox", &message).await;
+            }
+            Err(e) => {
+                error!("Voice command {:?} failed: {}", command.phrase, e);
+                self.record_failure(ErrorKind::Output, &e);
+                let _ = self.ui.show_error("TJvox", &e.to_string()).await;
+            }
+        }
+        true
+    }
+
+    /// Post-process a transcription and save it to history. Returns `None`
+    /// when no speech was detected.
+    async fn prepare_text(&mut self, result: Transcription, job: &InFlight) -> Option<String> {
+        let transcription = &self.config.transcription;
+        if result.is_no_speech(transcription.no_speech_threshold) {
+            info!(
+                "Silence (no-speech probability {:.2}), discarding: {:?}",
+                result.no_speech_probability.unwrap_or_default(),
+                result.text
+            );
+            let _ = self.ui.show_notification("TJvox", "No speech detected").await;
+            return None;
+        }
+        let punctuated = if transcription.auto_punctuate {
+            result.punctuated_text(transcription.pause_comma_ms, transcription.pause_period_ms)
+        } else {
+            None
+        };
+        let text = result.text;
+        if is_junk(&text, &transcription.junk_phrases, transcription.drop_punctuation_only) {
+            info!("Discarding spurious transcription: {:?}", text);
+            let _ = self.ui.show_notification("TJvox", "No speech detected").await;
+            return None;
+        }
+        let text = punctuated.unwrap_or(text);
+
         // LLM post-processing (grammar/punctuation correction)
-        let text = if let Some(ref llm) = self.llm_processor {
+        let llm = self.llm_processor.as_ref().filter(|_| self.llm_enabled);
+        let text = if let Some(llm) = llm {
             match llm.process(&text).await {
                 Ok(corrected) => corrected,
+                Err(TjvoxError::LlmTimeout(ms)) => {
+                    warn!(
+                        "LLM did not answer within {}ms, using original text; \
+                         raise llm.timeout_ms or set llm.timeout_retry_ms for slow models",
+                        ms
+                    );
+                    text
+                }
                 Err(e) => {
                     warn!("LLM processing failed, using original text: {}", e);
                     text
@@ -490,77 +1669,181 @@ impl Daemon {
         };
 
         // Apply post-processing
-        let text = self.post_process(&text);
-
-        let duration_ms = self
-            .recording_start
-            .map(|start| start.elapsed().as_millis() as u64)
-            .unwrap_or(0);
+        let text = self.post_process(&text, job.recording.options);
 
         if text.trim().is_empty() {
-            self.ui
-                .show_notification("TJvox", "No speech detected")
-                .await?;
-        } else {
-            // Type/paste the result
+            let _ = self.ui.show_notification("TJvox", "No speech detected").await;
+            return None;
+        }
+
+        // Save to history before typing, so a failed output can be retried
+        if let Some(history) = self.history.clone() {
+            let entry = crate::history::HistoryEntry {
+                id: 0,
+                timestamp: String::new(),
+                duration_ms: job.recording.duration_ms,
+                text: text.clone(),
+                model: self.config.transcription.model.clone(),
+                language: result.language.unwrap_or_default(),
+                confidence: result.confidence,
+            };
+            // A locked database is retried with backoff; keep serving input meanwhile
+            let saved = tokio::task::spawn_blocking(move || {
+                let store = history
+                    .lock()
+                    .map_err(|_| anyhow::anyhow!("History mutex poisoned"))?;
+                store.save(&entry)
+            })
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|saved| saved);
+            if let Err(e) = saved {
+                warn!("Failed to save history entry: {}", e);
+            }
+        }
+        Some(text)
+    }
+
+    /// Type/paste the text and run the post-transcription hook. `focus` is
+    /// the window to give focus back to first.
+    async fn type_output(&mut self, text: &str, focus: Option<&FocusTarget>) -> Result<()> {
+        // A new recording may already be running
+        if self.recorder.is_none() {
             self.state = DaemonState::Typing;
             self.notify_state();
-            let output = OutputManager::new(&self.config.output)?;
-            output.type_text(&text).await?;
-            self.ui
-                .show_notification(
-                    "TJvox",
-                    &format!("Typed: {}", &text[..text.len().min(50)]),
-                )
-                .await?;
-
-            // Save to history
-            if let Some(ref history) = self.history {
-                let entry = crate::history::HistoryEntry {
-                    id: 0,
-                    timestamp: String::new(),
-                    duration_ms,
-                    text: text.clone(),
-                    model: self.config.transcription.model.clone(),
-                    language: self
-                        .config
-                        .transcription
-                        .language
-                        .clone()
-                        .unwrap_or_default(),
-                };
-                if let Err(e) = history.save(&entry) {
-                    warn!("Failed to save history entry: {}", e);
-                }
+        }
+        let mut output_config = self.config.output.clone();
+        if let Some(ref keys) = self.paste_keys_override {
+            output_config.paste_keys = Some(keys.clone());
+        }
+        let output = OutputManager::new(&output_config)?;
+        if let Some(target) = focus {
+            if let Err(e) = crate::output::restore_focus(target).await {
+                warn!("Failed to focus the previous window: {}", e);
             }
         }
+        let method = output.type_text(text).await?;
+        let preview: String = text.chars().take(50).collect();
+        let message = if method == OutputMethod::ClipboardNotify {
+            format!("Copied to clipboard, press Ctrl+V to paste: {}", preview)
+        } else if self.config.ui.verbose {
+            format!("Typed ({}): {}", method, preview)
+        } else {
+            format!("Typed: {}", preview)
+        };
+        self.ui.show_notification("TJvox", &message).await?;
 
-        // Cleanup
-        if let Some(recorder) = self.recorder.take() {
-            recorder.cleanup().await.ok();
+        if let Some(ref cmd) = self.config.hooks.post_transcription {
+            crate::hooks::spawn_post_transcription(cmd, text);
         }
 
-        // Unload model in cold mode
-        if self.whisper_mode == WhisperMode::Cold {
-            self.transcriber.unload_model();
+        let cooldown = self.config.daemon.cooldown_ms;
+        if cooldown > 0 {
+            self.cooldown_until =
+                Some(std::time::Instant::now() + std::time::Duration::from_millis(cooldown));
         }
-
-        self.state = DaemonState::Idle;
-        self.notify_state();
-        self.ui.show_notification("TJvox", "Ready").await?;
         Ok(())
     }
 
-    fn post_process(&self, text: &str) -> String {
+    /// Keep a recording whose transcription or output failed, replacing any
+    /// earlier one, so `retry-last` can use it. In-memory recordings stay in
+    /// memory. Returns where the recording was kept.
+    async fn retain_recording(
+        &mut self,
+        recording: QueuedRecording,
+        text: Option<String>,
+    ) -> Option<RecordedAudio> {
+        let kept = match recording.audio {
+            RecordedAudio::File(path) => {
+                let kept = Path::new(&self.config.audio.temp_dir).join(RETAINED_RECORDING);
+                if let Err(e) = tokio::fs::rename(&path, &kept).await {
+                    warn!("Failed to keep recording for retry: {}", e);
+                    tokio::fs::remove_file(&path).await.ok();
+                    return None;
+                }
+                RecordedAudio::File(kept)
+            }
+            memory => memory,
+        };
+        if let Some(earlier) = self.retained.take() {
+            if earlier.audio.path() != kept.path() {
+                earlier.audio.remove().await;
+            }
+        }
+        self.retained = Some(RetainedRecording {
+            audio: kept.clone(),
+            duration_ms: recording.duration_ms,
+            text,
+            options: recording.options,
+        });
+        Some(kept)
+    }
+
+    /// Output the retained transcription again, re-transcribe the retained
+    /// audio, or fall back to the latest history entry.
+    async fn retry_last(&mut self) -> String {
+        if self.recorder.is_some() {
+            return "error: recording in progress".to_string();
+        }
+
+        let Some(retained) = self.retained.take() else {
+            let latest = self
+                .history
+                .as_ref()
+                .and_then(|history| history.lock().ok()?.list(1).ok())
+                .and_then(|entries| entries.into_iter().next());
+            let Some(entry) = latest else {
+                return "error: nothing to retry".to_string();
+            };
+            let result = self.type_output(&entry.text, None).await;
+            self.settle_state();
+            return match result {
+                Ok(()) => format!("ok: typed history entry #{}", entry.id),
+                Err(e) => format!("error: {}", e),
+            };
+        };
+
+        match retained.text {
+            Some(ref text) => {
+                let result = self.type_output(text, None).await;
+                self.settle_state();
+                match result {
+                    Ok(()) => {
+                        retained.audio.remove().await;
+                        "ok: typed last transcription".to_string()
+                    }
+                    Err(e) => {
+                        self.retained = Some(retained);
+                        format!("error: {}", e)
+                    }
+                }
+            }
+            None => {
+                info!("Re-transcribing {}", retained.audio);
+                self.queue.push_back(QueuedRecording {
+                    audio: retained.audio,
+                    duration_ms: retained.duration_ms,
+                    focus: None,
+                    options: retained.options,
+                });
+                self.settle_state();
+                self.start_next_transcription().await;
+                "ok: re-transcribing last recording".to_string()
+            }
+        }
+    }
+
+    fn post_process(&self, text: &str, options: SessionOptions) -> String {
         let mut result = text.to_string();
 
         // Apply word replacements before filler word removal
-        if let Some(ref engine) = self.replacement_engine {
+        let replacements = self.replacement_engine.as_ref().filter(|_| !options.no_replacements);
+        if let Some(engine) = replacements {
             result = engine.apply(&result);
         }
 
         // Remove filler words if configured (case-insensitive)
-        if self.config.transcription.remove_filler_words {
+        if self.config.transcription.remove_filler_words && !options.keep_fillers {
             let filler_patterns = [
                 r"(?i)\buh\b[,]?",
                 r"(?i)\bum\b[,]?",
@@ -580,8 +1863,17 @@ impl Daemon {
             result = result.trim().to_string();
         }
 
+        // Tidy spacing around spoken punctuation
+        if self.config.replacements.normalize_spacing {
+            result = crate::replacements::normalize_spacing(&result).trim().to_string();
+        }
+        if self.config.replacements.smart_formatting {
+            result = crate::replacements::capitalize_sentences(&result);
+        }
+
         // Append trailing space if configured
-        if self.config.output.append_trailing_space && !result.is_empty() {
+        let trailing_space = self.config.output.append_trailing_space && !options.no_trailing_space;
+        if trailing_space && !result.is_empty() {
             result.push(' ');
         }
 
@@ -589,22 +1881,31 @@ impl Daemon {
     }
 
     #[cfg_attr(not(feature = "gui"), allow(dead_code))]
-    async fn set_whisper_mode(&mut self, mode: WhisperMode) {
+    fn set_whisper_mode(&mut self, mode: WhisperMode) {
         info!("Switching whisper mode to: {}", mode);
         self.whisper_mode = mode;
+        // The running transcription keeps the model loaded, and the last one
+        // to finish unloads it in cold mode
+        if self.transcriber_busy() {
+            #[cfg(feature = "gui")]
+            self.notify_whisper_mode();
+            return;
+        }
         match mode {
             WhisperMode::Hot => {
-                if !self.transcriber.is_loaded() {
-                    #[cfg(feature = "gui")]
-                    self.broadcast(DaemonMsg::ModelLoading);
-                    if let Err(e) = self.transcriber.load_model().await {
-                        error!("Failed to load model for hot mode: {}", e);
+                let transcriber = self.transcriber.clone();
+                self.spawn_model_job(async move {
+                    let mut transcriber = transcriber.lock().await;
+                    if !transcriber.is_loaded() {
+                        if let Err(e) = transcriber.load_model().await {
+                            error!("Failed to load model for hot mode: {}", e);
+                        }
                     }
-                }
+                });
             }
             WhisperMode::Cold => {
                 if self.state == DaemonState::Idle {
-                    self.transcriber.unload_model();
+                    self.spawn_unload();
                 }
             }
         }
@@ -613,124 +1914,416 @@ impl Daemon {
     }
 
     #[cfg_attr(not(feature = "gui"), allow(dead_code))]
-    async fn set_model(&mut self, model: String) {
+    fn set_model(&mut self, model: String) {
+        self.base_model = model.clone();
+        if self.transcriber_busy() {
+            info!("Transcriber busy, switching to model '{}' once it finishes", model);
+            self.pending_model = Some(model);
+            return;
+        }
+        self.switch_model(model);
+    }
+
+    /// Switch to `model` on the transcription thread. Transcriptions started
+    /// afterwards wait there until it is loaded.
+    fn switch_model(&mut self, model: String) {
         if model == self.config.transcription.model {
             return;
         }
         info!("Switching model to: {}", model);
-        #[cfg(feature = "gui")]
-        self.broadcast(DaemonMsg::ModelLoading);
-        // Unload current model so next transcription loads the new one
-        self.transcriber.unload_model();
-        self.config.transcription.model = model;
-        // Recreate transcriber with new config
-        match TranscriptionService::new(&self.config.transcription) {
-            Ok(t) => {
-                self.transcriber = t;
-                // If hot mode, load new model immediately
-                if self.whisper_mode == WhisperMode::Hot {
-                    if let Err(e) = self.transcriber.load_model().await {
-                        error!("Failed to load new model: {}", e);
-                    }
+        self.config.transcription.model = model.clone();
+        let loader = self.model_loader();
+        let transcriber = self.transcriber.clone();
+        self.spawn_model_job(async move {
+            loader.switch(&mut *transcriber.lock().await, model).await;
+        });
+    }
+
+    /// Enable or disable LLM post-processing for this session. The processor
+    /// is built on first enable if `llm.enabled` was off at startup.
+    fn set_llm_enabled(&mut self, enabled: bool) {
+        if enabled && self.llm_processor.is_none() {
+            match LlmProcessor::new(&self.config.llm) {
+                Ok(processor) => self.llm_processor = Some(processor),
+                Err(e) => {
+                    warn!("Cannot enable LLM post-processing: {}", e);
+                    return;
                 }
             }
-            Err(e) => {
-                error!("Failed to create transcriber for new model: {}", e);
-            }
         }
+        info!("LLM post-processing {}", if enabled { "enabled" } else { "disabled" });
+        self.llm_enabled = enabled;
         #[cfg(feature = "gui")]
-        self.notify_model_changed();
+        self.broadcast(DaemonMsg::LlmChanged(self.llm_enabled));
     }
 
-    async fn handle_socket_command(
-        &mut self,
-        cmd: SocketCommand,
-        mut stream: tokio::net::UnixStream,
-    ) -> bool {
-        use tokio::io::AsyncWriteExt;
-
-        let mut should_quit = false;
-        let response = match cmd {
-            SocketCommand::Toggle => {
-                self.handle_toggle().await;
+    /// Show or hide the overlay on request. While shown and idle, the
+    /// microphone is monitored so the level meter stays live.
+    #[cfg(feature = "gui")]
+    async fn set_overlay_visible(&mut self, visible: bool) -> String {
+        if self.daemon_txs.is_empty() {
+            return "error: no overlay (daemon runs without GUI)".to_string();
+        }
+        self.overlay_pinned = visible;
+        self.broadcast(DaemonMsg::OverlayVisible(visible));
+        self.sync_overlay_meter().await;
+        format!("ok: overlay {}", if visible { "shown" } else { "hidden" })
+    }
+
+    #[cfg(not(feature = "gui"))]
+    async fn set_overlay_visible(&mut self, _visible: bool) -> String {
+        "error: no overlay (built without GUI)".to_string()
+    }
+
+    /// Run the meter capture only while the overlay is pinned and idle.
+    #[cfg(feature = "gui")]
+    async fn sync_overlay_meter(&mut self) {
+        let wanted = self.overlay_pinned && self.state == DaemonState::Idle;
+        if !wanted {
+            if let Some(mut meter) = self.overlay_meter.take() {
+                meter.stop_monitor();
+            }
+            return;
+        }
+        if self.overlay_meter.is_some() || self.amplitude_tx.is_none() {
+            return;
+        }
+        let meter = match self.new_recorder() {
+            Ok(mut meter) => meter.start_monitor().await.map(|_| meter),
+            Err(e) => Err(e),
+        };
+        match meter {
+            Ok(meter) => self.overlay_meter = Some(meter),
+            Err(e) => warn!("Could not monitor microphone for the overlay: {}", e),
+        }
+    }
+
+    /// Recorder feeding the overlay's level meter and spectrum.
+    fn new_recorder(&self) -> Result<AudioRecorder> {
+        let recorder = AudioRecorder::new(&self.config.audio, self.amplitude_tx.clone())?;
+        Ok(match self.overlay_sample_tx.clone() {
+            Some(tx) => recorder.with_sample_sink(tx),
+            None => recorder,
+        })
+    }
+
+    /// Answer a socket command. Returns whether the daemon should quit.
+    async fn handle_socket_request(&mut self, request: SocketRequest) -> bool {
+        let SocketRequest { command, reply } = request;
+        let quit = command == SocketCommand::Quit;
+        let response = match command {
+            SocketCommand::Transcribe(path) => {
+                // Answered from a background task so the daemon keeps
+                // handling input while the file is transcribed
+                self.spawn_file_transcription(path, reply);
+                return false;
+            }
+            SocketCommand::Toggle(options) => {
+                self.dispatch_session_input(InputEvent::Toggle, options).await;
                 format!("ok: {}", self.state)
             }
-            SocketCommand::PushStart => {
-                self.handle_push_start().await;
+            SocketCommand::PushStart(options) => {
+                self.dispatch_session_input(InputEvent::PushStart, options).await;
                 format!("ok: {}", self.state)
             }
             SocketCommand::PushStop => {
                 self.handle_push_stop().await;
                 format!("ok: {}", self.state)
             }
-            SocketCommand::Status => {
-                format!("ok: {}", self.state)
+            SocketCommand::Status => status_line(&self.state, self.last_error.as_ref()),
+            SocketCommand::StatusJson => status_json(&self.state, self.last_error.as_ref()),
+            SocketCommand::SetLlm(enabled) => {
+                self.set_llm_enabled(enabled);
+                format!("ok: llm {}", if self.llm_enabled { "on" } else { "off" })
             }
-            SocketCommand::Quit => {
-                should_quit = true;
-                "ok: quitting".to_string()
+            SocketCommand::ShowOverlay => self.set_overlay_visible(true).await,
+            SocketCommand::HideOverlay => self.set_overlay_visible(false).await,
+            SocketCommand::History(limit) => self.history_json(limit),
+            SocketCommand::RetryLast => self.retry_last().await,
+            SocketCommand::SetOutput(false) if self.history.is_none() => {
+                "error: history is disabled, dictations would be lost".to_string()
             }
+            SocketCommand::SetOutput(enabled) => {
+                self.output_enabled = enabled;
+                info!("Output {}", if enabled { "enabled" } else { "disabled, history only" });
+                format!("ok: output {}", if enabled { "on" } else { "off (history only)" })
+            }
+            SocketCommand::SetPasteKeys(keys) => self.set_paste_keys(keys),
+            SocketCommand::HistoryArchive(path) => match self.history {
+                Some(ref history) => match history.lock().map(|h| h.archive(&path)) {
+                    Ok(Ok(())) => format!("ok: history archived to {}", path.display()),
+                    Ok(Err(e)) => format!("error: {:#}", e),
+                    Err(_) => "error: history mutex poisoned".to_string(),
+                },
+                None => "error: history is disabled".to_string(),
+            },
+            SocketCommand::Quit => "ok: quitting".to_string(),
         };
+        let _ = reply.send(response);
+        quit
+    }
 
-        let _ = stream.write_all(format!("{}\n", response).as_bytes()).await;
-        should_quit
+    /// Transcribe an audio file for a `transcribe` request, and answer on
+    /// `reply` with the result as JSON.
+    fn spawn_file_transcription(&self, path: PathBuf, reply: tokio::sync::oneshot::Sender<String>) {
+        let job = self.file_transcription_job(path);
+        tokio::spawn(async move {
+            let response = match job {
+                Ok(job) => match job.await.map_err(anyhow::Error::from).and_then(|r| r) {
+                    Ok(transcription) => serde_json::to_string(&transcription)
+                        .unwrap_or_else(|e| format!("error: {}", e)),
+                    Err(e) => format!("error: {:#}", e),
+                },
+                Err(e) => format!("error: {:#}", e),
+            };
+            let _ = reply.send(response);
+        });
     }
 
-    async fn handle_push_start(&mut self) {
-        if self.state != DaemonState::Idle {
-            info!("Push-start ignored: currently in {} state", self.state);
-            return;
+    /// Transcribe an audio file dropped on the overlay or picked from the
+    /// tray, and send the text back to the GUI.
+    #[cfg(feature = "gui")]
+    fn spawn_gui_file_transcription(&self, path: PathBuf) {
+        let job = self.file_transcription_job(path.clone());
+        let txs = self.daemon_txs.clone();
+        tokio::spawn(async move {
+            let result = match job {
+                Ok(job) => job
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|r| r)
+                    .map(|transcription| transcription.text),
+                Err(e) => Err(e),
+            };
+            if let Err(ref e) = result {
+                warn!("Failed to transcribe {}: {:#}", path.display(), e);
+            }
+            let msg = DaemonMsg::FileTranscribed {
+                path,
+                result: result.map_err(|e| format!("{:#}", e)),
+            };
+            for tx in &txs {
+                let _ = tx.try_send(msg.clone());
+            }
+        });
+    }
+
+    /// Start transcribing an audio file with the shared transcriber, waiting
+    /// for any dictation in progress. Nothing is typed or saved to history.
+    /// Model and mode changes, and dictations recorded meanwhile, wait until
+    /// the file is done; the daemon then unloads the model in cold mode.
+    fn file_transcription_job(
+        &self,
+        path: PathBuf,
+    ) -> Result<tokio::task::JoinHandle<Result<Transcription>>> {
+        let path = self.check_transcribe_path(&path)?;
+        let transcriber = self.transcriber.clone();
+        let running = self.file_jobs.start();
+        Ok(self.executor.spawn(async move {
+            info!("Transcribing {}", path.display());
+            let result = transcriber
+                .lock()
+                .await
+                .transcribe_detailed(&RecordedAudio::File(path), None, None)
+                .await;
+            // Only once the transcriber is free again
+            drop(running);
+            result
+        }))
+    }
+
+    /// Canonical form of a `transcribe` path after checking that it is an
+    /// absolute path to a readable WAV file inside `daemon.transcribe_dirs`.
+    fn check_transcribe_path(&self, path: &Path) -> Result<PathBuf> {
+        if !path.is_absolute() {
+            anyhow::bail!("audio file path must be absolute: {}", path.display());
         }
-        if let Err(e) = self.start_recording().await {
-            error!("Failed to start recording (push): {}", e);
-            let _ = self.ui.show_error("TJvox", &e.to_string()).await;
-            self.state = DaemonState::Idle;
-            self.notify_state();
+        let canonical = path
+            .canonicalize()
+            .with_context(|| format!("cannot access {}", path.display()))?;
+        if !canonical.is_file() {
+            anyhow::bail!("{} is not a file", path.display());
+        }
+        if !self.config.daemon.transcribe_path_allowed(&canonical) {
+            anyhow::bail!("{} is outside daemon.transcribe_dirs", path.display());
+        }
+        // Checked up front so other formats fail at once with a clear
+        // message instead of after waiting for the transcriber
+        match hound::WavReader::open(&canonical) {
+            Ok(_) => Ok(canonical),
+            Err(hound::Error::IoError(e)) => {
+                Err(anyhow::Error::new(e).context(format!("cannot read {}", path.display())))
+            }
+            Err(_) => anyhow::bail!(
+                "{} is not a WAV file; only WAV audio can be transcribed",
+                path.display()
+            ),
         }
     }
 
-    async fn handle_push_stop(&mut self) {
-        if self.state != DaemonState::Recording {
-            info!("Push-stop ignored: currently in {} state", self.state);
-            return;
-        }
-        if let Err(e) = self.stop_and_transcribe().await {
-            error!("Failed to transcribe (push): {}", e);
-            let _ = self.ui.show_error("TJvox", &e.to_string()).await;
-            self.state = DaemonState::Idle;
-            self.notify_state();
+    /// Try a different paste keystroke without restarting the daemon.
+    fn set_paste_keys(&mut self, keys: Option<String>) -> String {
+        let Some(keys) = keys else {
+            self.paste_keys_override = None;
+            return match self.config.output.paste_keys {
+                Some(ref keys) => format!("ok: paste keys reset to {}", keys),
+                None => "ok: paste keys reset to ctrl+v".to_string(),
+            };
+        };
+        if let Err(e) = crate::output::parse_paste_keys(&keys) {
+            return format!("error: {}", e);
         }
+        info!("Paste keys set to {:?}", keys);
+        let response = format!("ok: paste keys set to {}", keys);
+        self.paste_keys_override = Some(keys);
+        response
+    }
+
+    /// Count a failed dictation and remember it for `status`.
+    fn record_failure(&mut self, kind: ErrorKind, error: &anyhow::Error) {
+        self.metrics.record_error(kind);
+        self.last_error = Some((std::time::Instant::now(), error.to_string()));
+    }
+
+    /// Recent history entries as a single line of JSON, newest first.
+    fn history_json(&self, limit: u32) -> String {
+        let Some(ref history) = self.history else {
+            return "error: history is disabled".to_string();
+        };
+        let Ok(history) = history.lock() else {
+            return "error: history mutex poisoned".to_string();
+        };
+        let entries = match history.list(limit) {
+            Ok(entries) => entries,
+            Err(e) => return format!("error: {}", e),
+        };
+        serde_json::to_string(&entries).unwrap_or_else(|e| format!("error: {}", e))
+    }
+
+    async fn handle_push_stop(&mut self) {
+        self.dispatch_input(InputEvent::PushStop).await;
     }
 
     #[cfg(feature = "ptt")]
     async fn handle_ptt_event(&mut self, evt: crate::ptt::monitor::PttEvent) {
-        match evt {
-            crate::ptt::monitor::PttEvent::KeyDown => self.handle_push_start().await,
-            crate::ptt::monitor::PttEvent::KeyUp => self.handle_push_stop().await,
+        match evt.input_event(self.config.input.ptt_trigger) {
+            Some(event) => self.dispatch_input(event).await,
+            None => tracing::debug!("PTT {:?} ignored (ptt_trigger = up)", evt),
         }
     }
 
     async fn shutdown(mut self) {
+        #[cfg(feature = "wake-word")]
+        if let Some(mut listener) = self.wake_word.take() {
+            listener.stop();
+        }
+        #[cfg(feature = "gui")]
+        if let Some(mut meter) = self.overlay_meter.take() {
+            meter.stop_monitor();
+        }
+
         // Stop any active recording
         if let Some(mut recorder) = self.recorder.take() {
             let _ = recorder.stop().await;
             let _ = recorder.cleanup().await;
         }
 
-        // Unload model
-        self.transcriber.unload_model();
+        // Abandon transcriptions that have not been typed yet
+        if let Some(job) = self.in_flight.take() {
+            job.handle.abort();
+            job.recording.audio.remove().await;
+        }
+        for recording in std::mem::take(&mut self.queue) {
+            recording.audio.remove().await;
+        }
+
+        // Unload model, unless a transcription still holds it
+        if let Ok(mut transcriber) = self.transcriber.try_lock() {
+            transcriber.unload_model();
+        }
 
-        // Remove PID file
-        let uid = unsafe { libc::getuid() };
-        let pid_file = std::path::PathBuf::from(format!("/run/user/{}/tjvox.pid", uid));
-        let _ = fs::remove_file(&pid_file).await;
+        // Compact history now that nothing else is waiting on the daemon
+        if let Some(history) = self.history.as_ref().and_then(|h| h.lock().ok()) {
+            if let Err(e) = history.vacuum() {
+                warn!("Failed to compact history: {}", e);
+            }
+        }
+
+        if let Some(ydotoold) = self.ydotoold.take() {
+            ydotoold.stop().await;
+        }
+        if let Some(server) = self.metrics_server.take() {
+            server.abort();
+        }
+        if let Some(server) = self.api_server.take() {
+            server.abort();
+        }
+
+        // PID file is removed when the lock is dropped with `self`
         info!("Daemon shut down cleanly");
     }
 }
 
+/// Whether a progress update is worth reporting. Whisper calls back often,
+/// so only every `PROGRESS_STEP` percent (and completion) gets through.
+fn progress_milestone(last_reported: &mut i32, progress: i32) -> bool {
+    let progress = progress.clamp(0, 100);
+    if progress >= *last_reported + PROGRESS_STEP
+        || (progress == 100 && *last_reported < 100)
+    {
+        *last_reported = progress;
+        true
+    } else {
+        false
+    }
+}
+
+/// `status` reply: the state, and the last error while it is unresolved.
+fn status_line(state: &DaemonState, last_error: Option<&(std::time::Instant, String)>) -> String {
+    match last_error {
+        Some((at, message)) => format!(
+            "ok: {}; last error {}s ago: {}",
+            state,
+            at.elapsed().as_secs(),
+            message.lines().next().unwrap_or_default()
+        ),
+        None => format!("ok: {}", state),
+    }
+}
+
+/// `status-json` reply, e.g.
+/// `{"state":"idle","last_error":{"message":"...","seconds_ago":42}}`.
+fn status_json(state: &DaemonState, last_error: Option<&(std::time::Instant, String)>) -> String {
+    let last_error = last_error.map(|(at, message)| {
+        serde_json::json!({ "message": message, "seconds_ago": at.elapsed().as_secs() })
+    });
+    serde_json::json!({ "state": state.to_string(), "last_error": last_error }).to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
+
+    /// A daemon with its PID file in `dir`, so it cannot clash with a
+    /// running one.
+    fn test_daemon(dir: &tempfile::TempDir) -> Daemon {
+        let mut config = Config::default();
+        config.history.enabled = false;
+        config.audio.temp_dir = dir.path().display().to_string();
+        let pid_lock = PidLock::acquire(&dir.path().join("tjvox.pid")).unwrap();
+        Daemon::build(config, pid_lock).unwrap()
+    }
+
+    /// Answer to `command`, failing if the daemon does not handle it at once.
+    async fn socket_reply(daemon: &mut Daemon, command: SocketCommand) -> String {
+        let (reply, response) = tokio::sync::oneshot::channel();
+        let request = daemon.handle_socket_request(SocketRequest { command, reply });
+        tokio::time::timeout(Duration::from_secs(1), request)
+            .await
+            .expect("daemon waited for the transcriber");
+        response.await.unwrap()
+    }
 
     #[test]
     fn test_daemon_state_display() {
@@ -754,4 +2347,92 @@ mod tests {
         assert_ne!(DaemonState::Recording, DaemonState::Transcribing);
         assert_ne!(DaemonState::Transcribing, DaemonState::Typing);
     }
+
+    #[test]
+    fn test_status_last_error() {
+        let state = DaemonState::Idle;
+        assert_eq!(status_line(&state, None), "ok: idle");
+        let json: serde_json::Value = serde_json::from_str(&status_json(&state, None)).unwrap();
+        assert_eq!(json["state"], "idle");
+        assert!(json["last_error"].is_null());
+
+        let error = (std::time::Instant::now(), "Output failed\nRecording kept".to_string());
+        assert_eq!(
+            status_line(&state, Some(&error)),
+            "ok: idle; last error 0s ago: Output failed"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&status_json(&state, Some(&error))).unwrap();
+        assert_eq!(json["state"], "idle");
+        assert_eq!(json["last_error"]["message"], "Output failed\nRecording kept");
+        assert_eq!(json["last_error"]["seconds_ago"], 0);
+    }
+
+    #[test]
+    fn test_progress_milestone() {
+        let mut last = 0;
+        let reported: Vec<i32> = [0, 5, 10, 12, 19, 20, 35, 95, 100, 100]
+            .into_iter()
+            .filter(|&p| progress_milestone(&mut last, p))
+            .collect();
+        assert_eq!(reported, vec![10, 20, 35, 95, 100]);
+    }
+
+    #[tokio::test]
+    async fn test_status_answers_during_file_transcription() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut daemon = test_daemon(&dir);
+        // Stands in for a long file transcription
+        let running = daemon.file_jobs.start();
+        let held = daemon.transcriber.clone().lock_owned().await;
+
+        assert_eq!(socket_reply(&mut daemon, SocketCommand::Status).await, "ok: idle");
+
+        // Model and mode changes wait for the file
+        daemon.set_model("small".to_string());
+        daemon.set_whisper_mode(WhisperMode::Hot);
+        daemon.set_whisper_mode(WhisperMode::Cold);
+        assert_eq!(daemon.pending_model.as_deref(), Some("small"));
+        assert_eq!(daemon.config.transcription.model, "base");
+
+        drop(held);
+        drop(running);
+        daemon.finish_file_job().await;
+        assert_eq!(daemon.pending_model, None);
+        assert_eq!(daemon.config.transcription.model, "small");
+    }
+
+    #[tokio::test]
+    async fn test_status_answers_while_dictation_picks_its_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut daemon = test_daemon(&dir);
+        daemon.config.transcription.language = None;
+        daemon
+            .config
+            .transcription
+            .language_models
+            .insert("de".to_string(), "small".to_string());
+        // Stands in for a long job on the transcription thread, like loading
+        // a model, that language detection has to wait for
+        let held = daemon.transcriber.clone().lock_owned().await;
+
+        daemon.queue.push_back(QueuedRecording {
+            audio: RecordedAudio::Memory {
+                samples: Arc::new(vec![0.0; 16000]),
+                sample_rate: 16000,
+            },
+            duration_ms: 1000,
+            focus: None,
+            options: SessionOptions::default(),
+        });
+        tokio::time::timeout(Duration::from_secs(1), daemon.start_next_transcription())
+            .await
+            .expect("daemon waited for language detection");
+        daemon.settle_state();
+        assert_eq!(socket_reply(&mut daemon, SocketCommand::Status).await, "ok: transcribing");
+
+        // Never run the job, which would load a real model
+        daemon.in_flight.take().unwrap().handle.abort();
+        drop(held);
+    }
 }
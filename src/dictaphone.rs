@@ -0,0 +1,250 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::audio::{AudioRecorder, SampleChunk};
+use crate::config::Config;
+use crate::error::TjvoxError;
+use crate::replacements::{capitalize_sentences, is_junk, normalize_spacing, ReplacementEngine};
+use crate::transcription::create_transcriber;
+
+/// How often captured audio is collected from the capture thread
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Longest run of repeated words looked for where two chunks overlap
+const MAX_OVERLAP_WORDS: usize = 12;
+
+pub struct DictaphoneOptions {
+    pub interval: Duration,
+    /// Audio from the end of each chunk that is transcribed again at the start
+    /// of the next one, so words spanning a boundary are not cut in half
+    pub overlap: Duration,
+    pub output: PathBuf,
+    /// Prefix each line with the local time
+    pub timestamps: bool,
+}
+
+/// Splits a continuous sample stream into chunks of `interval` new audio,
+/// each starting with the last `overlap` of the previous chunk.
+pub struct Chunker {
+    buffer: Vec<f32>,
+    /// Samples at the start of `buffer` already sent in the previous chunk
+    carried: usize,
+    interval_len: usize,
+    overlap_len: usize,
+}
+
+impl Chunker {
+    pub fn new(sample_rate: u32, interval: Duration, overlap: Duration) -> Self {
+        let samples = |d: Duration| (sample_rate as u128 * d.as_millis() / 1000) as usize;
+        let interval_len = samples(interval).max(1);
+        Self {
+            buffer: Vec::new(),
+            carried: 0,
+            interval_len,
+            overlap_len: samples(overlap).min(interval_len),
+        }
+    }
+
+    /// Add captured samples; returns a chunk once `interval` of new audio
+    /// has accumulated.
+    pub fn push(&mut self, samples: &[f32]) -> Option<Vec<f32>> {
+        self.buffer.extend_from_slice(samples);
+        if self.buffer.len() - self.carried < self.interval_len {
+            return None;
+        }
+        let end = self.carried + self.interval_len;
+        let chunk = self.buffer[..end].to_vec();
+        self.buffer.drain(..end - self.overlap_len);
+        self.carried = self.overlap_len;
+        Some(chunk)
+    }
+
+    /// Whatever new audio is left when recording stops.
+    pub fn flush(&mut self) -> Option<Vec<f32>> {
+        if self.buffer.len() <= self.carried {
+            return None;
+        }
+        self.carried = 0;
+        Some(std::mem::take(&mut self.buffer))
+    }
+}
+
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Drop the words at the start of `next` that repeat the end of `previous`,
+/// which is what transcribing the overlap window twice produces. Matching
+/// ignores case and punctuation; without a match `next` is kept as is.
+pub fn merge_overlap(previous: &str, next: &str) -> String {
+    let prev: Vec<String> = previous.split_whitespace().map(normalize).collect();
+    let words: Vec<&str> = next.split_whitespace().collect();
+    let norm: Vec<String> = words.iter().map(|w| normalize(w)).collect();
+
+    let longest = MAX_OVERLAP_WORDS.min(prev.len()).min(words.len());
+    let repeated = (1..=longest)
+        .rev()
+        .find(|&k| prev[prev.len() - k..] == norm[..k])
+        .unwrap_or(0);
+    words[repeated..].join(" ")
+}
+
+/// Record continuously and append a transcription of every `interval` to
+/// the output file until Ctrl+C.
+pub async fn run(config: Config, options: DictaphoneOptions) -> Result<()> {
+    if options.interval.is_zero() {
+        return Err(TjvoxError::Config(
+            "dictaphone interval must be at least 1 second".to_string(),
+        )
+        .into());
+    }
+    if options.overlap >= options.interval {
+        return Err(TjvoxError::Config(
+            "dictaphone overlap must be shorter than the interval".to_string(),
+        )
+        .into());
+    }
+
+    let mut transcriber = create_transcriber(&config.transcription, &config.audio)?;
+    transcriber.load_model().await?;
+
+    let replacements = if config.replacements.enabled {
+        ReplacementEngine::load(&config.replacements.file.paths())
+            .map_err(|e| warn!("Failed to load replacements: {}", e))
+            .ok()
+    } else {
+        None
+    };
+
+    let (sample_tx, sample_rx) = std::sync::mpsc::channel::<SampleChunk>();
+    let mut recorder = AudioRecorder::new(&config.audio, None)?.with_sample_sink(sample_tx);
+    recorder.start_monitor().await?;
+
+    let sample_rate = config.audio.sample_rate;
+    let mut chunker = Chunker::new(sample_rate, options.interval, options.overlap);
+    let mut previous = String::new();
+
+    let transcribe_chunk = |chunk: Vec<f32>, previous: &mut String| -> Result<Option<String>> {
+        let text = tokio::task::block_in_place(|| transcriber.transcribe_samples(&chunk, sample_rate))?;
+        let transcription = &config.transcription;
+        if is_junk(&text, &transcription.junk_phrases, transcription.drop_punctuation_only) {
+            return Ok(None);
+        }
+        let text = match replacements {
+            Some(ref engine) => engine.apply(&text),
+            None => text,
+        };
+        let text = if config.replacements.normalize_spacing {
+            normalize_spacing(&text).trim().to_string()
+        } else {
+            text
+        };
+        let text = if config.replacements.smart_formatting {
+            capitalize_sentences(&text)
+        } else {
+            text
+        };
+        let merged = merge_overlap(previous, &text);
+        *previous = text;
+        Ok((!merged.trim().is_empty()).then_some(merged))
+    };
+
+    eprintln!(
+        "Dictaphone: transcribing every {}s to {}. Press Ctrl+C to stop.",
+        options.interval.as_secs(),
+        options.output.display()
+    );
+
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    let mut ticker = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => break,
+            _ = ticker.tick() => {}
+        }
+        while let Ok(samples) = sample_rx.try_recv() {
+            if let Some(chunk) = chunker.push(&samples.into_rate(sample_rate)) {
+                if let Some(text) = transcribe_chunk(chunk, &mut previous)? {
+                    append_line(&options, &text).await?;
+                }
+            }
+        }
+    }
+
+    recorder.stop_monitor();
+    while let Ok(samples) = sample_rx.try_recv() {
+        if let Some(chunk) = chunker.push(&samples.into_rate(sample_rate)) {
+            if let Some(text) = transcribe_chunk(chunk, &mut previous)? {
+                append_line(&options, &text).await?;
+            }
+        }
+    }
+    if let Some(chunk) = chunker.flush() {
+        if let Some(text) = transcribe_chunk(chunk, &mut previous)? {
+            append_line(&options, &text).await?;
+        }
+    }
+
+    info!("Dictaphone stopped");
+    eprintln!("\nStopped. Notes saved to {}", options.output.display());
+    Ok(())
+}
+
+async fn append_line(options: &DictaphoneOptions, text: &str) -> Result<()> {
+    let line = if options.timestamps {
+        format!("[{}] {}", chrono::Local::now().format("%H:%M:%S"), text)
+    } else {
+        text.to_string()
+    };
+    eprintln!("{}", line);
+    crate::output::append_to_file(&options.output, &line).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunker_overlap() {
+        // 10 samples per second: interval 1s, overlap 0.2s
+        let mut chunker = Chunker::new(10, Duration::from_secs(1), Duration::from_millis(200));
+        let samples: Vec<f32> = (0..25).map(|i| i as f32).collect();
+
+        assert_eq!(chunker.push(&samples[..6]), None);
+        let first = chunker.push(&samples[6..12]).unwrap();
+        assert_eq!(first, (0..10).map(|i| i as f32).collect::<Vec<_>>());
+
+        // The next chunk starts with the last two samples of the first
+        let second = chunker.push(&samples[12..25]).unwrap();
+        assert_eq!(second, (8..20).map(|i| i as f32).collect::<Vec<_>>());
+
+        assert_eq!(chunker.flush().unwrap(), (18..25).map(|i| i as f32).collect::<Vec<_>>());
+        assert_eq!(chunker.flush(), None);
+    }
+
+    #[test]
+    fn test_chunker_flush_without_new_audio() {
+        let mut chunker = Chunker::new(10, Duration::from_secs(1), Duration::from_millis(200));
+        let samples = vec![0.0; 10];
+        assert!(chunker.push(&samples).is_some());
+        // Only the carried overlap remains, which was already transcribed
+        assert_eq!(chunker.flush(), None);
+    }
+
+    #[test]
+    fn test_merge_overlap() {
+        assert_eq!(
+            merge_overlap("we should meet on Tuesday", "on Tuesday, at noon."),
+            "at noon."
+        );
+        assert_eq!(merge_overlap("the end.", "The end of the story"), "of the story");
+        assert_eq!(merge_overlap("nothing shared", "completely new"), "completely new");
+        assert_eq!(merge_overlap("", "first chunk"), "first chunk");
+        assert_eq!(merge_overlap("same words", "same words"), "");
+    }
+}
@@ -25,6 +25,13 @@ pub enum TjvoxError {
 
     #[error("LLM processing error: {0}")]
     Llm(String),
+
+    /// The LLM did not answer within the deadline, in milliseconds
+    #[error("LLM request timed out after {0}ms")]
+    LlmTimeout(u64),
+
+    #[error("Hook error: {0}")]
+    Hook(String),
 }
 
 #[cfg(test)]
@@ -73,6 +80,18 @@ mod tests {
         assert_eq!(err.to_string(), "LLM processing error: timeout");
     }
 
+    #[test]
+    fn test_error_display_llm_timeout() {
+        let err = TjvoxError::LlmTimeout(5000);
+        assert_eq!(err.to_string(), "LLM request timed out after 5000ms");
+    }
+
+    #[test]
+    fn test_error_display_hook() {
+        let err = TjvoxError::Hook("exited with 1".to_string());
+        assert_eq!(err.to_string(), "Hook error: exited with 1");
+    }
+
     #[test]
     fn test_error_from_io() {
         let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "file not found");
@@ -0,0 +1,51 @@
+use serde::Serialize;
+
+/// Progress of a `tjvox run --events` session, written to stdout as one JSON
+/// object per line so another program can follow along.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum SessionEvent {
+    /// `path` is `null` for `audio.in_memory` recordings
+    RecordingStarted { path: Option<String> },
+    Transcribing { clipping: bool },
+    Transcribed { text: String },
+    NoSpeech,
+    Typed { method: String },
+}
+
+impl SessionEvent {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Print the event as a single line on stdout.
+    pub fn emit(&self) {
+        println!("{}", self.to_json());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_event_json() {
+        assert_eq!(
+            SessionEvent::RecordingStarted { path: Some("/tmp/a.wav".to_string()) }.to_json(),
+            r#"{"event":"recording_started","path":"/tmp/a.wav"}"#
+        );
+        assert_eq!(
+            SessionEvent::RecordingStarted { path: None }.to_json(),
+            r#"{"event":"recording_started","path":null}"#
+        );
+        assert_eq!(SessionEvent::NoSpeech.to_json(), r#"{"event":"no_speech"}"#);
+        assert_eq!(
+            SessionEvent::Transcribed { text: "say \"hi\"\nthere".to_string() }.to_json(),
+            r#"{"event":"transcribed","text":"say \"hi\"\nthere"}"#
+        );
+        assert_eq!(
+            SessionEvent::Typed { method: "paste".to_string() }.to_json(),
+            r#"{"event":"typed","method":"paste"}"#
+        );
+    }
+}
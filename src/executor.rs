@@ -0,0 +1,83 @@
+//! Dedicated thread for transcription work. Whisper blocks for as long as
+//! a transcription takes, so running it here keeps the daemon's tokio
+//! workers and blocking pool free for the socket, UI and output.
+
+use anyhow::{Context, Result};
+use std::future::Future;
+use tokio::runtime::{Builder, Handle};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// A single-threaded runtime on its own OS thread. Jobs run one after
+/// another; the thread exits when the executor is dropped.
+pub struct TranscriptionExecutor {
+    handle: Handle,
+    /// Dropped with the executor, which ends the thread
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl TranscriptionExecutor {
+    pub fn new() -> Result<Self> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .context("Failed to create the transcription runtime")?;
+        let handle = runtime.handle().clone();
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        std::thread::Builder::new()
+            .name("tjvox-transcribe".to_string())
+            .spawn(move || {
+                // Spawned jobs run while this waits for the shutdown signal
+                let _ = runtime.block_on(shutdown_rx);
+            })
+            .context("Failed to start the transcription thread")?;
+        Ok(Self {
+            handle,
+            _shutdown: shutdown_tx,
+        })
+    }
+
+    /// Run `job` on the transcription thread. The handle can be awaited
+    /// from any runtime.
+    pub fn spawn<F>(&self, job: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.handle.spawn(job)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn test_executor_keeps_runtime_responsive() {
+        let executor = TranscriptionExecutor::new().unwrap();
+        // Stands in for a long whisper run, which blocks its thread
+        let job = executor.spawn(async {
+            std::thread::sleep(Duration::from_millis(300));
+            std::thread::current().name().map(str::to_string)
+        });
+
+        // This test runs on a single-threaded runtime, so a socket or
+        // status request could not be served if the job blocked it
+        let started = Instant::now();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(started.elapsed() < Duration::from_millis(200));
+
+        assert_eq!(job.await.unwrap().as_deref(), Some("tjvox-transcribe"));
+    }
+
+    #[tokio::test]
+    async fn test_executor_runs_async_jobs() {
+        let executor = TranscriptionExecutor::new().unwrap();
+        let job = executor.spawn(async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            tokio::task::spawn_blocking(|| 42).await.unwrap()
+        });
+        assert_eq!(job.await.unwrap(), 42);
+    }
+}
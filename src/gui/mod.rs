@@ -1,12 +1,15 @@
 mod overlay;
+mod transcribe_file;
 mod tray;
+mod window_placement;
 
 use anyhow::Result;
 use gtk4::prelude::*;
 use gtk4::{self, glib};
 use tracing::{error, info};
 
-use crate::config::Config;
+use crate::audio::SampleChunk;
+use crate::config::{Config, Visualizer};
 use crate::daemon::Daemon;
 use crate::layer_shell::LayerShellFns;
 use crate::messages::{DaemonMsg, GuiMsg, RecordingState};
@@ -39,12 +42,37 @@ fn setup_gui(app: &gtk4::Application, config: Config) -> Result<()> {
 
     // Amplitude channel: std::sync::mpsc from PipeWire audio thread → GTK timer poll
     let (amp_tx, amp_rx) = std::sync::mpsc::channel::<f32>();
+    // Sample windows for the spectrum visualizer, only captured when shown
+    let (sample_tx, sample_rx) = std::sync::mpsc::channel::<SampleChunk>();
+    let sample_tx = (config.overlay.visualizer == Visualizer::Spectrum).then_some(sample_tx);
 
     // Try to load gtk4-layer-shell for wlroots compositors
     let layer_shell = LayerShellFns::load();
 
     // Create the overlay window
-    let overlay = overlay::OverlayWindow::new(app, &config.overlay, layer_shell.as_ref());
+    let overlay = overlay::OverlayWindow::new(
+        app,
+        &config.overlay,
+        config.audio.sample_rate,
+        layer_shell.as_ref(),
+    );
+
+    // Audio files dropped on the overlay are sent to the daemon
+    let drop_app = app.clone();
+    let drop_gui_tx = gui_tx.clone();
+    overlay.connect_file_drop(move |file| {
+        transcribe_file::request(&drop_app, &drop_gui_tx, file);
+    });
+
+    // The tray's "Transcribe File…" item opens the chooser on the GTK thread
+    let (choose_file_tx, choose_file_rx) = async_channel::bounded::<()>(1);
+    let chooser_app = app.clone();
+    let chooser_gui_tx = gui_tx.clone();
+    glib::spawn_future_local(async move {
+        while choose_file_rx.recv().await.is_ok() {
+            transcribe_file::choose(&chooser_app, &chooser_gui_tx);
+        }
+    });
 
     // Spawn the tray in the tokio runtime (background thread)
     let tray_gui_tx = gui_tx.clone();
@@ -60,7 +88,7 @@ fn setup_gui(app: &gtk4::Application, config: Config) -> Result<()> {
     rt.spawn(async move {
         match Daemon::new(daemon_config).await {
             Ok(daemon) => {
-                let daemon = daemon.with_channels(gui_rx, daemon_txs, Some(amp_tx));
+                let daemon = daemon.with_channels(gui_rx, daemon_txs, Some(amp_tx), sample_tx);
                 if let Err(e) = daemon.run().await {
                     error!("Daemon error: {}", e);
                 }
@@ -73,13 +101,16 @@ fn setup_gui(app: &gtk4::Application, config: Config) -> Result<()> {
 
     // Spawn tray in tokio runtime with its own dedicated receiver
     rt.spawn(async move {
-        tray::run_tray(tray_gui_tx, tray_rx).await;
+        tray::run_tray(tray_gui_tx, choose_file_tx, tray_rx).await;
     });
 
     // Listen for daemon messages on the GTK main thread (overlay's dedicated receiver)
     let overlay_for_daemon = overlay.clone();
     let app_for_quit = app.clone();
+    let auto_show = config.overlay.auto_show;
     glib::spawn_future_local(async move {
+        // Set by `show-overlay`; keeps the overlay up between recordings
+        let mut pinned = false;
         while let Ok(msg) = overlay_rx.recv().await {
             match msg {
                 DaemonMsg::StateChanged(state) => {
@@ -87,20 +118,41 @@ fn setup_gui(app: &gtk4::Application, config: Config) -> Result<()> {
                     overlay_for_daemon.set_state(state);
 
                     match state {
-                        RecordingState::Recording => {
-                            overlay_for_daemon.show();
-                        }
-                        RecordingState::Transcribing => {
-                            overlay_for_daemon.show();
+                        RecordingState::Recording | RecordingState::Transcribing => {
+                            if auto_show && !pinned {
+                                overlay_for_daemon.show();
+                            }
                         }
-                        RecordingState::Idle => {
-                            overlay_for_daemon.hide();
-                        }
-                        RecordingState::Typing => {
-                            overlay_for_daemon.hide();
+                        RecordingState::Idle | RecordingState::Typing => {
+                            if !pinned {
+                                overlay_for_daemon.hide();
+                            }
                         }
                     }
                 }
+                DaemonMsg::OverlayVisible(visible) => {
+                    pinned = visible;
+                    if visible {
+                        overlay_for_daemon.show();
+                    } else {
+                        overlay_for_daemon.hide();
+                    }
+                }
+                DaemonMsg::PartialTranscript(text) => {
+                    overlay_for_daemon.set_partial(&text);
+                }
+                DaemonMsg::TranscriptionProgress(percent) => {
+                    overlay_for_daemon.set_progress(percent);
+                }
+                DaemonMsg::ModelChanged(model) => {
+                    overlay_for_daemon.set_model(&model);
+                }
+                DaemonMsg::WhisperModeChanged(mode) => {
+                    overlay_for_daemon.set_whisper_mode(mode);
+                }
+                DaemonMsg::FileTranscribed { path, result } => {
+                    transcribe_file::show_result(&app_for_quit, &path, &result);
+                }
                 DaemonMsg::Error(e) => {
                     error!("Daemon error: {}", e);
                 }
@@ -113,11 +165,15 @@ fn setup_gui(app: &gtk4::Application, config: Config) -> Result<()> {
 
     // Poll amplitude from PipeWire audio thread via mpsc (non-blocking)
     let overlay_for_amp = overlay.clone();
+    let sample_rate = config.audio.sample_rate;
     glib::timeout_add_local(std::time::Duration::from_millis(25), move || {
         // Drain all pending amplitude values into overlay history
         while let Ok(amp) = amp_rx.try_recv() {
             overlay_for_amp.set_amplitude(amp);
         }
+        while let Ok(samples) = sample_rx.try_recv() {
+            overlay_for_amp.push_samples(&samples.into_rate(sample_rate));
+        }
         glib::ControlFlow::Continue
     });
 
@@ -1,15 +1,33 @@
 use gtk4::prelude::*;
-use gtk4::{self, glib};
+use gtk4::{self, gdk, gio, glib};
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
-use crate::config::OverlayConfig;
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+
+use crate::config::{OverlayConfig, Visualizer, WhisperMode};
+use crate::gui::window_placement;
 use crate::layer_shell::LayerShellFns;
 use crate::messages::RecordingState;
 
 const NUM_BARS: usize = 21;
 const AMPLITUDE_HISTORY_SIZE: usize = 64;
+/// How long the model/mode label stays fully visible after the overlay appears
+const LABEL_VISIBLE_SECS: f64 = 1.5;
+/// Fade-out duration of the model/mode label
+const LABEL_FADE_SECS: f64 = 0.5;
+/// Auto-gain never amplifies beyond what a peak of this RMS level would need,
+/// so silence and room noise stay flat
+const AUTO_GAIN_MIN_PEAK: f32 = 0.01;
+/// Auto-gain scales the recent peak to this bar level
+const AUTO_GAIN_TARGET: f32 = 0.9;
+/// Samples in each spectrum frame (32 ms at 16 kHz)
+const FFT_SIZE: usize = 512;
+/// Frequency range of the spectrum bars, covering speech
+const SPECTRUM_MIN_HZ: f32 = 80.0;
+const SPECTRUM_MAX_HZ: f32 = 8000.0;
 
 #[derive(Clone)]
 pub struct OverlayWindow {
@@ -17,14 +35,21 @@ pub struct OverlayWindow {
     drawing_area: gtk4::DrawingArea,
     state: Arc<Mutex<RecordingState>>,
     amplitude_history: Arc<Mutex<VecDeque<f32>>>,
+    /// Latest `FFT_SIZE` samples for the spectrum visualizer
+    samples: Arc<Mutex<VecDeque<f32>>>,
     bar_levels: Arc<Mutex<[f32; NUM_BARS]>>,
     start_time: Arc<Mutex<Instant>>,
+    model: Arc<Mutex<String>>,
+    whisper_mode: Arc<Mutex<WhisperMode>>,
+    partial: Arc<Mutex<String>>,
+    progress: Arc<Mutex<u8>>,
 }
 
 impl OverlayWindow {
     pub fn new(
         app: &gtk4::Application,
         config: &OverlayConfig,
+        sample_rate: u32,
         layer_shell: Option<&LayerShellFns>,
     ) -> Self {
         // Transparent window background via CSS
@@ -53,8 +78,9 @@ impl OverlayWindow {
         window.add_css_class("tjvox-overlay");
         window.set_focusable(false);
 
-        if let Some(ls) = layer_shell {
-            ls.apply_to_window(&window, &config.position);
+        match layer_shell {
+            Some(ls) => ls.apply_to_window(&window, &config.position),
+            None => window_placement::apply_fallback(&window, &config.position),
         }
 
         let drawing_area = gtk4::DrawingArea::new();
@@ -66,14 +92,24 @@ impl OverlayWindow {
         let state = Arc::new(Mutex::new(RecordingState::Idle));
         let amplitude_history =
             Arc::new(Mutex::new(VecDeque::with_capacity(AMPLITUDE_HISTORY_SIZE)));
+        let samples = Arc::new(Mutex::new(VecDeque::with_capacity(FFT_SIZE)));
         let bar_levels = Arc::new(Mutex::new([0.0f32; NUM_BARS]));
         let start_time = Arc::new(Mutex::new(Instant::now()));
+        let model = Arc::new(Mutex::new(String::new()));
+        let whisper_mode = Arc::new(Mutex::new(WhisperMode::default()));
+        let partial = Arc::new(Mutex::new(String::new()));
+        let progress = Arc::new(Mutex::new(0u8));
         let opacity = config.opacity;
+        let show_model = config.show_model;
 
         // Set up Cairo drawing
         let state_draw = state.clone();
         let bar_levels_draw = bar_levels.clone();
         let start_time_draw = start_time.clone();
+        let model_draw = model.clone();
+        let mode_draw = whisper_mode.clone();
+        let partial_draw = partial.clone();
+        let progress_draw = progress.clone();
 
         drawing_area.set_draw_func(move |_area, cr, w, h| {
             // Safely get values from shared state, using defaults if mutex is poisoned
@@ -89,6 +125,14 @@ impl OverlayWindow {
                 .lock()
                 .map(|g| g.elapsed().as_secs_f64())
                 .unwrap_or(0.0);
+            let partial = partial_draw.lock().map(|g| g.clone()).unwrap_or_default();
+
+            // Live caption replaces the processing dots once text arrives
+            if current_state == RecordingState::Transcribing && !partial.is_empty() {
+                draw_overlay(cr, w as f64, h as f64, opacity, RecordingState::Idle, &bars, elapsed);
+                draw_partial_text(cr, w as f64, h as f64, &partial);
+                return;
+            }
 
             draw_overlay(
                 cr,
@@ -99,17 +143,48 @@ impl OverlayWindow {
                 &bars,
                 elapsed,
             );
+
+            if current_state == RecordingState::Transcribing {
+                let progress = progress_draw.lock().map(|g| *g).unwrap_or(0);
+                if progress > 0 {
+                    draw_progress(cr, w as f64, h as f64, progress);
+                }
+            }
+
+            if show_model {
+                let alpha = label_alpha(elapsed);
+                if alpha > 0.0 {
+                    let model = model_draw.lock().map(|g| g.clone()).unwrap_or_default();
+                    let mode = mode_draw.lock().map(|g| *g).unwrap_or_default();
+                    draw_label(cr, w as f64, h as f64, &model, &mode.to_string(), alpha);
+                }
+            }
         });
 
         // 20 FPS update timer — also smooths bar levels from amplitude history
+        // or the spectrum of the latest samples
         let da_clone = drawing_area.clone();
         let hist_update = amplitude_history.clone();
+        let samples_update = samples.clone();
         let bars_update = bar_levels.clone();
         let state_update = state.clone();
+        let config_update = config.clone();
+        let mut spectrum = (config.visualizer == Visualizer::Spectrum)
+            .then(|| Spectrum::new(sample_rate));
         glib::timeout_add_local(std::time::Duration::from_millis(50), move || {
             if let Ok(state) = state_update.lock() {
                 if *state == RecordingState::Recording {
-                    update_bar_levels(&hist_update, &bars_update);
+                    match spectrum {
+                        Some(ref mut spectrum) => {
+                            update_spectrum_levels(
+                                spectrum,
+                                &samples_update,
+                                &bars_update,
+                                &config_update,
+                            );
+                        }
+                        None => update_bar_levels(&hist_update, &bars_update, &config_update),
+                    }
                 }
             }
             da_clone.queue_draw();
@@ -124,8 +199,13 @@ impl OverlayWindow {
             drawing_area,
             state,
             amplitude_history,
+            samples,
             bar_levels,
             start_time,
+            model,
+            whisper_mode,
+            partial,
+            progress,
         }
     }
 
@@ -146,9 +226,18 @@ impl OverlayWindow {
             *s = state;
         }
         if state == RecordingState::Recording {
+            if let Ok(mut partial) = self.partial.lock() {
+                partial.clear();
+            }
+            if let Ok(mut progress) = self.progress.lock() {
+                *progress = 0;
+            }
             if let Ok(mut history) = self.amplitude_history.lock() {
                 history.clear();
             }
+            if let Ok(mut samples) = self.samples.lock() {
+                samples.clear();
+            }
             if let Ok(mut bars) = self.bar_levels.lock() {
                 *bars = [0.0; NUM_BARS];
             }
@@ -161,6 +250,34 @@ impl OverlayWindow {
         self.drawing_area.queue_draw();
     }
 
+    pub fn set_model(&self, model: &str) {
+        if let Ok(mut m) = self.model.lock() {
+            *m = model.to_string();
+        }
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn set_whisper_mode(&self, mode: WhisperMode) {
+        if let Ok(mut m) = self.whisper_mode.lock() {
+            *m = mode;
+        }
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn set_partial(&self, text: &str) {
+        if let Ok(mut partial) = self.partial.lock() {
+            *partial = text.to_string();
+        }
+        self.drawing_area.queue_draw();
+    }
+
+    pub fn set_progress(&self, percent: u8) {
+        if let Ok(mut progress) = self.progress.lock() {
+            *progress = percent.min(100);
+        }
+        self.drawing_area.queue_draw();
+    }
+
     pub fn set_amplitude(&self, amp: f32) {
         if let Ok(mut history) = self.amplitude_history.lock() {
             history.push_back(amp);
@@ -169,11 +286,145 @@ impl OverlayWindow {
             }
         }
     }
+
+    /// Call `on_drop` with a file dropped on the overlay.
+    pub fn connect_file_drop<F: Fn(&gio::File) + 'static>(&self, on_drop: F) {
+        let target = gtk4::DropTarget::new(gio::File::static_type(), gdk::DragAction::COPY);
+        target.connect_drop(move |_, value, _, _| match value.get::<gio::File>() {
+            Ok(file) => {
+                on_drop(&file);
+                true
+            }
+            Err(_) => false,
+        });
+        self.window.add_controller(target);
+    }
+
+    /// Add captured samples for the spectrum, keeping the latest `FFT_SIZE`.
+    pub fn push_samples(&self, new: &[f32]) {
+        if let Ok(mut samples) = self.samples.lock() {
+            samples.extend(&new[new.len().saturating_sub(FFT_SIZE)..]);
+            let excess = samples.len().saturating_sub(FFT_SIZE);
+            samples.drain(..excess);
+        }
+    }
+}
+
+/// FFT plan and buffers for the spectrum visualizer.
+struct Spectrum {
+    fft: Arc<dyn Fft<f32>>,
+    buffer: Vec<Complex<f32>>,
+    /// Hann window, so frame edges do not smear energy across the bands
+    window: Vec<f32>,
+    /// FFT bin range of each band, lowest frequency first
+    bands: Vec<std::ops::Range<usize>>,
+}
+
+impl Spectrum {
+    fn new(sample_rate: u32) -> Self {
+        let fft = FftPlanner::new().plan_fft_forward(FFT_SIZE);
+        let window = (0..FFT_SIZE)
+            .map(|i| {
+                let phase = 2.0 * std::f32::consts::PI * i as f32 / FFT_SIZE as f32;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+        Self {
+            fft,
+            buffer: vec![Complex::default(); FFT_SIZE],
+            window,
+            bands: spectrum_bands(sample_rate, NUM_BARS / 2 + 1),
+        }
+    }
+
+    /// Magnitude of each band for `samples`, roughly on the scale of the
+    /// RMS level the amplitude bars use.
+    fn levels(&mut self, samples: &VecDeque<f32>) -> Vec<f32> {
+        // Zero-pad at the start until a full frame has been captured
+        let padding = FFT_SIZE.saturating_sub(samples.len());
+        for (i, slot) in self.buffer.iter_mut().enumerate() {
+            let sample = i
+                .checked_sub(padding)
+                .and_then(|i| samples.get(i))
+                .copied()
+                .unwrap_or(0.0);
+            *slot = Complex::new(sample * self.window[i], 0.0);
+        }
+        self.fft.process(&mut self.buffer);
+
+        let scale = 4.0 / FFT_SIZE as f32;
+        self.bands
+            .iter()
+            .map(|band| {
+                // Strongest bin, so narrow tones in wide high bands still show
+                let peak = self.buffer[band.clone()]
+                    .iter()
+                    .map(|bin| bin.norm())
+                    .fold(0.0f32, f32::max);
+                peak * scale
+            })
+            .collect()
+    }
+}
+
+/// Split `SPECTRUM_MIN_HZ`..`SPECTRUM_MAX_HZ` into `count` logarithmically
+/// spaced bands of FFT bins, each at least one bin wide.
+fn spectrum_bands(sample_rate: u32, count: usize) -> Vec<std::ops::Range<usize>> {
+    let bin_hz = sample_rate as f32 / FFT_SIZE as f32;
+    let max_hz = SPECTRUM_MAX_HZ.min(sample_rate as f32 / 2.0);
+    let ratio = (max_hz / SPECTRUM_MIN_HZ).powf(1.0 / count as f32);
+    let last_bin = FFT_SIZE / 2;
+    let mut start = ((SPECTRUM_MIN_HZ / bin_hz) as usize).max(1);
+    (1..=count)
+        .map(|i| {
+            let edge = ((SPECTRUM_MIN_HZ * ratio.powi(i as i32)) / bin_hz).round() as usize;
+            let end = edge.max(start + 1).min(last_bin + 1);
+            let band = start.min(end - 1)..end;
+            start = end;
+            band
+        })
+        .collect()
+}
+
+fn update_spectrum_levels(
+    spectrum: &mut Spectrum,
+    samples: &Arc<Mutex<VecDeque<f32>>>,
+    bar_levels: &Arc<Mutex<[f32; NUM_BARS]>>,
+    config: &OverlayConfig,
+) {
+    let (samples, mut bars) = match (samples.lock(), bar_levels.lock()) {
+        (Ok(s), Ok(b)) => (s, b),
+        _ => return, // Mutex poisoned, skip update
+    };
+    let levels = spectrum.levels(&samples);
+
+    let gain = if config.auto_gain {
+        let peak = levels.iter().copied().fold(0.0f32, f32::max);
+        AUTO_GAIN_TARGET / peak.max(AUTO_GAIN_MIN_PEAK)
+    } else {
+        config.gain
+    };
+
+    let center = NUM_BARS / 2;
+    for i in 0..NUM_BARS {
+        // Mirror from center: lowest band in the middle, highest at the edges
+        let distance = (i as i32 - center as i32).unsigned_abs() as usize;
+        let raw = levels.get(distance).copied().unwrap_or(0.0);
+        let target = (raw * gain).min(1.0).powf(config.compression);
+
+        let current = bars[i];
+        if target > current {
+            bars[i] = current + (target - current) * config.attack;
+        } else {
+            bars[i] = current + (target - current) * config.decay;
+        }
+    }
 }
 
 fn update_bar_levels(
     history: &Arc<Mutex<VecDeque<f32>>>,
     bar_levels: &Arc<Mutex<[f32; NUM_BARS]>>,
+    config: &OverlayConfig,
 ) {
     let (history, mut bars) = match (history.lock(), bar_levels.lock()) {
         (Ok(h), Ok(b)) => (h, b),
@@ -189,6 +440,14 @@ fn update_bar_levels(
 
     let center = NUM_BARS / 2;
 
+    // Auto-gain: normalize to the running peak over the amplitude history
+    let gain = if config.auto_gain {
+        let peak = history.iter().copied().fold(0.0f32, f32::max);
+        AUTO_GAIN_TARGET / peak.max(AUTO_GAIN_MIN_PEAK)
+    } else {
+        config.gain
+    };
+
     for i in 0..NUM_BARS {
         // Mirror from center: center bar uses most recent sample,
         // edge bars use progressively older samples — creates a natural
@@ -198,17 +457,17 @@ fn update_bar_levels(
         let raw_amp = history.get(hist_idx).copied().unwrap_or(0.0);
 
         // Apply gain (typical mic RMS is 0.001–0.1)
-        let gained = (raw_amp * 8.0).min(1.0);
+        let gained = (raw_amp * gain).min(1.0);
 
         // VoiceInk-style amplitude boosting: compress dynamic range
-        let target = gained.powf(0.5);
+        let target = gained.powf(config.compression);
 
-        // Smooth interpolation: fast attack, slow decay
+        // Smooth interpolation: fast attack, slow decay by default
         let current = bars[i];
         if target > current {
-            bars[i] = current + (target - current) * 0.6;
+            bars[i] = current + (target - current) * config.attack;
         } else {
-            bars[i] = current + (target - current) * 0.15;
+            bars[i] = current + (target - current) * config.decay;
         }
     }
 }
@@ -344,6 +603,80 @@ fn draw_waveform(
     }
 }
 
+/// Opacity of the model/mode label `elapsed` seconds after the overlay appeared.
+fn label_alpha(elapsed: f64) -> f64 {
+    if elapsed <= LABEL_VISIBLE_SECS {
+        1.0
+    } else {
+        (1.0 - (elapsed - LABEL_VISIBLE_SECS) / LABEL_FADE_SECS).max(0.0)
+    }
+}
+
+/// Draw the model name on the left and the whisper mode on the right,
+/// in the space beside the waveform.
+fn draw_label(cr: &cairo::Context, width: f64, height: f64, model: &str, mode: &str, alpha: f64) {
+    let inset = height / 2.0 * 0.6;
+    let baseline = height / 2.0 + 3.0;
+
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+    cr.set_font_size(9.0);
+    cr.set_source_rgba(0.85, 0.90, 1.0, 0.75 * alpha);
+
+    if !model.is_empty() {
+        cr.move_to(inset, baseline);
+        let _ = cr.show_text(model);
+    }
+
+    if let Ok(extents) = cr.text_extents(mode) {
+        cr.move_to(width - inset - extents.x_advance(), baseline);
+        let _ = cr.show_text(mode);
+    }
+}
+
+/// Draw the tail of the partial transcript, dropping leading text that
+/// does not fit so the newest words stay visible.
+fn draw_partial_text(cr: &cairo::Context, width: f64, height: f64, text: &str) {
+    let inset = height / 2.0;
+    let max_width = width - inset * 2.0;
+
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Normal);
+    cr.set_font_size(12.0);
+    cr.set_source_rgba(0.90, 0.94, 1.0, 0.95);
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut start = 0;
+    let mut visible = text.to_string();
+    while start < chars.len() {
+        let fits = cr
+            .text_extents(&visible)
+            .map(|e| e.x_advance() <= max_width)
+            .unwrap_or(true);
+        if fits {
+            break;
+        }
+        start += 1;
+        visible = format!("…{}", chars[start..].iter().collect::<String>());
+    }
+
+    cr.move_to(inset, height / 2.0 + 4.0);
+    let _ = cr.show_text(&visible);
+}
+
+/// Draw the transcription percentage at the right edge, beside the dots.
+fn draw_progress(cr: &cairo::Context, width: f64, height: f64, percent: u8) {
+    let inset = height / 2.0;
+    let text = format!("{}%", percent);
+
+    cr.select_font_face("Sans", cairo::FontSlant::Normal, cairo::FontWeight::Bold);
+    cr.set_font_size(10.0);
+    cr.set_source_rgba(0.75, 0.85, 1.0, 0.8);
+
+    if let Ok(extents) = cr.text_extents(&text) {
+        cr.move_to(width - inset - extents.x_advance(), height / 2.0 + 3.5);
+        let _ = cr.show_text(&text);
+    }
+}
+
 fn draw_processing_dots(cr: &cairo::Context, width: f64, height: f64, elapsed: f64) {
     let num_dots = 5;
     let dot_radius = 3.5;
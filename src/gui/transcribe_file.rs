@@ -0,0 +1,95 @@
+//! Ad-hoc transcription of audio files dropped on the overlay or picked from
+//! the tray. The daemon transcribes them like `tjvox transcribe` does, and
+//! the text is shown in a window instead of being typed.
+
+use gtk4::prelude::*;
+use gtk4::{self, gio, glib};
+use std::path::Path;
+use tracing::warn;
+
+use crate::messages::GuiMsg;
+
+/// Ask the daemon to transcribe `file`.
+pub fn request(app: &gtk4::Application, gui_tx: &async_channel::Sender<GuiMsg>, file: &gio::File) {
+    match file.path() {
+        Some(path) => {
+            if gui_tx.try_send(GuiMsg::TranscribeFile(path)).is_err() {
+                warn!("Daemon is not accepting requests, file not transcribed");
+            }
+        }
+        // Files from remote locations or other apps' sandboxes have no path
+        None => show_result(
+            app,
+            Path::new(&file.uri()),
+            &Err("only local files can be transcribed".to_string()),
+        ),
+    }
+}
+
+/// Let the user pick a WAV file and ask the daemon to transcribe it.
+pub fn choose(app: &gtk4::Application, gui_tx: &async_channel::Sender<GuiMsg>) {
+    let filter = gtk4::FileFilter::new();
+    filter.set_name(Some("WAV audio"));
+    filter.add_mime_type("audio/wav");
+    filter.add_mime_type("audio/x-wav");
+    filter.add_pattern("*.wav");
+    filter.add_pattern("*.WAV");
+
+    let dialog = gtk4::FileChooserNative::new(
+        Some("Transcribe Audio File"),
+        None::<&gtk4::Window>,
+        gtk4::FileChooserAction::Open,
+        Some("Transcribe"),
+        Some("Cancel"),
+    );
+    dialog.add_filter(&filter);
+
+    let app = app.clone();
+    let gui_tx = gui_tx.clone();
+    glib::spawn_future_local(async move {
+        if dialog.run_future().await == gtk4::ResponseType::Accept {
+            if let Some(file) = dialog.file() {
+                request(&app, &gui_tx, &file);
+            }
+        }
+        dialog.destroy();
+    });
+}
+
+/// Show the text of a transcribed file, or why it failed, in a window the
+/// text can be selected and copied from.
+pub fn show_result(app: &gtk4::Application, path: &Path, result: &Result<String, String>) {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    let (title, text) = match result {
+        Ok(text) => (format!("Transcription of {}", name), text.trim().to_string()),
+        Err(e) => (format!("Could not transcribe {}", name), e.clone()),
+    };
+
+    let view = gtk4::TextView::builder()
+        .editable(false)
+        .cursor_visible(false)
+        .wrap_mode(gtk4::WrapMode::WordChar)
+        .left_margin(12)
+        .right_margin(12)
+        .top_margin(12)
+        .bottom_margin(12)
+        .build();
+    view.buffer().set_text(&text);
+
+    let scrolled = gtk4::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk4::PolicyType::Never)
+        .child(&view)
+        .build();
+
+    let window = gtk4::Window::builder()
+        .application(app)
+        .title(title)
+        .default_width(480)
+        .default_height(320)
+        .child(&scrolled)
+        .build();
+    window.present();
+}
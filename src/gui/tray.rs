@@ -1,17 +1,55 @@
 use ksni::TrayMethods;
-use tracing::{error, info};
+use std::time::Duration;
+use tracing::{info, warn};
 
 use crate::config::WhisperMode;
 use crate::messages::{DaemonMsg, GuiMsg, RecordingState};
+use crate::transcription::resolve_model_alias;
 
-const MODELS: &[&str] = &["tiny", "base", "small", "medium", "large-v3-turbo"];
+const MODELS: &[&str] = &[
+    "tiny",
+    "base",
+    "base.en",
+    "small",
+    "small.en",
+    "medium",
+    "large-v3-turbo",
+    "large-v3-turbo-q5_0",
+];
 
+/// Tray registration attempts before giving up
+const REGISTER_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry; doubled on each further attempt
+const REGISTER_BACKOFF: Duration = Duration::from_secs(2);
+
+#[derive(Clone)]
 struct TjvoxTray {
     state: RecordingState,
     whisper_mode: WhisperMode,
     current_model: String,
     model_loading: bool,
+    llm_enabled: bool,
     gui_tx: async_channel::Sender<GuiMsg>,
+    /// Asks the GTK thread to open the file chooser
+    choose_file_tx: async_channel::Sender<()>,
+}
+
+impl TjvoxTray {
+    /// Reflect a daemon update in the menu and icon.
+    fn apply(&mut self, msg: DaemonMsg) {
+        match msg {
+            DaemonMsg::StateChanged(state) => self.state = state,
+            DaemonMsg::WhisperModeChanged(mode) => self.whisper_mode = mode,
+            DaemonMsg::ModelChanged(model) => {
+                self.current_model = model;
+                self.model_loading = false;
+            }
+            DaemonMsg::LlmChanged(enabled) => self.llm_enabled = enabled,
+            DaemonMsg::ModelLoading => self.model_loading = true,
+            _ => {}
+        }
+    }
 }
 
 impl ksni::Tray for TjvoxTray {
@@ -64,7 +102,7 @@ impl ksni::Tray for TjvoxTray {
             .iter()
             .map(|&model| {
                 let model_str = model.to_string();
-                let is_current = self.current_model == model;
+                let is_current = resolve_model_alias(&self.current_model) == model;
                 ksni::MenuItem::Standard(ksni::menu::StandardItem {
                     label: format!("{}{}", model, if is_current { " ●" } else { "" }),
                     activate: Box::new(move |tray: &mut Self| {
@@ -85,12 +123,12 @@ impl ksni::Tray for TjvoxTray {
             ksni::MenuItem::Separator,
             // Setup info
             ksni::MenuItem::Standard(ksni::menu::StandardItem {
-                label: "Setup: Bind 'tjvox toggle' to a key".to_string(),
+                label: format!("Setup: {}", crate::setup::HOTKEY_HINT),
                 enabled: false,
                 ..Default::default()
             }),
             ksni::MenuItem::Standard(ksni::menu::StandardItem {
-                label: "KDE Settings → Shortcuts → Custom".to_string(),
+                label: crate::setup::KDE_HOTKEY_HINT.to_string(),
                 enabled: false,
                 ..Default::default()
             }),
@@ -136,6 +174,26 @@ impl ksni::Tray for TjvoxTray {
                 ..Default::default()
             }),
             ksni::MenuItem::Separator,
+            // LLM post-processing (per session)
+            ksni::MenuItem::Checkmark(ksni::menu::CheckmarkItem {
+                label: "LLM Correction".to_string(),
+                checked: self.llm_enabled,
+                activate: Box::new(|tray: &mut Self| {
+                    let _ = tray
+                        .gui_tx
+                        .try_send(GuiMsg::SetLlmEnabled(!tray.llm_enabled));
+                }),
+                ..Default::default()
+            }),
+            ksni::MenuItem::Separator,
+            ksni::MenuItem::Standard(ksni::menu::StandardItem {
+                label: "Transcribe File…".to_string(),
+                activate: Box::new(|tray: &mut Self| {
+                    let _ = tray.choose_file_tx.try_send(());
+                }),
+                ..Default::default()
+            }),
+            ksni::MenuItem::Separator,
             ksni::MenuItem::Standard(ksni::menu::StandardItem {
                 label: "Quit".to_string(),
                 activate: Box::new(|tray: &mut Self| {
@@ -149,60 +207,66 @@ impl ksni::Tray for TjvoxTray {
 
 pub async fn run_tray(
     gui_tx: async_channel::Sender<GuiMsg>,
+    choose_file_tx: async_channel::Sender<()>,
     daemon_rx: async_channel::Receiver<DaemonMsg>,
 ) {
     info!("Starting system tray");
 
-    let tray = TjvoxTray {
+    let mut tray = TjvoxTray {
         state: RecordingState::Idle,
         whisper_mode: WhisperMode::Cold,
         current_model: "base".to_string(),
         model_loading: false,
+        llm_enabled: false,
         gui_tx,
+        choose_file_tx,
     };
 
-    let handle: ksni::Handle<TjvoxTray> = match tray.spawn().await {
-        Ok(h) => h,
-        Err(e) => {
-            error!("Failed to create tray: {}", e);
-            return;
+    // The StatusNotifierHost may appear after tjvox starts (e.g. a shell
+    // extension loading late), so registration is retried with backoff
+    let mut delay = REGISTER_BACKOFF;
+    let mut attempt = 1;
+    let handle: ksni::Handle<TjvoxTray> = loop {
+        match tray.clone().spawn().await {
+            Ok(h) => break h,
+            Err(e) if attempt < REGISTER_ATTEMPTS => {
+                warn!(
+                    "Failed to register tray ({}/{}): {}; retrying in {}s",
+                    attempt,
+                    REGISTER_ATTEMPTS,
+                    e,
+                    delay.as_secs()
+                );
+                // Keep up with the daemon's state while waiting
+                let wait = tokio::time::sleep(delay);
+                tokio::pin!(wait);
+                loop {
+                    tokio::select! {
+                        _ = &mut wait => break,
+                        msg = daemon_rx.recv() => match msg {
+                            Ok(msg) => tray.apply(msg),
+                            Err(_) => return,
+                        },
+                    }
+                }
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(e) => {
+                warn!(
+                    "System tray unavailable: {}. No StatusNotifierHost is running; on GNOME \
+                     install the AppIndicator extension. The overlay and the tjvox commands \
+                     keep working without it.",
+                    e
+                );
+                return;
+            }
         }
     };
 
     // Listen for daemon state updates and update tray
     while let Ok(msg) = daemon_rx.recv().await {
-        match msg {
-            DaemonMsg::StateChanged(state) => {
-                handle
-                    .update(|tray| {
-                        tray.state = state;
-                    })
-                    .await;
-            }
-            DaemonMsg::WhisperModeChanged(mode) => {
-                handle
-                    .update(|tray| {
-                        tray.whisper_mode = mode;
-                    })
-                    .await;
-            }
-            DaemonMsg::ModelChanged(model) => {
-                handle
-                    .update(|tray| {
-                        tray.current_model = model;
-                        tray.model_loading = false;
-                    })
-                    .await;
-            }
-            DaemonMsg::ModelLoading => {
-                handle
-                    .update(|tray| {
-                        tray.model_loading = true;
-                    })
-                    .await;
-            }
-            _ => {}
-        }
+        handle.update(|tray| tray.apply(msg)).await;
     }
 
     info!("Tray shutting down");
@@ -0,0 +1,271 @@
+use libloading::Library;
+use std::ffi::{c_char, c_int, c_long, c_ulong, c_void};
+use tracing::{debug, info, warn};
+
+/// Distance kept from the screen edges, in logical pixels
+const EDGE_MARGIN: i32 = 24;
+
+/// Window types asked of the window manager, most preferred first: an
+/// on-top, undecorated popup that is not part of the window list
+const WINDOW_TYPES: [&[u8]; 2] = [
+    b"_NET_WM_WINDOW_TYPE_NOTIFICATION\0",
+    b"_NET_WM_WINDOW_TYPE_UTILITY\0",
+];
+
+/// `XA_ATOM`, the type of `_NET_WM_WINDOW_TYPE`
+const XA_ATOM: c_ulong = 4;
+/// `PropModeReplace`
+const PROP_MODE_REPLACE: c_int = 0;
+/// `InputHint`, marking `XWmHints::input` as set
+const INPUT_HINT: c_long = 1;
+
+type GetXidFn = unsafe extern "C" fn(*mut c_void) -> c_ulong;
+type GetXDisplayFn = unsafe extern "C" fn(*mut c_void) -> *mut c_void;
+type SetHintFn = unsafe extern "C" fn(*mut c_void, c_int);
+type SetUserTimeFn = unsafe extern "C" fn(*mut c_void, u32);
+type XMoveWindowFn = unsafe extern "C" fn(*mut c_void, c_ulong, c_int, c_int) -> c_int;
+type XFlushFn = unsafe extern "C" fn(*mut c_void) -> c_int;
+type XInternAtomFn = unsafe extern "C" fn(*mut c_void, *const c_char, c_int) -> c_ulong;
+type XChangePropertyFn = unsafe extern "C" fn(
+    *mut c_void,
+    c_ulong,
+    c_ulong,
+    c_ulong,
+    c_int,
+    c_int,
+    *const u8,
+    c_int,
+) -> c_int;
+type XGetWmHintsFn = unsafe extern "C" fn(*mut c_void, c_ulong) -> *mut XWmHints;
+type XSetWmHintsFn = unsafe extern "C" fn(*mut c_void, c_ulong, *mut XWmHints) -> c_int;
+type XFreeFn = unsafe extern "C" fn(*mut c_void) -> c_int;
+
+/// Xlib's `XWMHints`
+#[repr(C)]
+#[derive(Default)]
+struct XWmHints {
+    flags: c_long,
+    input: c_int,
+    initial_state: c_int,
+    icon_pixmap: c_ulong,
+    icon_window: c_ulong,
+    icon_x: c_int,
+    icon_y: c_int,
+    icon_mask: c_ulong,
+    window_group: c_ulong,
+}
+
+/// Top-left corner for a window of `size` at `position` on a monitor with
+/// geometry `(x, y, width, height)`. Unknown positions fall back to centered.
+pub fn fallback_origin(position: &str, monitor: (i32, i32, i32, i32), size: (i32, i32)) -> (i32, i32) {
+    let (mx, my, mw, mh) = monitor;
+    let (w, h) = size;
+
+    let left = mx + EDGE_MARGIN;
+    let right = mx + mw - w - EDGE_MARGIN;
+    let center_x = mx + (mw - w) / 2;
+    let top = my + EDGE_MARGIN;
+    let bottom = my + mh - h - EDGE_MARGIN;
+    let center_y = my + (mh - h) / 2;
+
+    match position {
+        "bottom-center" => (center_x, bottom),
+        "top-center" => (center_x, top),
+        "bottom-left" => (left, bottom),
+        "bottom-right" => (right, bottom),
+        "top-left" => (left, top),
+        "top-right" => (right, top),
+        _ => (center_x, center_y),
+    }
+}
+
+/// Best-effort placement for when gtk4-layer-shell is unavailable.
+///
+/// GTK4 has no API to move a toplevel, and Wayland compositors choose where
+/// regular windows go, so this only works on X11 (including XWayland with
+/// `GDK_BACKEND=x11`). There the overlay is moved to the configured corner,
+/// typed as a notification so window managers keep it on top and out of the
+/// taskbar and pager, and told not to take input focus, so showing it never
+/// pulls focus away from the window being dictated into. GTK rewrites some
+/// of these hints whenever it shows the window, so they are set again on
+/// every map. Exact placement on Wayland needs layer-shell.
+pub fn apply_fallback(window: &gtk4::Window, position: &str) {
+    use gtk4::prelude::*;
+
+    let position = position.to_string();
+    window.connect_map(move |window| {
+        let Some(surface) = window.surface() else {
+            return;
+        };
+        // X11 toplevels are a subclass, GdkX11Toplevel; the base type is only
+        // registered when GTK was built with the X11 backend
+        let is_x11 = gtk4::glib::Type::from_name("GdkX11Surface")
+            .is_some_and(|x11| surface.type_().is_a(x11));
+        if !is_x11 {
+            debug!("Not an X11 surface; the compositor decides where the overlay appears");
+            return;
+        }
+
+        let display = surface.display();
+        let Some(monitor) = display.monitor_at_surface(&surface) else {
+            return;
+        };
+        let geometry = monitor.geometry();
+        let (x, y) = fallback_origin(
+            &position,
+            (geometry.x(), geometry.y(), geometry.width(), geometry.height()),
+            (window.width(), window.height()),
+        );
+
+        if let Err(e) = set_overlay_hints(
+            surface.as_ptr() as *mut c_void,
+            display.as_ptr() as *mut c_void,
+        ) {
+            warn!("Could not set overlay window hints: {}", e);
+        }
+
+        // X11 coordinates are in device pixels
+        let scale = surface.scale_factor();
+        if let Err(e) = move_x11_surface(
+            surface.as_ptr() as *mut c_void,
+            display.as_ptr() as *mut c_void,
+            x * scale,
+            y * scale,
+        ) {
+            warn!("Could not position overlay window: {}", e);
+        }
+    });
+
+    info!("gtk4-layer-shell unavailable; overlay placement is best-effort");
+}
+
+fn move_x11_surface(surface: *mut c_void, display: *mut c_void, x: i32, y: i32) -> Result<(), String> {
+    // The GDK X11 backend is part of the already loaded GTK library
+    let gtk = libloading::os::unix::Library::this();
+    let xlib = unsafe { Library::new("libX11.so.6") }.map_err(|e| e.to_string())?;
+
+    unsafe {
+        let get_xid = *gtk
+            .get::<GetXidFn>(b"gdk_x11_surface_get_xid\0")
+            .map_err(|e| e.to_string())?;
+        let get_xdisplay = *gtk
+            .get::<GetXDisplayFn>(b"gdk_x11_display_get_xdisplay\0")
+            .map_err(|e| e.to_string())?;
+        let move_window = *xlib
+            .get::<XMoveWindowFn>(b"XMoveWindow\0")
+            .map_err(|e| e.to_string())?;
+        let flush = *xlib.get::<XFlushFn>(b"XFlush\0").map_err(|e| e.to_string())?;
+
+        let xdisplay = get_xdisplay(display);
+        move_window(xdisplay, get_xid(surface), x, y);
+        flush(xdisplay);
+    }
+
+    debug!("Overlay moved to ({}, {}) via X11", x, y);
+    Ok(())
+}
+
+/// Keep the overlay out of the taskbar and pager, set its window type and
+/// stop it from taking input focus.
+fn set_overlay_hints(surface: *mut c_void, display: *mut c_void) -> Result<(), String> {
+    let gtk = libloading::os::unix::Library::this();
+    let xlib = unsafe { Library::new("libX11.so.6") }.map_err(|e| e.to_string())?;
+
+    unsafe {
+        let get_xid = *gtk
+            .get::<GetXidFn>(b"gdk_x11_surface_get_xid\0")
+            .map_err(|e| e.to_string())?;
+        let get_xdisplay = *gtk
+            .get::<GetXDisplayFn>(b"gdk_x11_display_get_xdisplay\0")
+            .map_err(|e| e.to_string())?;
+        let intern_atom = *xlib
+            .get::<XInternAtomFn>(b"XInternAtom\0")
+            .map_err(|e| e.to_string())?;
+        let change_property = *xlib
+            .get::<XChangePropertyFn>(b"XChangeProperty\0")
+            .map_err(|e| e.to_string())?;
+        let get_wm_hints = *xlib
+            .get::<XGetWmHintsFn>(b"XGetWMHints\0")
+            .map_err(|e| e.to_string())?;
+        let set_wm_hints = *xlib
+            .get::<XSetWmHintsFn>(b"XSetWMHints\0")
+            .map_err(|e| e.to_string())?;
+        let free = *xlib.get::<XFreeFn>(b"XFree\0").map_err(|e| e.to_string())?;
+        let flush = *xlib.get::<XFlushFn>(b"XFlush\0").map_err(|e| e.to_string())?;
+
+        for symbol in [
+            &b"gdk_x11_surface_set_skip_taskbar_hint\0"[..],
+            &b"gdk_x11_surface_set_skip_pager_hint\0"[..],
+        ] {
+            if let Ok(set_hint) = gtk.get::<SetHintFn>(symbol) {
+                set_hint(surface, 1);
+            }
+        }
+        // A user time of 0 asks the window manager not to focus the window
+        if let Ok(set_user_time) = gtk.get::<SetUserTimeFn>(b"gdk_x11_surface_set_user_time\0") {
+            set_user_time(surface, 0);
+        }
+
+        let xdisplay = get_xdisplay(display);
+        let xid = get_xid(surface);
+
+        let types: Vec<c_ulong> = WINDOW_TYPES
+            .iter()
+            .map(|name| intern_atom(xdisplay, name.as_ptr() as *const c_char, 0))
+            .collect();
+        let window_type =
+            intern_atom(xdisplay, b"_NET_WM_WINDOW_TYPE\0".as_ptr() as *const c_char, 0);
+        change_property(
+            xdisplay,
+            xid,
+            window_type,
+            XA_ATOM,
+            32,
+            PROP_MODE_REPLACE,
+            types.as_ptr() as *const u8,
+            types.len() as c_int,
+        );
+
+        // Keep the rest of the hints GTK set and only turn off input focus
+        let existing = get_wm_hints(xdisplay, xid);
+        let mut hints = if existing.is_null() {
+            XWmHints::default()
+        } else {
+            let hints = std::ptr::read(existing);
+            free(existing as *mut c_void);
+            hints
+        };
+        hints.flags |= INPUT_HINT;
+        hints.input = 0;
+        set_wm_hints(xdisplay, xid, &mut hints);
+
+        flush(xdisplay);
+    }
+
+    debug!("Overlay set as a notification window without input focus");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONITOR: (i32, i32, i32, i32) = (0, 0, 1920, 1080);
+    const SIZE: (i32, i32) = (280, 50);
+
+    #[test]
+    fn test_fallback_origin_corners() {
+        assert_eq!(fallback_origin("top-left", MONITOR, SIZE), (24, 24));
+        assert_eq!(fallback_origin("bottom-right", MONITOR, SIZE), (1616, 1006));
+        assert_eq!(fallback_origin("bottom-center", MONITOR, SIZE), (820, 1006));
+        assert_eq!(fallback_origin("top-center", MONITOR, SIZE), (820, 24));
+    }
+
+    #[test]
+    fn test_fallback_origin_second_monitor_and_unknown() {
+        let monitor = (1920, 0, 1280, 720);
+        assert_eq!(fallback_origin("top-left", monitor, SIZE), (1944, 24));
+        assert_eq!(fallback_origin("center", monitor, SIZE), (2420, 335));
+        assert_eq!(fallback_origin("nowhere", monitor, SIZE), (2420, 335));
+    }
+}
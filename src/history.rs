@@ -1,13 +1,25 @@
 use anyhow::{Context, Result};
-use rusqlite::Connection;
+use rusqlite::{Connection, ErrorCode};
+use serde::{Deserialize, Serialize};
 use std::path::Path;
-use tracing::info;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// How long SQLite waits on a lock held by another connection before failing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Extra attempts for a save that still hits a lock after the busy timeout
+const SAVE_RETRIES: u32 = 3;
+
+/// Delay before the first retry; doubled on each further attempt
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
 
 pub struct HistoryStore {
     conn: Connection,
     max_entries: u32,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
 pub struct HistoryEntry {
     pub id: i64,
     pub timestamp: String,
@@ -15,6 +27,9 @@ pub struct HistoryEntry {
     pub text: String,
     pub model: String,
     pub language: String,
+    /// Engine confidence in the transcript (0–1), when it reported one
+    #[serde(default)]
+    pub confidence: Option<f32>,
 }
 
 impl HistoryStore {
@@ -27,6 +42,8 @@ impl HistoryStore {
         let conn = Connection::open(db_path)
             .with_context(|| format!("Failed to open history database: {:?}", db_path))?;
 
+        // Another tjvox process (e.g. `tjvox history`) may briefly hold the lock
+        conn.busy_timeout(BUSY_TIMEOUT)?;
         conn.execute_batch("PRAGMA journal_mode=WAL;")?;
 
         conn.execute_batch(
@@ -39,24 +56,58 @@ impl HistoryStore {
                 language TEXT NOT NULL DEFAULT ''
             );",
         )?;
+        add_column_if_missing(&conn, "confidence", "REAL")?;
 
         info!("History database opened at {:?}", db_path);
         Ok(Self { conn, max_entries })
     }
 
+    /// Save an entry, retrying with backoff while another process holds the
+    /// database lock so a transcription is not dropped. This can block for
+    /// seconds, so async callers run it with `spawn_blocking`.
     pub fn save(&self, entry: &HistoryEntry) -> Result<()> {
-        self.conn.execute(
-            "INSERT INTO history (duration_ms, text, model, language) VALUES (?1, ?2, ?3, ?4)",
-            rusqlite::params![entry.duration_ms, entry.text, entry.model, entry.language],
-        )?;
+        let mut delay = RETRY_BACKOFF;
+        let mut attempt = 0;
+        loop {
+            match self.try_save(entry) {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < SAVE_RETRIES && is_locked(&e) => {
+                    attempt += 1;
+                    warn!(
+                        "History database is locked, retrying in {}ms ({}/{})",
+                        delay.as_millis(),
+                        attempt,
+                        SAVE_RETRIES
+                    );
+                    std::thread::sleep(delay);
+                    delay *= 2;
+                }
+                Err(e) => return Err(e).context("Failed to save history entry"),
+            }
+        }
+    }
 
+    /// Insert and trim in one transaction, so a retry never duplicates an entry.
+    fn try_save(&self, entry: &HistoryEntry) -> rusqlite::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO history (duration_ms, text, model, language, confidence) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                entry.duration_ms,
+                entry.text,
+                entry.model,
+                entry.language,
+                entry.confidence
+            ],
+        )?;
         self.enforce_retention()?;
-        Ok(())
+        tx.commit()
     }
 
     pub fn list(&self, limit: u32) -> Result<Vec<HistoryEntry>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, timestamp, duration_ms, text, model, language FROM history ORDER BY id DESC LIMIT ?1",
+            "SELECT id, timestamp, duration_ms, text, model, language, confidence FROM history ORDER BY id DESC LIMIT ?1",
         )?;
 
         let entries = stmt
@@ -68,6 +119,7 @@ impl HistoryStore {
                     text: row.get(3)?,
                     model: row.get(4)?,
                     language: row.get(5)?,
+                    confidence: row.get(6)?,
                 })
             })?
             .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -75,13 +127,90 @@ impl HistoryStore {
         Ok(entries)
     }
 
+    pub fn get(&self, id: i64) -> Result<Option<HistoryEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, timestamp, duration_ms, text, model, language, confidence FROM history WHERE id = ?1",
+        )?;
+
+        let mut rows = stmt.query_map(rusqlite::params![id], |row| {
+            Ok(HistoryEntry {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                duration_ms: row.get(2)?,
+                text: row.get(3)?,
+                model: row.get(4)?,
+                language: row.get(5)?,
+                confidence: row.get(6)?,
+            })
+        })?;
+
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Delete a single entry. Returns `false` if no entry has this id.
+    pub fn delete(&self, id: i64) -> Result<bool> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM history WHERE id = ?1", rusqlite::params![id])?;
+        if deleted > 0 {
+            info!("History entry {} deleted", id);
+        }
+        Ok(deleted > 0)
+    }
+
     pub fn clear(&self) -> Result<()> {
         self.conn.execute("DELETE FROM history", [])?;
         info!("History cleared");
         Ok(())
     }
 
-    fn enforce_retention(&self) -> Result<()> {
+    /// Rebuild the database file to reclaim space left by deleted entries,
+    /// then checkpoint and truncate the WAL. Slow on large databases, so
+    /// callers run it outside the dictation path (shutdown or on demand).
+    pub fn vacuum(&self) -> Result<()> {
+        self.conn
+            .execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")
+            .context("Failed to vacuum history database")?;
+        info!("History database compacted");
+        Ok(())
+    }
+
+    /// Copy the history to `dest` as a standalone database, then empty this
+    /// one. The copy is written next to `dest` and renamed into place, so a
+    /// failure never leaves a half-written archive behind.
+    pub fn archive(&self, dest: &Path) -> Result<()> {
+        if dest.exists() {
+            anyhow::bail!("Archive destination already exists: {:?}", dest);
+        }
+        let mut part = dest.as_os_str().to_owned();
+        part.push(".part");
+        let part = std::path::PathBuf::from(part);
+        std::fs::remove_file(&part).ok();
+
+        let part_str = part
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("Archive path is not valid UTF-8: {:?}", dest))?;
+        self.conn
+            .execute("VACUUM INTO ?1", rusqlite::params![part_str])
+            .with_context(|| format!("Failed to write history archive: {:?}", part))?;
+        if let Err(e) = std::fs::rename(&part, dest) {
+            std::fs::remove_file(&part).ok();
+            return Err(e).with_context(|| format!("Failed to move archive to {:?}", dest));
+        }
+
+        self.conn.execute_batch(
+            "DELETE FROM history; DELETE FROM sqlite_sequence WHERE name = 'history';",
+        )?;
+        self.vacuum()?;
+        info!("History archived to {:?}", dest);
+        Ok(())
+    }
+
+    fn enforce_retention(&self) -> rusqlite::Result<()> {
+        // 0 means unlimited retention
+        if self.max_entries == 0 {
+            return Ok(());
+        }
         self.conn.execute(
             "DELETE FROM history WHERE id NOT IN (SELECT id FROM history ORDER BY id DESC LIMIT ?1)",
             rusqlite::params![self.max_entries],
@@ -90,6 +219,97 @@ impl HistoryStore {
     }
 }
 
+/// Add a column introduced after the table was first created, so databases
+/// from older versions keep working.
+fn add_column_if_missing(conn: &Connection, column: &str, definition: &str) -> Result<()> {
+    let exists = conn
+        .prepare("SELECT 1 FROM pragma_table_info('history') WHERE name = ?1")?
+        .exists(rusqlite::params![column])?;
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE history ADD COLUMN {} {};", column, definition))?;
+        info!("Added history column {}", column);
+    }
+    Ok(())
+}
+
+fn is_locked(err: &rusqlite::Error) -> bool {
+    matches!(
+        err.sqlite_error_code(),
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+    )
+}
+
+fn duration_label(duration_ms: u64) -> String {
+    if duration_ms > 0 {
+        format!("{:.1}s", duration_ms as f64 / 1000.0)
+    } else {
+        "?".to_string()
+    }
+}
+
+/// One line per entry: `#id [timestamp] (duration, model, language) text`.
+pub fn format_plain(entries: &[HistoryEntry]) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "#{} [{}] ({}, {}, {}) {}\n",
+                entry.id,
+                entry.timestamp,
+                duration_label(entry.duration_ms),
+                entry.model,
+                entry.language,
+                entry.text
+            )
+        })
+        .collect()
+}
+
+/// Entries in aligned columns under a header; the text column is last and
+/// never padded, so long dictations only widen their own line.
+pub fn format_table(entries: &[HistoryEntry]) -> String {
+    let header = ["ID", "TIME", "DURATION", "MODEL", "LANG", "TEXT"];
+    let rows: Vec<[String; 6]> = entries
+        .iter()
+        .map(|entry| {
+            [
+                entry.id.to_string(),
+                entry.timestamp.clone(),
+                duration_label(entry.duration_ms),
+                entry.model.clone(),
+                entry.language.clone(),
+                entry.text.replace('\n', " "),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: [&str; 6]| {
+        let mut out = String::new();
+        for (i, cell) in cells.iter().enumerate() {
+            if i == cells.len() - 1 {
+                out.push_str(cell);
+            } else {
+                out.push_str(&format!("{:<width$}  ", cell, width = widths[i]));
+            }
+        }
+        out.push('\n');
+        out
+    };
+
+    let mut out = line(header);
+    for row in &rows {
+        out.push_str(&line([&row[0], &row[1], &row[2], &row[3], &row[4], &row[5]]));
+    }
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,6 +329,7 @@ mod tests {
             text: "Test transcription".to_string(),
             model: "base".to_string(),
             language: "en".to_string(),
+            confidence: None,
         };
 
         store.save(&entry).unwrap();
@@ -119,6 +340,74 @@ mod tests {
         assert_eq!(entries[0].model, "base");
     }
 
+    fn sample_entries() -> Vec<HistoryEntry> {
+        vec![
+            HistoryEntry {
+                id: 12,
+                timestamp: "2024-05-01 09:30:00".to_string(),
+                duration_ms: 1500,
+                text: "Second note".to_string(),
+                model: "large-v3-turbo".to_string(),
+                language: "en".to_string(),
+                confidence: None,
+            },
+            HistoryEntry {
+                id: 3,
+                timestamp: "2024-05-01 09:00:00".to_string(),
+                duration_ms: 0,
+                text: "First\nnote".to_string(),
+                model: "base".to_string(),
+                language: "de".to_string(),
+                confidence: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_format_plain() {
+        assert_eq!(
+            format_plain(&sample_entries()),
+            "#12 [2024-05-01 09:30:00] (1.5s, large-v3-turbo, en) Second note\n\
+             #3 [2024-05-01 09:00:00] (?, base, de) First\nnote\n"
+        );
+    }
+
+    #[test]
+    fn test_format_table() {
+        let table = format_table(&sample_entries());
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "ID  TIME                 DURATION  MODEL           LANG  TEXT"
+        );
+        assert_eq!(
+            lines[1],
+            "12  2024-05-01 09:30:00  1.5s      large-v3-turbo  en    Second note"
+        );
+        assert_eq!(
+            lines[2],
+            "3   2024-05-01 09:00:00  ?         base            de    First note"
+        );
+    }
+
+    #[test]
+    fn test_history_entry_json() {
+        let entry = HistoryEntry {
+            id: 7,
+            timestamp: "2024-05-01 09:30:00".to_string(),
+            duration_ms: 1200,
+            text: "Say \"hi\"\nthen stop".to_string(),
+            model: "base".to_string(),
+            language: "en".to_string(),
+            confidence: None,
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(!json.contains('\n'));
+        assert!(json.contains("\"id\":7"));
+        assert!(json.contains("\"duration_ms\":1200"));
+    }
+
     #[test]
     fn test_history_store_retention() {
         let temp_dir = TempDir::new().unwrap();
@@ -135,6 +424,7 @@ mod tests {
                 text: format!("Entry {}", i),
                 model: "base".to_string(),
                 language: "en".to_string(),
+                confidence: None,
             };
             store.save(&entry).unwrap();
         }
@@ -162,6 +452,7 @@ mod tests {
             text: "Test".to_string(),
             model: "base".to_string(),
             language: "en".to_string(),
+            confidence: None,
         };
 
         store.save(&entry).unwrap();
@@ -186,6 +477,7 @@ mod tests {
                 text: format!("Entry number {}", i),
                 model: "base".to_string(),
                 language: "en".to_string(),
+                confidence: None,
             };
             store.save(&entry).unwrap();
         }
@@ -196,4 +488,202 @@ mod tests {
         assert_eq!(entries[0].text, "Entry number 9");
         assert_eq!(entries[4].text, "Entry number 5");
     }
+
+    #[test]
+    fn test_history_store_unlimited_retention() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test_history.db");
+
+        let store = HistoryStore::open(&db_path, 0).unwrap();
+
+        for i in 0..20 {
+            let entry = HistoryEntry {
+                id: 0,
+                timestamp: String::new(),
+                duration_ms: 0,
+                text: format!("Entry number {}", i),
+                model: "base".to_string(),
+                language: "en".to_string(),
+                confidence: None,
+            };
+            store.save(&entry).unwrap();
+        }
+
+        assert_eq!(store.list(100).unwrap().len(), 20);
+    }
+
+    #[test]
+    fn test_history_store_vacuum() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test_history.db");
+
+        let store = HistoryStore::open(&db_path, 5).unwrap();
+
+        for i in 0..50 {
+            let entry = HistoryEntry {
+                id: 0,
+                timestamp: String::new(),
+                duration_ms: 0,
+                text: format!("Entry number {} {}", i, "x".repeat(500)),
+                model: "base".to_string(),
+                language: "en".to_string(),
+                confidence: None,
+            };
+            store.save(&entry).unwrap();
+        }
+
+        store.vacuum().unwrap();
+        assert_eq!(store.list(100).unwrap().len(), 5);
+        let wal = temp_dir.path().join("test_history.db-wal");
+        assert!(!wal.exists() || std::fs::metadata(&wal).unwrap().len() == 0);
+    }
+
+    #[test]
+    fn test_history_store_save_waits_for_lock() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test_history.db");
+
+        let store = HistoryStore::open(&db_path, 100).unwrap();
+
+        // A second connection, as another tjvox process would have, holds the write lock
+        let other = Connection::open(&db_path).unwrap();
+        other.execute_batch("BEGIN IMMEDIATE;").unwrap();
+        let holder = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(300));
+            other.execute_batch("COMMIT;").unwrap();
+        });
+
+        let entry = HistoryEntry {
+            id: 0,
+            timestamp: String::new(),
+            duration_ms: 1000,
+            text: "Saved despite lock".to_string(),
+            model: "base".to_string(),
+            language: "en".to_string(),
+            confidence: None,
+        };
+        store.save(&entry).unwrap();
+        holder.join().unwrap();
+
+        let entries = store.list(10).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].text, "Saved despite lock");
+    }
+
+    #[test]
+    fn test_is_locked() {
+        let busy = rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            None,
+        );
+        assert!(is_locked(&busy));
+        assert!(!is_locked(&rusqlite::Error::QueryReturnedNoRows));
+    }
+
+    #[test]
+    fn test_history_store_delete() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test_history.db");
+
+        let store = HistoryStore::open(&db_path, 100).unwrap();
+
+        for text in ["keep me", "my password is hunter2"] {
+            let entry = HistoryEntry {
+                id: 0,
+                timestamp: String::new(),
+                duration_ms: 0,
+                text: text.to_string(),
+                model: "base".to_string(),
+                language: "en".to_string(),
+                confidence: None,
+            };
+            store.save(&entry).unwrap();
+        }
+
+        let sensitive = store.list(1).unwrap().remove(0);
+        assert_eq!(
+            store.get(sensitive.id).unwrap().unwrap().text,
+            "my password is hunter2"
+        );
+
+        assert!(store.delete(sensitive.id).unwrap());
+        assert!(store.get(sensitive.id).unwrap().is_none());
+        assert!(!store.delete(sensitive.id).unwrap());
+
+        let entries = store.list(10).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].text, "keep me");
+    }
+
+    #[test]
+    fn test_history_store_adds_confidence_column() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test_history.db");
+
+        // A database created before the confidence column existed
+        Connection::open(&db_path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE history (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    timestamp TEXT NOT NULL DEFAULT (datetime('now')),
+                    duration_ms INTEGER NOT NULL DEFAULT 0,
+                    text TEXT NOT NULL,
+                    model TEXT NOT NULL DEFAULT '',
+                    language TEXT NOT NULL DEFAULT ''
+                );
+                INSERT INTO history (text) VALUES ('old entry');",
+            )
+            .unwrap();
+
+        let store = HistoryStore::open(&db_path, 100).unwrap();
+        store
+            .save(&HistoryEntry {
+                id: 0,
+                timestamp: String::new(),
+                duration_ms: 900,
+                text: "new entry".to_string(),
+                model: "base".to_string(),
+                language: "en".to_string(),
+                confidence: Some(0.75),
+            })
+            .unwrap();
+
+        let entries = store.list(10).unwrap();
+        assert_eq!(entries[0].confidence, Some(0.75));
+        assert_eq!(entries[1].text, "old entry");
+        assert_eq!(entries[1].confidence, None);
+
+        // Reopening does not try to add the column again
+        drop(store);
+        assert!(HistoryStore::open(&db_path, 100).is_ok());
+    }
+
+    #[test]
+    fn test_history_store_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test_history.db");
+        let archive_path = temp_dir.path().join("archive.db");
+
+        let store = HistoryStore::open(&db_path, 100).unwrap();
+        for entry in sample_entries() {
+            store.save(&entry).unwrap();
+        }
+        let saved = store.list(100).unwrap().len();
+
+        store.archive(&archive_path).unwrap();
+        assert!(store.list(100).unwrap().is_empty());
+        assert!(!temp_dir.path().join("archive.db.part").exists());
+
+        // Ids start over in the emptied database
+        store.save(&sample_entries()[0]).unwrap();
+        assert_eq!(store.list(10).unwrap()[0].id, 1);
+
+        let archived = HistoryStore::open(&archive_path, 100).unwrap();
+        assert_eq!(archived.list(100).unwrap().len(), saved);
+
+        // An existing archive is never overwritten
+        assert!(store.archive(&archive_path).is_err());
+        assert_eq!(archived.list(100).unwrap().len(), saved);
+    }
 }
@@ -0,0 +1,166 @@
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, warn};
+
+use crate::error::TjvoxError;
+
+/// Placeholder replaced with the transcribed text in hook arguments
+const TEXT_PLACEHOLDER: &str = "{text}";
+
+/// Hooks that run longer than this are killed
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Split a command template into arguments.
+///
+/// Supports single quotes, double quotes and backslash escapes. No shell is
+/// involved, so the transcription can never be interpreted as shell syntax.
+pub fn split_command(template: &str) -> Result<Vec<String>, TjvoxError> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => match chars.next() {
+                Some(next) => current.push(next),
+                None => {
+                    return Err(TjvoxError::Hook(
+                        "command ends with a dangling backslash".to_string(),
+                    ))
+                }
+            },
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err(TjvoxError::Hook("unterminated quote in command".to_string()));
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Build the argument list for a hook, substituting `{text}` in each argument.
+pub fn build_args(template: &str, text: &str) -> Result<Vec<String>, TjvoxError> {
+    let args: Vec<String> = split_command(template)?
+        .into_iter()
+        .map(|arg| arg.replace(TEXT_PLACEHOLDER, text))
+        .collect();
+    if args.is_empty() {
+        return Err(TjvoxError::Hook("command is empty".to_string()));
+    }
+    Ok(args)
+}
+
+/// Run the post-transcription hook in the background.
+/// Failures are logged and never affect the dictation pipeline.
+pub fn spawn_post_transcription(template: &str, text: &str) {
+    let args = match build_args(template, text) {
+        Ok(args) => args,
+        Err(e) => {
+            warn!("Invalid post_transcription hook: {}", e);
+            return;
+        }
+    };
+    let text = text.to_string();
+
+    tokio::spawn(async move {
+        match tokio::time::timeout(HOOK_TIMEOUT, run_hook(&args, &text)).await {
+            Ok(Ok(())) => debug!("post_transcription hook finished"),
+            Ok(Err(e)) => warn!("post_transcription hook failed: {}", e),
+            Err(_) => warn!(
+                "post_transcription hook timed out after {}s",
+                HOOK_TIMEOUT.as_secs()
+            ),
+        }
+    });
+}
+
+async fn run_hook(args: &[String], text: &str) -> Result<(), TjvoxError> {
+    let mut child = tokio::process::Command::new(&args[0])
+        .args(&args[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| TjvoxError::Hook(format!("failed to run '{}': {}", args[0], e)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // The hook may not read stdin at all; a broken pipe is not an error
+        let _ = stdin.write_all(text.as_bytes()).await;
+    }
+
+    let status = child.wait().await?;
+    if !status.success() {
+        return Err(TjvoxError::Hook(format!("'{}' exited with {}", args[0], status)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_command_plain() {
+        assert_eq!(
+            split_command("logger -t tjvox").unwrap(),
+            vec!["logger", "-t", "tjvox"]
+        );
+    }
+
+    #[test]
+    fn test_split_command_quotes() {
+        assert_eq!(
+            split_command(r#"notify-send 'Dictated text' "{text}" a\ b"#).unwrap(),
+            vec!["notify-send", "Dictated text", "{text}", "a b"]
+        );
+        assert_eq!(split_command("echo ''").unwrap(), vec!["echo", ""]);
+    }
+
+    #[test]
+    fn test_split_command_errors() {
+        assert!(split_command("echo 'open").is_err());
+        assert!(split_command("echo \\").is_err());
+    }
+
+    #[test]
+    fn test_build_args_placeholder_is_single_argument() {
+        let args = build_args("printf %s {text}", "hi; rm -rf ~ $(whoami)").unwrap();
+        assert_eq!(args, vec!["printf", "%s", "hi; rm -rf ~ $(whoami)"]);
+    }
+
+    #[test]
+    fn test_build_args_empty() {
+        assert!(build_args("   ", "text").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_failure_is_reported() {
+        let args = vec!["false".to_string()];
+        assert!(run_hook(&args, "text").await.is_err());
+        let args = vec!["tjvox-hook-that-does-not-exist".to_string()];
+        assert!(run_hook(&args, "text").await.is_err());
+    }
+}
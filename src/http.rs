@@ -0,0 +1,53 @@
+//! The bare HTTP/1.1 serving shared by the metrics endpoint and the control
+//! API: one request per connection, answered and closed.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+use crate::socket::accept_with_backoff;
+
+/// Requests larger than this are answered with an error
+const MAX_REQUEST: usize = 8 * 1024;
+/// Slow clients are dropped after this long
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Accept connections on `listener` until the task is aborted. The request
+/// head, cut off after `MAX_REQUEST` bytes, is passed to `respond` and its
+/// result written back as the whole response. `name` labels log messages.
+pub fn serve<F, Fut>(listener: TcpListener, name: &'static str, respond: F) -> JoinHandle<()>
+where
+    F: Fn(Vec<u8>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = String> + Send + 'static,
+{
+    let respond = Arc::new(respond);
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, peer) = accept_with_backoff(name, || listener.accept()).await;
+            let respond = respond.clone();
+            tokio::spawn(async move {
+                let exchange = async {
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                        let n = stream.read(&mut buf).await?;
+                        if n == 0 || request.len() + n > MAX_REQUEST {
+                            break;
+                        }
+                        request.extend_from_slice(&buf[..n]);
+                    }
+                    let response = respond(request).await;
+                    stream.write_all(response.as_bytes()).await?;
+                    stream.shutdown().await
+                };
+                if let Ok(Err(e)) = tokio::time::timeout(REQUEST_TIMEOUT, exchange).await {
+                    debug!("{} request from {} failed: {}", name, peer, e);
+                }
+            });
+        }
+    })
+}
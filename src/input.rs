@@ -8,6 +8,7 @@ use crate::error::TjvoxError;
 
 /// Evdev key codes for modifier keys.
 const KEY_LEFTSHIFT: u16 = 42;
+const KEY_ENTER: u16 = 28;
 const KEY_RIGHTALT: u16 = 100; // AltGr
 
 /// Linux input event types.
@@ -49,6 +50,7 @@ struct InputEvent {
 pub struct VirtualKeyboard {
     char_map: HashMap<char, KeyCombo>,
     socket_path: String,
+    shift_enter: bool,
 }
 
 impl VirtualKeyboard {
@@ -75,9 +77,17 @@ impl VirtualKeyboard {
         Ok(Self {
             char_map,
             socket_path,
+            shift_enter: false,
         })
     }
 
+    /// Type newlines as Shift+Enter, which inserts a line break in chat apps
+    /// where a plain Enter sends the message.
+    pub fn with_shift_enter(mut self, shift_enter: bool) -> Self {
+        self.shift_enter = shift_enter;
+        self
+    }
+
     /// Type a string by sending keycode events for each character.
     /// Opens a fresh socket connection for each call.
     pub fn type_text(&self, text: &str) -> Result<()> {
@@ -86,7 +96,14 @@ impl VirtualKeyboard {
         })?;
 
         for ch in text.chars() {
-            if let Some(combo) = self.char_map.get(&ch) {
+            if ch == '\n' && self.shift_enter {
+                let combo = KeyCombo {
+                    keycode: KEY_ENTER,
+                    shift: true,
+                    altgr: false,
+                };
+                self.send_key_combo(&mut stream, &combo)?;
+            } else if let Some(combo) = self.char_map.get(&ch) {
                 self.send_key_combo(&mut stream, combo)?;
             } else {
                 debug!("No mapping for '{}' (U+{:04X}), skipping", ch, ch as u32);
@@ -225,7 +242,7 @@ fn build_char_map() -> Result<HashMap<char, KeyCombo>> {
     // Explicit mappings for control characters commonly in transcribed text
     // KEY_ENTER = 28, KEY_TAB = 15, KEY_SPACE = 57
     map.entry('\n').or_insert(KeyCombo {
-        keycode: 28,
+        keycode: KEY_ENTER,
         shift: false,
         altgr: false,
     });
@@ -1,16 +1,32 @@
+pub mod api;
 pub mod audio;
+pub mod backend;
+pub mod commands;
 pub mod config;
 pub mod daemon;
+pub mod dictaphone;
 pub mod error;
+pub mod events;
+pub mod executor;
 pub mod history;
+pub mod hooks;
+#[cfg(any(feature = "metrics", feature = "api"))]
+mod http;
 pub mod input;
 pub mod llm;
+pub mod metrics;
 pub mod output;
+pub mod pidfile;
 pub mod ptt;
 pub mod replacements;
+pub mod setup;
 pub mod socket;
 pub mod transcription;
 pub mod ui;
+#[cfg(feature = "vosk")]
+pub mod vosk_engine;
+#[cfg(feature = "wake-word")]
+pub mod wake_word;
 
 #[cfg(feature = "gui")]
 pub mod gui;
@@ -23,5 +39,5 @@ pub use audio::AudioRecorder;
 pub use config::Config;
 pub use error::TjvoxError;
 pub use output::OutputManager;
-pub use transcription::TranscriptionService;
+pub use transcription::{create_transcriber, Transcriber, Transcription, TranscriptionService};
 pub use ui::UiManager;
@@ -1,8 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
-use tracing::debug;
+use tracing::{debug, warn};
 
-use crate::config::LlmConfig;
+use crate::config::{LlmConfig, LlmExample};
 use crate::error::TjvoxError;
 
 #[derive(Serialize)]
@@ -38,12 +38,40 @@ pub struct LlmProcessor {
     endpoint: String,
     model: String,
     prompt: String,
+    examples: Vec<LlmExample>,
+    timeout: Duration,
+    /// Longer deadline for a second attempt after a timeout
+    retry_timeout: Option<Duration>,
+}
+
+/// Read the system prompt, loading it from a file for `file:/path/prompt.txt`.
+fn resolve_prompt(prompt: &str) -> Result<String, TjvoxError> {
+    let Some(path) = prompt.strip_prefix("file:") else {
+        return Ok(prompt.to_string());
+    };
+    let path = match path.strip_prefix("~/") {
+        Some(rest) => std::env::var("HOME")
+            .map(|home| std::path::Path::new(&home).join(rest))
+            .map_err(|_| TjvoxError::Llm(format!("cannot expand '{}': HOME is not set", path)))?,
+        None => std::path::PathBuf::from(path),
+    };
+    let content = std::fs::read_to_string(&path).map_err(|e| {
+        TjvoxError::Llm(format!("failed to read prompt file {}: {}", path.display(), e))
+    })?;
+    Ok(content.trim().to_string())
+}
+
+fn message(role: &str, content: &str) -> ChatMessage {
+    ChatMessage {
+        role: role.to_string(),
+        content: content.to_string(),
+    }
 }
 
 impl LlmProcessor {
     pub fn new(config: &LlmConfig) -> Result<Self, TjvoxError> {
-        let mut builder = reqwest::Client::builder()
-            .timeout(Duration::from_millis(config.timeout_ms));
+        // Deadlines are set per request so a retry can wait longer
+        let mut builder = reqwest::Client::builder();
 
         if !config.api_key.is_empty() {
             let mut headers = reqwest::header::HeaderMap::new();
@@ -64,35 +92,67 @@ impl LlmProcessor {
             client,
             endpoint: config.endpoint.clone(),
             model: config.model.clone(),
-            prompt: config.prompt.clone(),
+            prompt: resolve_prompt(&config.prompt)?,
+            examples: config.examples.clone(),
+            timeout: Duration::from_millis(config.timeout_ms),
+            retry_timeout: (config.timeout_retry_ms > 0)
+                .then(|| Duration::from_millis(config.timeout_retry_ms)),
         })
     }
 
+    /// System prompt, then each example as a user/assistant exchange, then
+    /// the dictated text.
+    fn messages(&self, text: &str) -> Vec<ChatMessage> {
+        let mut messages = vec![message("system", &self.prompt)];
+        for example in &self.examples {
+            messages.push(message("user", &example.input));
+            messages.push(message("assistant", &example.output));
+        }
+        messages.push(message("user", text));
+        messages
+    }
+
+    /// Correct `text`, retrying once with `llm.timeout_retry_ms` when the
+    /// first request times out.
     pub async fn process(&self, text: &str) -> Result<String, TjvoxError> {
+        match self.request(text, self.timeout).await {
+            Err(TjvoxError::LlmTimeout(ms)) if self.retry_timeout.is_some() => {
+                let retry = self.retry_timeout.unwrap_or(self.timeout);
+                warn!(
+                    "LLM request timed out after {}ms, retrying with {}ms",
+                    ms,
+                    retry.as_millis()
+                );
+                self.request(text, retry).await
+            }
+            result => result,
+        }
+    }
+
+    async fn request(&self, text: &str, timeout: Duration) -> Result<String, TjvoxError> {
         debug!("Sending text to LLM for post-processing");
 
         let request = ChatRequest {
             model: self.model.clone(),
-            messages: vec![
-                ChatMessage {
-                    role: "system".to_string(),
-                    content: self.prompt.clone(),
-                },
-                ChatMessage {
-                    role: "user".to_string(),
-                    content: text.to_string(),
-                },
-            ],
+            messages: self.messages(text),
             temperature: 0.3,
         };
+        let failed = |what: &str, e: reqwest::Error| {
+            if e.is_timeout() {
+                TjvoxError::LlmTimeout(timeout.as_millis() as u64)
+            } else {
+                TjvoxError::Llm(format!("{}: {}", what, e))
+            }
+        };
 
         let response = self
             .client
             .post(&self.endpoint)
+            .timeout(timeout)
             .json(&request)
             .send()
             .await
-            .map_err(|e| TjvoxError::Llm(format!("request failed: {}", e)))?;
+            .map_err(|e| failed("request failed", e))?;
 
         if !response.status().is_success() {
             return Err(TjvoxError::Llm(format!(
@@ -104,7 +164,7 @@ impl LlmProcessor {
         let body: ChatResponse = response
             .json()
             .await
-            .map_err(|e| TjvoxError::Llm(format!("failed to parse response: {}", e)))?;
+            .map_err(|e| failed("failed to parse response", e))?;
 
         let content = body
             .choices
@@ -121,7 +181,6 @@ impl LlmProcessor {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::LlmConfig;
 
     #[test]
     fn test_llm_processor_new() {
@@ -132,6 +191,8 @@ mod tests {
             model: "llama3".to_string(),
             prompt: "Fix grammar.".to_string(),
             timeout_ms: 5000,
+            timeout_retry_ms: 0,
+            examples: Vec::new(),
         };
         let processor = LlmProcessor::new(&config);
         assert!(processor.is_ok());
@@ -146,11 +207,46 @@ mod tests {
             model: "gpt-4".to_string(),
             prompt: "Fix grammar.".to_string(),
             timeout_ms: 10000,
+            timeout_retry_ms: 0,
+            examples: Vec::new(),
         };
         let processor = LlmProcessor::new(&config);
         assert!(processor.is_ok());
     }
 
+    #[test]
+    fn test_prompt_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prompt.txt");
+        std::fs::write(&path, "Fix grammar.\nKeep technical terms.\n").unwrap();
+
+        assert_eq!(resolve_prompt("Fix grammar.").unwrap(), "Fix grammar.");
+        assert_eq!(
+            resolve_prompt(&format!("file:{}", path.display())).unwrap(),
+            "Fix grammar.\nKeep technical terms."
+        );
+        assert!(resolve_prompt(&format!("file:{}", dir.path().join("missing").display())).is_err());
+    }
+
+    #[test]
+    fn test_messages_with_examples() {
+        let config = LlmConfig {
+            examples: vec![LlmExample {
+                input: "call me at five pm".to_string(),
+                output: "Call me at 5 PM.".to_string(),
+            }],
+            ..LlmConfig::default()
+        };
+        let processor = LlmProcessor::new(&config).unwrap();
+        let messages = processor.messages("see you tomorrow");
+
+        let roles: Vec<&str> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["system", "user", "assistant", "user"]);
+        assert_eq!(messages[1].content, "call me at five pm");
+        assert_eq!(messages[2].content, "Call me at 5 PM.");
+        assert_eq!(messages[3].content, "see you tomorrow");
+    }
+
     #[test]
     fn test_chat_request_serialization() {
         let request = ChatRequest {
@@ -191,4 +287,70 @@ mod tests {
         let response: ChatResponse = serde_json::from_str(json).unwrap();
         assert!(response.choices.is_empty());
     }
+
+    /// Local endpoint answering one connection per entry of `delays`, each
+    /// with `status` after waiting that many milliseconds.
+    async fn fake_endpoint(status: &'static str, delays: Vec<u64>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for delay in delays {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let _ = stream.read(&mut buf).await;
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    let body = r#"{"choices":[{"message":{"content":"Fixed."}}]}"#;
+                    let response = format!(
+                        "HTTP/1.1 {}\r\nContent-Type: application/json\r\n\
+                         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{}/v1/chat/completions", addr)
+    }
+
+    fn processor(endpoint: String, timeout_ms: u64, timeout_retry_ms: u64) -> LlmProcessor {
+        LlmProcessor::new(&LlmConfig {
+            endpoint,
+            timeout_ms,
+            timeout_retry_ms,
+            ..LlmConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_process_timeout_is_distinct() {
+        let endpoint = fake_endpoint("200 OK", vec![1000]).await;
+        let result = processor(endpoint, 100, 0).process("hello").await;
+        assert!(matches!(result, Err(TjvoxError::LlmTimeout(100))), "{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_process_retries_after_timeout() {
+        let endpoint = fake_endpoint("200 OK", vec![1000, 0]).await;
+        let result = processor(endpoint, 100, 2000).process("hello").await;
+        assert_eq!(result.unwrap(), "Fixed.");
+    }
+
+    #[tokio::test]
+    async fn test_process_error_is_not_retried() {
+        // Only one connection is served, so a retry would fail to connect
+        let endpoint = fake_endpoint("500 Internal Server Error", vec![0]).await;
+        let result = processor(endpoint, 1000, 2000).process("hello").await;
+        match result {
+            Err(TjvoxError::Llm(message)) => assert!(message.contains("500"), "{}", message),
+            other => panic!("expected an API error, got {:?}", other),
+        }
+    }
 }
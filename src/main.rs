@@ -1,16 +1,19 @@
-use std::path::PathBuf;
-use anyhow::Result;
-use clap::{Parser, Subcommand};
-use tracing::info;
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand};
+use tracing::{info, warn};
 
-use tjvox::config::Config;
+use tjvox::config::{Config, DefaultCommand};
+use tjvox::events::SessionEvent;
 use tjvox::daemon::Daemon;
-use tjvox::history::HistoryStore;
-use tjvox::socket;
+use tjvox::history::{self, HistoryEntry, HistoryStore};
+use tjvox::pidfile::{self, DaemonStatus};
+use tjvox::replacements;
+use tjvox::socket::{self, SessionOptions};
 use tjvox::ui::UiManager;
 use tjvox::audio::AudioRecorder;
-use tjvox::transcription::TranscriptionService;
-use tjvox::output::OutputManager;
+use tjvox::transcription::{create_transcriber, Transcription};
+use tjvox::output::{OutputManager, OutputMethod};
 
 #[derive(Parser)]
 #[command(name = "tjvox")]
@@ -24,37 +27,193 @@ struct Cli {
     config: Option<PathBuf>,
 }
 
+/// Post-processing overrides for the dictation a command starts.
+#[derive(Args)]
+struct SessionFlags {
+    /// Type the text as transcribed: keep filler words, skip replacements
+    /// and the trailing space
+    #[arg(long)]
+    verbatim: bool,
+    /// Keep filler words for this dictation
+    #[arg(long)]
+    keep_fillers: bool,
+    /// Skip the replacement rules for this dictation
+    #[arg(long)]
+    no_replacements: bool,
+    /// Leave out the trailing space for this dictation
+    #[arg(long)]
+    no_trailing_space: bool,
+}
+
+impl SessionFlags {
+    fn options(&self) -> SessionOptions {
+        if self.verbatim {
+            return SessionOptions::VERBATIM;
+        }
+        SessionOptions {
+            keep_fillers: self.keep_fillers,
+            no_replacements: self.no_replacements,
+            no_trailing_space: self.no_trailing_space,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
+    /// Check dependencies, write a starter config and download a model
+    Setup,
+    /// Report the GPU backend, tools, config and model without changing anything
+    Doctor,
+    /// Print version information
+    Version {
+        /// Also show the compiled whisper backend and detected GPU
+        #[arg(short, long)]
+        verbose: bool,
+    },
     /// Run a single dictation session
-    Run,
+    Run {
+        /// Print only the transcription to stdout instead of typing it (pipe-safe)
+        #[arg(long)]
+        stdout: bool,
+        /// Report progress as JSON lines on stdout instead of human-readable
+        /// messages (recording_started, transcribing, transcribed, typed)
+        #[arg(long)]
+        events: bool,
+        /// Save the transcription to history without typing or printing it
+        #[arg(long, conflicts_with = "stdout")]
+        no_output: bool,
+    },
     /// Start background daemon (headless)
-    Daemon,
+    Daemon {
+        /// Save dictations to history without typing them (see `set-output`)
+        #[arg(long)]
+        no_output: bool,
+    },
     /// Start GUI with overlay and system tray
     #[cfg(feature = "gui")]
     Gui,
+    /// Show a live microphone level meter to check the input device
+    MicTest {
+        /// How long to monitor the microphone
+        #[arg(short, long, default_value = "5")]
+        seconds: u64,
+    },
+    /// Record continuously and append a transcription every interval to a file
+    Dictaphone {
+        /// Seconds of audio per transcription
+        #[arg(short, long, default_value_t = 30)]
+        interval: u64,
+        /// File the transcriptions are appended to
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        /// Seconds from the end of each chunk transcribed again with the next,
+        /// so words across the boundary are kept
+        #[arg(long, default_value_t = 2)]
+        overlap: u64,
+        /// Prefix each line with the time it was transcribed
+        #[arg(long)]
+        timestamps: bool,
+    },
     /// Toggle recording (send SIGUSR1 to daemon)
-    Toggle,
+    Toggle {
+        #[command(flatten)]
+        session: SessionFlags,
+    },
     /// Stop background daemon
     Stop,
-    /// Check daemon status
-    Status,
+    /// Check daemon status and the last error
+    Status {
+        /// Print the state and last error as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Show transcription history
     History {
         /// Maximum number of entries to show
         #[arg(short, long, default_value = "20")]
         limit: u32,
+        /// Output format: aligned columns, one line per entry, or JSON
+        #[arg(short, long, value_parser = ["plain", "table", "json"], default_value = "plain")]
+        format: String,
+        /// Read the entries through the running daemon instead of opening
+        /// the database (via socket)
+        #[arg(long)]
+        daemon: bool,
+    },
+    /// Delete a single history entry by its id
+    HistoryDelete {
+        /// Entry id as shown by `tjvox history`
+        id: i64,
+        /// Delete without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
     },
     /// Clear all transcription history
     HistoryClear,
+    /// Compact the history database and its write-ahead log
+    HistoryVacuum,
+    /// Move the history to a new database file and start over with an empty
+    /// one; coordinated with the running daemon (via socket) if there is one
+    HistoryArchive {
+        /// Where to write the archive; must not exist yet
+        path: PathBuf,
+    },
+    /// Transcribe a WAV file with the running daemon's loaded model (via socket)
+    Transcribe {
+        /// Audio file to transcribe
+        path: PathBuf,
+        /// Print the full result (language, segments, confidence) as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Restore the default replacements file, keeping the current one as
+    /// a `.bak` backup
+    ReplacementsReset {
+        /// Reset without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Open the replacements file in $VISUAL or $EDITOR and check it afterwards
+    ReplacementsEdit,
     /// Start push-to-talk recording (via socket)
-    PushStart,
+    PushStart {
+        #[command(flatten)]
+        session: SessionFlags,
+    },
     /// Stop push-to-talk recording (via socket)
     PushStop,
+    /// Turn LLM post-processing on or off for the running daemon (via socket)
+    SetLlm {
+        #[arg(value_parser = ["on", "off"])]
+        state: String,
+    },
+    /// Show the overlay without recording, with a live mic meter (via socket)
+    ShowOverlay,
+    /// Hide the overlay shown with show-overlay (via socket)
+    HideOverlay,
+    /// Output the last dictation again, re-transcribing its kept recording
+    /// if transcription failed (via socket)
+    RetryLast,
+    /// Turn typing off (history only) or back on for the running daemon (via socket)
+    SetOutput {
+        #[arg(value_parser = ["on", "off"])]
+        state: String,
+    },
+    /// Change the paste keystroke of the running daemon, e.g. `shift+insert`
+    /// or `ctrl+v ctrl+v`; `default` restores the configured keys (via socket)
+    SetPasteKeys {
+        /// Space-separated chords of `+`-joined keys
+        #[arg(required = true, num_args = 1..)]
+        keys: Vec<String>,
+    },
 }
 
 fn main() -> Result<()> {
-    tracing_subscriber::fmt::init();
+    // Logs go to stderr so stdout stays clean for `run --stdout`
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
 
     let cli = Cli::parse();
 
@@ -66,8 +225,42 @@ fn main() -> Result<()> {
         .ok_or_else(|| anyhow::anyhow!("Could not determine config path"))?;
 
     match cli.command {
-        Some(Commands::Toggle) => {
-            toggle_daemon()?;
+        Some(Commands::Setup) => {
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(tjvox::setup::run_setup(&config_path))?;
+        }
+        Some(Commands::Doctor) => {
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(tjvox::setup::run_doctor(&config_path))?;
+        }
+        Some(Commands::Version { verbose }) => {
+            println!("tjvox {}", env!("CARGO_PKG_VERSION"));
+            if verbose {
+                tjvox::setup::print_backend_info();
+            }
+        }
+        Some(Commands::MicTest { seconds }) => {
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(async {
+                let config = Config::load(&config_path).await?;
+                run_mic_test(config, seconds).await
+            })?;
+        }
+        Some(Commands::Dictaphone { interval, output, overlap, timestamps }) => {
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(async {
+                let config = Config::load(&config_path).await?;
+                let options = tjvox::dictaphone::DictaphoneOptions {
+                    interval: std::time::Duration::from_secs(interval),
+                    overlap: std::time::Duration::from_secs(overlap),
+                    output,
+                    timestamps,
+                };
+                tjvox::dictaphone::run(config, options).await
+            })?;
+        }
+        Some(Commands::Toggle { session }) => {
+            toggle_daemon(session.options())?;
         }
         Some(Commands::Stop) => {
             let rt = tokio::runtime::Runtime::new()?;
@@ -76,55 +269,108 @@ fn main() -> Result<()> {
                 stop_daemon().await
             })?;
         }
-        Some(Commands::Status) => {
+        Some(Commands::Status { json }) => {
             let rt = tokio::runtime::Runtime::new()?;
-            rt.block_on(check_status())?;
+            rt.block_on(check_status(json))?;
         }
         #[cfg(feature = "gui")]
-        Some(Commands::Gui) | None => {
+        Some(Commands::Gui) => {
             let rt = tokio::runtime::Runtime::new()?;
             let config = rt.block_on(Config::load(&config_path))?;
             drop(rt);
             info!("Starting TJvox GUI");
             tjvox::gui::run_gui(config)?;
         }
-        #[cfg(not(feature = "gui"))]
         None => {
             let rt = tokio::runtime::Runtime::new()?;
             let config = rt.block_on(Config::load(&config_path))?;
-            info!("Starting TJvox daemon");
-            rt.block_on(async {
-                let daemon = Daemon::new(config).await?;
-                daemon.run().await
-            })?;
+            match config.daemon.default_command {
+                #[cfg(feature = "gui")]
+                DefaultCommand::Gui => {
+                    drop(rt);
+                    info!("Starting TJvox GUI");
+                    tjvox::gui::run_gui(config)?;
+                }
+                command => {
+                    if command == DefaultCommand::Gui {
+                        warn!("default_command = \"gui\" needs a build with the gui feature");
+                    }
+                    info!("Starting TJvox daemon");
+                    rt.block_on(async {
+                        let daemon = Daemon::new(config).await?;
+                        daemon.run().await
+                    })?;
+                }
+            }
         }
-        Some(Commands::Daemon) => {
+        Some(Commands::Daemon { no_output }) => {
             let rt = tokio::runtime::Runtime::new()?;
             rt.block_on(async {
                 let config = Config::load(&config_path).await?;
                 info!("Starting TJvox daemon");
-                let daemon = Daemon::new(config).await?;
+                let daemon = Daemon::new(config).await?.with_output(!no_output);
                 daemon.run().await
             })?;
         }
-        Some(Commands::Run) => {
+        Some(Commands::Run { stdout, events, no_output }) => {
             let rt = tokio::runtime::Runtime::new()?;
             rt.block_on(async {
                 let config = Config::load(&config_path).await?;
                 info!("Running single TJvox session");
-                run_single_session(config).await
+                run_single_session(config, stdout, events, no_output).await
             })?;
         }
-        Some(Commands::History { limit }) => {
-            show_history(limit)?;
+        Some(Commands::History { limit, format, daemon }) => {
+            let entries = if daemon {
+                let rt = tokio::runtime::Runtime::new()?;
+                let response = rt.block_on(socket::send_command(&format!("history {}", limit)))?;
+                if let Some(error) = response.strip_prefix("error: ") {
+                    return Err(anyhow::anyhow!("{}", error));
+                }
+                Some(serde_json::from_str(&response)?)
+            } else {
+                read_history(limit)?
+            };
+            show_history(entries, &format)?;
+        }
+        Some(Commands::HistoryDelete { id, yes }) => {
+            delete_history_entry(id, yes)?;
         }
         Some(Commands::HistoryClear) => {
             clear_history()?;
         }
-        Some(Commands::PushStart) => {
+        Some(Commands::HistoryVacuum) => {
+            vacuum_history()?;
+        }
+        Some(Commands::HistoryArchive { path }) => {
+            archive_history(&path)?;
+        }
+        Some(Commands::Transcribe { path, json }) => {
+            transcribe_with_daemon(&path, json)?;
+        }
+        Some(Commands::ReplacementsReset { yes }) => {
+            let rt = tokio::runtime::Runtime::new()?;
+            let config = rt.block_on(Config::load(&config_path))?;
+            reset_replacements(&config, yes)?;
+        }
+        Some(Commands::ReplacementsEdit) => {
+            let rt = tokio::runtime::Runtime::new()?;
+            let config = rt.block_on(Config::load(&config_path))?;
+            edit_replacements(&config)?;
+        }
+        Some(Commands::PushStart { session }) => {
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(async {
+                let command = format!("push-start{}", session.options().to_flags());
+                let response = socket::send_command(&command).await?;
+                println!("{}", response);
+                Ok::<(), anyhow::Error>(())
+            })?;
+        }
+        Some(Commands::SetLlm { state }) => {
             let rt = tokio::runtime::Runtime::new()?;
             rt.block_on(async {
-                let response = socket::send_command("push-start").await?;
+                let response = socket::send_command(&format!("set-llm {}", state)).await?;
                 println!("{}", response);
                 Ok::<(), anyhow::Error>(())
             })?;
@@ -137,92 +383,188 @@ fn main() -> Result<()> {
                 Ok::<(), anyhow::Error>(())
             })?;
         }
+        Some(Commands::ShowOverlay) => {
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(async {
+                let response = socket::send_command("show-overlay").await?;
+                println!("{}", response);
+                Ok::<(), anyhow::Error>(())
+            })?;
+        }
+        Some(Commands::HideOverlay) => {
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(async {
+                let response = socket::send_command("hide-overlay").await?;
+                println!("{}", response);
+                Ok::<(), anyhow::Error>(())
+            })?;
+        }
+        Some(Commands::RetryLast) => {
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(async {
+                let response = socket::send_command("retry-last").await?;
+                println!("{}", response);
+                Ok::<(), anyhow::Error>(())
+            })?;
+        }
+        Some(Commands::SetOutput { state }) => {
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(async {
+                let response = socket::send_command(&format!("set-output {}", state)).await?;
+                println!("{}", response);
+                Ok::<(), anyhow::Error>(())
+            })?;
+        }
+        Some(Commands::SetPasteKeys { keys }) => {
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(async {
+                let response =
+                    socket::send_command(&format!("set-paste-keys {}", keys.join(" "))).await?;
+                println!("{}", response);
+                Ok::<(), anyhow::Error>(())
+            })?;
+        }
     }
 
     Ok(())
 }
 
-async fn run_single_session(config: Config) -> Result<()> {
+/// Run one recording session. With `to_stdout`, the transcription is printed
+/// undecorated to stdout instead of being typed; status messages go to stderr.
+async fn run_single_session(
+    config: Config,
+    to_stdout: bool,
+    events: bool,
+    no_output: bool,
+) -> Result<()> {
     let ui = UiManager::with_config(&config.ui);
+    if no_output && !config.history.enabled {
+        anyhow::bail!("--no-output saves to history, but history.enabled is false");
+    }
+    let started = std::time::Instant::now();
 
     let mut recorder = AudioRecorder::new(&config.audio, None)?;
     let recording_path = recorder.start().await?;
 
-    println!("Recording to: {}", recording_path.display());
-    println!("Press Enter to stop recording...");
+    if events {
+        SessionEvent::RecordingStarted {
+            path: recording_path.as_ref().map(|path| path.display().to_string()),
+        }
+        .emit();
+    } else {
+        match recording_path {
+            Some(ref path) => eprintln!("Recording to: {}", path.display()),
+            None => eprintln!("Recording to memory"),
+        }
+        eprintln!("Press Enter to stop recording...");
+    }
     ui.show_notification("TJvox", "Recording... Press Enter to stop").await?;
 
     // Wait for Enter key to stop
     let mut line = String::new();
     std::io::stdin().read_line(&mut line)?;
 
-    println!("Stopping recording...");
-    let audio_path = recorder.stop().await?;
-
-    println!("Transcribing...");
+    if !events {
+        eprintln!("Stopping recording...");
+    }
+    let audio = recorder.stop().await?;
+    let duration_ms = started.elapsed().as_millis() as u64;
+    if events {
+        SessionEvent::Transcribing {
+            clipping: recorder.is_clipping(),
+        }
+        .emit();
+    } else {
+        if recorder.is_clipping() {
+            eprintln!("Warning: input is clipping, lower your mic gain.");
+        }
+        eprintln!("Transcribing...");
+    }
     ui.show_notification("TJvox", "Transcribing...").await?;
 
-    let mut transcriber = TranscriptionService::new(&config.transcription)?;
-    let text = transcriber.transcribe(&audio_path).await?;
-
-    if text.trim().is_empty() {
-        println!("No speech detected.");
+    let mut transcriber = create_transcriber(&config.transcription, &config.audio)?;
+    if !events {
+        transcriber.set_download_callback(std::sync::Arc::new(|event| eprintln!("{}", event)));
+    }
+    let result = transcriber.transcribe_detailed(&audio, None, None).await?;
+    let text = result.text.clone();
+
+    let transcription = &config.transcription;
+    let junk = replacements::is_junk(&text, &transcription.junk_phrases, transcription.drop_punctuation_only);
+    let silent = result.is_no_speech(transcription.no_speech_threshold);
+    if text.trim().is_empty() || junk || silent {
+        if events {
+            SessionEvent::NoSpeech.emit();
+        } else {
+            eprintln!("No speech detected.");
+        }
         ui.show_notification("TJvox", "No speech detected").await?;
+    } else if no_output {
+        save_to_history(&config, &result, duration_ms)?;
+        if events {
+            SessionEvent::Transcribed { text: text.clone() }.emit();
+        } else {
+            eprintln!("Saved to history.");
+        }
+    } else if events {
+        SessionEvent::Transcribed { text: text.clone() }.emit();
+        if !to_stdout {
+            let output = OutputManager::new(&config.output)?;
+            let method = output.type_text(&text).await?;
+            SessionEvent::Typed {
+                method: method.to_string(),
+            }
+            .emit();
+        }
+    } else if to_stdout {
+        println!("{}", text);
     } else {
-        println!("Transcribed: {}", text);
+        eprintln!("Transcribed: {}", text);
         let output = OutputManager::new(&config.output)?;
-        output.type_text(&text).await?;
-        ui.show_notification("TJvox", &format!("Transcribed: {}", &text[..text.len().min(50)])).await?;
+        let method = output.type_text(&text).await?;
+        let preview: String = text.chars().take(50).collect();
+        if method == OutputMethod::ClipboardNotify {
+            eprintln!("Copied to clipboard, paste it manually.");
+            ui.show_notification("TJvox", &format!("Copied to clipboard, press Ctrl+V to paste: {}", preview)).await?;
+        } else if config.ui.verbose {
+            eprintln!("Output method: {}", method);
+            ui.show_notification("TJvox", &format!("Transcribed ({}): {}", method, preview)).await?;
+        } else {
+            ui.show_notification("TJvox", &format!("Transcribed: {}", preview)).await?;
+        }
     }
 
     // Cleanup
-    let _ = tokio::fs::remove_file(&audio_path).await;
+    audio.remove().await;
 
     Ok(())
 }
 
-/// Get the PID file path in the user's runtime directory (secure, user-private).
-fn pid_file_path() -> PathBuf {
-    let uid = unsafe { libc::getuid() };
-    PathBuf::from(format!("/run/user/{}/tjvox.pid", uid))
-}
-
-/// Check if a process with the given PID is actually a dictation process.
-fn is_tjvox_process(pid: i32) -> bool {
-    let cmdline_path = format!("/proc/{}/cmdline", pid);
-    if let Ok(cmdline) = std::fs::read_to_string(&cmdline_path) {
-        cmdline.contains("tjvox")
-    } else {
-        false
-    }
-}
-
-fn toggle_daemon() -> Result<()> {
+fn toggle_daemon(options: SessionOptions) -> Result<()> {
     // Try socket first, fall back to SIGUSR1
     let rt = tokio::runtime::Runtime::new()?;
-    match rt.block_on(socket::send_command("toggle")) {
+    match rt.block_on(socket::send_command(&format!("toggle{}", options.to_flags()))) {
         Ok(response) => {
             println!("{}", response);
             return Ok(());
         }
+        // The signal cannot carry the overrides
+        Err(e) if options != SessionOptions::default() => return Err(e),
         Err(_) => {
             info!("Socket not available, falling back to SIGUSR1");
         }
     }
 
-    let pid_file = pid_file_path();
-    if !pid_file.exists() {
-        anyhow::bail!("Daemon is not running (no PID file)");
-    }
-
-    let pid_str = std::fs::read_to_string(&pid_file)?;
-    let pid: i32 = pid_str.trim().parse()?;
-
-    if !is_tjvox_process(pid) {
-        // Stale PID file — clean it up
-        std::fs::remove_file(&pid_file).ok();
-        anyhow::bail!("Stale PID file (PID {} is not a tjvox process)", pid);
-    }
+    let pid_file = pidfile::pid_file_path();
+    let pid = match pidfile::daemon_status(&pid_file)? {
+        DaemonStatus::Running(pid) => pid,
+        DaemonStatus::NotRunning => anyhow::bail!("Daemon is not running (no PID file)"),
+        DaemonStatus::Stale => {
+            // Lock is not held — the daemon died without cleaning up
+            std::fs::remove_file(&pid_file).ok();
+            anyhow::bail!("Daemon is not running (removed stale PID file)");
+        }
+    };
 
     let ret = unsafe { libc::kill(pid, libc::SIGUSR1) };
     if ret != 0 {
@@ -234,20 +576,19 @@ fn toggle_daemon() -> Result<()> {
 }
 
 async fn stop_daemon() -> Result<()> {
-    let pid_file = pid_file_path();
-    if !pid_file.exists() {
-        println!("Daemon is not running");
-        return Ok(());
-    }
-
-    let pid = tokio::fs::read_to_string(&pid_file).await?;
-    let pid: i32 = pid.trim().parse()?;
-
-    if !is_tjvox_process(pid) {
-        tokio::fs::remove_file(&pid_file).await.ok();
-        println!("Removed stale PID file (PID {} is not a tjvox process)", pid);
-        return Ok(());
-    }
+    let pid_file = pidfile::pid_file_path();
+    let pid = match pidfile::daemon_status(&pid_file)? {
+        DaemonStatus::Running(pid) => pid,
+        DaemonStatus::NotRunning => {
+            println!("Daemon is not running");
+            return Ok(());
+        }
+        DaemonStatus::Stale => {
+            tokio::fs::remove_file(&pid_file).await.ok();
+            println!("Removed stale PID file (daemon is not running)");
+            return Ok(());
+        }
+    };
 
     let ret = unsafe { libc::kill(pid, libc::SIGTERM) };
     if ret != 0 {
@@ -255,30 +596,129 @@ async fn stop_daemon() -> Result<()> {
         anyhow::bail!("Failed to send SIGTERM to PID {}: {}", pid, err);
     }
 
-    tokio::fs::remove_file(&pid_file).await.ok();
+    // The daemon removes its PID file when it releases the lock
     println!("Daemon stopped");
     Ok(())
 }
 
-async fn check_status() -> Result<()> {
-    let pid_file = pid_file_path();
-    if pid_file.exists() {
-        let pid_str = tokio::fs::read_to_string(&pid_file).await?;
-        let pid: i32 = pid_str.trim().parse()?;
-        // Verify process is actually alive and is dictation
-        if is_tjvox_process(pid) {
-            println!("Daemon is running (PID: {})", pid);
-        } else {
+async fn check_status(json: bool) -> Result<()> {
+    let pid_file = pidfile::pid_file_path();
+    let pid = match pidfile::daemon_status(&pid_file)? {
+        DaemonStatus::Running(pid) => pid,
+        DaemonStatus::NotRunning => {
+            if json {
+                println!("{}", serde_json::json!({ "running": false }));
+            } else {
+                println!("Daemon is not running");
+            }
+            return Ok(());
+        }
+        DaemonStatus::Stale => {
             // Clean up stale PID file
             tokio::fs::remove_file(&pid_file).await.ok();
-            println!("Daemon is not running (cleaned up stale PID file)");
+            if json {
+                println!("{}", serde_json::json!({ "running": false }));
+            } else {
+                println!("Daemon is not running (cleaned up stale PID file)");
+            }
+            return Ok(());
+        }
+    };
+
+    // State and last error come from the daemon; older daemons or a
+    // missing socket only leave the PID to report
+    let status: serde_json::Value = match socket::send_command("status-json").await {
+        Ok(response) => serde_json::from_str(&response).unwrap_or_default(),
+        Err(e) => {
+            tracing::debug!("Could not query daemon state: {:#}", e);
+            serde_json::Value::Null
         }
+    };
+    if json {
+        let mut status = match status {
+            serde_json::Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        status.insert("running".to_string(), true.into());
+        status.insert("pid".to_string(), pid.into());
+        println!("{}", serde_json::Value::Object(status));
+        return Ok(());
+    }
+
+    println!("Daemon is running (PID: {})", pid);
+    if let Some(state) = status["state"].as_str() {
+        println!("State: {}", state);
+    }
+    let last_error = &status["last_error"];
+    if let Some(message) = last_error["message"].as_str() {
+        println!(
+            "Last error ({}s ago): {}",
+            last_error["seconds_ago"].as_u64().unwrap_or_default(),
+            message
+        );
+    }
+    Ok(())
+}
+
+/// Print a live RMS meter from the capture stream, then a short verdict.
+async fn run_mic_test(config: Config, seconds: u64) -> Result<()> {
+    use std::io::Write;
+    use std::time::{Duration, Instant};
+
+    let (amp_tx, amp_rx) = std::sync::mpsc::channel();
+    let mut recorder = AudioRecorder::new(&config.audio, Some(amp_tx))?;
+    recorder.start_monitor().await?;
+
+    println!(
+        "Monitoring microphone for {}s ({} Hz, {} channel(s)). Speak normally.",
+        seconds, config.audio.sample_rate, config.audio.channels
+    );
+
+    let deadline = Instant::now() + Duration::from_secs(seconds);
+    let mut peak = 0.0f32;
+    let mut stdout = std::io::stdout();
+    while Instant::now() < deadline {
+        match amp_rx.recv_timeout(Duration::from_millis(200)) {
+            Ok(rms) => {
+                peak = peak.max(rms);
+                print!("\r{}", tjvox::audio::level_meter(rms, 40));
+                stdout.flush()?;
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    recorder.stop_monitor();
+
+    let peak_db = tjvox::audio::rms_to_dbfs(peak);
+    println!("\n\nPeak level: {:.1} dBFS", peak_db);
+    if peak_db <= tjvox::audio::METER_FLOOR_DBFS {
+        println!("No signal. Check that the right input device is selected and not muted.");
+    } else if peak_db < -40.0 {
+        println!("Very quiet. Raise the input volume or move closer to the microphone.");
+    } else if peak_db > -3.0 {
+        println!("Close to clipping. Lower the input volume.");
     } else {
-        println!("Daemon is not running");
+        println!("Levels look good.");
     }
     Ok(())
 }
 
+/// Record a `run --no-output` transcription in the history database.
+fn save_to_history(config: &Config, result: &Transcription, duration_ms: u64) -> Result<()> {
+    let db_path = data_dir_fallback().join("tjvox/history.db");
+    let store = HistoryStore::open(&db_path, config.history.max_entries)?;
+    store.save(&HistoryEntry {
+        id: 0,
+        timestamp: String::new(),
+        duration_ms,
+        text: result.text.clone(),
+        model: config.transcription.model.clone(),
+        language: result.language.clone().unwrap_or_default(),
+        confidence: result.confidence,
+    })
+}
+
 fn data_dir_fallback() -> PathBuf {
     dirs::data_dir().unwrap_or_else(|| {
         // Fall back to $HOME/.local/share instead of unexpandable tilde
@@ -288,38 +728,138 @@ fn data_dir_fallback() -> PathBuf {
     })
 }
 
-fn show_history(limit: u32) -> Result<()> {
+/// Read the latest entries from the history database, or `None` if there is
+/// no database yet.
+fn read_history(limit: u32) -> Result<Option<Vec<HistoryEntry>>> {
     let db_path = data_dir_fallback().join("tjvox/history.db");
 
     if !db_path.exists() {
-        println!("No history found.");
-        return Ok(());
+        return Ok(None);
     }
 
     let store = HistoryStore::open(&db_path, 1000)?;
-    let entries = store.list(limit)?;
+    Ok(Some(store.list(limit)?))
+}
+
+fn show_history(entries: Option<Vec<HistoryEntry>>, format: &str) -> Result<()> {
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&entries.unwrap_or_default())?);
+        return Ok(());
+    }
+
+    let Some(entries) = entries else {
+        println!("No history found.");
+        return Ok(());
+    };
 
     if entries.is_empty() {
         println!("No transcription history.");
         return Ok(());
     }
 
-    for entry in &entries {
-        let duration = if entry.duration_ms > 0 {
-            format!("{:.1}s", entry.duration_ms as f64 / 1000.0)
-        } else {
-            "?".to_string()
-        };
-        println!(
-            "[{}] ({}, {}, {}) {}",
-            entry.timestamp, duration, entry.model, entry.language, entry.text
-        );
+    if format == "table" {
+        print!("{}", history::format_table(&entries));
+    } else {
+        print!("{}", history::format_plain(&entries));
     }
 
     println!("\n{} entries shown.", entries.len());
     Ok(())
 }
 
+/// The last of the replacements files, whose rules override the others
+fn personal_replacements(config: &Config) -> Result<PathBuf> {
+    config
+        .replacements
+        .file
+        .personal()
+        .context("replacements.file names no file")
+}
+
+fn reset_replacements(config: &Config, yes: bool) -> Result<()> {
+    let path = personal_replacements(config)?;
+    if path.exists()
+        && !yes
+        && !tjvox::setup::prompt_yes_no(
+            &format!("Replace {} with the default rules?", path.display()),
+            false,
+        )?
+    {
+        println!("Kept.");
+        return Ok(());
+    }
+
+    match replacements::ReplacementEngine::reset(&path)? {
+        Some(backup) => println!(
+            "Replacements reset; previous rules kept in {}.",
+            backup.display()
+        ),
+        None => println!("Default replacements written to {}.", path.display()),
+    }
+    println!("Restart the daemon to use them.");
+    Ok(())
+}
+
+fn edit_replacements(config: &Config) -> Result<()> {
+    let path = personal_replacements(config)?;
+    if !path.exists() {
+        replacements::ReplacementEngine::create_default(&path)?;
+    }
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // Allows editors with arguments, e.g. EDITOR="code --wait"
+    let args = tjvox::hooks::split_command(&editor)?;
+    let Some((program, args)) = args.split_first() else {
+        anyhow::bail!("$EDITOR is empty");
+    };
+    let status = std::process::Command::new(program)
+        .args(args)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("Failed to run editor '{}'", program))?;
+    if !status.success() {
+        anyhow::bail!("Editor exited with {}", status);
+    }
+
+    match replacements::ReplacementEngine::load(&config.replacements.file.paths()) {
+        Ok(_) => println!("Replacements saved. Restart the daemon to use them."),
+        Err(e) => {
+            eprintln!("Warning: {:#}", e);
+            eprintln!(
+                "Run `tjvox replacements-edit` again to fix it, or `tjvox replacements-reset`."
+            );
+        }
+    }
+    Ok(())
+}
+
+fn delete_history_entry(id: i64, yes: bool) -> Result<()> {
+    let db_path = data_dir_fallback().join("tjvox/history.db");
+
+    if !db_path.exists() {
+        println!("No history found.");
+        return Ok(());
+    }
+
+    let store = HistoryStore::open(&db_path, 1000)?;
+    let Some(entry) = store.get(id)? else {
+        println!("No history entry #{}.", id);
+        return Ok(());
+    };
+
+    println!("#{} [{}] {}", entry.id, entry.timestamp, entry.text);
+    if !yes && !tjvox::setup::prompt_yes_no("Delete this entry?", false)? {
+        println!("Kept.");
+        return Ok(());
+    }
+
+    store.delete(id)?;
+    println!("Entry deleted.");
+    Ok(())
+}
+
 fn clear_history() -> Result<()> {
     let db_path = data_dir_fallback().join("tjvox/history.db");
 
@@ -333,3 +873,66 @@ fn clear_history() -> Result<()> {
     println!("History cleared.");
     Ok(())
 }
+
+fn archive_history(path: &Path) -> Result<()> {
+    // The daemon resolves the path itself, so relative paths must not depend on its cwd
+    let path = std::env::current_dir()?.join(path);
+
+    let rt = tokio::runtime::Runtime::new()?;
+    match rt.block_on(socket::send_command(&format!("history-archive {}", path.display()))) {
+        Ok(response) => {
+            println!("{}", response);
+            return Ok(());
+        }
+        Err(_) => {
+            if let DaemonStatus::Running(pid) = pidfile::daemon_status(&pidfile::pid_file_path())? {
+                anyhow::bail!("Daemon (PID {}) is running but its socket is not reachable", pid);
+            }
+        }
+    }
+
+    let db_path = data_dir_fallback().join("tjvox/history.db");
+    if !db_path.exists() {
+        println!("No history to archive.");
+        return Ok(());
+    }
+
+    let store = HistoryStore::open(&db_path, 1000)?;
+    store.archive(&path)?;
+    println!("History archived to {}.", path.display());
+    Ok(())
+}
+
+fn transcribe_with_daemon(path: &Path, json: bool) -> Result<()> {
+    // The daemon only accepts absolute paths
+    let path = std::env::current_dir()?.join(path);
+
+    let rt = tokio::runtime::Runtime::new()?;
+    let response = rt
+        .block_on(socket::send_command(&format!("transcribe {}", path.display())))
+        .context("Failed to reach the daemon; start it with `tjvox daemon`")?;
+    if let Some(error) = response.strip_prefix("error: ") {
+        anyhow::bail!("{}", error);
+    }
+    if json {
+        println!("{}", response);
+    } else {
+        let transcription: serde_json::Value = serde_json::from_str(&response)?;
+        println!("{}", transcription["text"].as_str().unwrap_or_default().trim());
+    }
+    Ok(())
+}
+
+fn vacuum_history() -> Result<()> {
+    let db_path = data_dir_fallback().join("tjvox/history.db");
+
+    if !db_path.exists() {
+        println!("No history to compact.");
+        return Ok(());
+    }
+
+    let store = HistoryStore::open(&db_path, 1000)?;
+    store.vacuum()?;
+    println!("History compacted.");
+    Ok(())
+}
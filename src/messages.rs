@@ -1,4 +1,5 @@
 use crate::config::WhisperMode;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub enum DaemonMsg {
@@ -7,6 +8,19 @@ pub enum DaemonMsg {
     WhisperModeChanged(WhisperMode),
     ModelChanged(String),
     ModelLoading,
+    LlmChanged(bool),
+    /// Transcript accumulated so far while transcription is in progress
+    PartialTranscript(String),
+    /// Transcription progress in percent
+    TranscriptionProgress(u8),
+    /// Overlay explicitly shown or hidden via `show-overlay`/`hide-overlay`
+    OverlayVisible(bool),
+    /// Text of an audio file sent with `GuiMsg::TranscribeFile`, or why it
+    /// could not be transcribed
+    FileTranscribed {
+        path: PathBuf,
+        result: Result<String, String>,
+    },
     Error(String),
 }
 
@@ -15,6 +29,9 @@ pub enum GuiMsg {
     ToggleRecording,
     SetWhisperMode(WhisperMode),
     SetModel(String),
+    SetLlmEnabled(bool),
+    /// Transcribe an audio file dropped on the overlay or picked from the tray
+    TranscribeFile(PathBuf),
     Quit,
 }
 
@@ -0,0 +1,193 @@
+//! Counters kept by the daemon, optionally served in the Prometheus text
+//! format at `http://<metrics.listen>/metrics` (`metrics` build feature).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Stage of a dictation that failed, reported as the `type` label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Recording,
+    Transcription,
+    Output,
+}
+
+impl ErrorKind {
+    const ALL: [ErrorKind; 3] = [
+        ErrorKind::Recording,
+        ErrorKind::Transcription,
+        ErrorKind::Output,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            ErrorKind::Recording => "recording",
+            ErrorKind::Transcription => "transcription",
+            ErrorKind::Output => "output",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    transcriptions: AtomicU64,
+    no_speech: AtomicU64,
+    audio_ms: AtomicU64,
+    transcribe_ms: AtomicU64,
+    errors: [AtomicU64; 3],
+}
+
+impl Metrics {
+    /// Count a finished transcription of `audio_ms` of audio that took `elapsed`.
+    pub fn record_transcription(&self, audio_ms: u64, elapsed: Duration) {
+        self.transcriptions.fetch_add(1, Ordering::Relaxed);
+        self.audio_ms.fetch_add(audio_ms, Ordering::Relaxed);
+        self.transcribe_ms
+            .fetch_add(elapsed.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_no_speech(&self) {
+        self.no_speech.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_error(&self, kind: ErrorKind) {
+        self.errors[kind as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// All counters in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        let audio_ms = load(&self.audio_ms);
+        let transcribe_ms = load(&self.transcribe_ms);
+        let rtf = if audio_ms > 0 {
+            transcribe_ms as f64 / audio_ms as f64
+        } else {
+            0.0
+        };
+
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(String, String)]| {
+            out.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", name, help, name, kind));
+            for (labels, value) in samples {
+                out.push_str(&format!("{}{} {}\n", name, labels, value));
+            }
+        };
+        let single = |value: String| [(String::new(), value)];
+
+        metric(
+            "tjvox_transcriptions_total",
+            "counter",
+            "Recordings transcribed.",
+            &single(load(&self.transcriptions).to_string()),
+        );
+        metric(
+            "tjvox_no_speech_total",
+            "counter",
+            "Transcriptions discarded because no speech was detected.",
+            &single(load(&self.no_speech).to_string()),
+        );
+        metric(
+            "tjvox_audio_seconds_total",
+            "counter",
+            "Seconds of audio transcribed.",
+            &single(format!("{:.3}", audio_ms as f64 / 1000.0)),
+        );
+        metric(
+            "tjvox_transcription_seconds_total",
+            "counter",
+            "Seconds spent transcribing, including model loading.",
+            &single(format!("{:.3}", transcribe_ms as f64 / 1000.0)),
+        );
+        metric(
+            "tjvox_real_time_factor",
+            "gauge",
+            "Transcription time divided by audio time over all transcriptions.",
+            &single(format!("{:.3}", rtf)),
+        );
+        let errors: Vec<(String, String)> = ErrorKind::ALL
+            .iter()
+            .map(|kind| {
+                (
+                    format!("{{type=\"{}\"}}", kind.label()),
+                    load(&self.errors[*kind as usize]).to_string(),
+                )
+            })
+            .collect();
+        metric("tjvox_errors_total", "counter", "Failed dictations by stage.", &errors);
+        out
+    }
+}
+
+/// Serve `metrics` over plain HTTP until the task is dropped. Every request
+/// gets a single response; only `GET /metrics` returns the counters.
+#[cfg(feature = "metrics")]
+pub async fn serve(
+    listen: &str,
+    metrics: std::sync::Arc<Metrics>,
+) -> anyhow::Result<tokio::task::JoinHandle<()>> {
+    use anyhow::Context;
+    use tracing::info;
+
+    let listener = tokio::net::TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to bind metrics endpoint to {}", listen))?;
+    info!("Serving metrics at http://{}/metrics", listen);
+
+    Ok(crate::http::serve(listener, "Metrics endpoint", move |request| {
+        let metrics = metrics.clone();
+        async move { respond(&request, &metrics) }
+    }))
+}
+
+/// HTTP response for a raw request.
+#[cfg(any(feature = "metrics", test))]
+fn respond(request: &[u8], metrics: &Metrics) -> String {
+    let request_line = String::from_utf8_lossy(request);
+    let mut parts = request_line.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", metrics.render()),
+        (Some("GET"), Some(_)) => ("404 Not Found", "Not found\n".to_string()),
+        _ => ("400 Bad Request", "Bad request\n".to_string()),
+    };
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_render() {
+        let metrics = Metrics::default();
+        metrics.record_transcription(4000, Duration::from_millis(1000));
+        metrics.record_transcription(6000, Duration::from_millis(1500));
+        metrics.record_no_speech();
+        metrics.record_error(ErrorKind::Output);
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE tjvox_transcriptions_total counter\n"));
+        assert!(text.contains("tjvox_transcriptions_total 2\n"));
+        assert!(text.contains("tjvox_no_speech_total 1\n"));
+        assert!(text.contains("tjvox_audio_seconds_total 10.000\n"));
+        assert!(text.contains("tjvox_transcription_seconds_total 2.500\n"));
+        assert!(text.contains("tjvox_real_time_factor 0.250\n"));
+        assert!(text.contains("tjvox_errors_total{type=\"recording\"} 0\n"));
+        assert!(text.contains("tjvox_errors_total{type=\"output\"} 1\n"));
+    }
+
+    #[test]
+    fn test_metrics_respond() {
+        let metrics = Metrics::default();
+        let ok = respond(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n", &metrics);
+        assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(ok.ends_with(&metrics.render()));
+
+        assert!(respond(b"GET / HTTP/1.1\r\n\r\n", &metrics).starts_with("HTTP/1.1 404"));
+        assert!(respond(b"POST /metrics HTTP/1.1\r\n\r\n", &metrics).starts_with("HTTP/1.1 400"));
+    }
+}
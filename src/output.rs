@@ -1,13 +1,24 @@
 use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::future::Future;
 use std::io::Read;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use tokio::process::Command;
 use tokio::time::sleep;
 use tracing::{debug, info, warn};
 
-use crate::config::OutputConfig;
+use crate::config::{ClipboardTool, OutputBackend, OutputConfig};
 use crate::error::TjvoxError;
 
+/// Pause between clipboard attempts, giving a busy compositor time to recover
+const CLIPBOARD_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// How long the focused window's class is reused before asking again; one
+/// output checks it several times (sensitive apps, Shift+Enter, terminal)
+const WINDOW_CLASS_TTL: Duration = Duration::from_millis(200);
+
 /// Known terminal emulator resource class patterns (lowercase).
 /// Matched as substrings against the active window's resourceClass.
 const TERMINAL_PATTERNS: &[&str] = &[
@@ -36,92 +47,568 @@ const TERMINAL_PATTERNS: &[&str] = &[
     "rio",
 ];
 
+/// The output method that was actually used for a given piece of text.
+/// In `auto` mode this reflects the terminal/GUI decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMethod {
+    /// Clipboard + Ctrl+V
+    Paste,
+    /// Clipboard + Ctrl+Shift+V (terminal paste shortcut)
+    TerminalPaste,
+    /// Direct typing via the configured keystroke backend
+    Type,
+    /// Clipboard only, no keystroke
+    Clipboard,
+    /// Clipboard only; the caller prompts the user to paste manually
+    ClipboardNotify,
+    /// Primary selection + middle click, leaving the clipboard alone
+    Primary,
+    /// Appended to a file
+    File,
+    /// Printed to standard output
+    Stdout,
+}
+
+impl std::fmt::Display for OutputMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputMethod::Paste => write!(f, "paste"),
+            OutputMethod::TerminalPaste => write!(f, "terminal-paste"),
+            OutputMethod::Type => write!(f, "type"),
+            OutputMethod::Clipboard => write!(f, "clipboard"),
+            OutputMethod::ClipboardNotify => write!(f, "clipboard-notify"),
+            OutputMethod::Primary => write!(f, "primary"),
+            OutputMethod::File => write!(f, "file"),
+            OutputMethod::Stdout => write!(f, "stdout"),
+        }
+    }
+}
+
+/// Output methods accepted by `output.method` and as sinks.
+const METHODS: &[&str] = &["auto", "paste", "type", "clipboard", "clipboard-notify", "primary"];
+
+/// One destination for transcribed text, configured in `output.sinks`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputSink {
+    /// Deliver to the focused app with one of the output methods
+    Method(String),
+    /// Append the text and a newline to a file (`file:/path/log.txt`)
+    File(PathBuf),
+    /// Print the text to standard output
+    Stdout,
+}
+
+impl std::fmt::Display for OutputSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputSink::Method(method) => write!(f, "{}", method),
+            OutputSink::File(path) => write!(f, "file:{}", path.display()),
+            OutputSink::Stdout => write!(f, "stdout"),
+        }
+    }
+}
+
+/// Parse a sink such as `"type"`, `"stdout"` or `"file:/path/log.txt"`.
+pub fn parse_sink(sink: &str) -> Result<OutputSink, TjvoxError> {
+    let sink = sink.trim();
+    if let Some(path) = sink.strip_prefix("file:") {
+        if path.trim().is_empty() {
+            return Err(TjvoxError::Output("file sink needs a path".to_string()));
+        }
+        return Ok(OutputSink::File(PathBuf::from(path.trim())));
+    }
+    if sink == "stdout" {
+        return Ok(OutputSink::Stdout);
+    }
+    if METHODS.contains(&sink) {
+        return Ok(OutputSink::Method(sink.to_string()));
+    }
+    Err(TjvoxError::Output(format!("unknown output sink '{}'", sink)))
+}
+
+/// A key usable in `output.paste_keys` and voice commands: its evdev code
+/// for ydotool and its wtype name (a modifier for `-M`/`-m`, otherwise a keysym for `-k`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasteKey {
+    code: u16,
+    wtype_name: String,
+    modifier: bool,
+}
+
+/// Named keys accepted in a key sequence besides letters: name, evdev code,
+/// wtype name, whether it is a modifier
+const NAMED_PASTE_KEYS: &[(&str, u16, &str, bool)] = &[
+    ("ctrl", 29, "ctrl", true),
+    ("control", 29, "ctrl", true),
+    ("shift", 42, "shift", true),
+    ("alt", 56, "alt", true),
+    ("super", 125, "logo", true),
+    ("insert", 110, "Insert", false),
+    ("enter", 28, "Return", false),
+    ("tab", 15, "Tab", false),
+    ("space", 57, "space", false),
+    ("escape", 1, "Escape", false),
+    ("backspace", 14, "BackSpace", false),
+    ("delete", 111, "Delete", false),
+    ("home", 102, "Home", false),
+    ("end", 107, "End", false),
+    ("pageup", 104, "Prior", false),
+    ("pagedown", 109, "Next", false),
+    ("up", 103, "Up", false),
+    ("down", 108, "Down", false),
+    ("left", 105, "Left", false),
+    ("right", 106, "Right", false),
+];
+
+/// evdev code of a letter key, from the rows of a US keyboard
+fn letter_code(letter: char) -> Option<u16> {
+    ["qwertyuiop", "asdfghjkl", "zxcvbnm"]
+        .iter()
+        .zip([16u16, 30, 44])
+        .find_map(|(row, first)| row.find(letter).map(|i| first + i as u16))
+}
+
+fn paste_key(name: &str) -> Option<PasteKey> {
+    let name = name.to_lowercase();
+    if let Some(&(_, code, wtype_name, modifier)) =
+        NAMED_PASTE_KEYS.iter().find(|(known, ..)| *known == name)
+    {
+        return Some(PasteKey { code, wtype_name: wtype_name.to_string(), modifier });
+    }
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) => letter_code(letter).map(|code| PasteKey {
+            code,
+            wtype_name: name.clone(),
+            modifier: false,
+        }),
+        _ => None,
+    }
+}
+
+/// One chord of a key sequence, e.g. `ctrl+v`: modifiers, then the key
+pub type PasteChord = Vec<PasteKey>;
+
+/// Parse a paste sequence such as `"ctrl+v"`, `"shift+insert"` or
+/// `"ctrl+v ctrl+v"`: space-separated chords of `+`-joined keys, each chord
+/// ending in exactly one non-modifier key.
+pub fn parse_paste_keys(seq: &str) -> Result<Vec<PasteChord>, TjvoxError> {
+    let chords = seq
+        .split_whitespace()
+        .map(|chord| {
+            let keys = chord
+                .split('+')
+                .map(|name| {
+                    paste_key(name).ok_or_else(|| {
+                        TjvoxError::Output(format!("unknown key '{}' in key sequence", name))
+                    })
+                })
+                .collect::<Result<PasteChord, _>>()?;
+            match keys.split_last() {
+                Some((last, mods)) if !last.modifier && mods.iter().all(|k| k.modifier) => Ok(keys),
+                _ => Err(TjvoxError::Output(format!(
+                    "key chord '{}' must be modifiers followed by one key",
+                    chord
+                ))),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if chords.is_empty() {
+        return Err(TjvoxError::Output("key sequence cannot be empty".to_string()));
+    }
+    Ok(chords)
+}
+
+/// `ydotool key` arguments pressing each chord in turn
+fn ydotool_key_args(chords: &[PasteChord]) -> Vec<String> {
+    let mut args = vec!["key".to_string()];
+    for chord in chords {
+        args.extend(chord.iter().map(|k| format!("{}:1", k.code)));
+        args.extend(chord.iter().rev().map(|k| format!("{}:0", k.code)));
+    }
+    args
+}
+
+/// `wtype` arguments pressing each chord in turn
+fn wtype_key_args(chords: &[PasteChord]) -> Vec<String> {
+    let mut args = Vec::new();
+    for chord in chords {
+        for key in chord {
+            let flag = if key.modifier { "-M" } else { "-k" };
+            args.extend([flag.to_string(), key.wtype_name.clone()]);
+        }
+        for key in chord.iter().rev().filter(|k| k.modifier) {
+            args.extend(["-m".to_string(), key.wtype_name.clone()]);
+        }
+    }
+    args
+}
+
+/// How newlines reach a window, chosen per window class by
+/// `output.newline_strategy`. Editors and terminals may auto-indent, be in
+/// a normal mode, or run a typed newline as a command, so this is a
+/// heuristic: the class is that of the window, not of what runs inside it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NewlineStrategy {
+    /// Output newlines as they are, even in `shift_enter_apps`
+    Keep,
+    /// Replace line breaks with a single space
+    Join,
+    /// Drop line breaks
+    Remove,
+    /// Type each line and press these keys between them, e.g. `escape o`
+    /// to open a new line from Vim's normal mode
+    Keys(Vec<PasteChord>),
+}
+
+impl NewlineStrategy {
+    /// Parse `"keep"`, `"join"`, `"remove"` or a key sequence.
+    pub fn parse(value: &str) -> Result<Self, TjvoxError> {
+        match value.trim() {
+            "keep" => Ok(Self::Keep),
+            "join" => Ok(Self::Join),
+            "remove" => Ok(Self::Remove),
+            keys => parse_paste_keys(keys).map(Self::Keys),
+        }
+    }
+}
+
+/// Why `OutputManager` types text directly instead of using the output method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TypingOverride {
+    /// Multi-line text for a window in `shift_enter_apps`
+    ShiftEnter,
+    /// `output.sensitive` or a window in `sensitive_apps`
+    Sensitive,
+    /// `auto` or `paste` into a window in `type_apps`
+    TypeApps,
+}
+
 pub struct OutputManager {
     config: OutputConfig,
+    sinks: Vec<OutputSink>,
+    /// Replaces Ctrl+V in `paste` output when `output.paste_keys` is set
+    paste_keys: Option<Vec<PasteChord>>,
+    /// Window class patterns and how newlines are output there
+    newline_strategies: Vec<(String, NewlineStrategy)>,
+    /// Focused window class, cached for this manager only so one dictation
+    /// never sees the window of an earlier one
+    window: WindowClassCache,
 }
 
 impl OutputManager {
     pub fn new(config: &OutputConfig) -> Result<Self> {
+        // Without explicit sinks the single `method` is the only destination
+        let sinks = if config.sinks.is_empty() {
+            vec![OutputSink::Method(config.method.clone())]
+        } else {
+            config
+                .sinks
+                .iter()
+                .map(|s| parse_sink(s))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        let paste_keys = config.paste_keys.as_deref().map(parse_paste_keys).transpose()?;
+        let newline_strategies = config
+            .newline_strategy
+            .iter()
+            .map(|(class, strategy)| Ok((class.clone(), NewlineStrategy::parse(strategy)?)))
+            .collect::<Result<Vec<_>, TjvoxError>>()?;
+
         Ok(Self {
             config: config.clone(),
+            sinks,
+            paste_keys,
+            newline_strategies,
+            window: WindowClassCache::new(Box::new(KWinWindowSource), WINDOW_CLASS_TTL),
         })
     }
 
-    pub async fn type_text(&self, text: &str) -> Result<()> {
+    /// Truncate text to `max_output_chars`, send it to every configured sink
+    /// in order and return the method used for the focused app (or the first
+    /// sink if none targets an app).
+    /// A failing sink does not stop the others; its error is returned after
+    /// all sinks have run.
+    pub async fn type_text(&self, text: &str) -> Result<OutputMethod> {
+        let truncated = self
+            .config
+            .max_output_chars
+            .and_then(|max| truncate_output(text, max, self.config.truncation_marker));
+        let text = match truncated {
+            Some(ref short) => {
+                warn!(
+                    "Output truncated from {} to {} characters",
+                    text.chars().count(),
+                    self.config.max_output_chars.unwrap_or_default()
+                );
+                short.as_str()
+            }
+            None => text,
+        };
+
+        let mut used = Vec::new();
+        let mut first_error = None;
+
+        for sink in &self.sinks {
+            let result = match sink {
+                OutputSink::Method(method) => self.output_with_method(text, method).await,
+                OutputSink::File(path) => append_to_file(path, text)
+                    .await
+                    .map(|_| OutputMethod::File),
+                OutputSink::Stdout => {
+                    println!("{}", text);
+                    Ok(OutputMethod::Stdout)
+                }
+            };
+            match result {
+                Ok(method) => used.push(method),
+                Err(e) => {
+                    warn!("Output sink '{}' failed: {}", sink, e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        if let Some(e) = first_error {
+            return Err(e);
+        }
+        Ok(primary_method(&used))
+    }
+
+    /// Output text to the focused app using `method` and return the method
+    /// that was actually used.
+    async fn output_with_method(&self, text: &str, method: &str) -> Result<OutputMethod> {
         info!("Outputting {} characters", text.len());
 
         // Small delay before output
         sleep(Duration::from_millis(self.config.delay_ms)).await;
 
-        match self.config.method.as_str() {
+        // Clipboard-only methods never type, so the focused window's newline
+        // strategy does not apply to them
+        let rewritten;
+        let mut text = text;
+        let strategy = if enters_text(method) {
+            self.newline_strategy(text).await
+        } else {
+            None
+        };
+        match strategy {
+            Some(NewlineStrategy::Keys(chords)) => {
+                info!("Typing newlines as the configured keys for this window");
+                self.type_lines(text, chords).await?;
+                info!("Text output successfully via {}", OutputMethod::Type);
+                return Ok(OutputMethod::Type);
+            }
+            Some(strategy) => {
+                if let Some(joined) = rewrite_newlines(text, strategy) {
+                    rewritten = joined;
+                    text = rewritten.as_str();
+                }
+            }
+            None => {}
+        }
+
+        if let Some(reason) = self.typing_override(text, method, strategy).await {
+            match reason {
+                TypingOverride::ShiftEnter => {
+                    info!("Chat window detected, typing newlines as Shift+Enter");
+                    self.type_direct_shift_enter(text).await?;
+                }
+                TypingOverride::Sensitive => {
+                    info!("Sensitive target, typing directly without touching the clipboard");
+                    self.type_direct(text).await?;
+                }
+                TypingOverride::TypeApps => {
+                    info!("Window is listed in type_apps, typing instead of pasting");
+                    self.type_direct(text).await?;
+                }
+            }
+            info!("Text output successfully via {}", OutputMethod::Type);
+            return Ok(OutputMethod::Type);
+        }
+
+        let method = match method {
             "auto" => self.auto_output(text).await?,
-            "paste" => self.paste_text(text).await?,
-            "type" => self.type_with_ydotool(text).await?,
-            "clipboard" => self.clipboard_only(text).await?,
+            "paste" => {
+                self.paste_text(text).await?;
+                OutputMethod::Paste
+            }
+            "type" => {
+                self.type_direct(text).await?;
+                OutputMethod::Type
+            }
+            "clipboard" => {
+                self.clipboard_only(text).await?;
+                OutputMethod::Clipboard
+            }
+            "clipboard-notify" => {
+                self.clipboard_only(text).await?;
+                OutputMethod::ClipboardNotify
+            }
+            "primary" => {
+                self.paste_primary(text).await?;
+                OutputMethod::Primary
+            }
             other => {
                 warn!("Unknown output method '{}', falling back to auto", other);
-                self.auto_output(text).await?;
+                self.auto_output(text).await?
+            }
+        };
+
+        info!("Text output successfully via {}", method);
+        Ok(method)
+    }
+
+    /// Why text for `method` is typed directly instead of going through the
+    /// method, if it is. `strategy` is the window's newline strategy.
+    async fn typing_override(
+        &self,
+        text: &str,
+        method: &str,
+        strategy: Option<&NewlineStrategy>,
+    ) -> Option<TypingOverride> {
+        let enters_text = enters_text(method);
+        if enters_text && strategy.is_none() && self.is_shift_enter_target(text).await {
+            return Some(TypingOverride::ShiftEnter);
+        }
+        // Every method, including the clipboard-only ones: a secret must never
+        // reach the clipboard or primary selection
+        if self.is_sensitive_target().await {
+            return Some(TypingOverride::Sensitive);
+        }
+        if matches!(method, "auto" | "paste") && !self.config.type_apps.is_empty() {
+            let class = self.window.active_window_class().await;
+            if prefers_typing(class.as_deref(), &self.config.type_apps) {
+                return Some(TypingOverride::TypeApps);
             }
         }
+        None
+    }
+
+    /// Whether the text must bypass the clipboard, either because sensitive
+    /// mode is enabled globally or the focused window matches `sensitive_apps`.
+    async fn is_sensitive_target(&self) -> bool {
+        if self.config.sensitive {
+            return true;
+        }
+        if self.config.sensitive_apps.is_empty() {
+            return false;
+        }
+        match self.window.active_window_class().await {
+            Some(class) => class_matches(&class, &self.config.sensitive_apps),
+            None => false,
+        }
+    }
+
+    /// The `output.newline_strategy` of the focused window, if `text` has
+    /// newlines and a pattern matches.
+    async fn newline_strategy(&self, text: &str) -> Option<&NewlineStrategy> {
+        if !text.contains('\n') || self.newline_strategies.is_empty() {
+            return None;
+        }
+        let class = self.window.active_window_class().await;
+        newline_strategy_for(class.as_deref(), &self.newline_strategies)
+    }
 
-        info!("Text output successfully");
+    /// Type `text` line by line, pressing `chords` instead of each newline.
+    async fn type_lines(&self, text: &str, chords: &[PasteChord]) -> Result<()> {
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.press_keys(chords).await?;
+            }
+            if !line.is_empty() {
+                self.type_direct(line).await?;
+            }
+        }
         Ok(())
     }
 
+    /// Whether multi-line text goes to a window where Enter would submit it.
+    async fn is_shift_enter_target(&self, text: &str) -> bool {
+        if !text.contains('\n') || self.config.shift_enter_apps.is_empty() {
+            return false;
+        }
+        let class = self.window.active_window_class().await;
+        needs_shift_enter(text, class.as_deref(), &self.config.shift_enter_apps)
+    }
+
     /// Smart output: detect active window type and choose the best method.
     /// Terminals get clipboard + Ctrl+Shift+V (terminal paste shortcut).
     /// GUI apps get clipboard + Ctrl+V (standard paste).
-    async fn auto_output(&self, text: &str) -> Result<()> {
-        let is_terminal = detect_terminal_focused().await;
+    async fn auto_output(&self, text: &str) -> Result<OutputMethod> {
+        let is_terminal = self.detect_terminal_focused().await;
 
         if is_terminal {
             info!("Terminal detected, using clipboard + Ctrl+Shift+V");
-            self.paste_text_terminal(text).await
+            self.paste_text_terminal(text).await?;
+            Ok(OutputMethod::TerminalPaste)
         } else {
             debug!("GUI window detected, using clipboard + Ctrl+V");
-            self.paste_text(text).await
+            self.paste_text(text).await?;
+            Ok(OutputMethod::Paste)
+        }
+    }
+
+    /// Detect if the currently focused window is a terminal emulator.
+    /// Returns false if detection fails (safe default: use clipboard paste).
+    async fn detect_terminal_focused(&self) -> bool {
+        let Some(class) = self.window.active_window_class().await else {
+            return false;
+        };
+
+        let is_terminal = is_terminal_class(&class);
+        debug!("Active window resourceClass='{}', terminal={}", class, is_terminal);
+        is_terminal
+    }
+
+    /// Clipboard content to put back after pasting; `None` when it cannot be
+    /// read or `output.keep_on_clipboard` leaves the transcription there.
+    async fn save_clipboard(&self) -> Option<String> {
+        if self.config.keep_on_clipboard {
+            return None;
         }
+        get_clipboard(&self.config.clipboard_tools, self.config.clipboard_retries)
+            .await
+            .ok()
+    }
+
+    /// Put `saved` back on the clipboard once the paste has had time to land.
+    fn restore_clipboard_later(&self, saved: Option<String>) {
+        let Some(original) = saved else {
+            return;
+        };
+        let delay_ms = self.config.paste_delay_ms.max(2000);
+        let retries = self.config.clipboard_retries;
+        let tools = self.config.clipboard_tools.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(delay_ms)).await;
+            if let Err(e) = set_clipboard(&original, &tools, retries).await {
+                debug!("Failed to restore clipboard: {}", e);
+            }
+        });
     }
 
     /// Set clipboard then simulate Ctrl+Shift+V paste (terminal paste shortcut).
     /// Works in Ghostty, Konsole, Kitty, Alacritty, WezTerm, and most terminals.
     async fn paste_text_terminal(&self, text: &str) -> Result<()> {
         // Save current clipboard content
-        let saved_clipboard = get_clipboard().await.ok();
+        let saved_clipboard = self.save_clipboard().await;
 
         // Set clipboard to transcribed text
-        set_clipboard(text).await?;
+        set_clipboard(text, &self.config.clipboard_tools, self.config.clipboard_retries).await?;
 
         // Brief delay to let clipboard settle
         sleep(Duration::from_millis(self.config.paste_delay_ms)).await;
 
-        // Simulate Ctrl+Shift+V via ydotool
-        // 29 = KEY_LEFTCTRL, 42 = KEY_LEFTSHIFT, 47 = KEY_V
-        self.ensure_ydotoold().await?;
-        let output = Command::new("ydotool")
-            .args(["key", "29:1", "42:1", "47:1", "47:0", "42:0", "29:0"])
-            .output()
-            .await
-            .map_err(|e| TjvoxError::Output(format!("ydotool key failed: {}", e)))?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(
-                TjvoxError::Output(format!("ydotool Ctrl+Shift+V failed: {}", stderr))
-                    .into(),
-            );
+        match self.config.backend {
+            OutputBackend::Ydotool => self.send_terminal_paste_keystroke_ydotool().await?,
+            OutputBackend::VirtualKeyboard => {
+                self.send_terminal_paste_keystroke_wtype().await?
+            }
         }
 
         // Restore original clipboard after a delay
-        if let Some(original) = saved_clipboard {
-            let delay_ms = self.config.paste_delay_ms.max(2000);
-            tokio::spawn(async move {
-                sleep(Duration::from_millis(delay_ms)).await;
-                if let Err(e) = set_clipboard(&original).await {
-                    debug!("Failed to restore clipboard: {}", e);
-                }
-            });
-        }
+        self.restore_clipboard_later(saved_clipboard);
 
         Ok(())
     }
@@ -129,42 +616,123 @@ impl OutputManager {
     /// Set clipboard then simulate Ctrl+V paste.
     async fn paste_text(&self, text: &str) -> Result<()> {
         // Save current clipboard content
-        let saved_clipboard = get_clipboard().await.ok();
+        let saved_clipboard = self.save_clipboard().await;
 
         // Set clipboard to transcribed text
-        set_clipboard(text).await?;
+        set_clipboard(text, &self.config.clipboard_tools, self.config.clipboard_retries).await?;
 
         // Brief delay to let clipboard settle
         sleep(Duration::from_millis(self.config.paste_delay_ms)).await;
 
-        // Simulate Ctrl+V — try ydotool first (works on KDE Plasma Wayland),
-        // fall back to wtype
-        if let Err(e) = self.send_paste_keystroke_ydotool().await {
-            debug!("ydotool paste failed ({}), trying wtype", e);
-            self.send_paste_keystroke_wtype().await?;
+        // Simulate Ctrl+V — with the ydotool backend try ydotool first (works on
+        // KDE Plasma Wayland) and fall back to wtype
+        match self.config.backend {
+            _ if self.paste_keys.is_some() => {
+                self.press_keys(self.paste_keys.as_deref().unwrap_or_default()).await?
+            }
+            OutputBackend::Ydotool => {
+                if let Err(e) = self.send_paste_keystroke_ydotool().await {
+                    debug!("ydotool paste failed ({}), trying wtype", e);
+                    self.send_paste_keystroke_wtype().await?;
+                }
+            }
+            OutputBackend::VirtualKeyboard => self.send_paste_keystroke_wtype().await?,
         }
 
         // Restore original clipboard after a delay
-        if let Some(original) = saved_clipboard {
-            let delay_ms = self.config.paste_delay_ms.max(2000);
-            tokio::spawn(async move {
-                sleep(Duration::from_millis(delay_ms)).await;
-                if let Err(e) = set_clipboard(&original).await {
-                    debug!("Failed to restore clipboard: {}", e);
-                }
-            });
-        }
+        self.restore_clipboard_later(saved_clipboard);
+
+        Ok(())
+    }
+
+    /// Paste through the primary selection, which clipboard managers do not
+    /// record by default. The ydotool backend pastes with a middle click (at
+    /// the mouse pointer in some apps); wtype cannot click and presses
+    /// Shift+Insert, which pastes the primary selection in terminals and
+    /// X11 apps.
+    async fn paste_primary(&self, text: &str) -> Result<()> {
+        set_primary_selection(text, &self.config.clipboard_tools, self.config.clipboard_retries)
+            .await?;
+        sleep(Duration::from_millis(self.config.paste_delay_ms)).await;
 
+        let output = match self.config.backend {
+            OutputBackend::Ydotool => {
+                self.ensure_ydotoold().await?;
+                // 0xC2 = middle button (0x02), pressed (0x40) and released (0x80)
+                Command::new("ydotool")
+                    .args(["click", "0xC2"])
+                    .output()
+                    .await
+                    .map_err(|e| TjvoxError::Output(format!("ydotool click failed: {}", e)))?
+            }
+            OutputBackend::VirtualKeyboard => Command::new("wtype")
+                .args(["-M", "shift", "-k", "Insert", "-m", "shift"])
+                .output()
+                .await
+                .map_err(|e| TjvoxError::Output(format!("wtype failed: {}", e)))?,
+        };
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(
+                TjvoxError::Output(format!("Primary selection paste failed: {}", stderr)).into(),
+            );
+        }
         Ok(())
     }
 
     /// Just set the clipboard, don't paste. User can Ctrl+V manually.
     async fn clipboard_only(&self, text: &str) -> Result<()> {
-        set_clipboard(text).await?;
+        set_clipboard(text, &self.config.clipboard_tools, self.config.clipboard_retries).await?;
         info!("Text copied to clipboard (use Ctrl+V to paste)");
         Ok(())
     }
 
+    /// Ctrl+Shift+V via ydotool
+    async fn send_terminal_paste_keystroke_ydotool(&self) -> Result<()> {
+        // 29 = KEY_LEFTCTRL, 42 = KEY_LEFTSHIFT, 47 = KEY_V
+        self.ensure_ydotoold().await?;
+        let output = Command::new("ydotool")
+            .args(["key", "29:1", "42:1", "47:1", "47:0", "42:0", "29:0"])
+            .output()
+            .await
+            .map_err(|e| TjvoxError::Output(format!("ydotool key failed: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(
+                TjvoxError::Output(format!("ydotool Ctrl+Shift+V failed: {}", stderr))
+                    .into(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Ctrl+Shift+V via wtype (virtual-keyboard-v1 protocol)
+    async fn send_terminal_paste_keystroke_wtype(&self) -> Result<()> {
+        if which::which("wtype").is_err() {
+            return Err(TjvoxError::Output(
+                "wtype not found (required by the virtual_keyboard backend)".to_string(),
+            )
+            .into());
+        }
+
+        let output = Command::new("wtype")
+            .args(["-M", "ctrl", "-M", "shift", "-k", "v", "-m", "shift", "-m", "ctrl"])
+            .output()
+            .await
+            .map_err(|e| TjvoxError::Output(format!("wtype failed: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(
+                TjvoxError::Output(format!("wtype Ctrl+Shift+V failed: {}", stderr)).into(),
+            );
+        }
+
+        Ok(())
+    }
+
     /// Ctrl+V via ydotool (works on KDE Plasma Wayland)
     async fn send_paste_keystroke_ydotool(&self) -> Result<()> {
         if which::which("ydotool").is_err() {
@@ -215,16 +783,147 @@ impl OutputManager {
         Ok(())
     }
 
+    /// Press a key sequence such as `output.paste_keys` or a voice command,
+    /// using the same backend order as the default paste.
+    pub async fn press_keys(&self, chords: &[PasteChord]) -> Result<()> {
+        if self.config.backend == OutputBackend::Ydotool && which::which("ydotool").is_ok() {
+            self.ensure_ydotoold().await?;
+            let output = Command::new("ydotool")
+                .args(ydotool_key_args(chords))
+                .output()
+                .await
+                .map_err(|e| TjvoxError::Output(format!("ydotool key failed: {}", e)))?;
+            if output.status.success() {
+                return Ok(());
+            }
+            debug!(
+                "ydotool key failed ({}), trying wtype",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        if which::which("wtype").is_err() {
+            return Err(TjvoxError::Output(
+                "Neither ydotool nor wtype available for keystrokes".to_string(),
+            )
+            .into());
+        }
+        let output = Command::new("wtype")
+            .args(wtype_key_args(chords))
+            .output()
+            .await
+            .map_err(|e| TjvoxError::Output(format!("wtype failed: {}", e)))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(TjvoxError::Output(format!("wtype key failed: {}", stderr)).into());
+        }
+        Ok(())
+    }
+
     async fn ensure_ydotoold(&self) -> Result<()> {
         if !is_process_running("ydotoold").await {
             warn!("ydotoold not running, attempting to start...");
-            let _ = Command::new("ydotoold").spawn()?;
+            let args = ydotoold_args(&self.config)?;
+            let _ = Command::new(&args[0]).args(&args[1..]).spawn()?;
             sleep(Duration::from_millis(500)).await;
         }
 
         Ok(())
     }
 
+    /// Type text directly (no clipboard) using the configured backend.
+    async fn type_direct(&self, text: &str) -> Result<()> {
+        match self.config.backend {
+            OutputBackend::Ydotool => self.type_with_ydotool(text).await,
+            OutputBackend::VirtualKeyboard => self.type_with_wtype(text).await,
+        }
+    }
+
+    /// Type text directly, pressing Shift+Enter for each newline so chat
+    /// apps insert a line break instead of sending the message.
+    async fn type_direct_shift_enter(&self, text: &str) -> Result<()> {
+        match self.config.backend {
+            OutputBackend::Ydotool => {
+                self.ensure_ydotoold().await?;
+                let text = text.to_string();
+                tokio::task::spawn_blocking(move || {
+                    let vk = crate::input::VirtualKeyboard::new()?.with_shift_enter(true);
+                    vk.type_text(&text)
+                })
+                .await?
+            }
+            OutputBackend::VirtualKeyboard => {
+                for (i, line) in text.split('\n').enumerate() {
+                    if i > 0 {
+                        self.send_shift_enter_wtype().await?;
+                    }
+                    if !line.is_empty() {
+                        self.type_with_wtype(line).await?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Shift+Enter via wtype
+    async fn send_shift_enter_wtype(&self) -> Result<()> {
+        let output = Command::new("wtype")
+            .args(["-M", "shift", "-k", "Return", "-m", "shift"])
+            .output()
+            .await
+            .map_err(|e| TjvoxError::Output(format!("wtype failed: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(
+                TjvoxError::Output(format!("wtype Shift+Enter failed: {}", stderr)).into(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Type text through the Wayland virtual-keyboard protocol using wtype.
+    /// wtype uploads its own keymap, so no ydotoold or layout detection is needed.
+    /// Text is passed on stdin so it never shows up in the process list.
+    async fn type_with_wtype(&self, text: &str) -> Result<()> {
+        if which::which("wtype").is_err() {
+            return Err(TjvoxError::Output(
+                "wtype not found (required by the virtual_keyboard backend)".to_string(),
+            )
+            .into());
+        }
+
+        let mut child = Command::new("wtype")
+            .arg("-")
+            .stdin(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| TjvoxError::Output(format!("Failed to run wtype: {}", e)))?;
+
+        if let Some(ref mut stdin) = child.stdin {
+            use tokio::io::AsyncWriteExt;
+            stdin
+                .write_all(text.as_bytes())
+                .await
+                .map_err(|e| TjvoxError::Output(format!("Failed to write to wtype: {}", e)))?;
+        }
+        drop(child.stdin.take());
+
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|e| TjvoxError::Output(format!("wtype failed: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(TjvoxError::Output(format!("wtype type failed: {}", stderr)).into());
+        }
+
+        Ok(())
+    }
+
     /// Type text using native VirtualKeyboard (xkbcommon + ydotoold socket).
     /// Respects the user's keyboard layout for correct character mapping.
     async fn type_with_ydotool(&self, text: &str) -> Result<()> {
@@ -239,8 +938,111 @@ impl OutputManager {
     }
 }
 
-async fn is_process_running(name: &str) -> bool {
-    let name = name.to_string();
+/// The method to report for a multi-sink output: the first one that
+/// reached the focused app, otherwise the first sink's.
+fn primary_method(used: &[OutputMethod]) -> OutputMethod {
+    used.iter()
+        .copied()
+        .find(|m| !matches!(m, OutputMethod::File | OutputMethod::Stdout))
+        .or_else(|| used.first().copied())
+        .unwrap_or(OutputMethod::Type)
+}
+
+/// Append `text` and a newline to `path`, creating the file and its
+/// directory if needed.
+pub(crate) async fn append_to_file(path: &std::path::Path, text: &str) -> Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+    }
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(|e| TjvoxError::Output(format!("Failed to open {}: {}", path.display(), e)))?;
+    file.write_all(format!("{}\n", text).as_bytes()).await?;
+    file.flush().await?;
+    Ok(())
+}
+
+/// Marker appended to output that was cut short by `max_output_chars`.
+const TRUNCATION_MARKER: &str = "… [truncated]";
+
+/// Truncate `text` to at most `max_chars` characters, optionally followed by
+/// a marker. Returns None if the text already fits.
+fn truncate_output(text: &str, max_chars: usize, marker: bool) -> Option<String> {
+    let (cut, _) = text.char_indices().nth(max_chars)?;
+    let mut result = text[..cut].trim_end().to_string();
+    if marker {
+        result.push(' ');
+        result.push_str(TRUNCATION_MARKER);
+    }
+    Some(result)
+}
+
+/// The command that starts ydotoold: `output.ydotoold_cmd`, or plain `ydotoold`.
+fn ydotoold_args(config: &OutputConfig) -> Result<Vec<String>> {
+    let Some(ref cmd) = config.ydotoold_cmd else {
+        return Ok(vec!["ydotoold".to_string()]);
+    };
+    let args = crate::hooks::split_command(cmd)
+        .map_err(|e| TjvoxError::Output(format!("Invalid ydotoold_cmd: {}", e)))?;
+    if args.is_empty() {
+        return Err(TjvoxError::Output("ydotoold_cmd is empty".to_string()).into());
+    }
+    Ok(args)
+}
+
+/// How long ydotoold gets to exit after SIGTERM before it is killed
+const YDOTOOLD_STOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// ydotoold started by the daemon (`output.manage_ydotoold`) and stopped
+/// with it, so no orphaned input daemon is left behind.
+pub struct ManagedYdotoold {
+    child: tokio::process::Child,
+}
+
+impl ManagedYdotoold {
+    /// Start ydotoold, unless one is already running (for example as a
+    /// system service), in which case it is left alone.
+    pub async fn start(config: &OutputConfig) -> Result<Option<Self>> {
+        if is_process_running("ydotoold").await {
+            info!("ydotoold is already running, not managing it");
+            return Ok(None);
+        }
+        let args = ydotoold_args(config)?;
+        let child = Command::new(&args[0])
+            .args(&args[1..])
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| TjvoxError::Output(format!("Failed to start '{}': {}", args[0], e)))?;
+        info!("Started ydotoold (PID {})", child.id().unwrap_or_default());
+        Ok(Some(Self { child }))
+    }
+
+    /// Ask ydotoold to exit, killing it if it does not within a few seconds.
+    pub async fn stop(mut self) {
+        if let Some(pid) = self.child.id() {
+            unsafe {
+                libc::kill(pid as i32, libc::SIGTERM);
+            }
+        }
+        match tokio::time::timeout(YDOTOOLD_STOP_TIMEOUT, self.child.wait()).await {
+            Ok(_) => info!("Stopped ydotoold"),
+            Err(_) => {
+                warn!("ydotoold did not exit, killing it");
+                let _ = self.child.kill().await;
+            }
+        }
+    }
+}
+
+async fn is_process_running(name: &str) -> bool {
+    let name = name.to_string();
     tokio::task::spawn_blocking(move || {
         let proc_dir = match std::fs::read_dir("/proc") {
             Ok(dir) => dir,
@@ -271,10 +1073,61 @@ async fn is_process_running(name: &str) -> bool {
     .unwrap_or(false)
 }
 
-/// Detect if the currently focused window is a terminal emulator.
-/// Uses KDE's KWin D-Bus API to query the active window's resourceClass.
-/// Returns false if detection fails (safe default: use clipboard paste).
-async fn detect_terminal_focused() -> bool {
+/// Finds the class (lowercased) of the focused window. Desktops other than
+/// KDE can add their own source and share `WindowClassCache`.
+#[async_trait]
+pub trait WindowClassSource: Send + Sync {
+    /// `None` if the query fails or the class is empty.
+    async fn active_window_class(&self) -> Option<String>;
+}
+
+/// Reuses the last answer of a `WindowClassSource` for `ttl`, so the checks
+/// made for one output do not each spawn a query.
+pub struct WindowClassCache {
+    source: Box<dyn WindowClassSource>,
+    ttl: Duration,
+    last: std::sync::Mutex<Option<(Instant, Option<String>)>>,
+}
+
+impl WindowClassCache {
+    pub fn new(source: Box<dyn WindowClassSource>, ttl: Duration) -> Self {
+        Self {
+            source,
+            ttl,
+            last: std::sync::Mutex::new(None),
+        }
+    }
+
+    pub async fn active_window_class(&self) -> Option<String> {
+        if let Some(class) = self.cached(Instant::now()) {
+            return class;
+        }
+        let class = self.source.active_window_class().await;
+        if let Ok(mut last) = self.last.lock() {
+            *last = Some((Instant::now(), class.clone()));
+        }
+        class
+    }
+
+    /// The stored answer if it is younger than `ttl` at `now`.
+    fn cached(&self, now: Instant) -> Option<Option<String>> {
+        let last = self.last.lock().ok()?;
+        let (at, class) = last.as_ref()?;
+        (now.saturating_duration_since(*at) < self.ttl).then(|| class.clone())
+    }
+}
+
+/// KDE Plasma: the active window's resourceClass from KWin's D-Bus API.
+pub struct KWinWindowSource;
+
+#[async_trait]
+impl WindowClassSource for KWinWindowSource {
+    async fn active_window_class(&self) -> Option<String> {
+        query_kwin_window_class().await
+    }
+}
+
+async fn query_kwin_window_class() -> Option<String> {
     let output = Command::new("gdbus")
         .args([
             "call", "--session",
@@ -289,7 +1142,7 @@ async fn detect_terminal_focused() -> bool {
         Ok(o) if o.status.success() => o,
         _ => {
             debug!("KWin D-Bus query failed, assuming GUI window");
-            return false;
+            return None;
         }
     };
 
@@ -302,90 +1155,486 @@ async fn detect_terminal_focused() -> bool {
         .to_lowercase();
 
     if class.is_empty() {
-        return false;
+        None
+    } else {
+        Some(class)
+    }
+}
+
+/// A window to give focus back to before output, identified the way its
+/// compositor addresses windows.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FocusTarget {
+    /// KWin window uuid, e.g. `{0b1c…}`
+    KWin(String),
+    /// Hyprland window address, e.g. `0x55d1c0a8e0f0`
+    Hyprland(String),
+}
+
+/// Name the refocus script is loaded under, so a leftover one can be replaced
+const KWIN_FOCUS_SCRIPT: &str = "tjvox-refocus";
+
+/// The currently focused window, or `None` if the compositor cannot be asked.
+pub async fn capture_focus() -> Option<FocusTarget> {
+    if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        let output = Command::new("hyprctl")
+            .args(["activewindow", "-j"])
+            .output()
+            .await
+            .ok()
+            .filter(|o| o.status.success())?;
+        return parse_hyprland_address(&String::from_utf8_lossy(&output.stdout))
+            .map(FocusTarget::Hyprland);
+    }
+
+    let output = Command::new("gdbus")
+        .args([
+            "call", "--session",
+            "--dest", "org.kde.KWin",
+            "--object-path", "/KWin",
+            "--method", "org.kde.KWin.queryWindowInfo",
+        ])
+        .output()
+        .await
+        .ok()
+        .filter(|o| o.status.success())?;
+    extract_dbus_string(&String::from_utf8_lossy(&output.stdout), "uuid")
+        .filter(|uuid| is_window_uuid(uuid))
+        .map(FocusTarget::KWin)
+}
+
+/// Give focus back to `target`.
+pub async fn restore_focus(target: &FocusTarget) -> Result<()> {
+    match target {
+        FocusTarget::Hyprland(address) => {
+            let window = format!("address:{}", address);
+            run_focus_command("hyprctl", &["dispatch", "focuswindow", &window]).await?;
+        }
+        FocusTarget::KWin(uuid) => run_kwin_focus_script(uuid).await?,
+    }
+    debug!("Restored focus to {:?}", target);
+    Ok(())
+}
+
+/// KWin has no D-Bus call to activate a window, so load a one-off script
+/// that does it, run it and unload it again.
+async fn run_kwin_focus_script(uuid: &str) -> Result<()> {
+    let script = kwin_focus_script(uuid)
+        .ok_or_else(|| TjvoxError::Output(format!("Invalid KWin window id: {}", uuid)))?;
+    let path = kwin_focus_script_path();
+    write_private_file(&path, &script).await?;
+
+    const SCRIPTING: [&str; 6] =
+        ["call", "--session", "--dest", "org.kde.KWin", "--object-path", "/Scripting"];
+    const UNLOAD: [&str; 3] =
+        ["--method", "org.kde.kwin.Scripting.unloadScript", KWIN_FOCUS_SCRIPT];
+    // A script left behind by an interrupted run would block loading under the same name
+    let _ = Command::new("gdbus").args(SCRIPTING).args(UNLOAD).output().await;
+
+    let result = async {
+        let path = path.to_string_lossy();
+        let mut load = SCRIPTING.to_vec();
+        load.extend(["--method", "org.kde.kwin.Scripting.loadScript", &path, KWIN_FOCUS_SCRIPT]);
+        let loaded = run_focus_command("gdbus", &load).await?;
+        let id = parse_gdbus_int(&loaded)
+            .filter(|id| *id >= 0)
+            .ok_or_else(|| TjvoxError::Output("KWin refused the refocus script".to_string()))?;
+        let object = format!("/Scripting/Script{}", id);
+        let run = [
+            "call", "--session",
+            "--dest", "org.kde.KWin",
+            "--object-path", &object,
+            "--method", "org.kde.kwin.Script.run",
+        ];
+        run_focus_command("gdbus", &run).await.map(|_| ())
     }
+    .await;
 
-    let is_terminal = TERMINAL_PATTERNS.iter().any(|p| class.contains(p));
-    debug!("Active window resourceClass='{}', terminal={}", class, is_terminal);
-    is_terminal
+    let _ = Command::new("gdbus").args(SCRIPTING).args(UNLOAD).output().await;
+    tokio::fs::remove_file(&path).await.ok();
+    result
 }
 
-/// Extract a string value from KWin's D-Bus variant map output.
-/// Looks for pattern: 'key': <'value'>
+/// Where the refocus script is written: the user's runtime directory, which
+/// only they can write to, unlike the shared temp directory where another
+/// user could plant a symlink or swap the script before KWin loads it.
+fn kwin_focus_script_path() -> PathBuf {
+    let uid = unsafe { libc::getuid() };
+    PathBuf::from(format!("/run/user/{}/{}.js", uid, KWIN_FOCUS_SCRIPT))
+}
+
+/// Write `contents` to a new file only the user can read, replacing one left
+/// behind by an interrupted run. The file is created exclusively, so a link
+/// put in its place is never followed.
+async fn write_private_file(path: &std::path::Path, contents: &str) -> Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .await
+        .map_err(|e| TjvoxError::Output(format!("Failed to create {}: {}", path.display(), e)))?;
+    file.write_all(contents.as_bytes()).await?;
+    file.flush().await?;
+    Ok(())
+}
+
+/// Run a compositor command and return its stdout.
+async fn run_focus_command(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| TjvoxError::Output(format!("{} failed: {}", program, e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(TjvoxError::Output(format!("{} failed: {}", program, stderr.trim())).into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Window uuids as KWin reports them: hex digits and dashes, optionally in braces.
+fn is_window_uuid(uuid: &str) -> bool {
+    let inner = uuid.trim_start_matches('{').trim_end_matches('}');
+    !inner.is_empty() && inner.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+}
+
+/// KWin script activating the window with this uuid; works with the Plasma 6
+/// (`windowList`/`activeWindow`) and Plasma 5 (`clientList`/`activeClient`) APIs.
+fn kwin_focus_script(uuid: &str) -> Option<String> {
+    if !is_window_uuid(uuid) {
+        return None;
+    }
+    Some(format!(
+        r#"const windows = workspace.windowList ? workspace.windowList() : workspace.clientList();
+for (const w of windows) {{
+    if (w.internalId.toString() === "{}") {{
+        if ("activeWindow" in workspace) workspace.activeWindow = w;
+        else workspace.activeClient = w;
+    }}
+}}
+"#,
+        uuid
+    ))
+}
+
+/// The `address` of `hyprctl activewindow -j` output, if a window is focused.
+fn parse_hyprland_address(json: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+    let address = value.get("address")?.as_str()?;
+    let valid = address
+        .strip_prefix("0x")
+        .is_some_and(|hex| !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()));
+    valid.then(|| address.to_string())
+}
+
+/// The integer in a gdbus reply such as `(3,)` or `(int32 3,)`.
+fn parse_gdbus_int(output: &str) -> Option<i64> {
+    output
+        .trim()
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .trim_end_matches(',')
+        .trim()
+        .trim_start_matches("int32 ")
+        .parse()
+        .ok()
+}
+
+/// Extract a single value from KWin's D-Bus variant map output.
 fn extract_dbus_string(output: &str, key: &str) -> Option<String> {
-    let pattern = format!("'{}': <'", key);
-    let start = output.find(&pattern)? + pattern.len();
-    let rest = &output[start..];
-    let end = rest.find("'>")?;
-    Some(rest[..end].to_string())
-}
-
-/// Get clipboard contents. Tries native wl-clipboard-rs first (wlroots protocol),
-/// falls back to wl-paste (standard Wayland protocol via wl_data_device_manager).
-async fn get_clipboard() -> Result<String> {
-    // Try native Rust clipboard (wlroots data-control protocol)
-    let native_result = tokio::task::spawn_blocking(|| {
-        use wl_clipboard_rs::paste;
-        let result = paste::get_contents(
-            paste::ClipboardType::Regular,
-            paste::Seat::Unspecified,
-            paste::MimeType::Text,
-        );
-        match result {
-            Ok((mut pipe, _)) => {
-                let mut contents = String::new();
-                pipe.read_to_string(&mut contents)?;
-                Ok(contents)
+    parse_window_info(output).remove(key)
+}
+
+/// Parse KWin's `queryWindowInfo` output (GVariant text format) into a map.
+///
+/// Format: `({'resourceClass': <'firefox'>, 'caption': <"Bob's page">, 'x': <0.0>},)`
+///
+/// String values are unquoted and unescaped (both `'…'` and `"…"` quoting, with
+/// backslash escapes). Non-string values such as numbers, booleans or nested
+/// containers are kept as their raw text. Malformed input never panics; parsing
+/// stops at the first value that cannot be read and returns what was found.
+pub fn parse_window_info(output: &str) -> HashMap<String, String> {
+    let chars: Vec<char> = output.chars().collect();
+    let mut map = HashMap::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] != '\'' && chars[i] != '"' {
+            i += 1;
+            continue;
+        }
+
+        let (key, after_key) = match parse_quoted(&chars, i) {
+            Some(parsed) => parsed,
+            None => break,
+        };
+
+        // Expect `: <` after the key, otherwise this was not a dict key
+        let colon = skip_whitespace(&chars, after_key);
+        if chars.get(colon) != Some(&':') {
+            i = after_key;
+            continue;
+        }
+        let open = skip_whitespace(&chars, colon + 1);
+        if chars.get(open) != Some(&'<') {
+            i = after_key;
+            continue;
+        }
+
+        let value_start = skip_whitespace(&chars, open + 1);
+        let quoted = match chars.get(value_start) {
+            Some('\'') | Some('"') => parse_quoted(&chars, value_start),
+            Some(_) => None,
+            None => break,
+        };
+
+        // A quoted value must be followed by the closing `>`; anything else
+        // (e.g. `<'a' 'b'>`) is kept raw.
+        let quoted = quoted.and_then(|(value, end)| {
+            let close = skip_whitespace(&chars, end);
+            (chars.get(close) == Some(&'>')).then_some((value, close + 1))
+        });
+
+        let (value, next) = match quoted {
+            Some(parsed) => parsed,
+            None => parse_raw_value(&chars, value_start),
+        };
+
+        map.insert(key, value);
+        i = next;
+    }
+
+    map
+}
+
+/// Parse a quoted string starting at `start` (which must be `'` or `"`).
+/// Returns the unescaped contents and the index just past the closing quote.
+fn parse_quoted(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let quote = *chars.get(start)?;
+    let mut value = String::new();
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                let escaped = *chars.get(i + 1)?;
+                value.push(match escaped {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    other => other,
+                });
+                i += 2;
+            }
+            c if c == quote => return Some((value, i + 1)),
+            c => {
+                value.push(c);
+                i += 1;
             }
-            Err(e) => Err(anyhow::anyhow!("{}", e)),
         }
-    })
-    .await?;
+    }
+
+    None
+}
+
+/// Read a non-string variant value up to its closing `>`, honouring nested
+/// brackets and quoted strings. Returns the trimmed raw text and the index
+/// just past the closing `>` (or the end of input).
+fn parse_raw_value(chars: &[char], start: usize) -> (String, usize) {
+    let mut depth = 0usize;
+    let mut i = start;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\'' | '"' => match parse_quoted(chars, i) {
+                Some((_, end)) => {
+                    i = end;
+                    continue;
+                }
+                None => break,
+            },
+            '<' | '[' | '(' | '{' => depth += 1,
+            ']' | ')' | '}' => depth = depth.saturating_sub(1),
+            '>' if depth == 0 => {
+                let raw: String = chars[start..i].iter().collect();
+                return (raw.trim().to_string(), i + 1);
+            }
+            '>' => depth -= 1,
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let end = i.min(chars.len());
+    let raw: String = chars[start..end].iter().collect();
+    (raw.trim().to_string(), chars.len())
+}
+
+fn skip_whitespace(chars: &[char], mut i: usize) -> usize {
+    while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+        i += 1;
+    }
+    i
+}
 
-    if let Ok(text) = native_result {
-        return Ok(text);
+/// Run a clipboard operation, retrying up to `retries` more times after a
+/// short delay. The data-control protocol occasionally fails transiently on
+/// a busy compositor.
+async fn with_clipboard_retries<T, F, Fut>(retries: u32, what: &str, mut op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < retries => {
+                attempt += 1;
+                debug!("Clipboard {} failed ({}), retry {}/{}", what, e, attempt, retries);
+                sleep(CLIPBOARD_RETRY_DELAY).await;
+            }
+            Err(e) => return Err(e),
+        }
     }
+}
+
+/// Get clipboard contents, retrying transient failures.
+async fn get_clipboard(tools: &[ClipboardTool], retries: u32) -> Result<String> {
+    with_clipboard_retries(retries, "read", || try_get_clipboard(tools)).await
+}
+
+/// Set clipboard contents, retrying transient failures.
+async fn set_clipboard(text: &str, tools: &[ClipboardTool], retries: u32) -> Result<()> {
+    with_clipboard_retries(retries, "write", || try_set_clipboard(text, false, tools)).await
+}
+
+/// Set the primary selection (middle-click paste), retrying transient failures.
+async fn set_primary_selection(text: &str, tools: &[ClipboardTool], retries: u32) -> Result<()> {
+    with_clipboard_retries(retries, "write", || try_set_clipboard(text, true, tools)).await
+}
+
+/// Command line reading the clipboard with an external tool, `None` for the
+/// in-process native tool.
+fn clipboard_read_command(tool: ClipboardTool) -> Option<(&'static str, Vec<&'static str>)> {
+    match tool {
+        ClipboardTool::Native => None,
+        ClipboardTool::WlClipboard => Some(("wl-paste", vec!["--no-newline"])),
+        ClipboardTool::Xclip => Some(("xclip", vec!["-selection", "clipboard", "-o"])),
+    }
+}
+
+/// Command line writing its stdin to the clipboard, or to the primary
+/// selection if `primary` is set; `None` for the native tool.
+fn clipboard_write_command(
+    tool: ClipboardTool,
+    primary: bool,
+) -> Option<(&'static str, Vec<&'static str>)> {
+    match tool {
+        ClipboardTool::Native => None,
+        ClipboardTool::WlClipboard => {
+            Some(("wl-copy", if primary { vec!["--primary"] } else { vec![] }))
+        }
+        ClipboardTool::Xclip => Some((
+            "xclip",
+            vec!["-selection", if primary { "primary" } else { "clipboard" }],
+        )),
+    }
+}
+
+/// Get clipboard contents with the first of `tools` that works.
+async fn try_get_clipboard(tools: &[ClipboardTool]) -> Result<String> {
+    let mut last_error = None;
+    for &tool in tools {
+        match get_clipboard_with(tool).await {
+            Ok(text) => return Ok(text),
+            Err(e) => {
+                debug!("Clipboard read with {} failed: {}", tool, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No clipboard tools configured")))
+}
+
+async fn get_clipboard_with(tool: ClipboardTool) -> Result<String> {
+    let Some((program, args)) = clipboard_read_command(tool) else {
+        // Native Rust clipboard (wlroots data-control protocol)
+        return tokio::task::spawn_blocking(|| {
+            use wl_clipboard_rs::paste;
+            let (mut pipe, _) = paste::get_contents(
+                paste::ClipboardType::Regular,
+                paste::Seat::Unspecified,
+                paste::MimeType::Text,
+            )
+            .map_err(|e| anyhow::anyhow!("{}", e))?;
+            let mut contents = String::new();
+            pipe.read_to_string(&mut contents)?;
+            Ok(contents)
+        })
+        .await?;
+    };
 
-    // Fallback: wl-paste (supports standard wl_data_device_manager on KDE etc.)
-    debug!("Native clipboard read unavailable, using wl-paste");
-    let output = Command::new("wl-paste")
-        .arg("--no-newline")
+    let output = Command::new(program)
+        .args(args)
         .output()
         .await
-        .map_err(|e| anyhow::anyhow!("wl-paste failed: {}", e))?;
+        .map_err(|e| anyhow::anyhow!("{} failed: {}", program, e))?;
 
     if output.status.success() {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
-        Err(anyhow::anyhow!("wl-paste returned error"))
-    }
-}
-
-/// Set clipboard contents. Tries native wl-clipboard-rs first (wlroots protocol),
-/// falls back to wl-copy (standard Wayland protocol via wl_data_device_manager).
-async fn set_clipboard(text: &str) -> Result<()> {
-    // Try native Rust clipboard (wlroots data-control protocol)
-    let text_for_native = text.to_string();
-    let native_result = tokio::task::spawn_blocking(move || {
-        use wl_clipboard_rs::copy::{MimeType, Options, Source};
-        let opts = Options::new();
-        opts.copy(
-            Source::Bytes(text_for_native.into_bytes().into()),
-            MimeType::Text,
-        )
-    })
-    .await?;
+        Err(anyhow::anyhow!("{} returned error", program))
+    }
+}
 
-    if native_result.is_ok() {
-        return Ok(());
+/// Set clipboard contents, or the primary selection if `primary` is set,
+/// with the first of `tools` that works.
+async fn try_set_clipboard(text: &str, primary: bool, tools: &[ClipboardTool]) -> Result<()> {
+    let mut last_error = None;
+    for &tool in tools {
+        match set_clipboard_with(tool, text, primary).await {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                debug!("Clipboard write with {} failed: {}", tool, e);
+                last_error = Some(e);
+            }
+        }
     }
+    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No clipboard tools configured")))
+}
+
+async fn set_clipboard_with(tool: ClipboardTool, text: &str, primary: bool) -> Result<()> {
+    let Some((program, args)) = clipboard_write_command(tool, primary) else {
+        // Native Rust clipboard (wlroots data-control protocol)
+        let text_for_native = text.to_string();
+        return tokio::task::spawn_blocking(move || {
+            use wl_clipboard_rs::copy::{ClipboardType, MimeType, Options, Source};
+            let mut opts = Options::new();
+            if primary {
+                opts.clipboard(ClipboardType::Primary);
+            }
+            opts.copy(
+                Source::Bytes(text_for_native.into_bytes().into()),
+                MimeType::Text,
+            )
+            .map_err(|e| anyhow::anyhow!("{}", e))
+        })
+        .await?;
+    };
 
-    // Fallback: wl-copy (supports standard wl_data_device_manager on KDE etc.)
-    debug!("Native clipboard write unavailable, using wl-copy");
-    let mut child = Command::new("wl-copy")
+    let mut child = Command::new(program)
+        .args(args)
         .stdin(std::process::Stdio::piped())
         .spawn()
-        .map_err(|e| TjvoxError::Output(format!("Failed to run wl-copy: {}", e)))?;
+        .map_err(|e| TjvoxError::Output(format!("Failed to run {}: {}", program, e)))?;
 
     if let Some(ref mut stdin) = child.stdin {
         use tokio::io::AsyncWriteExt;
@@ -393,18 +1642,18 @@ async fn set_clipboard(text: &str) -> Result<()> {
             .write_all(text.as_bytes())
             .await
             .map_err(|e| {
-                TjvoxError::Output(format!("Failed to write to wl-copy: {}", e))
+                TjvoxError::Output(format!("Failed to write to {}: {}", program, e))
             })?;
     }
     drop(child.stdin.take());
 
     let status = child.wait().await.map_err(|e| {
-        TjvoxError::Output(format!("wl-copy failed: {}", e))
+        TjvoxError::Output(format!("{} failed: {}", program, e))
     })?;
 
     if !status.success() {
         return Err(
-            TjvoxError::Output("wl-copy exited with error".to_string()).into(),
+            TjvoxError::Output(format!("{} exited with error", program)).into(),
         );
     }
 
@@ -412,12 +1661,69 @@ async fn set_clipboard(text: &str) -> Result<()> {
 }
 
 /// Check if a window class matches any known terminal pattern.
-#[cfg(test)]
 fn is_terminal_class(class: &str) -> bool {
     let lower = class.to_lowercase();
     TERMINAL_PATTERNS.iter().any(|p| lower.contains(p))
 }
 
+/// Newlines must become Shift+Enter when the text spans several lines and
+/// the focused window is a chat app where Enter sends the message.
+fn needs_shift_enter(text: &str, class: Option<&str>, patterns: &[String]) -> bool {
+    text.contains('\n') && class.is_some_and(|class| class_matches(class, patterns))
+}
+
+/// The strategy of the longest pattern matching `class`, so a specific
+/// entry such as `"neovide"` overrides a broader one.
+fn newline_strategy_for<'a>(
+    class: Option<&str>,
+    strategies: &'a [(String, NewlineStrategy)],
+) -> Option<&'a NewlineStrategy> {
+    let class = class?.to_lowercase();
+    strategies
+        .iter()
+        .filter(|(pattern, _)| !pattern.is_empty() && class.contains(&pattern.to_lowercase()))
+        .max_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then_with(|| b.cmp(a)))
+        .map(|(_, strategy)| strategy)
+}
+
+/// `text` with its line breaks joined or removed, `None` when the strategy
+/// leaves them alone.
+fn rewrite_newlines(text: &str, strategy: &NewlineStrategy) -> Option<String> {
+    match strategy {
+        NewlineStrategy::Join => Some(
+            text.split('\n')
+                .map(str::trim_end)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        NewlineStrategy::Remove => Some(text.replace('\n', "")),
+        NewlineStrategy::Keep | NewlineStrategy::Keys(_) => None,
+    }
+}
+
+/// Whether the focused window is one where pasting is avoided.
+fn prefers_typing(class: Option<&str>, type_apps: &[String]) -> bool {
+    class.is_some_and(|class| class_matches(class, type_apps))
+}
+
+/// Whether `method` puts the text into the focused window by typing or
+/// pasting, rather than only onto a clipboard or selection. Unknown methods
+/// fall back to `auto`.
+fn enters_text(method: &str) -> bool {
+    !matches!(method, "clipboard" | "clipboard-notify" | "primary")
+}
+
+/// Whether `class` contains any of `patterns`, ignoring case. Empty
+/// patterns match nothing.
+fn class_matches(class: &str, patterns: &[String]) -> bool {
+    let lower = class.to_lowercase();
+    patterns
+        .iter()
+        .filter(|p| !p.is_empty())
+        .any(|p| lower.contains(&p.to_lowercase()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -456,6 +1762,75 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    #[test]
+    fn test_parse_window_info_all_pairs() {
+        let output = "({'resourceClass': <'firefox'>, 'caption': <'Mozilla Firefox'>, 'x': <12.0>, 'minimized': <false>},)";
+        let info = parse_window_info(output);
+        assert_eq!(info.len(), 4);
+        assert_eq!(info["resourceClass"], "firefox");
+        assert_eq!(info["caption"], "Mozilla Firefox");
+        assert_eq!(info["x"], "12.0");
+        assert_eq!(info["minimized"], "false");
+    }
+
+    #[test]
+    fn test_parse_window_info_caption_with_apostrophe() {
+        // GLib switches to double quotes when the string contains an apostrophe
+        let output = r#"({'caption': <"Bob's notes - Kate">, 'resourceClass': <'org.kde.kate'>},)"#;
+        let info = parse_window_info(output);
+        assert_eq!(info["caption"], "Bob's notes - Kate");
+        assert_eq!(info["resourceClass"], "org.kde.kate");
+    }
+
+    #[test]
+    fn test_parse_window_info_escaped_quotes() {
+        let output = r#"({'caption': <'It\'s "quoted"'>, 'resourceClass': <'kitty'>},)"#;
+        let info = parse_window_info(output);
+        assert_eq!(info["caption"], r#"It's "quoted""#);
+        assert_eq!(info["resourceClass"], "kitty");
+    }
+
+    #[test]
+    fn test_parse_window_info_nested_brackets() {
+        let output = "({'caption': <'[draft] (v2) <main> {x}'>, 'geometry': <{'w': <800>, 'h': [1, 2]}>, 'resourceClass': <'code'>},)";
+        let info = parse_window_info(output);
+        assert_eq!(info["caption"], "[draft] (v2) <main> {x}");
+        assert_eq!(info["geometry"], "{'w': <800>, 'h': [1, 2]}");
+        assert_eq!(info["resourceClass"], "code");
+        assert!(!info.contains_key("w"));
+    }
+
+    #[test]
+    fn test_parse_window_info_truncated_input_never_panics() {
+        let output = r#"({'caption': <"Bob's [x] <y>">, 'geometry': <{'w': <800>}>, 'resourceClass': <'it\'s'>},)"#;
+        for (idx, _) in output.char_indices() {
+            let _ = parse_window_info(&output[..idx]);
+        }
+        assert!(parse_window_info("").is_empty());
+        assert!(parse_window_info("'unterminated").is_empty());
+    }
+
+    #[test]
+    fn test_prefers_typing() {
+        let type_apps = vec!["firefox".to_string(), "Chromium".to_string()];
+        assert!(prefers_typing(Some("org.mozilla.firefox"), &type_apps));
+        assert!(prefers_typing(Some("chromium-browser"), &type_apps));
+        assert!(!prefers_typing(Some("org.kde.kate"), &type_apps));
+        // Unknown window keeps the configured method
+        assert!(!prefers_typing(None, &type_apps));
+        assert!(!prefers_typing(Some("firefox"), &[]));
+    }
+
+    #[test]
+    fn test_enters_text() {
+        for method in ["auto", "paste", "type", "unknown"] {
+            assert!(enters_text(method), "{}", method);
+        }
+        for method in ["clipboard", "clipboard-notify", "primary"] {
+            assert!(!enters_text(method), "{}", method);
+        }
+    }
+
     #[test]
     fn test_is_terminal_class_ghostty() {
         assert!(is_terminal_class("com.mitchellh.ghostty"));
@@ -491,12 +1866,140 @@ mod tests {
         assert!(is_terminal_class("org.wezfurlong.wezterm"));
     }
 
+    #[test]
+    fn test_class_matches_match() {
+        let patterns = vec!["KeePassXC".to_string()];
+        assert!(class_matches("org.keepassxc.keepassxc", &patterns));
+    }
+
+    #[test]
+    fn test_class_matches_no_match() {
+        let patterns = vec!["keepassxc".to_string(), String::new()];
+        assert!(!class_matches("firefox", &patterns));
+        assert!(!class_matches("firefox", &[]));
+    }
+
+    #[test]
+    fn test_needs_shift_enter() {
+        let patterns = vec!["slack".to_string(), "discord".to_string()];
+        assert!(needs_shift_enter("line one\nline two", Some("Slack"), &patterns));
+        assert!(needs_shift_enter("trailing\n", Some("com.discordapp.Discord"), &patterns));
+    }
+
+    #[test]
+    fn test_needs_shift_enter_not_needed() {
+        let patterns = vec!["slack".to_string()];
+        // Single line: nothing to convert
+        assert!(!needs_shift_enter("just one line", Some("slack"), &patterns));
+        // Editors keep plain newlines
+        assert!(!needs_shift_enter("a\nb", Some("org.kde.kate"), &patterns));
+        // Unknown window or no patterns configured
+        assert!(!needs_shift_enter("a\nb", None, &patterns));
+        assert!(!needs_shift_enter("a\nb", Some("slack"), &[]));
+    }
+
+    #[test]
+    fn test_newline_strategy_parse() {
+        assert_eq!(NewlineStrategy::parse("keep").unwrap(), NewlineStrategy::Keep);
+        assert_eq!(NewlineStrategy::parse(" join ").unwrap(), NewlineStrategy::Join);
+        assert_eq!(NewlineStrategy::parse("remove").unwrap(), NewlineStrategy::Remove);
+        match NewlineStrategy::parse("escape o").unwrap() {
+            NewlineStrategy::Keys(chords) => assert_eq!(chords.len(), 2),
+            other => panic!("expected keys, got {:?}", other),
+        }
+        assert!(NewlineStrategy::parse("").is_err());
+        assert!(NewlineStrategy::parse("suppress").is_err());
+    }
+
+    #[test]
+    fn test_newline_strategy_selection() {
+        let strategies = vec![
+            ("kitty".to_string(), NewlineStrategy::Join),
+            ("vim".to_string(), NewlineStrategy::Keep),
+            ("gvim".to_string(), NewlineStrategy::parse("escape o").unwrap()),
+            (String::new(), NewlineStrategy::Remove),
+        ];
+        let pick = |class| newline_strategy_for(class, &strategies);
+        assert_eq!(pick(Some("Kitty")), Some(&NewlineStrategy::Join));
+        assert_eq!(pick(Some("nvim")), Some(&NewlineStrategy::Keep));
+        // The longest matching pattern wins
+        assert!(matches!(pick(Some("Gvim")), Some(NewlineStrategy::Keys(_))));
+        // Empty patterns never match
+        assert_eq!(pick(Some("org.kde.kate")), None);
+        assert_eq!(pick(None), None);
+    }
+
+    #[test]
+    fn test_rewrite_newlines() {
+        let text = "first line \n\nsecond\n";
+        assert_eq!(rewrite_newlines(text, &NewlineStrategy::Join).unwrap(), "first line second");
+        assert_eq!(rewrite_newlines(text, &NewlineStrategy::Remove).unwrap(), "first line second");
+        assert_eq!(rewrite_newlines(text, &NewlineStrategy::Keep), None);
+    }
+
+    #[test]
+    fn test_truncate_output_fits() {
+        assert_eq!(truncate_output("hello", 5, true), None);
+        assert_eq!(truncate_output("hello", 100, true), None);
+    }
+
+    #[test]
+    fn test_truncate_output_with_marker() {
+        let result = truncate_output("hello world again", 11, true);
+        assert_eq!(result, Some("hello world … [truncated]".to_string()));
+    }
+
+    #[test]
+    fn test_truncate_output_without_marker() {
+        let result = truncate_output("hello world", 5, false);
+        assert_eq!(result, Some("hello".to_string()));
+    }
+
+    #[test]
+    fn test_truncate_output_multibyte() {
+        let result = truncate_output("blåbærsyltetøy", 4, false);
+        assert_eq!(result, Some("blåb".to_string()));
+    }
+
     #[test]
     fn test_terminal_patterns_count() {
         // Ensure we have a reasonable number of patterns
         assert!(TERMINAL_PATTERNS.len() >= 20);
     }
 
+    #[test]
+    fn test_output_method_display() {
+        assert_eq!(OutputMethod::Paste.to_string(), "paste");
+        assert_eq!(OutputMethod::TerminalPaste.to_string(), "terminal-paste");
+        assert_eq!(OutputMethod::Type.to_string(), "type");
+        assert_eq!(OutputMethod::Clipboard.to_string(), "clipboard");
+        assert_eq!(OutputMethod::ClipboardNotify.to_string(), "clipboard-notify");
+        assert_eq!(OutputMethod::Primary.to_string(), "primary");
+        assert_eq!(OutputMethod::File.to_string(), "file");
+        assert_eq!(OutputMethod::Stdout.to_string(), "stdout");
+    }
+
+    #[test]
+    fn test_parse_sink() {
+        assert_eq!(parse_sink("type").unwrap(), OutputSink::Method("type".to_string()));
+        assert_eq!(parse_sink(" stdout ").unwrap(), OutputSink::Stdout);
+        assert_eq!(
+            parse_sink("file:/tmp/dictation.log").unwrap(),
+            OutputSink::File(PathBuf::from("/tmp/dictation.log"))
+        );
+        assert!(parse_sink("file:").is_err());
+        assert_eq!(parse_sink("primary").unwrap(), OutputSink::Method("primary".to_string()));
+        assert!(parse_sink("fax").is_err());
+    }
+
+    #[test]
+    fn test_primary_method() {
+        use OutputMethod::*;
+        assert_eq!(primary_method(&[File, Type]), Type);
+        assert_eq!(primary_method(&[ClipboardNotify, Stdout]), ClipboardNotify);
+        assert_eq!(primary_method(&[Stdout, File]), Stdout);
+    }
+
     #[test]
     fn test_output_manager_new() {
         let config = OutputConfig {
@@ -504,8 +2007,251 @@ mod tests {
             paste_delay_ms: 50,
             append_trailing_space: true,
             method: "auto".to_string(),
+            backend: OutputBackend::Ydotool,
+            sensitive: false,
+            sensitive_apps: Vec::new(),
+            shift_enter_apps: Vec::new(),
+            type_apps: Vec::new(),
+            newline_strategy: HashMap::new(),
+            sinks: Vec::new(),
+            max_output_chars: None,
+            truncation_marker: true,
+            clipboard_retries: 2,
+            clipboard_tools: vec![ClipboardTool::Native],
+            keep_on_clipboard: false,
+            paste_keys: None,
+            refocus_previous: false,
+            ydotoold_cmd: None,
+            manage_ydotoold: false,
         };
         let manager = OutputManager::new(&config);
         assert!(manager.is_ok());
+        assert_eq!(
+            manager.unwrap().sinks,
+            vec![OutputSink::Method("auto".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_sink_appends_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("logs/dictation.log");
+        let config = OutputConfig {
+            delay_ms: 0,
+            sinks: vec![format!("file:{}", log.display())],
+            ..crate::config::Config::default().output
+        };
+        let manager = OutputManager::new(&config).unwrap();
+
+        assert_eq!(manager.type_text("first").await.unwrap(), OutputMethod::File);
+        manager.type_text("second").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "first\nsecond\n");
+    }
+
+    #[tokio::test]
+    async fn test_clipboard_retries_until_success() {
+        let mut calls = 0;
+        let result = with_clipboard_retries(2, "write", || {
+            calls += 1;
+            let attempt = calls;
+            async move {
+                if attempt < 3 {
+                    Err(anyhow::anyhow!("transient"))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_clipboard_retries_bounded() {
+        let mut calls = 0;
+        let result: Result<()> = with_clipboard_retries(1, "read", || {
+            calls += 1;
+            async { Err(anyhow::anyhow!("still busy")) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_clipboard_tool_commands() {
+        assert_eq!(clipboard_read_command(ClipboardTool::Native), None);
+        assert_eq!(
+            clipboard_read_command(ClipboardTool::Xclip),
+            Some(("xclip", vec!["-selection", "clipboard", "-o"]))
+        );
+        assert_eq!(
+            clipboard_write_command(ClipboardTool::WlClipboard, true),
+            Some(("wl-copy", vec!["--primary"]))
+        );
+        assert_eq!(
+            clipboard_write_command(ClipboardTool::Xclip, true),
+            Some(("xclip", vec!["-selection", "primary"]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clipboard_no_tools() {
+        assert!(try_get_clipboard(&[]).await.is_err());
+        assert!(try_set_clipboard("text", false, &[]).await.is_err());
+    }
+
+    #[test]
+    fn test_parse_paste_keys() {
+        let chords = parse_paste_keys("ctrl+v").unwrap();
+        assert_eq!(ydotool_key_args(&chords), ["key", "29:1", "47:1", "47:0", "29:0"]);
+        assert_eq!(wtype_key_args(&chords), ["-M", "ctrl", "-k", "v", "-m", "ctrl"]);
+
+        let chords = parse_paste_keys(" Shift+Insert ").unwrap();
+        assert_eq!(ydotool_key_args(&chords), ["key", "42:1", "110:1", "110:0", "42:0"]);
+        assert_eq!(wtype_key_args(&chords), ["-M", "shift", "-k", "Insert", "-m", "shift"]);
+
+        let chords = parse_paste_keys("ctrl+v ctrl+v").unwrap();
+        assert_eq!(chords.len(), 2);
+        assert_eq!(ydotool_key_args(&chords).len(), 9);
+
+        assert_eq!(parse_paste_keys("ctrl+shift+y").unwrap()[0][2].code, 21);
+        let chords = parse_paste_keys("ctrl+backspace left").unwrap();
+        assert_eq!(
+            wtype_key_args(&chords),
+            ["-M", "ctrl", "-k", "BackSpace", "-m", "ctrl", "-k", "Left"]
+        );
+        assert!(parse_paste_keys("").is_err());
+        assert!(parse_paste_keys("ctrl").is_err());
+        assert!(parse_paste_keys("v+ctrl").is_err());
+        assert!(parse_paste_keys("ctrl+v+c").is_err());
+        assert!(parse_paste_keys("ctrl+f13").is_err());
+    }
+
+    /// Counts queries and reports a different class each time
+    struct CountingSource(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    #[async_trait]
+    impl WindowClassSource for CountingSource {
+        async fn active_window_class(&self) -> Option<String> {
+            let n = self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Some(format!("window-{}", n))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_window_class_cache_ttl() {
+        let queries = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let ttl = Duration::from_millis(50);
+        let cache = WindowClassCache::new(Box::new(CountingSource(queries.clone())), ttl);
+
+        assert_eq!(cache.cached(Instant::now()), None);
+        assert_eq!(cache.active_window_class().await.as_deref(), Some("window-0"));
+        assert_eq!(cache.active_window_class().await.as_deref(), Some("window-0"));
+        assert_eq!(queries.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // Expired answers are not reused
+        assert_eq!(cache.cached(Instant::now() + ttl), None);
+        tokio::time::sleep(ttl + Duration::from_millis(10)).await;
+        assert_eq!(cache.active_window_class().await.as_deref(), Some("window-1"));
+        assert_eq!(queries.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    /// Always reports the same focused window
+    struct FixedSource(&'static str);
+
+    #[async_trait]
+    impl WindowClassSource for FixedSource {
+        async fn active_window_class(&self) -> Option<String> {
+            Some(self.0.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sensitive_target_never_uses_clipboard() {
+        let mut config = crate::config::Config::default().output;
+        config.sensitive_apps = vec!["keepassxc".to_string()];
+        let mut manager = OutputManager::new(&config).unwrap();
+        let keepassxc = FixedSource("org.keepassxc.keepassxc");
+        manager.window = WindowClassCache::new(Box::new(keepassxc), WINDOW_CLASS_TTL);
+
+        // These methods would otherwise only set the clipboard or selection
+        for method in ["clipboard", "clipboard-notify", "primary"] {
+            assert_eq!(
+                manager.typing_override("secret", method, None).await,
+                Some(TypingOverride::Sensitive),
+                "{}",
+                method
+            );
+        }
+
+        let kate = FixedSource("org.kde.kate");
+        manager.window = WindowClassCache::new(Box::new(kate), WINDOW_CLASS_TTL);
+        assert_eq!(manager.typing_override("notes", "clipboard", None).await, None);
+        manager.config.sensitive = true;
+        assert_eq!(
+            manager.typing_override("secret", "primary", None).await,
+            Some(TypingOverride::Sensitive)
+        );
+    }
+
+    #[test]
+    fn test_parse_hyprland_address() {
+        let json = r#"{"address": "0x55d1c0a8e0f0", "class": "firefox", "title": "Mozilla"}"#;
+        assert_eq!(parse_hyprland_address(json), Some("0x55d1c0a8e0f0".to_string()));
+        // No focused window
+        assert_eq!(parse_hyprland_address("{}"), None);
+        assert_eq!(parse_hyprland_address(r#"{"address": "0x; rm -rf ~"}"#), None);
+        assert_eq!(parse_hyprland_address("Invalid"), None);
+    }
+
+    #[test]
+    fn test_kwin_focus_script() {
+        let uuid = "{0b1c2d3e-4f50-6172-8394-a5b6c7d8e9f0}";
+        let script = kwin_focus_script(uuid).unwrap();
+        assert!(script.contains(&format!("=== \"{}\"", uuid)));
+        assert!(kwin_focus_script("\"); workspace.slotKillWindow(); (\"").is_none());
+        assert!(kwin_focus_script("{}").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_write_private_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("script.js");
+        let target = dir.path().join("victim");
+        std::fs::write(&target, "keep").unwrap();
+        std::os::unix::fs::symlink(&target, &path).unwrap();
+
+        // A planted link is replaced, never written through
+        write_private_file(&path, "callDBus();").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "keep");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "callDBus();");
+        let mode = std::fs::symlink_metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_parse_gdbus_int() {
+        assert_eq!(parse_gdbus_int("(3,)\n"), Some(3));
+        assert_eq!(parse_gdbus_int("(int32 -1,)"), Some(-1));
+        assert_eq!(parse_gdbus_int("()"), None);
+    }
+
+    #[test]
+    fn test_ydotoold_args() {
+        let mut config = crate::config::Config::default().output;
+        assert_eq!(ydotoold_args(&config).unwrap(), vec!["ydotoold"]);
+
+        config.ydotoold_cmd =
+            Some("/usr/local/bin/ydotoold --socket-path '/tmp/my socket'".to_string());
+        assert_eq!(
+            ydotoold_args(&config).unwrap(),
+            vec!["/usr/local/bin/ydotoold", "--socket-path", "/tmp/my socket"]
+        );
+
+        config.ydotoold_cmd = Some(String::new());
+        assert!(ydotoold_args(&config).is_err());
     }
 }
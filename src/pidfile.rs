@@ -0,0 +1,168 @@
+use anyhow::Result;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::error::TjvoxError;
+
+/// Get the PID file path in the user's runtime directory (secure, user-private).
+pub fn pid_file_path() -> PathBuf {
+    let uid = unsafe { libc::getuid() };
+    PathBuf::from(format!("/run/user/{}/tjvox.pid", uid))
+}
+
+/// Liveness of the daemon as seen through its PID file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaemonStatus {
+    /// No PID file exists
+    NotRunning,
+    /// A PID file exists but nobody holds its lock (daemon died uncleanly)
+    Stale,
+    /// The lock is held by a live daemon with this PID
+    Running(i32),
+}
+
+/// Exclusive advisory lock on the PID file, held for the daemon's lifetime.
+///
+/// The kernel drops the lock when the process exits, even on SIGKILL, so a
+/// leftover file without a lock holder is always stale.
+pub struct PidLock {
+    _file: File,
+    path: PathBuf,
+}
+
+impl PidLock {
+    /// Lock the PID file and write the current PID into it.
+    /// Fails if another process already holds the lock.
+    pub fn acquire(path: &Path) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        if !try_flock(&file, libc::LOCK_EX)? {
+            let mut pid = String::new();
+            file.read_to_string(&mut pid).ok();
+            return Err(TjvoxError::Config(format!(
+                "tjvox daemon is already running (PID {})",
+                pid.trim()
+            ))
+            .into());
+        }
+
+        // Only truncate once we own the lock
+        file.set_len(0)?;
+        file.rewind()?;
+        write!(file, "{}", std::process::id())?;
+        file.flush()?;
+
+        debug!("Acquired PID lock {}", path.display());
+        Ok(Self {
+            _file: file,
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for PidLock {
+    fn drop(&mut self) {
+        // Remove while still holding the lock; closing the file releases it
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Determine whether a daemon holds the lock on the given PID file.
+pub fn daemon_status(path: &Path) -> Result<DaemonStatus> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(DaemonStatus::NotRunning)
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    if try_flock(&file, libc::LOCK_SH)? {
+        // We got the lock, so no daemon holds it; closing the file releases it
+        return Ok(DaemonStatus::Stale);
+    }
+
+    let mut pid = String::new();
+    file.read_to_string(&mut pid)?;
+    let pid: i32 = pid
+        .trim()
+        .parse()
+        .map_err(|e| TjvoxError::Config(format!("Invalid PID file contents: {}", e)))?;
+    Ok(DaemonStatus::Running(pid))
+}
+
+/// Try to take a non-blocking flock. Returns `Ok(false)` if it is held elsewhere.
+fn try_flock(file: &File, operation: libc::c_int) -> Result<bool> {
+    let ret = unsafe { libc::flock(file.as_raw_fd(), operation | libc::LOCK_NB) };
+    if ret == 0 {
+        return Ok(true);
+    }
+    let err = std::io::Error::last_os_error();
+    if err.raw_os_error() == Some(libc::EWOULDBLOCK) {
+        Ok(false)
+    } else {
+        Err(err.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daemon_status_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tjvox.pid");
+        assert_eq!(daemon_status(&path).unwrap(), DaemonStatus::NotRunning);
+    }
+
+    #[test]
+    fn test_daemon_status_stale_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tjvox.pid");
+        std::fs::write(&path, "12345").unwrap();
+        assert_eq!(daemon_status(&path).unwrap(), DaemonStatus::Stale);
+    }
+
+    #[test]
+    fn test_pid_lock_running_and_released() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tjvox.pid");
+
+        let lock = PidLock::acquire(&path).unwrap();
+        let pid = std::process::id() as i32;
+        assert_eq!(daemon_status(&path).unwrap(), DaemonStatus::Running(pid));
+
+        drop(lock);
+        assert!(!path.exists());
+        assert_eq!(daemon_status(&path).unwrap(), DaemonStatus::NotRunning);
+    }
+
+    #[test]
+    fn test_pid_lock_exclusive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tjvox.pid");
+
+        let _lock = PidLock::acquire(&path).unwrap();
+        assert!(PidLock::acquire(&path).is_err());
+    }
+
+    #[test]
+    fn test_pid_lock_takes_over_stale_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tjvox.pid");
+        std::fs::write(&path, "99999999").unwrap();
+
+        let _lock = PidLock::acquire(&path).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents, std::process::id().to_string());
+    }
+}
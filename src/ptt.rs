@@ -2,15 +2,70 @@
 pub mod monitor {
     use anyhow::{Context, Result};
     use evdev::{Device, InputEventKind, Key};
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
     use tokio::sync::mpsc;
     use tracing::{debug, info, warn};
 
+    use crate::config::PttTrigger;
+    use crate::ptt::arbiter::InputEvent;
+
+    /// Where evdev event nodes live
+    const INPUT_DIR: &str = "/dev/input";
+
+    /// Identical events arriving from different device nodes within this window
+    /// are treated as a single physical key press.
+    const COALESCE_WINDOW: Duration = Duration::from_millis(50);
+
+    /// A raw edge of the push-to-talk key, as read from evdev.
     #[derive(Debug, Clone, Copy, PartialEq)]
     pub enum PttEvent {
         KeyDown,
         KeyUp,
     }
 
+    impl PttEvent {
+        /// The input this edge stands for under `input.ptt_trigger`.
+        ///
+        /// - `down` (default): press starts and release stops, hold-to-talk.
+        /// - `up`: presses are ignored and every release toggles recording,
+        ///   for keyboards whose press arrives late or is lost. Double-tap
+        ///   latching does not apply, since each release already toggles.
+        pub fn input_event(self, trigger: PttTrigger) -> Option<InputEvent> {
+            match (trigger, self) {
+                (PttTrigger::Down, PttEvent::KeyDown) => Some(InputEvent::PushStart),
+                (PttTrigger::Down, PttEvent::KeyUp) => Some(InputEvent::PushStop),
+                (PttTrigger::Up, PttEvent::KeyDown) => None,
+                (PttTrigger::Up, PttEvent::KeyUp) => Some(InputEvent::Toggle),
+            }
+        }
+    }
+
+    /// Drops duplicate events when one physical keyboard is exposed as several
+    /// event nodes (each of which reports the same key press).
+    struct EventCoalescer {
+        last: Option<(PttEvent, Instant)>,
+        window: Duration,
+    }
+
+    impl EventCoalescer {
+        fn new(window: Duration) -> Self {
+            Self { last: None, window }
+        }
+
+        /// Returns true if the event should be forwarded.
+        fn accept(&mut self, event: PttEvent, now: Instant) -> bool {
+            if let Some((last, at)) = self.last {
+                if last == event && now.saturating_duration_since(at) < self.window {
+                    return false;
+                }
+            }
+            self.last = Some((event, now));
+            true
+        }
+    }
+
     pub struct PttMonitor {
         key: Key,
     }
@@ -27,25 +82,11 @@ pub mod monitor {
             let key = self.key;
 
             // Scan for input devices with the target key
-            let devices = evdev::enumerate()
-                .filter_map(|(_, device)| {
-                    if device
-                        .supported_keys()
-                        .map_or(false, |keys| keys.contains(key))
-                    {
-                        Some(device)
-                    } else {
-                        None
-                    }
-                })
-                .collect::<Vec<_>>();
-
-            if devices.is_empty() {
-                anyhow::bail!(
-                    "No input device found with key {:?}. Check permissions on /dev/input/event*.",
-                    key
-                );
+            let scan = scan_devices(Path::new(INPUT_DIR), key);
+            if scan.devices.is_empty() {
+                anyhow::bail!(missing_device_message(key, scan.nodes, scan.denied));
             }
+            let devices = scan.devices;
 
             info!(
                 "Monitoring {} device(s) for key {:?}",
@@ -53,12 +94,15 @@ pub mod monitor {
                 key
             );
 
-            // Monitor all matching devices concurrently
+            // Monitor all matching devices concurrently, sharing one coalescer
+            // so duplicate nodes of the same keyboard only trigger once
+            let coalescer = Arc::new(Mutex::new(EventCoalescer::new(COALESCE_WINDOW)));
             let mut handles = Vec::new();
             for device in devices {
                 let tx = tx.clone();
+                let coalescer = coalescer.clone();
                 let handle = tokio::task::spawn_blocking(move || {
-                    monitor_device(device, key, tx)
+                    monitor_device(device, key, tx, coalescer)
                 });
                 handles.push(handle);
             }
@@ -74,10 +118,70 @@ pub mod monitor {
         }
     }
 
+    /// Event nodes with the push-to-talk key, plus how many nodes were seen
+    /// and how many could not be opened for lack of permission.
+    struct DeviceScan {
+        devices: Vec<Device>,
+        nodes: usize,
+        denied: usize,
+    }
+
+    fn scan_devices(dir: &Path, key: Key) -> DeviceScan {
+        let mut scan = DeviceScan { devices: Vec::new(), nodes: 0, denied: 0 };
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return scan;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("event"))
+            })
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            scan.nodes += 1;
+            match Device::open(&path) {
+                Ok(device) => {
+                    if device.supported_keys().is_some_and(|keys| keys.contains(key)) {
+                        scan.devices.push(device);
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => scan.denied += 1,
+                Err(e) => debug!("Skipping {}: {}", path.display(), e),
+            }
+        }
+        scan
+    }
+
+    /// Why no device with `key` could be watched: no event nodes at all,
+    /// nodes the user may not open (the usual setup problem), or simply no
+    /// keyboard with that key.
+    fn missing_device_message(key: Key, nodes: usize, denied: usize) -> String {
+        if nodes == 0 {
+            format!("No input devices found in {}", INPUT_DIR)
+        } else if denied > 0 {
+            format!(
+                "Permission denied opening {} of {} input devices in {}, so key {:?} cannot \
+                 be watched. Add your user to the input group (`sudo usermod -aG input \
+                 $USER`, then log out and back in) or grant access with a udev rule.",
+                denied, nodes, INPUT_DIR, key
+            )
+        } else {
+            format!(
+                "No input device has key {:?}; set input.ptt_key to a key your keyboard has",
+                key
+            )
+        }
+    }
+
     fn monitor_device(
         mut device: Device,
         key: Key,
         tx: mpsc::Sender<PttEvent>,
+        coalescer: Arc<Mutex<EventCoalescer>>,
     ) -> Result<()> {
         let name = device
             .name()
@@ -100,6 +204,14 @@ pub mod monitor {
                         };
 
                         if let Some(evt) = ptt_event {
+                            let accepted = coalescer
+                                .lock()
+                                .map(|mut c| c.accept(evt, Instant::now()))
+                                .unwrap_or(true);
+                            if !accepted {
+                                debug!("PTT event {:?} from {} coalesced (duplicate)", evt, name);
+                                continue;
+                            }
                             debug!("PTT event: {:?} from {}", evt, name);
                             if tx.blocking_send(evt).is_err() {
                                 return Ok(()); // channel closed
@@ -158,4 +270,365 @@ pub mod monitor {
             _ => None,
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_coalescer_merges_duplicate_devices() {
+            let mut coalescer = EventCoalescer::new(COALESCE_WINDOW);
+            let t0 = Instant::now();
+
+            // Two event nodes of the same keyboard report the press and release
+            assert!(coalescer.accept(PttEvent::KeyDown, t0));
+            assert!(!coalescer.accept(PttEvent::KeyDown, t0 + Duration::from_millis(2)));
+            assert!(coalescer.accept(PttEvent::KeyUp, t0 + Duration::from_millis(300)));
+            assert!(!coalescer.accept(PttEvent::KeyUp, t0 + Duration::from_millis(301)));
+        }
+
+        #[test]
+        fn test_coalescer_allows_repeated_presses_outside_window() {
+            let mut coalescer = EventCoalescer::new(COALESCE_WINDOW);
+            let t0 = Instant::now();
+
+            assert!(coalescer.accept(PttEvent::KeyDown, t0));
+            assert!(coalescer.accept(PttEvent::KeyUp, t0 + Duration::from_millis(10)));
+            assert!(coalescer.accept(PttEvent::KeyDown, t0 + Duration::from_millis(20)));
+            assert!(coalescer.accept(PttEvent::KeyDown, t0 + Duration::from_secs(1)));
+        }
+
+        #[test]
+        fn test_ptt_trigger_edges() {
+            assert_eq!(
+                PttEvent::KeyDown.input_event(PttTrigger::Down),
+                Some(InputEvent::PushStart)
+            );
+            assert_eq!(
+                PttEvent::KeyUp.input_event(PttTrigger::Down),
+                Some(InputEvent::PushStop)
+            );
+            assert_eq!(PttEvent::KeyDown.input_event(PttTrigger::Up), None);
+            assert_eq!(PttEvent::KeyUp.input_event(PttTrigger::Up), Some(InputEvent::Toggle));
+        }
+
+        #[test]
+        fn test_missing_device_message() {
+            let message = missing_device_message(Key::KEY_F13, 12, 12);
+            assert!(message.contains("Permission denied opening 12 of 12"), "{}", message);
+            assert!(message.contains("input group"), "{}", message);
+
+            let message = missing_device_message(Key::KEY_F13, 12, 0);
+            assert!(message.starts_with("No input device has key KEY_F13"), "{}", message);
+            assert!(missing_device_message(Key::KEY_F13, 0, 0).contains("/dev/input"));
+        }
+
+        #[test]
+        fn test_scan_devices_skips_other_files() {
+            let dir = tempfile::tempdir().unwrap();
+            std::fs::write(dir.path().join("mouse0"), b"").unwrap();
+            std::fs::write(dir.path().join("event0"), b"").unwrap();
+            let scan = scan_devices(dir.path(), Key::KEY_F13);
+            assert_eq!(scan.nodes, 1);
+            assert!(scan.devices.is_empty());
+
+            let scan = scan_devices(&dir.path().join("missing"), Key::KEY_F13);
+            assert_eq!(scan.nodes, 0);
+        }
+
+        #[test]
+        fn test_parse_key_name() {
+            assert_eq!(parse_key_name("KEY_F13"), Some(Key::KEY_F13));
+            assert_eq!(parse_key_name("f13"), Some(Key::KEY_F13));
+            assert_eq!(parse_key_name("KEY_NOPE"), None);
+        }
+    }
+}
+
+/// Arbitration between toggle and push-to-talk input.
+///
+/// Toggle (SIGUSR1, socket, tray) and push-to-talk (evdev key, socket
+/// push-start/push-stop) can be used together. The arbiter remembers which
+/// path started the current recording so that one path cannot stop a
+/// recording the other one owns by accident:
+///
+/// - Toggle starts from idle and stops any recording.
+/// - A push release only stops a recording that was started by a push.
+/// - A push press during a toggled or latched recording stops it; the
+///   matching release is swallowed.
+/// - With `double_tap` enabled, two taps in quick succession latch a push
+///   recording into continuous mode, ended by the next press or a toggle.
+/// - Everything is ignored while the daemon is typing, or the transcription
+///   queue is full.
+pub mod arbiter {
+    use std::time::{Duration, Instant};
+
+    /// Daemon phase as far as input handling is concerned.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Phase {
+        Idle,
+        Recording,
+        /// Typing, or the transcription queue is full; input is ignored
+        Busy,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum InputEvent {
+        Toggle,
+        PushStart,
+        PushStop,
+        /// The double-tap window after a short tap has elapsed
+        TapTimeout,
+    }
+
+    /// What the daemon should do in response to an input event.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum InputAction {
+        Start,
+        Stop,
+        /// Keep recording in continuous (latched) mode
+        Latch,
+        Ignore,
+    }
+
+    /// Which input path started the current recording.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Source {
+        Toggle,
+        Push,
+        Latched,
+    }
+
+    pub struct InputArbiter {
+        source: Option<Source>,
+        press_started: Option<Instant>,
+        /// Release time of a short tap that may become a double tap
+        pending_release: Option<Instant>,
+        swallow_release: bool,
+        double_tap: Duration,
+    }
+
+    impl InputArbiter {
+        /// `double_tap` of zero disables double-tap latching.
+        pub fn new(double_tap: Duration) -> Self {
+            Self {
+                source: None,
+                press_started: None,
+                pending_release: None,
+                swallow_release: false,
+                double_tap,
+            }
+        }
+
+        /// When the daemon should deliver `InputEvent::TapTimeout`, if at all.
+        pub fn deadline(&self) -> Option<Instant> {
+            self.pending_release.map(|at| at + self.double_tap)
+        }
+
+        pub fn handle(&mut self, event: InputEvent, phase: Phase, now: Instant) -> InputAction {
+            if phase == Phase::Busy {
+                self.clear();
+                return InputAction::Ignore;
+            }
+
+            match (event, phase) {
+                (InputEvent::Toggle, Phase::Idle) => {
+                    self.clear();
+                    self.source = Some(Source::Toggle);
+                    InputAction::Start
+                }
+                (InputEvent::Toggle, Phase::Recording) => self.stop(),
+
+                (InputEvent::PushStart, Phase::Idle) => {
+                    self.clear();
+                    self.source = Some(Source::Push);
+                    self.press_started = Some(now);
+                    InputAction::Start
+                }
+                (InputEvent::PushStart, Phase::Recording) => {
+                    if self.deadline().is_some_and(|deadline| now <= deadline) {
+                        self.pending_release = None;
+                        self.source = Some(Source::Latched);
+                        self.swallow_release = true;
+                        return InputAction::Latch;
+                    }
+                    match self.source {
+                        Some(Source::Push) => InputAction::Ignore,
+                        _ => {
+                            let action = self.stop();
+                            self.swallow_release = true;
+                            action
+                        }
+                    }
+                }
+
+                (InputEvent::PushStop, _) if self.swallow_release => {
+                    self.swallow_release = false;
+                    InputAction::Ignore
+                }
+                (InputEvent::PushStop, Phase::Recording) if self.source == Some(Source::Push) => {
+                    let held = self
+                        .press_started
+                        .map(|at| now.saturating_duration_since(at))
+                        .unwrap_or_default();
+                    if held < self.double_tap {
+                        self.pending_release = Some(now);
+                        InputAction::Ignore
+                    } else {
+                        self.stop()
+                    }
+                }
+
+                (InputEvent::TapTimeout, Phase::Recording)
+                    if self.deadline().is_some_and(|deadline| now >= deadline) =>
+                {
+                    self.stop()
+                }
+                (InputEvent::TapTimeout, _) => {
+                    if self.deadline().is_some_and(|deadline| now >= deadline) {
+                        self.pending_release = None;
+                    }
+                    InputAction::Ignore
+                }
+
+                _ => InputAction::Ignore,
+            }
+        }
+
+        fn stop(&mut self) -> InputAction {
+            self.clear();
+            InputAction::Stop
+        }
+
+        fn clear(&mut self) {
+            self.source = None;
+            self.press_started = None;
+            self.pending_release = None;
+            self.swallow_release = false;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Drives the arbiter like the daemon does: `Start` moves to recording,
+        /// `Stop` runs the (instant) transcription pipeline back to idle.
+        struct Harness {
+            arbiter: InputArbiter,
+            phase: Phase,
+            t0: Instant,
+        }
+
+        impl Harness {
+            fn new(double_tap_ms: u64) -> Self {
+                Self {
+                    arbiter: InputArbiter::new(Duration::from_millis(double_tap_ms)),
+                    phase: Phase::Idle,
+                    t0: Instant::now(),
+                }
+            }
+
+            fn send(&mut self, event: InputEvent, at_ms: u64) -> InputAction {
+                let now = self.t0 + Duration::from_millis(at_ms);
+                let action = self.arbiter.handle(event, self.phase, now);
+                match action {
+                    InputAction::Start => self.phase = Phase::Recording,
+                    InputAction::Stop => self.phase = Phase::Idle,
+                    InputAction::Latch | InputAction::Ignore => {}
+                }
+                action
+            }
+        }
+
+        #[test]
+        fn test_hold_to_talk() {
+            let mut h = Harness::new(300);
+            assert_eq!(h.send(InputEvent::PushStart, 0), InputAction::Start);
+            assert_eq!(h.send(InputEvent::PushStop, 2000), InputAction::Stop);
+            assert_eq!(h.phase, Phase::Idle);
+        }
+
+        #[test]
+        fn test_push_release_does_not_stop_toggle_recording() {
+            let mut h = Harness::new(300);
+            assert_eq!(h.send(InputEvent::Toggle, 0), InputAction::Start);
+            assert_eq!(h.send(InputEvent::PushStop, 100), InputAction::Ignore);
+            assert_eq!(h.phase, Phase::Recording);
+            assert_eq!(h.send(InputEvent::Toggle, 3000), InputAction::Stop);
+        }
+
+        #[test]
+        fn test_push_press_stops_toggle_recording_and_swallows_release() {
+            let mut h = Harness::new(300);
+            assert_eq!(h.send(InputEvent::Toggle, 0), InputAction::Start);
+            assert_eq!(h.send(InputEvent::PushStart, 2000), InputAction::Stop);
+            assert_eq!(h.send(InputEvent::PushStop, 2100), InputAction::Ignore);
+            assert_eq!(h.phase, Phase::Idle);
+
+            // Next press starts a fresh push recording
+            assert_eq!(h.send(InputEvent::PushStart, 4000), InputAction::Start);
+            assert_eq!(h.send(InputEvent::PushStop, 6000), InputAction::Stop);
+        }
+
+        #[test]
+        fn test_toggle_stops_push_recording() {
+            let mut h = Harness::new(300);
+            assert_eq!(h.send(InputEvent::PushStart, 0), InputAction::Start);
+            assert_eq!(h.send(InputEvent::Toggle, 1000), InputAction::Stop);
+            // Release of the still-held key must not start anything
+            assert_eq!(h.send(InputEvent::PushStop, 1500), InputAction::Ignore);
+            assert_eq!(h.phase, Phase::Idle);
+        }
+
+        #[test]
+        fn test_double_tap_latches_continuous_mode() {
+            let mut h = Harness::new(300);
+            assert_eq!(h.send(InputEvent::PushStart, 0), InputAction::Start);
+            assert_eq!(h.send(InputEvent::PushStop, 100), InputAction::Ignore);
+            assert!(h.arbiter.deadline().is_some());
+            assert_eq!(h.send(InputEvent::PushStart, 200), InputAction::Latch);
+            assert_eq!(h.send(InputEvent::PushStop, 250), InputAction::Ignore);
+            assert_eq!(h.arbiter.deadline(), None);
+            assert_eq!(h.send(InputEvent::TapTimeout, 500), InputAction::Ignore);
+            assert_eq!(h.phase, Phase::Recording);
+
+            // A later press ends continuous mode; its release is swallowed
+            assert_eq!(h.send(InputEvent::PushStart, 10_000), InputAction::Stop);
+            assert_eq!(h.send(InputEvent::PushStop, 10_100), InputAction::Ignore);
+            assert_eq!(h.phase, Phase::Idle);
+        }
+
+        #[test]
+        fn test_single_short_tap_stops_after_window() {
+            let mut h = Harness::new(300);
+            assert_eq!(h.send(InputEvent::PushStart, 0), InputAction::Start);
+            assert_eq!(h.send(InputEvent::PushStop, 100), InputAction::Ignore);
+            assert_eq!(h.send(InputEvent::TapTimeout, 200), InputAction::Ignore);
+            assert_eq!(h.send(InputEvent::TapTimeout, 400), InputAction::Stop);
+            assert_eq!(h.phase, Phase::Idle);
+        }
+
+        #[test]
+        fn test_double_tap_disabled_stops_immediately() {
+            let mut h = Harness::new(0);
+            assert_eq!(h.send(InputEvent::PushStart, 0), InputAction::Start);
+            assert_eq!(h.send(InputEvent::PushStop, 50), InputAction::Stop);
+            assert_eq!(h.arbiter.deadline(), None);
+        }
+
+        #[test]
+        fn test_busy_ignores_all_input() {
+            let mut arbiter = InputArbiter::new(Duration::from_millis(300));
+            let now = Instant::now();
+            for event in [
+                InputEvent::Toggle,
+                InputEvent::PushStart,
+                InputEvent::PushStop,
+                InputEvent::TapTimeout,
+            ] {
+                assert_eq!(arbiter.handle(event, Phase::Busy, now), InputAction::Ignore);
+            }
+        }
+    }
 }
@@ -1,65 +1,225 @@
 use anyhow::{Context, Result};
-use regex::Regex;
-use std::path::Path;
+use regex::{Regex, RegexBuilder};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
+/// Compiled size budget for the combined pattern; hundreds of case-insensitive
+/// phrases fit comfortably, larger sets fall back to one regex per rule.
+const COMBINED_SIZE_LIMIT: usize = 64 * 1024 * 1024;
+
+/// Possessive and plural endings kept after a replaced word, so "iphone's"
+/// becomes "iPhone's" and "iphones" becomes "iPhones"
+const WORD_SUFFIXES: &str = "(?:'s|\u{2019}s|es|s)?";
+
 pub struct ReplacementEngine {
-    rules: Vec<(Regex, String)>,
+    /// All phrases as one alternation, scanned in a single pass
+    combined: Option<Regex>,
+    /// The same alternation with one capture group per rule, used to identify
+    /// the rule when case folding defeats the `by_phrase` lookup
+    grouped: Option<Regex>,
+    /// Rule index by lowercased phrase
+    by_phrase: HashMap<String, usize>,
+    /// Replacement for each rule
+    replacements: Vec<String>,
+    /// Per-rule patterns, used only if the combined pattern could not be built
+    fallback: Vec<(Regex, String)>,
 }
 
 impl ReplacementEngine {
-    pub fn load(path: &Path) -> Result<Self> {
-        if !path.exists() {
-            info!(
-                "Replacements file not found at {:?}, creating default",
-                path
-            );
-            Self::create_default(path)?;
-        }
+    /// Load and merge the rules of `paths` in order. A rule in a later file
+    /// replaces one with the same phrase from an earlier file. The last file
+    /// is created with the default rules if missing; earlier missing files
+    /// are skipped.
+    pub fn load<P: AsRef<Path>>(paths: &[P]) -> Result<Self> {
+        let mut pairs: Vec<(String, String)> = Vec::new();
+        let mut by_phrase = HashMap::new();
+        for (i, path) in paths.iter().enumerate() {
+            let path = path.as_ref();
+            if !path.exists() {
+                if i + 1 < paths.len() {
+                    warn!("Replacements file not found at {:?}, skipping it", path);
+                    continue;
+                }
+                info!(
+                    "Replacements file not found at {:?}, creating default",
+                    path
+                );
+                Self::create_default(path)?;
+            }
 
-        let content = std::fs::read_to_string(path)
-            .with_context(|| format!("Failed to read replacements file: {:?}", path))?;
-
-        let table: toml::Value = toml::from_str(&content)
-            .with_context(|| format!("Failed to parse replacements file: {:?}", path))?;
-
-        let replacements = table
-            .get("replacements")
-            .and_then(|v| v.as_table())
-            .cloned()
-            .unwrap_or_default();
-
-        let mut rules = Vec::new();
-        for (key, value) in &replacements {
-            if let Some(replacement) = value.as_str() {
-                let pattern = format!(r"(?i)\b{}\b", regex::escape(key));
-                match Regex::new(&pattern) {
-                    Ok(re) => {
-                        debug!("Loaded replacement rule: {:?} -> {:?}", key, replacement);
-                        rules.push((re, replacement.to_string()));
+            for (key, replacement) in read_rules(path)? {
+                // Phrases match case-insensitively, so "iPhone" overrides "iphone"
+                match by_phrase.entry(key.to_lowercase()) {
+                    Entry::Occupied(entry) => {
+                        debug!("{:?} overrides replacement rule {:?}", path, key);
+                        pairs[*entry.get()] = (key, replacement);
                     }
-                    Err(e) => {
-                        warn!("Invalid replacement pattern for {:?}: {}", key, e);
+                    Entry::Vacant(entry) => {
+                        entry.insert(pairs.len());
+                        pairs.push((key, replacement));
                     }
                 }
             }
         }
 
-        info!("Loaded {} replacement rules from {:?}", rules.len(), path);
-        Ok(Self { rules })
+        Ok(Self::from_pairs(pairs))
     }
 
+    /// Build an engine from `(phrase, replacement)` pairs. Phrases match
+    /// case-insensitively on word boundaries. For proper nouns such as
+    /// "iphone" -> "iPhone", a trailing possessive or plural ending also
+    /// matches and is kept after the replacement.
+    pub fn from_pairs<K, V>(pairs: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        let mut rules: Vec<(String, String)> = pairs
+            .into_iter()
+            .map(|(k, v)| (k.as_ref().to_string(), v.into()))
+            .collect();
+        // Longest phrase first, so "new paragraph" wins over "new" at the
+        // same position; the alternation picks the first branch that matches
+        rules.sort_by_key(|(key, _)| std::cmp::Reverse(key.chars().count()));
+
+        // Capture groups force a slower regex engine, so the scan uses a
+        // group-free alternation and only falls back to groups when needed
+        let alternation = |group: &str| {
+            rules
+                .iter()
+                .map(|(key, replacement)| rule_pattern(key, replacement, group))
+                .collect::<Vec<_>>()
+                .join("|")
+        };
+        let build = |pattern: String| {
+            RegexBuilder::new(&pattern)
+                .case_insensitive(true)
+                .size_limit(COMBINED_SIZE_LIMIT)
+                .build()
+        };
+
+        let (combined, grouped) = if rules.is_empty() {
+            (None, None)
+        } else {
+            match (build(alternation("?:")), build(alternation(""))) {
+                (Ok(combined), Ok(grouped)) => (Some(combined), Some(grouped)),
+                (Err(e), _) | (_, Err(e)) => {
+                    warn!("Combined replacement pattern too large, applying rules one by one: {}", e);
+                    (None, None)
+                }
+            }
+        };
+
+        let mut by_phrase = HashMap::new();
+        for (i, (key, _)) in rules.iter().enumerate() {
+            // Keep the first (longest-first order) rule for duplicate spellings
+            by_phrase.entry(key.to_lowercase()).or_insert(i);
+        }
+
+        let fallback = if combined.is_none() {
+            rules
+                .iter()
+                .filter_map(|(key, replacement)| {
+                    let pattern = format!("(?i){}", rule_pattern(key, replacement, ""));
+                    match Regex::new(&pattern) {
+                        Ok(re) => Some((re, replacement.clone())),
+                        Err(e) => {
+                            warn!("Invalid replacement pattern for {:?}: {}", key, e);
+                            None
+                        }
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Self {
+            combined,
+            grouped,
+            by_phrase,
+            replacements: rules.into_iter().map(|(_, r)| r).collect(),
+            fallback,
+        }
+    }
+
+    /// Number of loaded rules.
+    pub fn len(&self) -> usize {
+        self.replacements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.replacements.is_empty()
+    }
+
+    /// Replace every phrase in a single pass over the text. Replaced text is
+    /// never matched again, so one rule's output cannot trigger another.
     pub fn apply(&self, text: &str) -> String {
-        let mut result = text.to_string();
-        for (pattern, replacement) in &self.rules {
-            result = pattern
-                .replace_all(&result, replacement.as_str())
-                .to_string();
+        let Some(ref combined) = self.combined else {
+            let mut result = text.to_string();
+            for (pattern, replacement) in &self.fallback {
+                result = pattern
+                    .replace_all(&result, |caps: &regex::Captures| {
+                        // Group 1 is the phrase; anything after it is a kept suffix
+                        format!("{}{}", replacement, &caps[0][caps[1].len()..])
+                    })
+                    .to_string();
+            }
+            return result;
+        };
+
+        let mut result = String::with_capacity(text.len());
+        let mut last = 0;
+        for m in combined.find_iter(text) {
+            result.push_str(&text[last..m.start()]);
+            match self.rule_for_match(text, m) {
+                Some((i, stem_end)) => {
+                    result.push_str(&self.replacements[i]);
+                    result.push_str(&text[stem_end..m.end()]);
+                }
+                None => result.push_str(m.as_str()),
+            }
+            last = m.end();
         }
+        result.push_str(&text[last..]);
         result
     }
 
-    fn create_default(path: &Path) -> Result<()> {
+    /// Find which rule produced a match of the combined pattern, and where
+    /// its phrase ends in `text`; a kept suffix follows up to the match end.
+    fn rule_for_match(&self, text: &str, m: regex::Match) -> Option<(usize, usize)> {
+        if let Some(&i) = self.by_phrase.get(&m.as_str().to_lowercase()) {
+            return Some((i, m.end()));
+        }
+        // A suffix was matched, or case folding matched a spelling that
+        // lowercases differently (e.g. the long s); re-run with groups from
+        // the match position
+        let caps = self.grouped.as_ref()?.captures_at(text, m.start())?;
+        (1..caps.len()).find_map(|i| caps.get(i).map(|stem| (i - 1, stem.end())))
+    }
+
+    /// Write the built-in rules to `path`, keeping an existing file as
+    /// `<path>.bak` (replacing an older backup). Returns the backup's path.
+    pub fn reset(path: &Path) -> Result<Option<PathBuf>> {
+        let backup = if path.exists() {
+            let mut backup = path.as_os_str().to_owned();
+            backup.push(".bak");
+            let backup = PathBuf::from(backup);
+            std::fs::copy(path, &backup)
+                .with_context(|| format!("Failed to back up replacements file to {:?}", backup))?;
+            Some(backup)
+        } else {
+            None
+        };
+        Self::create_default(path)?;
+        info!("Replacements file {:?} reset to defaults", path);
+        Ok(backup)
+    }
+
+    /// Write the built-in rules to `path`, creating its directory.
+    pub fn create_default(path: &Path) -> Result<()> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
@@ -78,18 +238,144 @@ impl ReplacementEngine {
     }
 }
 
+/// The `(phrase, replacement)` rules of one replacements file, in file order.
+fn read_rules(path: &Path) -> Result<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read replacements file: {:?}", path))?;
+
+    let table: toml::Value = toml::from_str(&content)
+        .with_context(|| format!("Failed to parse replacements file: {:?}", path))?;
+
+    let replacements = table
+        .get("replacements")
+        .and_then(|v| v.as_table())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut pairs = Vec::new();
+    for (key, value) in &replacements {
+        match value.as_str() {
+            Some(replacement) if !key.is_empty() => {
+                debug!("Loaded replacement rule: {:?} -> {:?}", key, replacement);
+                pairs.push((key.clone(), replacement.to_string()));
+            }
+            _ => warn!("Ignoring replacement rule {:?}: expected a string", key),
+        }
+    }
+
+    info!("Loaded {} replacement rules from {:?}", pairs.len(), path);
+    Ok(pairs)
+}
+
+/// Pattern for one rule: the phrase on a word boundary, wrapped in a group
+/// opened with `group` (`""` to capture, `"?:"` not to), followed by an
+/// optional suffix when the rule spells a proper noun: phrase and replacement
+/// end in a letter and the replacement has a capital.
+fn rule_pattern(key: &str, replacement: &str, group: &str) -> String {
+    let ends_in_letter = |s: &str| s.chars().last().is_some_and(char::is_alphabetic);
+    let proper_noun = ends_in_letter(key)
+        && ends_in_letter(replacement)
+        && replacement.chars().any(char::is_uppercase);
+    let suffix = if proper_noun {
+        WORD_SUFFIXES
+    } else {
+        ""
+    };
+    format!(r"\b({}{}){}\b", group, regex::escape(key), suffix)
+}
+
+/// Tidy the spacing left behind by spoken punctuation: drop spaces before
+/// `,.;:!?` and line breaks and after line breaks, and collapse runs of
+/// spaces into one. Tabs and line breaks themselves are kept.
+pub fn normalize_spacing(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut pending_space = false;
+    for c in text.chars() {
+        if c == ' ' {
+            pending_space = true;
+            continue;
+        }
+        if std::mem::take(&mut pending_space)
+            && !matches!(c, ',' | '.' | ';' | ':' | '!' | '?' | '\n')
+            && !result.is_empty()
+            && !result.ends_with('\n')
+        {
+            result.push(' ');
+        }
+        result.push(c);
+    }
+    if pending_space && !result.is_empty() && !result.ends_with('\n') {
+        result.push(' ');
+    }
+    result
+}
+
+/// Capitalize the first letter after a sentence-ending `.`, `?` or `!`
+/// followed by whitespace, as left by spoken "period" or "question mark".
+/// Punctuation inside a word ("file.txt", "3.5") is left alone.
+pub fn capitalize_sentences(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut sentence_end = false;
+    let mut gap = false;
+    for c in text.chars() {
+        if sentence_end && gap && c.is_alphabetic() {
+            result.extend(c.to_uppercase());
+            sentence_end = false;
+            continue;
+        }
+        match c {
+            '.' | '?' | '!' => {
+                sentence_end = true;
+                gap = false;
+            }
+            c if c.is_whitespace() => gap |= sentence_end,
+            _ => sentence_end = false,
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// Whether a transcript is noise whisper tends to hallucinate rather than
+/// speech: punctuation only (when `drop_punctuation_only` is set), or, once
+/// case and punctuation are ignored, exactly one of the `denylist` phrases.
+pub fn is_junk(text: &str, denylist: &[String], drop_punctuation_only: bool) -> bool {
+    let words = |s: &str| -> Vec<String> {
+        s.split(|c: char| !c.is_alphanumeric() && c != '\'')
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let spoken = words(text);
+    if spoken.is_empty() {
+        return drop_punctuation_only && !text.trim().is_empty();
+    }
+    denylist.iter().any(|phrase| words(phrase) == spoken)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_is_junk() {
+        let denylist = vec!["you".to_string(), "Thank you".to_string()];
+        assert!(is_junk(".", &denylist, true));
+        assert!(is_junk(" ... ", &denylist, true));
+        assert!(is_junk("Thank you.", &denylist, true));
+        assert!(is_junk(" you", &denylist, true));
+        assert!(is_junk("THANK YOU!", &denylist, false));
+
+        assert!(!is_junk(".", &denylist, false));
+        assert!(!is_junk("Thank you for the report.", &denylist, true));
+        assert!(!is_junk("Yes.", &denylist, true));
+        assert!(!is_junk("Thank you.", &[], true));
+    }
+
     #[test]
     fn test_replacement_engine_apply() {
-        let rules = vec![
-            (Regex::new(r"(?i)\bperiod\b").unwrap(), ".".to_string()),
-            (Regex::new(r"(?i)\bcomma\b").unwrap(), ",".to_string()),
-        ];
-        let engine = ReplacementEngine { rules };
+        let engine = ReplacementEngine::from_pairs([("period", "."), ("comma", ",")]);
 
         let result = engine.apply("Hello period world comma");
         assert_eq!(result, "Hello . world ,");
@@ -97,8 +383,7 @@ mod tests {
 
     #[test]
     fn test_replacement_engine_case_insensitive() {
-        let rules = vec![(Regex::new(r"(?i)\bperiod\b").unwrap(), ".".to_string())];
-        let engine = ReplacementEngine { rules };
+        let engine = ReplacementEngine::from_pairs([("period", ".")]);
 
         let result = engine.apply("Hello PERIOD world Period");
         assert_eq!(result, "Hello . world .");
@@ -106,8 +391,7 @@ mod tests {
 
     #[test]
     fn test_replacement_engine_whole_word_only() {
-        let rules = vec![(Regex::new(r"(?i)\bperiod\b").unwrap(), ".".to_string())];
-        let engine = ReplacementEngine { rules };
+        let engine = ReplacementEngine::from_pairs([("period", ".")]);
 
         // "periods" should not be replaced because of word boundary
         // Only "period" should be replaced
@@ -117,14 +401,8 @@ mod tests {
 
     #[test]
     fn test_replacement_engine_special_chars() {
-        let rules = vec![
-            (Regex::new(r"(?i)\bnew line\b").unwrap(), "\n".to_string()),
-            (
-                Regex::new(r"(?i)\bnew paragraph\b").unwrap(),
-                "\n\n".to_string(),
-            ),
-        ];
-        let engine = ReplacementEngine { rules };
+        let engine =
+            ReplacementEngine::from_pairs([("new line", "\n"), ("new paragraph", "\n\n")]);
 
         let result = engine.apply("Line one new line Line two new paragraph Line three");
         assert_eq!(result, "Line one \n Line two \n\n Line three");
@@ -132,7 +410,7 @@ mod tests {
 
     #[test]
     fn test_replacement_engine_empty() {
-        let engine = ReplacementEngine { rules: vec![] };
+        let engine = ReplacementEngine::from_pairs(Vec::<(String, String)>::new());
         let text = "Hello world";
         let result = engine.apply(text);
         assert_eq!(result, text);
@@ -150,22 +428,185 @@ mod tests {
 "#;
         std::fs::write(&path, content).unwrap();
 
-        let engine = ReplacementEngine::load(&path).unwrap();
+        let engine = ReplacementEngine::load(&[&path]).unwrap();
         let result = engine.apply("test hello world");
         assert_eq!(result, "replaced hi world");
     }
 
+    #[test]
+    fn test_replacement_engine_later_file_overrides() {
+        let temp_dir = TempDir::new().unwrap();
+        let team = temp_dir.path().join("team.toml");
+        let personal = temp_dir.path().join("personal.toml");
+        std::fs::write(&team, "[replacements]\n\"acme\" = \"ACME\"\n\"kube\" = \"Kubernetes\"\n")
+            .unwrap();
+        std::fs::write(&personal, "[replacements]\n\"Kube\" = \"k8s\"\n\"tj\" = \"TJ\"\n").unwrap();
+
+        let engine = ReplacementEngine::load(&[&team, &personal]).unwrap();
+        assert_eq!(engine.apply("acme runs kube says tj"), "ACME runs k8s says TJ");
+
+        // A missing shared file is skipped rather than created
+        let missing = temp_dir.path().join("missing.toml");
+        let engine = ReplacementEngine::load(&[&missing, &personal]).unwrap();
+        assert_eq!(engine.apply("acme runs kube"), "acme runs k8s");
+        assert!(!missing.exists());
+    }
+
     #[test]
     fn test_replacement_engine_creates_default() {
         let temp_dir = TempDir::new().unwrap();
         let path = temp_dir.path().join("replacements.toml");
 
         // Should create default file
-        let engine = ReplacementEngine::load(&path).unwrap();
+        let engine = ReplacementEngine::load(&[&path]).unwrap();
         assert!(path.exists());
 
         // Verify default works
         let result = engine.apply("Hello period world comma");
         assert_eq!(result, "Hello . world ,");
     }
+
+    #[test]
+    fn test_replacement_engine_reset() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("replacements.toml");
+
+        // Nothing to back up yet
+        assert_eq!(ReplacementEngine::reset(&path).unwrap(), None);
+        assert!(path.exists());
+
+        std::fs::write(&path, "[replacements\nbroken").unwrap();
+        let backup = ReplacementEngine::reset(&path).unwrap().unwrap();
+        assert_eq!(backup, temp_dir.path().join("replacements.toml.bak"));
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), "[replacements\nbroken");
+
+        let engine = ReplacementEngine::load(&[&path]).unwrap();
+        assert_eq!(engine.apply("Hello period"), "Hello .");
+    }
+
+    #[test]
+    fn test_replacement_engine_longest_match() {
+        let engine = ReplacementEngine::from_pairs([
+            ("line", "LINE"),
+            ("new line", "\n"),
+            ("new", "NEW"),
+        ]);
+        assert_eq!(engine.apply("a new line and a new idea on one line"), "a \n and a NEW idea on one LINE");
+    }
+
+    #[test]
+    fn test_normalize_spacing_punctuation() {
+        let engine = ReplacementEngine::from_pairs([
+            ("comma", ","),
+            ("period", "."),
+            ("question mark", "?"),
+        ]);
+        assert_eq!(normalize_spacing(&engine.apply("word comma word")), "word, word");
+        assert_eq!(
+            normalize_spacing(&engine.apply("Hello comma world period Really question mark")),
+            "Hello, world. Really?"
+        );
+        assert_eq!(normalize_spacing("a  ;  b : c !"), "a; b: c!");
+    }
+
+    #[test]
+    fn test_capitalize_after_spoken_punctuation() {
+        let engine = ReplacementEngine::from_pairs([
+            ("period", "."),
+            ("question mark", "?"),
+            ("exclamation mark", "!"),
+            ("new line", "\n"),
+        ]);
+        let format = |s: &str| capitalize_sentences(&normalize_spacing(&engine.apply(s)));
+        assert_eq!(
+            format("it works period does it question mark yes exclamation mark"),
+            "it works. Does it? Yes!"
+        );
+        assert_eq!(format("first period new line second"), "first.\nSecond");
+        // Without spacing normalization the detached punctuation still counts
+        assert_eq!(capitalize_sentences(&engine.apply("done period next")), "done . Next");
+        assert_eq!(capitalize_sentences("see file.txt or 3.5 ... ok"), "see file.txt or 3.5 ... Ok");
+        assert_eq!(capitalize_sentences("wait... what?! élan"), "wait... What?! Élan");
+    }
+
+    #[test]
+    fn test_normalize_spacing_lines_and_edges() {
+        let engine = ReplacementEngine::from_pairs([("new line", "\n"), ("new paragraph", "\n\n")]);
+        assert_eq!(
+            normalize_spacing(&engine.apply("Line one new line Line two new paragraph Line three")),
+            "Line one\nLine two\n\nLine three"
+        );
+        assert_eq!(normalize_spacing("  leading and trailing  "), "leading and trailing ");
+        assert_eq!(normalize_spacing("keep\ttabs"), "keep\ttabs");
+        assert_eq!(normalize_spacing(""), "");
+    }
+
+    #[test]
+    fn test_replacement_engine_single_pass() {
+        // The output of one rule is not fed into another
+        let engine = ReplacementEngine::from_pairs([("comma", "period"), ("period", ".")]);
+        assert_eq!(engine.apply("comma period"), "period .");
+    }
+
+    #[test]
+    fn test_replacement_engine_word_boundary_falls_back_to_shorter() {
+        // "new lines" fails the boundary after "new line", so "new" still matches
+        let engine = ReplacementEngine::from_pairs([("new line", "\n"), ("new", "NEW")]);
+        assert_eq!(engine.apply("two new lines"), "two NEW lines");
+    }
+
+    #[test]
+    fn test_replacement_engine_keeps_suffixes() {
+        let engine = ReplacementEngine::from_pairs([
+            ("iphone", "iPhone"),
+            ("github", "GitHub"),
+            ("new line", "\n"),
+            ("ok", "okay"),
+        ]);
+        assert_eq!(engine.apply("my iphone's screen"), "my iPhone's screen");
+        assert_eq!(engine.apply("two iphones and IPHONE\u{2019}s case"), "two iPhones and iPhone\u{2019}s case");
+        assert_eq!(engine.apply("the iphones' cables"), "the iPhones' cables");
+        assert_eq!(engine.apply("githubs"), "GitHubs");
+        // Other suffixes still fail the word boundary
+        assert_eq!(engine.apply("iphoney iphoned"), "iphoney iphoned");
+        // Punctuation and lowercase rules are left as they were
+        assert_eq!(engine.apply("new lines"), "new lines");
+        assert_eq!(engine.apply("oks"), "oks");
+    }
+
+    #[test]
+    fn test_replacement_engine_suffixes_without_combined_pattern() {
+        let engine = ReplacementEngine::from_pairs([("iphone", "iPhone"), ("dollar", "$1")]);
+        let fallback = ReplacementEngine {
+            combined: None,
+            grouped: None,
+            fallback: ["iphone", "dollar"]
+                .iter()
+                .zip(&engine.replacements)
+                .map(|(key, replacement)| {
+                    let re = Regex::new(&format!("(?i){}", rule_pattern(key, replacement, ""))).unwrap();
+                    (re, replacement.clone())
+                })
+                .collect(),
+            ..engine
+        };
+        assert_eq!(fallback.apply("iphone's dollar"), "iPhone's $1");
+    }
+
+    #[test]
+    fn test_replacement_engine_case_folding() {
+        // U+017F LATIN SMALL LETTER LONG S folds to "s" but is already lowercase
+        let engine = ReplacementEngine::from_pairs([("yes", "Yes!")]);
+        assert_eq!(engine.apply("ye\u{17F} please"), "Yes! please");
+    }
+
+    #[test]
+    fn test_replacement_engine_many_rules() {
+        let pairs: Vec<(String, String)> = (0..500)
+            .map(|i| (format!("word{}", i), format!("<{}>", i)))
+            .collect();
+        let engine = ReplacementEngine::from_pairs(pairs);
+        assert_eq!(engine.len(), 500);
+        assert_eq!(engine.apply("WORD7 and word499 but not word5000"), "<7> and <499> but not word5000");
+    }
 }
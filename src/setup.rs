@@ -0,0 +1,228 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
+
+use crate::backend::Backend;
+use crate::config::{Config, TranscriptionEngine};
+use crate::replacements::ReplacementEngine;
+use crate::transcription::TranscriptionService;
+
+/// How to trigger dictation once the daemon is running
+pub const HOTKEY_HINT: &str = "Bind 'tjvox toggle' to a key";
+/// Where KDE Plasma keeps custom shortcuts
+pub const KDE_HOTKEY_HINT: &str = "KDE Settings → Shortcuts → Custom";
+
+/// An external tool tjvox shells out to.
+struct Tool {
+    binary: &'static str,
+    package: &'static str,
+    purpose: &'static str,
+    required: bool,
+}
+
+const TOOLS: &[Tool] = &[
+    Tool {
+        binary: "ydotoold",
+        package: "ydotool",
+        purpose: "keystroke injection daemon (default output backend)",
+        required: true,
+    },
+    Tool {
+        binary: "ydotool",
+        package: "ydotool",
+        purpose: "paste keystrokes",
+        required: true,
+    },
+    Tool {
+        binary: "wl-copy",
+        package: "wl-clipboard",
+        purpose: "clipboard fallback",
+        required: false,
+    },
+    Tool {
+        binary: "notify-send",
+        package: "libnotify",
+        purpose: "desktop notifications",
+        required: false,
+    },
+    Tool {
+        binary: "wtype",
+        package: "wtype",
+        purpose: "virtual_keyboard output backend (wlroots)",
+        required: false,
+    },
+];
+
+fn check_tools() {
+    println!("Checking tools:");
+    let mut missing_required = false;
+    for tool in TOOLS {
+        let found = which::which(tool.binary).is_ok();
+        let mark = if found { "✓" } else { "✗" };
+        println!("  {} {:<12} {}", mark, tool.binary, tool.purpose);
+        if !found {
+            println!("      install the '{}' package", tool.package);
+            missing_required |= tool.required;
+        }
+    }
+    if missing_required {
+        println!("  Some required tools are missing; text output will not work until they are installed.");
+    }
+    if !Path::new("/dev/uinput").exists() {
+        println!("  ✗ /dev/uinput not found; ydotoold needs the uinput kernel module");
+    }
+}
+
+/// Print the compiled whisper backend and whether a device for it was found.
+pub fn print_backend_info() {
+    let backend = Backend::compiled();
+    println!("  Backend: {}", backend);
+    if backend.is_gpu() {
+        match backend.detect_device() {
+            Some(device) => println!("  ✓ GPU detected: {}", device),
+            None => println!("  ✗ No {} device detected; model loading will fail", backend),
+        }
+    } else {
+        println!("  Built without GPU acceleration (features: cuda, vulkan, metal, hipblas)");
+    }
+}
+
+/// Report the build, acceleration backend, tools, config and model without
+/// changing anything.
+pub async fn run_doctor(config_path: &Path) -> Result<()> {
+    println!("TJvox doctor\n");
+
+    println!("Build:");
+    println!("  Version: {}", env!("CARGO_PKG_VERSION"));
+    print_backend_info();
+    println!();
+
+    check_tools();
+
+    println!("\nConfiguration:");
+    if !config_path.exists() {
+        println!("  ✗ No config at {}; run 'tjvox setup'", config_path.display());
+        return Ok(());
+    }
+    let config = Config::load(config_path).await?;
+    println!("  ✓ Using config {}", config_path.display());
+    if Backend::compiled().is_gpu() && !config.transcription.use_gpu {
+        println!("  GPU disabled by transcription.use_gpu = false");
+    }
+
+    if config.transcription.engine == TranscriptionEngine::Vosk {
+        println!("\nVosk model:");
+        let dir = config.transcription.vosk_model.as_deref().unwrap_or_default();
+        if !cfg!(feature = "vosk") {
+            println!("  ✗ engine = \"vosk\" but this build lacks the vosk feature");
+        } else if Path::new(dir).is_dir() {
+            println!("  ✓ Using {}", dir);
+        } else {
+            println!("  ✗ Model directory '{}' not found", dir);
+        }
+        return Ok(());
+    }
+
+    println!("\nWhisper model:");
+    let transcriber = TranscriptionService::new(&config.transcription)?;
+    if transcriber.model_path().exists() {
+        println!("  ✓ Model '{}' is installed", config.transcription.model);
+    } else {
+        println!("  ✗ Model '{}' is not downloaded yet", config.transcription.model);
+    }
+    Ok(())
+}
+
+/// Guided first-run setup: check tools, write config and replacements,
+/// offer a model download and print hotkey instructions.
+pub async fn run_setup(config_path: &Path) -> Result<()> {
+    println!("TJvox setup\n");
+
+    check_tools();
+
+    println!("\nConfiguration:");
+    let config_existed = config_path.exists();
+    let config = Config::load(config_path).await?;
+    if config_existed {
+        println!("  ✓ Using existing config {}", config_path.display());
+    } else {
+        println!("  ✓ Wrote starter config {}", config_path.display());
+    }
+
+    let replacements_paths = config.replacements.file.paths();
+    let replacements_path = replacements_paths
+        .last()
+        .context("replacements.file names no file")?;
+    let replacements_existed = replacements_path.exists();
+    ReplacementEngine::load(&replacements_paths)?;
+    if replacements_existed {
+        println!("  ✓ Using existing replacements {}", replacements_path.display());
+    } else {
+        println!("  ✓ Wrote starter replacements {}", replacements_path.display());
+    }
+
+    println!("\nWhisper model:");
+    let mut transcriber = TranscriptionService::new(&config.transcription)?;
+    if transcriber.model_path().exists() {
+        println!("  ✓ Model '{}' is installed", config.transcription.model);
+    } else if prompt_yes_no(
+        &format!("  Download model '{}' now?", config.transcription.model),
+        true,
+    )? {
+        transcriber.ensure_model().await?;
+        println!("  ✓ Model '{}' downloaded", config.transcription.model);
+    } else {
+        println!("  Skipped; the model is downloaded on first use");
+    }
+
+    println!("\nHotkey:");
+    println!("  {}", HOTKEY_HINT);
+    println!("  {}", KDE_HOTKEY_HINT);
+    println!("  Then start the daemon with 'tjvox' (GUI) or 'tjvox daemon'.");
+    Ok(())
+}
+
+pub fn prompt_yes_no(question: &str, default: bool) -> Result<bool> {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    print!("{} {} ", question, hint);
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(parse_yes_no(&answer, default))
+}
+
+/// Interpret a y/n answer; anything unrecognised yields the default.
+fn parse_yes_no(answer: &str, default: bool) -> bool {
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_yes_no() {
+        assert!(parse_yes_no("y\n", false));
+        assert!(parse_yes_no("YES", false));
+        assert!(!parse_yes_no("n", true));
+        assert!(!parse_yes_no(" no ", true));
+    }
+
+    #[test]
+    fn test_parse_yes_no_default() {
+        assert!(parse_yes_no("\n", true));
+        assert!(!parse_yes_no("", false));
+        assert!(parse_yes_no("maybe", true));
+    }
+
+    #[test]
+    fn test_required_tools_listed() {
+        assert!(TOOLS.iter().any(|t| t.binary == "ydotoold" && t.required));
+        assert!(TOOLS.iter().all(|t| !t.binary.is_empty() && !t.package.is_empty()));
+    }
+}
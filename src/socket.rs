@@ -1,19 +1,111 @@
 use anyhow::{Context, Result};
-use tokio::io::AsyncWriteExt;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
 use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
 use tracing::{debug, info, warn};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum SocketCommand {
-    Toggle,
-    PushStart,
+    /// Start or stop recording; the options apply if this starts one
+    Toggle(SessionOptions),
+    PushStart(SessionOptions),
     PushStop,
     Status,
+    /// State and last error as a line of JSON
+    StatusJson,
+    /// Enable or disable LLM post-processing for this daemon session
+    SetLlm(bool),
+    /// Show or hide the overlay regardless of the recording state
+    ShowOverlay,
+    HideOverlay,
+    /// Most recent history entries as JSON, read through the daemon's own
+    /// database handle
+    History(u32),
+    /// Output the last failed dictation again
+    RetryLast,
+    /// Type dictations (`true`) or only save them to history (`false`)
+    SetOutput(bool),
+    /// Override the paste keystroke until the daemon exits; `None` restores
+    /// `output.paste_keys`
+    SetPasteKeys(Option<String>),
+    /// Move the history to this file and continue with an empty database
+    HistoryArchive(PathBuf),
+    /// Transcribe this audio file with the daemon's model and answer with
+    /// the result as JSON
+    Transcribe(PathBuf),
     Quit,
 }
 
+/// Post-processing overrides for a single dictation, given as flags after
+/// `toggle` or `push-start`, e.g. `push-start --verbatim`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionOptions {
+    /// Keep filler words despite `transcription.remove_filler_words`
+    pub keep_fillers: bool,
+    /// Skip the replacement rules
+    pub no_replacements: bool,
+    /// Skip `output.append_trailing_space`
+    pub no_trailing_space: bool,
+}
+
+impl SessionOptions {
+    /// Every override, so the text is typed as transcribed
+    pub const VERBATIM: Self = Self {
+        keep_fillers: true,
+        no_replacements: true,
+        no_trailing_space: true,
+    };
+
+    /// Options from whitespace-separated flags.
+    pub fn parse(flags: &str) -> Result<Self> {
+        let mut options = Self::default();
+        for flag in flags.split_whitespace() {
+            match flag {
+                "--verbatim" => options = Self::VERBATIM,
+                "--keep-fillers" => options.keep_fillers = true,
+                "--no-replacements" => options.no_replacements = true,
+                "--no-trailing-space" => options.no_trailing_space = true,
+                other => return Err(anyhow::anyhow!("Unknown session flag: {}", other)),
+            }
+        }
+        Ok(options)
+    }
+
+    /// The options as flags for a socket command, each after a space.
+    pub fn to_flags(self) -> String {
+        if self == Self::VERBATIM {
+            return " --verbatim".to_string();
+        }
+        let mut flags = String::new();
+        for (set, flag) in [
+            (self.keep_fillers, " --keep-fillers"),
+            (self.no_replacements, " --no-replacements"),
+            (self.no_trailing_space, " --no-trailing-space"),
+        ] {
+            if set {
+                flags.push_str(flag);
+            }
+        }
+        flags
+    }
+}
+
+/// A command read from a client, and where its one-line answer goes.
+pub struct SocketRequest {
+    pub command: SocketCommand,
+    pub reply: oneshot::Sender<String>,
+}
+
+/// Accepts clients in the background. Each connection is read by its own
+/// task, which forwards one command at a time and waits for its answer, so
+/// a client can keep sending commands on the same connection.
 pub struct SocketServer {
-    listener: UnixListener,
+    path: PathBuf,
+    requests: mpsc::Receiver<SocketRequest>,
+    accept_task: JoinHandle<()>,
 }
 
 fn socket_path() -> std::path::PathBuf {
@@ -34,71 +126,168 @@ impl SocketServer {
             .with_context(|| format!("Failed to bind Unix socket at {:?}", path))?;
 
         info!("Socket server listening at {:?}", path);
-        Ok(Self { listener })
+        Ok(Self::serve(listener, path))
     }
 
-    pub async fn accept(&self) -> Result<(SocketCommand, UnixStream)> {
-        let (stream, _addr) = self.listener.accept().await?;
-
-        // Read the command line without consuming the stream
-        let line = read_line(&stream).await?;
-
-        let cmd = match line.trim() {
-            "toggle" => SocketCommand::Toggle,
-            "push-start" => SocketCommand::PushStart,
-            "push-stop" => SocketCommand::PushStop,
-            "status" => SocketCommand::Status,
-            "quit" => SocketCommand::Quit,
-            other => {
-                warn!("Unknown socket command: {:?}", other);
-                return Err(anyhow::anyhow!("Unknown command: {}", other));
+    fn serve(listener: UnixListener, path: PathBuf) -> Self {
+        let (tx, requests) = mpsc::channel(REQUEST_QUEUE);
+        let accept_task = tokio::spawn(async move {
+            loop {
+                let (stream, _addr) =
+                    accept_with_backoff("Socket server", || listener.accept()).await;
+                tokio::spawn(serve_connection(stream, tx.clone()));
             }
-        };
+        });
+        Self {
+            path,
+            requests,
+            accept_task,
+        }
+    }
 
-        debug!("Received socket command: {:?}", cmd);
-        Ok((cmd, stream))
+    /// The next command from any client.
+    pub async fn next_request(&mut self) -> Option<SocketRequest> {
+        self.requests.recv().await
     }
 
     pub fn cleanup(&self) {
-        let path = socket_path();
-        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(&self.path).ok();
     }
 }
 
 impl Drop for SocketServer {
     fn drop(&mut self) {
+        self.accept_task.abort();
         self.cleanup();
     }
 }
 
+/// Read commands from one client until it hangs up, answering each before
+/// reading the next. Malformed commands are answered with an error.
+async fn serve_connection(stream: UnixStream, requests: mpsc::Sender<SocketRequest>) {
+    let (mut reader, mut writer) = stream.into_split();
+    let mut lines = LineReader::new(MAX_LINE_LENGTH);
+    loop {
+        let line = match lines.read_line(&mut reader).await {
+            Ok(Some(line)) => line,
+            Ok(None) => return,
+            Err(e) => {
+                let _ = writer.write_all(format!("error: {}\n", e).as_bytes()).await;
+                return;
+            }
+        };
+        let response = match parse_command(&line) {
+            Ok(command) => {
+                debug!("Received socket command: {:?}", command);
+                let (reply, answer) = oneshot::channel();
+                if requests.send(SocketRequest { command, reply }).await.is_err() {
+                    return;
+                }
+                match answer.await {
+                    Ok(response) => response,
+                    // The daemon is shutting down
+                    Err(_) => return,
+                }
+            }
+            Err(e) => {
+                warn!("Unknown socket command: {:?}", line.trim());
+                format!("error: {}", e)
+            }
+        };
+        if writer.write_all(format!("{}\n", response).as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Commands waiting for the daemon, across all connections
+const REQUEST_QUEUE: usize = 16;
+
+/// Pause after a failed accept before trying again
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// The next connection from `accept`, which is retried after a pause when it
+/// fails: errors such as EMFILE persist, so retrying at once would spin.
+/// Shared by the socket server and the HTTP endpoints; `name` labels log
+/// messages.
+pub(crate) async fn accept_with_backoff<T, F, Fut>(name: &str, mut accept: F) -> T
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = std::io::Result<T>>,
+{
+    loop {
+        match accept().await {
+            Ok(connection) => return connection,
+            Err(e) => {
+                warn!("{} could not accept a connection: {}", name, e);
+                tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+            }
+        }
+    }
+}
+
 /// Maximum allowed command line length (prevents unbounded memory allocation).
 const MAX_LINE_LENGTH: usize = 1024;
 
-async fn read_line(stream: &UnixStream) -> Result<String> {
-    let mut buf = Vec::with_capacity(128);
-    loop {
-        stream.readable().await?;
-        let mut tmp = [0u8; 128];
-        match stream.try_read(&mut tmp) {
-            Ok(0) => break,
-            Ok(n) => {
-                buf.extend_from_slice(&tmp[..n]);
-                if buf.len() > MAX_LINE_LENGTH {
-                    return Err(anyhow::anyhow!(
-                        "Command too long ({} bytes, max {})",
-                        buf.len(),
-                        MAX_LINE_LENGTH
-                    ));
-                }
-                if buf.contains(&b'\n') {
-                    break;
+/// Maximum response length accepted by `send_command`; history responses
+/// can be much longer than a command.
+const MAX_RESPONSE_LENGTH: usize = 4 * 1024 * 1024;
+
+/// Entries returned when `history` is sent without a limit
+const DEFAULT_HISTORY_LIMIT: u32 = 20;
+
+/// Upper bound on the `history` limit
+const MAX_HISTORY_LIMIT: u32 = 1000;
+
+/// Splits what a client sends into lines. Bytes after a newline are kept
+/// for the next line, so commands written together or split across writes
+/// are each read whole.
+pub struct LineReader {
+    buf: Vec<u8>,
+    max_length: usize,
+}
+
+impl LineReader {
+    pub fn new(max_length: usize) -> Self {
+        Self {
+            buf: Vec::with_capacity(128),
+            max_length,
+        }
+    }
+
+    /// The next line without its newline, reading from `stream` until one
+    /// is complete. An unterminated line at the end of the stream is
+    /// returned as the last one; after that the result is `None`.
+    pub async fn read_line<R: AsyncRead + Unpin>(&mut self, stream: &mut R) -> Result<Option<String>> {
+        loop {
+            if let Some(line) = self.buffered_line() {
+                return Ok(Some(line));
+            }
+            if self.buf.len() > self.max_length {
+                return Err(anyhow::anyhow!(
+                    "Line too long ({} bytes, max {})",
+                    self.buf.len(),
+                    self.max_length
+                ));
+            }
+            let mut tmp = [0u8; 128];
+            match stream.read(&mut tmp).await? {
+                0 if self.buf.is_empty() => return Ok(None),
+                0 => {
+                    let rest = std::mem::take(&mut self.buf);
+                    return Ok(Some(String::from_utf8_lossy(&rest).to_string()));
                 }
+                n => self.buf.extend_from_slice(&tmp[..n]),
             }
-            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
-            Err(e) => return Err(e.into()),
         }
     }
-    Ok(String::from_utf8_lossy(&buf).to_string())
+
+    /// A complete line that has already been read, if any.
+    fn buffered_line(&mut self) -> Option<String> {
+        let end = self.buf.iter().position(|&b| b == b'\n')?;
+        let line: Vec<u8> = self.buf.drain(..=end).collect();
+        Some(String::from_utf8_lossy(&line[..end]).to_string())
+    }
 }
 
 pub async fn send_command(cmd: &str) -> Result<String> {
@@ -113,19 +302,61 @@ pub async fn send_command(cmd: &str) -> Result<String> {
     stream.flush().await?;
 
     // Read response
-    let response = read_line(&stream).await?;
+    let response = LineReader::new(MAX_RESPONSE_LENGTH)
+        .read_line(&mut stream)
+        .await?
+        .unwrap_or_default();
     Ok(response.trim().to_string())
 }
 
-/// Parse a command string into a SocketCommand (used by tests and accept).
+/// Parse a command string into a SocketCommand.
 pub fn parse_command(input: &str) -> Result<SocketCommand> {
     match input.trim() {
-        "toggle" => Ok(SocketCommand::Toggle),
-        "push-start" => Ok(SocketCommand::PushStart),
         "push-stop" => Ok(SocketCommand::PushStop),
         "status" => Ok(SocketCommand::Status),
+        "status-json" => Ok(SocketCommand::StatusJson),
         "quit" => Ok(SocketCommand::Quit),
-        other => Err(anyhow::anyhow!("Unknown command: {}", other)),
+        "set-llm on" => Ok(SocketCommand::SetLlm(true)),
+        "set-llm off" => Ok(SocketCommand::SetLlm(false)),
+        "show-overlay" => Ok(SocketCommand::ShowOverlay),
+        "hide-overlay" => Ok(SocketCommand::HideOverlay),
+        "history" => Ok(SocketCommand::History(DEFAULT_HISTORY_LIMIT)),
+        "retry-last" => Ok(SocketCommand::RetryLast),
+        "set-output on" => Ok(SocketCommand::SetOutput(true)),
+        "set-output off" => Ok(SocketCommand::SetOutput(false)),
+        other => {
+            match other.split_once(' ').unwrap_or((other, "")) {
+                ("toggle", flags) => return Ok(SocketCommand::Toggle(SessionOptions::parse(flags)?)),
+                ("push-start", flags) => {
+                    return Ok(SocketCommand::PushStart(SessionOptions::parse(flags)?));
+                }
+                _ => {}
+            }
+            if let Some(limit) = other.strip_prefix("history ") {
+                let limit: u32 = limit
+                    .trim()
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid history limit: {}", limit))?;
+                return Ok(SocketCommand::History(limit.min(MAX_HISTORY_LIMIT)));
+            }
+            if let Some(path) = other.strip_prefix("history-archive ").map(str::trim) {
+                if path.is_empty() {
+                    return Err(anyhow::anyhow!("Missing archive path"));
+                }
+                return Ok(SocketCommand::HistoryArchive(PathBuf::from(path)));
+            }
+            if let Some(path) = other.strip_prefix("transcribe ").map(str::trim) {
+                if path.is_empty() {
+                    return Err(anyhow::anyhow!("Missing audio file path"));
+                }
+                return Ok(SocketCommand::Transcribe(PathBuf::from(path)));
+            }
+            match other.strip_prefix("set-paste-keys ").map(str::trim) {
+                Some("default") => Ok(SocketCommand::SetPasteKeys(None)),
+                Some(seq) if !seq.is_empty() => Ok(SocketCommand::SetPasteKeys(Some(seq.to_string()))),
+                _ => Err(anyhow::anyhow!("Unknown command: {}", other)),
+            }
+        }
     }
 }
 
@@ -136,13 +367,38 @@ mod tests {
     #[test]
     fn test_parse_command_toggle() {
         let cmd = parse_command("toggle").unwrap();
-        assert_eq!(cmd, SocketCommand::Toggle);
+        assert_eq!(cmd, SocketCommand::Toggle(SessionOptions::default()));
     }
 
     #[test]
     fn test_parse_command_push_start() {
         let cmd = parse_command("push-start").unwrap();
-        assert_eq!(cmd, SocketCommand::PushStart);
+        assert_eq!(cmd, SocketCommand::PushStart(SessionOptions::default()));
+    }
+
+    #[test]
+    fn test_parse_command_session_flags() {
+        assert_eq!(
+            parse_command("push-start --verbatim\n").unwrap(),
+            SocketCommand::PushStart(SessionOptions::VERBATIM)
+        );
+        let options = SessionOptions {
+            keep_fillers: true,
+            no_trailing_space: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_command("toggle --keep-fillers  --no-trailing-space").unwrap(),
+            SocketCommand::Toggle(options)
+        );
+        assert!(parse_command("toggle --loud").is_err());
+        assert!(parse_command("push-stop --verbatim").is_err());
+
+        // Flags survive the round trip through `send_command`
+        for options in [SessionOptions::default(), SessionOptions::VERBATIM, options] {
+            let cmd = format!("push-start{}", options.to_flags());
+            assert_eq!(parse_command(&cmd).unwrap(), SocketCommand::PushStart(options));
+        }
     }
 
     #[test]
@@ -155,6 +411,8 @@ mod tests {
     fn test_parse_command_status() {
         let cmd = parse_command("status").unwrap();
         assert_eq!(cmd, SocketCommand::Status);
+        let cmd = parse_command("status-json").unwrap();
+        assert_eq!(cmd, SocketCommand::StatusJson);
     }
 
     #[test]
@@ -163,6 +421,75 @@ mod tests {
         assert_eq!(cmd, SocketCommand::Quit);
     }
 
+    #[test]
+    fn test_parse_command_set_llm() {
+        assert_eq!(parse_command("set-llm on").unwrap(), SocketCommand::SetLlm(true));
+        assert_eq!(parse_command("set-llm off\n").unwrap(), SocketCommand::SetLlm(false));
+        assert!(parse_command("set-llm maybe").is_err());
+        assert!(parse_command("set-llm").is_err());
+    }
+
+    #[test]
+    fn test_parse_command_overlay() {
+        assert_eq!(parse_command("show-overlay").unwrap(), SocketCommand::ShowOverlay);
+        assert_eq!(parse_command("hide-overlay\n").unwrap(), SocketCommand::HideOverlay);
+    }
+
+    #[test]
+    fn test_parse_command_history() {
+        assert_eq!(parse_command("history").unwrap(), SocketCommand::History(20));
+        assert_eq!(parse_command("history 5\n").unwrap(), SocketCommand::History(5));
+        assert_eq!(parse_command("history 99999").unwrap(), SocketCommand::History(1000));
+        assert!(parse_command("history -1").is_err());
+        assert!(parse_command("history all").is_err());
+    }
+
+    #[test]
+    fn test_parse_command_retry_last() {
+        assert_eq!(parse_command("retry-last\n").unwrap(), SocketCommand::RetryLast);
+    }
+
+    #[test]
+    fn test_parse_command_set_output() {
+        assert_eq!(parse_command("set-output off\n").unwrap(), SocketCommand::SetOutput(false));
+        assert_eq!(parse_command("set-output on").unwrap(), SocketCommand::SetOutput(true));
+        assert!(parse_command("set-output").is_err());
+    }
+
+    #[test]
+    fn test_parse_command_set_paste_keys() {
+        assert_eq!(
+            parse_command("set-paste-keys ctrl+v ctrl+v\n").unwrap(),
+            SocketCommand::SetPasteKeys(Some("ctrl+v ctrl+v".to_string()))
+        );
+        assert_eq!(
+            parse_command("set-paste-keys default").unwrap(),
+            SocketCommand::SetPasteKeys(None)
+        );
+        assert!(parse_command("set-paste-keys").is_err());
+        assert!(parse_command("set-paste-keys   ").is_err());
+    }
+
+    #[test]
+    fn test_parse_command_history_archive() {
+        assert_eq!(
+            parse_command("history-archive /tmp/old history.db\n").unwrap(),
+            SocketCommand::HistoryArchive(PathBuf::from("/tmp/old history.db"))
+        );
+        assert!(parse_command("history-archive").is_err());
+        assert!(parse_command("history-archive   ").is_err());
+    }
+
+    #[test]
+    fn test_parse_command_transcribe() {
+        assert_eq!(
+            parse_command("transcribe /home/me/memo 1.wav\n").unwrap(),
+            SocketCommand::Transcribe(PathBuf::from("/home/me/memo 1.wav"))
+        );
+        assert!(parse_command("transcribe").is_err());
+        assert!(parse_command("transcribe  ").is_err());
+    }
+
     #[test]
     fn test_parse_command_unknown() {
         let result = parse_command("foobar");
@@ -172,7 +499,7 @@ mod tests {
     #[test]
     fn test_parse_command_with_whitespace() {
         let cmd = parse_command("  toggle  ").unwrap();
-        assert_eq!(cmd, SocketCommand::Toggle);
+        assert_eq!(cmd, SocketCommand::Toggle(SessionOptions::default()));
     }
 
     #[test]
@@ -187,47 +514,115 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// A server on a socket in `dir`, and a client connected to it.
+    async fn connect(dir: &tempfile::TempDir) -> (SocketServer, UnixStream) {
+        let sock_path = dir.path().join("test.sock");
+        let listener = UnixListener::bind(&sock_path).unwrap();
+        let server = SocketServer::serve(listener, sock_path.clone());
+        let client = UnixStream::connect(&sock_path).await.unwrap();
+        (server, client)
+    }
+
+    /// Answer the next request, which must be `expected`.
+    async fn answer(server: &mut SocketServer, expected: SocketCommand, response: &str) {
+        let request = server.next_request().await.unwrap();
+        assert_eq!(request.command, expected);
+        request.reply.send(response.to_string()).unwrap();
+    }
+
     #[tokio::test]
     async fn test_socket_server_bind_and_accept() {
         let temp_dir = tempfile::TempDir::new().unwrap();
-        let sock_path = temp_dir.path().join("test.sock");
+        let (mut server, mut client) = connect(&temp_dir).await;
+        client.write_all(b"toggle\n").await.unwrap();
 
-        let listener = UnixListener::bind(&sock_path).unwrap();
-        let server = SocketServer { listener };
-
-        // Spawn a client that sends a command
-        let sock_path_clone = sock_path.clone();
-        let client = tokio::spawn(async move {
-            let mut stream = UnixStream::connect(&sock_path_clone).await.unwrap();
-            stream.write_all(b"toggle\n").await.unwrap();
-            stream.flush().await.unwrap();
-        });
+        answer(&mut server, SocketCommand::Toggle(SessionOptions::default()), "ok: recording").await;
+        let mut reader = LineReader::new(MAX_LINE_LENGTH);
+        assert_eq!(reader.read_line(&mut client).await.unwrap().as_deref(), Some("ok: recording"));
 
-        let (cmd, _stream) = server.accept().await.unwrap();
-        assert_eq!(cmd, SocketCommand::Toggle);
+        drop(server);
+        assert!(!temp_dir.path().join("test.sock").exists());
+    }
 
-        client.await.unwrap();
+    #[tokio::test]
+    async fn test_socket_two_commands_in_one_write() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (mut server, mut client) = connect(&temp_dir).await;
+        client.write_all(b"set-llm off\nstatus\n").await.unwrap();
+
+        answer(&mut server, SocketCommand::SetLlm(false), "ok: llm off").await;
+        answer(&mut server, SocketCommand::Status, "idle").await;
+        let mut reader = LineReader::new(MAX_LINE_LENGTH);
+        assert_eq!(reader.read_line(&mut client).await.unwrap().as_deref(), Some("ok: llm off"));
+        assert_eq!(reader.read_line(&mut client).await.unwrap().as_deref(), Some("idle"));
     }
 
     #[tokio::test]
-    async fn test_socket_server_unknown_command() {
+    async fn test_socket_persistent_connection() {
         let temp_dir = tempfile::TempDir::new().unwrap();
-        let sock_path = temp_dir.path().join("test.sock");
+        let (mut server, mut client) = connect(&temp_dir).await;
+        let mut reader = LineReader::new(MAX_LINE_LENGTH);
+
+        // Each command is written only after the previous answer arrived
+        client.write_all(b"push-start\n").await.unwrap();
+        answer(&mut server, SocketCommand::PushStart(SessionOptions::default()), "ok: recording")
+            .await;
+        assert_eq!(reader.read_line(&mut client).await.unwrap().as_deref(), Some("ok: recording"));
+
+        client.write_all(b"bogus\npush-stop\n").await.unwrap();
+        answer(&mut server, SocketCommand::PushStop, "ok: transcribing").await;
+        assert_eq!(
+            reader.read_line(&mut client).await.unwrap().as_deref(),
+            Some("error: Unknown command: bogus")
+        );
+        assert_eq!(
+            reader.read_line(&mut client).await.unwrap().as_deref(),
+            Some("ok: transcribing")
+        );
+    }
 
-        let listener = UnixListener::bind(&sock_path).unwrap();
-        let server = SocketServer { listener };
+    #[tokio::test]
+    async fn test_accept_with_backoff_retries() {
+        let mut attempts = 0;
+        let connection = accept_with_backoff("Test", || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(std::io::Error::from_raw_os_error(libc::EMFILE))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+        assert_eq!(connection, 3);
+    }
 
-        let sock_path_clone = sock_path.clone();
-        let client = tokio::spawn(async move {
-            let mut stream = UnixStream::connect(&sock_path_clone).await.unwrap();
-            stream.write_all(b"invalid-cmd\n").await.unwrap();
-            stream.flush().await.unwrap();
+    #[tokio::test]
+    async fn test_line_reader_split_writes() {
+        let (mut server, mut client) = UnixStream::pair().unwrap();
+        let writer = tokio::spawn(async move {
+            client.write_all(b"push-").await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            client.write_all(b"start\nhistory 5\nquit").await.unwrap();
         });
 
-        let result = server.accept().await;
-        assert!(result.is_err());
+        let mut reader = LineReader::new(MAX_LINE_LENGTH);
+        assert_eq!(reader.read_line(&mut server).await.unwrap().as_deref(), Some("push-start"));
+        assert_eq!(reader.read_line(&mut server).await.unwrap().as_deref(), Some("history 5"));
+        writer.await.unwrap();
+        // The unterminated last line still counts once the client is done
+        assert_eq!(reader.read_line(&mut server).await.unwrap().as_deref(), Some("quit"));
+        assert_eq!(reader.read_line(&mut server).await.unwrap(), None);
+    }
 
-        client.await.unwrap();
+    #[tokio::test]
+    async fn test_line_reader_too_long() {
+        let (mut server, mut client) = UnixStream::pair().unwrap();
+        client.write_all(&[b'a'; 300]).await.unwrap();
+        let mut reader = LineReader::new(200);
+        assert!(reader.read_line(&mut server).await.is_err());
     }
 
     #[test]
@@ -1,21 +1,336 @@
 use anyhow::Result;
+use async_trait::async_trait;
+use serde::Serialize;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::{debug, info, warn};
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
 
-use crate::config::TranscriptionConfig;
+use crate::audio::RecordedAudio;
+use crate::backend::{resolve_gpu, Backend};
+use crate::config::{
+    AudioConfig, MonoMode, ResampleQuality, TranscriptionConfig, TranscriptionEngine,
+    WHISPER_SAMPLE_RATE,
+};
 use crate::error::TjvoxError;
 
 const HF_BASE_URL: &str = "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
 
-/// Valid Whisper model names that can be downloaded
-const VALID_MODELS: &[&str] = &["tiny", "base", "small", "medium", "large-v3-turbo"];
+/// Receives the transcript accumulated so far each time whisper finishes a segment.
+pub type PartialCallback = Box<dyn FnMut(&str) + Send>;
+
+/// Receives whisper's progress in percent (0–100) while transcribing.
+pub type ProgressCallback = Box<dyn FnMut(i32) + Send>;
+
+/// Valid Whisper model names that can be downloaded: the multilingual
+/// models, their English-only `.en` variants and quantized `-q5_*`/`-q8_0`
+/// variants, which are smaller and faster at a small cost in accuracy
+pub const VALID_MODELS: &[&str] = &[
+    "tiny",
+    "tiny.en",
+    "tiny-q5_1",
+    "tiny.en-q5_1",
+    "tiny-q8_0",
+    "base",
+    "base.en",
+    "base-q5_1",
+    "base.en-q5_1",
+    "base-q8_0",
+    "small",
+    "small.en",
+    "small-q5_1",
+    "small.en-q5_1",
+    "small-q8_0",
+    "medium",
+    "medium.en",
+    "medium-q5_0",
+    "medium.en-q5_0",
+    "medium-q8_0",
+    "large-v3",
+    "large-v3-q5_0",
+    "large-v3-turbo",
+    "large-v3-turbo-q5_0",
+    "large-v3-turbo-q8_0",
+];
+
+/// Short names accepted for `transcription.model`, and the model they mean
+const MODEL_ALIASES: &[(&str, &str)] = &[
+    ("turbo", "large-v3-turbo"),
+    ("turbo-q5_0", "large-v3-turbo-q5_0"),
+    ("turbo-q8_0", "large-v3-turbo-q8_0"),
+    ("large", "large-v3"),
+];
+
+/// Approximate download size of the known models, in MB
+const MODEL_SIZES_MB: &[(&str, u32)] = &[
+    ("tiny", 75),
+    ("tiny.en", 75),
+    ("tiny-q5_1", 31),
+    ("tiny.en-q5_1", 31),
+    ("tiny-q8_0", 42),
+    ("base", 142),
+    ("base.en", 142),
+    ("base-q5_1", 57),
+    ("base.en-q5_1", 57),
+    ("base-q8_0", 78),
+    ("small", 466),
+    ("small.en", 466),
+    ("small-q5_1", 181),
+    ("small.en-q5_1", 181),
+    ("small-q8_0", 252),
+    ("medium", 1533),
+    ("medium.en", 1533),
+    ("medium-q5_0", 514),
+    ("medium.en-q5_0", 514),
+    ("medium-q8_0", 785),
+    ("large-v3", 3095),
+    ("large-v3-q5_0", 1081),
+    ("large-v3-turbo", 1624),
+    ("large-v3-turbo-q5_0", 547),
+    ("large-v3-turbo-q8_0", 834),
+];
+
+/// Start and end of a model download, which makes the first transcription
+/// take far longer than usual.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelDownload {
+    Started { model: String, size_mb: Option<u32> },
+    Finished { model: String },
+}
+
+impl std::fmt::Display for ModelDownload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModelDownload::Started {
+                model,
+                size_mb: Some(size),
+            } => write!(f, "Downloading {} (~{}MB)…", model, size),
+            ModelDownload::Started { model, size_mb: None } => write!(f, "Downloading {}…", model),
+            ModelDownload::Finished { .. } => write!(f, "Model ready"),
+        }
+    }
+}
+
+/// Told about model downloads, e.g. to show a notification.
+pub type DownloadCallback = Arc<dyn Fn(ModelDownload) + Send + Sync>;
 
 /// Transcription service that handles Whisper model loading and audio transcription
 pub struct TranscriptionService {
     config: TranscriptionConfig,
     context: Option<WhisperContext>,
     model_path: PathBuf,
+    mono_mode: MonoMode,
+    channel_weights: Vec<f32>,
+    preroll_ms: u32,
+    resample_quality: ResampleQuality,
+    /// Service for `escalate_model`, created the first time it is needed
+    escalation: Option<Box<TranscriptionService>>,
+    on_download: Option<DownloadCallback>,
+}
+
+/// A stretch of the transcript with its position in the recording.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Segment {
+    pub text: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// Everything an engine reports about one transcription.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Transcription {
+    pub text: String,
+    /// Spoken language, configured or detected, when the engine knows it
+    pub language: Option<String>,
+    /// Empty for engines without timing information
+    pub segments: Vec<Segment>,
+    /// Mean probability of the text tokens, `None` when nothing was decoded
+    /// or the engine does not report one
+    pub confidence: Option<f32>,
+    /// Length of the transcribed audio
+    pub duration_ms: u64,
+    /// Mean probability (0–1) whisper gave the segments of being silence,
+    /// `None` when nothing was decoded or the engine does not report one
+    pub no_speech_probability: Option<f32>,
+}
+
+impl Transcription {
+    /// A result with only text, for engines that report nothing else.
+    pub fn from_text(text: String, samples: &[f32]) -> Self {
+        Self {
+            text,
+            duration_ms: samples_to_ms(samples.len()),
+            ..Self::default()
+        }
+    }
+
+    /// Whether whisper considers the audio silence, whatever text it decoded.
+    pub fn is_no_speech(&self, threshold: f32) -> bool {
+        self.no_speech_probability.is_some_and(|p| p > threshold)
+    }
+
+    /// The text rebuilt from the segments with punctuation at the pauses
+    /// between them: a comma after `comma_ms` of silence, a sentence break
+    /// after `period_ms` and at the end. Segments that already end in
+    /// punctuation keep it. `None` without segments to time the pauses.
+    pub fn punctuated_text(&self, comma_ms: u64, period_ms: u64) -> Option<String> {
+        let mut text = String::new();
+        let mut sentence_start = true;
+        for (i, segment) in self.segments.iter().enumerate() {
+            let words = segment.text.trim();
+            if words.is_empty() {
+                continue;
+            }
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            let mut chars = words.chars();
+            match chars.next() {
+                Some(first) if sentence_start && first.is_lowercase() => {
+                    text.extend(first.to_uppercase());
+                    text.push_str(chars.as_str());
+                }
+                _ => text.push_str(words),
+            }
+
+            if words.ends_with(['.', '!', '?', '…']) {
+                sentence_start = true;
+                continue;
+            }
+            if words.ends_with([',', ';', ':', '-']) {
+                sentence_start = false;
+                continue;
+            }
+            let pause = self
+                .segments
+                .get(i + 1)
+                .map(|next| next.start_ms.saturating_sub(segment.end_ms));
+            sentence_start = match pause {
+                Some(pause) if pause < comma_ms => false,
+                Some(pause) if pause < period_ms => {
+                    text.push(',');
+                    false
+                }
+                _ => {
+                    text.push('.');
+                    true
+                }
+            };
+        }
+        (!text.is_empty()).then_some(text)
+    }
+}
+
+fn samples_to_ms(samples: usize) -> u64 {
+    samples as u64 * 1000 / WHISPER_SAMPLE_RATE as u64
+}
+
+/// HTTP client for model downloads with the configured proxy and timeouts.
+/// Proxies from the environment are honored unless `download_proxy` is set.
+fn download_client(config: &TranscriptionConfig) -> Result<reqwest::blocking::Client> {
+    let mut builder = reqwest::blocking::Client::builder()
+        .connect_timeout(Duration::from_secs(config.download_connect_timeout_secs))
+        .timeout(Duration::from_secs(config.download_read_timeout_secs));
+
+    if let Some(ref proxy) = config.download_proxy {
+        let proxy = reqwest::Proxy::all(proxy.as_str()).map_err(|e| {
+            TjvoxError::ModelDownload(format!("Invalid download proxy '{}': {}", proxy, e))
+        })?;
+        builder = builder.proxy(proxy);
+    }
+
+    builder
+        .build()
+        .map_err(|e| TjvoxError::ModelDownload(format!("Failed to build HTTP client: {}", e)).into())
+}
+
+/// Error message for a failed download request, naming the timeout that expired.
+fn describe_download_error(e: &reqwest::Error, connect_timeout: u64, read_timeout: u64) -> String {
+    if e.is_timeout() && e.is_connect() {
+        format!(
+            "Failed to download model: could not connect within {}s \
+             (transcription.download_connect_timeout_secs)",
+            connect_timeout
+        )
+    } else if e.is_timeout() {
+        format!(
+            "Failed to download model: no response within {}s \
+             (transcription.download_read_timeout_secs)",
+            read_timeout
+        )
+    } else {
+        format!("Failed to download model: {}", e)
+    }
+}
+
+/// Whether a body read failed because the read timeout expired.
+fn is_timeout(e: &std::io::Error) -> bool {
+    e.kind() == std::io::ErrorKind::TimedOut
+        || e
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<reqwest::Error>())
+            .is_some_and(reqwest::Error::is_timeout)
+}
+
+/// Per-user download location used when `models_dir` is not writable
+fn fallback_models_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("tjvox/models"))
+}
+
+fn check_writable(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(format!(".tjvox-write-test-{}", std::process::id()));
+    std::fs::File::create(&probe)?;
+    std::fs::remove_file(&probe)
+}
+
+/// Where a missing model should be downloaded: the configured location if
+/// its directory is writable, otherwise `fallback_dir`. A shared read-only
+/// `models_dir` can still provide models; only new downloads go elsewhere.
+fn download_path(model_path: &Path, fallback_dir: Option<&Path>) -> Result<PathBuf> {
+    let dir = model_path.parent().unwrap_or(Path::new("."));
+    let reason = match check_writable(dir) {
+        Ok(()) => return Ok(model_path.to_path_buf()),
+        Err(e) => e,
+    };
+    let file_name = model_path.file_name().unwrap_or_default();
+
+    match fallback_dir {
+        Some(fallback) if fallback != dir && check_writable(fallback).is_ok() => {
+            warn!(
+                "models_dir {} is not writable ({}); downloading to {} instead",
+                dir.display(),
+                reason,
+                fallback.display()
+            );
+            Ok(fallback.join(file_name))
+        }
+        _ => Err(TjvoxError::ModelDownload(format!(
+            "Cannot download the model into {}: {}. Ask an administrator to install {} there, \
+             or set transcription.models_dir to a writable directory",
+            dir.display(),
+            reason,
+            file_name.to_string_lossy()
+        ))
+        .into()),
+    }
+}
+
+fn model_size_mb(name: &str) -> Option<u32> {
+    MODEL_SIZES_MB
+        .iter()
+        .find(|(model, _)| *model == name)
+        .map(|(_, size)| *size)
+}
+
+/// The model an alias such as `turbo` stands for; other names are returned
+/// unchanged.
+pub fn resolve_model_alias(name: &str) -> &str {
+    MODEL_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, model)| *model)
 }
 
 /// Validate that a model name is safe and known
@@ -41,7 +356,11 @@ fn validate_model_name(name: &str) -> Result<()> {
 
     // Check against known valid models
     if !VALID_MODELS.contains(&name) {
-        warn!("Model '{}' is not in the known model list: {:?}", name, VALID_MODELS);
+        warn!(
+            "Model '{}' is not in the known model list: {}",
+            name,
+            VALID_MODELS.join(", ")
+        );
         // We don't error here because users might use custom models
         // But we log a warning
     }
@@ -49,9 +368,78 @@ fn validate_model_name(name: &str) -> Result<()> {
     Ok(())
 }
 
+/// A speech-to-text engine. The daemon and the other front ends only use
+/// this trait, so engines can be swapped with `transcription.engine`.
+#[async_trait]
+pub trait Transcriber: Send {
+    /// Load (and if needed download) the model. Called automatically by
+    /// `transcribe_detailed`.
+    async fn load_model(&mut self) -> Result<()>;
+
+    fn unload_model(&mut self);
+
+    fn is_loaded(&self) -> bool;
+
+    /// Run a throwaway transcription so the first real one is fast.
+    async fn prewarm(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Transcribe a WAV file to plain text.
+    async fn transcribe(&mut self, audio_path: &Path) -> Result<String> {
+        let audio = RecordedAudio::File(audio_path.to_path_buf());
+        Ok(self.transcribe_detailed(&audio, None, None).await?.text)
+    }
+
+    /// Transcribe a recording, from its WAV file or from memory, with
+    /// language, segments and confidence. Engines that cannot report partial
+    /// results or progress ignore the callbacks.
+    async fn transcribe_detailed(
+        &mut self,
+        audio: &RecordedAudio,
+        on_partial: Option<PartialCallback>,
+        on_progress: Option<ProgressCallback>,
+    ) -> Result<Transcription>;
+
+    /// Transcribe mono samples with the already loaded model. Blocks.
+    fn transcribe_samples(&self, samples: &[f32], sample_rate: u32) -> Result<String>;
+
+    /// Report model downloads to `callback`. Engines that never download
+    /// ignore it.
+    fn set_download_callback(&mut self, _callback: DownloadCallback) {}
+
+    /// Detect the spoken language of a recording, if the engine can.
+    async fn detect_language(&mut self, _audio: &RecordedAudio) -> Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+/// Create the engine selected by `transcription.engine`.
+pub fn create_transcriber(
+    config: &TranscriptionConfig,
+    audio: &AudioConfig,
+) -> Result<Box<dyn Transcriber>> {
+    match config.engine {
+        TranscriptionEngine::Whisper => {
+            Ok(Box::new(TranscriptionService::new(config)?.with_audio(audio)))
+        }
+        #[cfg(feature = "vosk")]
+        TranscriptionEngine::Vosk => Ok(Box::new(crate::vosk_engine::VoskTranscriber::new(
+            config, audio,
+        )?)),
+        #[cfg(not(feature = "vosk"))]
+        TranscriptionEngine::Vosk => Err(TjvoxError::Config(
+            "transcription.engine = \"vosk\" needs a build with --features vosk".to_string(),
+        )
+        .into()),
+    }
+}
+
 impl TranscriptionService {
     pub fn new(config: &TranscriptionConfig) -> Result<Self> {
-        // Validate model name
+        // Work with the real model name, so files and downloads match it
+        let mut config = config.clone();
+        config.model = resolve_model_alias(&config.model).to_string();
         validate_model_name(&config.model)?;
         
         // Validate models_dir doesn't contain path traversal
@@ -62,25 +450,61 @@ impl TranscriptionService {
         }
         
         let model_filename = format!("ggml-{}.bin", config.model);
-        let model_path = PathBuf::from(&config.models_dir).join(&model_filename);
+        let mut model_path = PathBuf::from(&config.models_dir).join(&model_filename);
+        // Use a copy downloaded earlier because models_dir was read-only
+        if !model_path.exists() {
+            if let Some(fallback) = fallback_models_dir().map(|dir| dir.join(&model_filename)) {
+                if fallback.exists() {
+                    model_path = fallback;
+                }
+            }
+        }
 
         Ok(Self {
-            config: config.clone(),
+            config,
             context: None,
             model_path,
+            mono_mode: MonoMode::default(),
+            channel_weights: Vec::new(),
+            preroll_ms: 0,
+            resample_quality: ResampleQuality::default(),
+            escalation: None,
+            on_download: None,
         })
     }
 
-    pub async fn ensure_model(&self) -> Result<()> {
+    /// Use the audio config's stereo-to-mono mixing, resampler and pre-roll
+    /// when loading WAV files.
+    pub fn with_audio(mut self, audio: &AudioConfig) -> Self {
+        self.mono_mode = audio.mono_mode;
+        self.channel_weights = audio.channel_weights.clone();
+        self.preroll_ms = audio.preroll_ms;
+        self.resample_quality = audio.resample_quality;
+        self
+    }
+
+    /// Location of the model file for the configured model.
+    pub fn model_path(&self) -> &Path {
+        &self.model_path
+    }
+
+    pub async fn ensure_model(&mut self) -> Result<()> {
         if self.model_path.exists() {
             debug!("Model already exists: {}", self.model_path.display());
             return Ok(());
         }
+        self.model_path = download_path(&self.model_path, fallback_models_dir().as_deref())?;
 
         let model_filename = format!("ggml-{}.bin", self.config.model);
         let url = format!("{}/{}", HF_BASE_URL, model_filename);
 
         info!("Downloading model '{}' from {}", self.config.model, url);
+        if let Some(ref on_download) = self.on_download {
+            on_download(ModelDownload::Started {
+                model: self.config.model.clone(),
+                size_mb: model_size_mb(&self.config.model),
+            });
+        }
 
         // Ensure models directory exists
         if let Some(parent) = self.model_path.parent() {
@@ -93,100 +517,144 @@ impl TranscriptionService {
             })?;
         }
 
-        // Run blocking HTTP request in spawn_blocking to avoid blocking async runtime
-        let url_clone = url.clone();
+        let config = self.config.clone();
+        let read_timeout = self.config.download_read_timeout_secs;
+        let connect_timeout = self.config.download_connect_timeout_secs;
         let model_name = self.config.model.clone();
         let model_path = self.model_path.clone();
-        
-        let bytes = tokio::task::spawn_blocking(move || -> Result<Vec<u8>> {
-            let response = reqwest::blocking::get(&url_clone).map_err(|e| {
-                TjvoxError::ModelDownload(format!("Failed to download model: {}", e))
+        // Download next to the model and rename at the end, so an interrupted
+        // download never leaves a truncated model behind
+        let part_path = self.model_path.with_extension("bin.part");
+
+        // Run blocking HTTP request in spawn_blocking to avoid blocking async runtime
+        let size = tokio::task::spawn_blocking(move || -> Result<u64> {
+            let client = download_client(&config)?;
+            let mut response = client.get(&url).send().map_err(|e| {
+                TjvoxError::ModelDownload(describe_download_error(&e, connect_timeout, read_timeout))
             })?;
 
             if !response.status().is_success() {
                 return Err(TjvoxError::ModelDownload(format!(
-                    "HTTP {} when downloading model '{}'. Available models: tiny, base, small, medium, large-v3-turbo",
+                    "HTTP {} when downloading model '{}'. Available models: {}",
                     response.status(),
-                    model_name
+                    model_name,
+                    VALID_MODELS.join(", ")
                 )).into());
             }
 
-            let bytes = response.bytes().map_err(|e| {
-                TjvoxError::ModelDownload(format!("Failed to read model data: {}", e))
+            let mut file = std::fs::File::create(&part_path).map_err(|e| {
+                TjvoxError::ModelDownload(format!(
+                    "Failed to create {}: {}",
+                    part_path.display(),
+                    e
+                ))
+            })?;
+            // Each read waits at most `read_timeout`, so a stalled connection fails
+            let copied = std::io::copy(&mut response, &mut file).map_err(|e| {
+                let _ = std::fs::remove_file(&part_path);
+                if is_timeout(&e) {
+                    TjvoxError::ModelDownload(format!(
+                        "Model download stalled: no data received for {}s \
+                         (transcription.download_read_timeout_secs)",
+                        read_timeout
+                    ))
+                } else {
+                    TjvoxError::ModelDownload(format!("Failed to read model data: {}", e))
+                }
             })?;
 
-            Ok(bytes.to_vec())
+            std::fs::rename(&part_path, &model_path).map_err(|e| {
+                TjvoxError::ModelDownload(format!(
+                    "Failed to write model to {}: {}",
+                    model_path.display(),
+                    e
+                ))
+            })?;
+            Ok(copied)
         }).await.map_err(|e| {
             TjvoxError::ModelDownload(format!("Download task failed: {}", e))
         })??;
 
-        info!(
-            "Downloaded {} bytes, saving to {}",
-            bytes.len(),
-            model_path.display()
-        );
-
-        tokio::fs::write(&model_path, &bytes).await.map_err(|e| {
-            TjvoxError::ModelDownload(format!(
-                "Failed to write model to {}: {}",
-                model_path.display(),
-                e
-            ))
-        })?;
+        info!("Downloaded {} bytes to {}", size, self.model_path.display());
 
         info!("Model '{}' downloaded successfully", self.config.model);
-        Ok(())
-    }
-
-    pub async fn load_model(&mut self) -> Result<()> {
-        if self.context.is_some() {
-            debug!("Model already loaded");
-            return Ok(());
+        if let Some(ref on_download) = self.on_download {
+            on_download(ModelDownload::Finished {
+                model: self.config.model.clone(),
+            });
         }
-
-        self.ensure_model().await?;
-
-        info!("Loading whisper model from {}", self.model_path.display());
-
-        let model_path = self.model_path.clone();
-        let ctx = tokio::task::spawn_blocking(move || {
-            WhisperContext::new_with_params(
-                model_path.to_str().ok_or_else(|| {
-                    TjvoxError::ModelLoad("Invalid model path encoding".to_string())
-                })?,
-                WhisperContextParameters::default(),
-            )
-            .map_err(|e| TjvoxError::ModelLoad(format!("Failed to load whisper model: {}", e)))
-        })
-        .await
-        .map_err(|e| TjvoxError::ModelLoad(format!("Model load task failed: {}", e)))??;
-
-        self.context = Some(ctx);
-        info!("Whisper model loaded successfully");
         Ok(())
     }
 
-    pub fn unload_model(&mut self) {
-        if self.context.is_some() {
-            info!("Unloading whisper model");
-            self.context = None;
+    /// Re-run a result below `min_confidence` with `escalate_model` and keep
+    /// whichever of the two whisper is more confident in. Falls back to the
+    /// first result if the larger model cannot be used.
+    async fn escalate(&mut self, decoded: Transcription, samples: &[f32]) -> Transcription {
+        let Some(ref model) = self.config.escalate_model else {
+            return decoded;
+        };
+        let Some(confidence) = decoded.confidence else {
+            return decoded;
+        };
+        if confidence >= self.config.min_confidence
+            || resolve_model_alias(model) == self.config.model
+            || decoded.is_no_speech(self.config.no_speech_threshold)
+        {
+            return decoded;
         }
-    }
-
-    pub fn is_loaded(&self) -> bool {
-        self.context.is_some()
-    }
-
-    pub async fn transcribe(&mut self, audio_path: &Path) -> Result<String> {
-        info!("Transcribing: {}", audio_path.display());
+        info!(
+            "Confidence {:.2} below {:.2}, re-transcribing with '{}'",
+            confidence, self.config.min_confidence, model
+        );
 
-        // Load model if not already loaded
-        if self.context.is_none() {
-            self.load_model().await?;
+        if self.escalation.is_none() {
+            let mut config = self.config.clone();
+            config.model = model.clone();
+            config.escalate_model = None;
+            match TranscriptionService::new(&config) {
+                Ok(mut service) => {
+                    service.on_download = self.on_download.clone();
+                    self.escalation = Some(Box::new(service));
+                }
+                Err(e) => {
+                    warn!("Cannot use escalation model '{}': {}", model, e);
+                    return decoded;
+                }
+            }
         }
+        let Some(ref mut escalation) = self.escalation else {
+            return decoded;
+        };
+        if let Err(e) = escalation.load_model().await {
+            warn!("Failed to load escalation model: {}", e);
+            return decoded;
+        }
+        match escalation.run_full(samples, None, None) {
+            Ok(better) if better.confidence.unwrap_or(0.0) >= confidence => {
+                info!(
+                    "Using '{}' result (confidence {:.2})",
+                    escalation.config.model,
+                    better.confidence.unwrap_or(0.0)
+                );
+                better
+            }
+            Ok(_) => {
+                info!("Escalation was not more confident, keeping the first result");
+                decoded
+            }
+            Err(e) => {
+                warn!("Escalation transcription failed: {}", e);
+                decoded
+            }
+        }
+    }
 
-        let samples = self.read_audio(audio_path)?;
-
+    fn run_full(
+        &self,
+        samples: &[f32],
+        on_partial: Option<PartialCallback>,
+        on_progress: Option<ProgressCallback>,
+    ) -> Result<Transcription> {
         let ctx = self.context.as_ref().ok_or_else(|| {
             TjvoxError::Transcription("Model not loaded".to_string())
         })?;
@@ -195,7 +663,9 @@ impl TranscriptionService {
             TjvoxError::Transcription(format!("Failed to create whisper state: {}", e))
         })?;
 
-        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+        let mut params = FullParams::new(SamplingStrategy::Greedy {
+            best_of: self.config.best_of,
+        });
 
         // Configure language
         if let Some(ref lang) = self.config.language {
@@ -203,124 +673,252 @@ impl TranscriptionService {
         }
 
         // Configure threads
-        let threads = self.config.threads.unwrap_or_else(|| {
-            let cpus = num_cpus::get() as u32;
-            cpus.saturating_sub(2).clamp(1, 8)
-        });
-        params.set_n_threads(threads as i32);
+        params.set_n_threads(self.thread_count() as i32);
 
         // Low temperature for deterministic output
         params.set_temperature(0.2);
 
+        // Short-command tuning
+        params.set_no_context(self.config.no_context);
+        params.set_single_segment(self.config.single_segment);
+
+        // Subtitle-style segments; whisper splits on token timestamps
+        if self.config.max_segment_len > 0 {
+            params.set_token_timestamps(true);
+            params.set_max_len(self.config.max_segment_len);
+            params.set_split_on_word(true);
+        }
+
         // Disable printing to stdout
         params.set_print_special(false);
         params.set_print_progress(false);
         params.set_print_realtime(false);
         params.set_print_timestamps(false);
 
-        state.full(params, &samples).map_err(|e| {
+        if let Some(mut on_partial) = on_partial {
+            let mut partial = String::new();
+            params.set_segment_callback_safe_lossy(move |data: whisper_rs::SegmentCallbackData| {
+                partial.push_str(&data.text);
+                on_partial(partial.trim());
+            });
+        }
+
+        if let Some(mut on_progress) = on_progress {
+            params.set_progress_callback_safe(move |progress: i32| on_progress(progress));
+        }
+
+        state.full(params, samples).map_err(|e| {
             TjvoxError::Transcription(format!("Whisper transcription failed: {}", e))
         })?;
 
         let num_segments = state.full_n_segments();
 
+        // Special tokens (timestamps, end of text) sort after the text tokens
+        let first_special = ctx.token_eot();
         let mut text = String::new();
+        let mut segments = Vec::new();
+        let mut probability_sum = 0.0;
+        let mut text_tokens = 0;
+        let mut no_speech_sum = 0.0;
         for i in 0..num_segments {
             if let Some(segment) = state.get_segment(i) {
+                no_speech_sum += segment.no_speech_probability();
                 if let Ok(segment_text) = segment.to_str() {
                     text.push_str(segment_text);
+                    // Timestamps are in units of 10 ms
+                    segments.push(Segment {
+                        text: segment_text.trim().to_string(),
+                        start_ms: segment.start_timestamp().max(0) as u64 * 10,
+                        end_ms: segment.end_timestamp().max(0) as u64 * 10,
+                    });
+                }
+                for j in 0..segment.n_tokens() {
+                    if let Some(token) = segment.get_token(j) {
+                        if token.token_id() < first_special {
+                            probability_sum += token.token_probability();
+                            text_tokens += 1;
+                        }
+                    }
                 }
             }
         }
 
-        let result = text.trim().to_string();
-        info!("Transcription completed: {} chars", result.len());
-        Ok(result)
+        let confidence = (text_tokens > 0).then(|| probability_sum / text_tokens as f32);
+        let no_speech_probability = (num_segments > 0).then(|| no_speech_sum / num_segments as f32);
+        debug!(
+            "Transcript confidence: {:?}, no-speech probability: {:?}",
+            confidence, no_speech_probability
+        );
+        let language = match self.config.language {
+            Some(ref lang) => Some(lang.clone()),
+            None => whisper_rs::get_lang_str(state.full_lang_id_from_state()).map(str::to_string),
+        };
+        Ok(Transcription {
+            text: text.trim().to_string(),
+            language,
+            segments,
+            confidence,
+            duration_ms: samples_to_ms(samples.len()),
+            no_speech_probability,
+        })
     }
 
-    fn read_audio(&self, audio_path: &Path) -> Result<Vec<f32>> {
-        let reader = hound::WavReader::open(audio_path).map_err(|e| {
-            TjvoxError::Transcription(format!(
-                "Failed to open WAV file {}: {}",
-                audio_path.display(),
-                e
-            ))
-        })?;
+    fn thread_count(&self) -> u32 {
+        self.config.threads.unwrap_or_else(|| {
+            let cpus = num_cpus::get() as u32;
+            cpus.saturating_sub(2).clamp(1, 8)
+        })
+    }
 
-        let spec = reader.spec();
-        debug!(
-            "WAV: {} Hz, {} channels, {:?}, {} bits",
-            spec.sample_rate, spec.channels, spec.sample_format, spec.bits_per_sample
-        );
+}
 
-        // Read samples as f32
-        let samples: Vec<f32> = match spec.sample_format {
-            hound::SampleFormat::Float => reader
-                .into_samples::<f32>()
-                .filter_map(|s| s.ok())
-                .collect(),
-            hound::SampleFormat::Int => {
-                let max_val = (1 << (spec.bits_per_sample - 1)) as f32;
-                reader
-                    .into_samples::<i32>()
-                    .filter_map(|s| s.ok())
-                    .map(|s| s as f32 / max_val)
-                    .collect()
-            }
-        };
+#[async_trait]
+impl Transcriber for TranscriptionService {
+    async fn load_model(&mut self) -> Result<()> {
+        if self.context.is_some() {
+            debug!("Model already loaded");
+            return Ok(());
+        }
 
-        // Convert to mono if stereo
-        let mono = if spec.channels > 1 {
-            samples
-                .chunks(spec.channels as usize)
-                .map(|chunk| chunk.iter().sum::<f32>() / chunk.len() as f32)
-                .collect()
-        } else {
-            samples
-        };
+        self.ensure_model().await?;
 
-        // Resample to 16kHz if needed
-        let resampled = if spec.sample_rate != 16000 {
-            warn!(
-                "Audio is {} Hz, resampling to 16000 Hz (simple linear)",
-                spec.sample_rate
+        let backend = Backend::compiled();
+        let device = backend.detect_device();
+        let use_gpu = resolve_gpu(backend, self.config.use_gpu, device.as_deref())
+            .map_err(TjvoxError::ModelLoad)?;
+        if use_gpu {
+            info!(
+                "Loading whisper model from {} ({} on {})",
+                self.model_path.display(),
+                backend,
+                device.as_deref().unwrap_or("unknown device")
             );
-            Self::resample(&mono, spec.sample_rate, 16000)
         } else {
-            mono
-        };
+            info!("Loading whisper model from {} (CPU)", self.model_path.display());
+        }
 
-        debug!("Audio loaded: {} samples at 16kHz", resampled.len());
-        Ok(resampled)
+        let model_path = self.model_path.clone();
+        let ctx = tokio::task::spawn_blocking(move || {
+            let mut params = WhisperContextParameters::default();
+            params.use_gpu(use_gpu);
+            WhisperContext::new_with_params(
+                model_path.to_str().ok_or_else(|| {
+                    TjvoxError::ModelLoad("Invalid model path encoding".to_string())
+                })?,
+                params,
+            )
+            .map_err(|e| TjvoxError::ModelLoad(format!("Failed to load whisper model: {}", e)))
+        })
+        .await
+        .map_err(|e| TjvoxError::ModelLoad(format!("Model load task failed: {}", e)))??;
+
+        self.context = Some(ctx);
+        info!("Whisper model loaded successfully");
+        Ok(())
     }
 
-    fn resample(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
-        let ratio = from_rate as f64 / to_rate as f64;
-        let output_len = (input.len() as f64 / ratio) as usize;
-        let mut output = Vec::with_capacity(output_len);
+    fn unload_model(&mut self) {
+        if self.context.is_some() {
+            info!("Unloading whisper model");
+            self.context = None;
+        }
+        if let Some(ref mut escalation) = self.escalation {
+            escalation.unload_model();
+        }
+    }
 
-        for i in 0..output_len {
-            let src_idx = i as f64 * ratio;
-            let idx = src_idx as usize;
-            let frac = src_idx - idx as f64;
+    fn is_loaded(&self) -> bool {
+        self.context.is_some()
+    }
 
-            let sample = if idx + 1 < input.len() {
-                input[idx] as f64 * (1.0 - frac) + input[idx + 1] as f64 * frac
-            } else if idx < input.len() {
-                input[idx] as f64
-            } else {
-                0.0
-            };
+    fn set_download_callback(&mut self, callback: DownloadCallback) {
+        self.on_download = Some(callback);
+    }
+
+    /// Transcribe, reporting partial results through `on_partial` as segments
+    /// complete and whisper's progress percentage through `on_progress`.
+    async fn transcribe_detailed(
+        &mut self,
+        audio: &RecordedAudio,
+        on_partial: Option<PartialCallback>,
+        on_progress: Option<ProgressCallback>,
+    ) -> Result<Transcription> {
+        info!("Transcribing: {}", audio);
+
+        // Load model if not already loaded
+        if self.context.is_none() {
+            self.load_model().await?;
+        }
+
+        let samples = load_audio(
+            audio,
+            self.mono_mode,
+            &self.channel_weights,
+            self.resample_quality,
+        )?;
+        let duration_ms = samples_to_ms(samples.len());
+        let samples = crate::audio::prepend_silence(samples, WHISPER_SAMPLE_RATE, self.preroll_ms);
+        let decoded = self.run_full(&samples, on_partial, on_progress)?;
+        let mut result = self.escalate(decoded, &samples).await;
+        // Report the recording's length, not the padded one
+        result.duration_ms = duration_ms;
+        info!("Transcription completed: {} chars", result.text.len());
+        Ok(result)
+    }
+
+    /// Transcribe mono samples captured at `sample_rate` with the already
+    /// loaded model. Blocks; used for short in-memory clips such as the
+    /// wake-word listener's rolling window.
+    fn transcribe_samples(&self, samples: &[f32], sample_rate: u32) -> Result<String> {
+        let decoded = if sample_rate == WHISPER_SAMPLE_RATE {
+            self.run_full(samples, None, None)?
+        } else {
+            let resampled =
+                resample(samples, sample_rate, WHISPER_SAMPLE_RATE, self.resample_quality);
+            self.run_full(&resampled, None, None)?
+        };
+        Ok(decoded.text)
+    }
 
-            output.push(sample as f32);
+    /// Detect the spoken language of a recording (e.g. `"de"`).
+    /// Returns `None` if whisper could not determine a language.
+    async fn detect_language(&mut self, audio: &RecordedAudio) -> Result<Option<String>> {
+        if self.context.is_none() {
+            self.load_model().await?;
         }
 
-        output
+        let samples = load_audio(
+            audio,
+            self.mono_mode,
+            &self.channel_weights,
+            self.resample_quality,
+        )?;
+        let threads = self.thread_count() as usize;
+
+        let ctx = self.context.as_ref().ok_or_else(|| {
+            TjvoxError::Transcription("Model not loaded".to_string())
+        })?;
+
+        let mut state = ctx.create_state().map_err(|e| {
+            TjvoxError::Transcription(format!("Failed to create whisper state: {}", e))
+        })?;
+
+        state.pcm_to_mel(&samples, threads).map_err(|e| {
+            TjvoxError::Transcription(format!("Failed to compute mel spectrogram: {}", e))
+        })?;
+
+        let (lang_id, _probs) = state.lang_detect(0, threads).map_err(|e| {
+            TjvoxError::Transcription(format!("Language detection failed: {}", e))
+        })?;
+
+        let language = whisper_rs::get_lang_str(lang_id).map(str::to_string);
+        debug!("Detected language: {:?}", language);
+        Ok(language)
     }
 
     /// Pre-warm the model by running a dummy transcription on silence.
     /// This warms up whisper.cpp's internal buffers.
-    pub async fn prewarm(&mut self) -> Result<()> {
+    async fn prewarm(&mut self) -> Result<()> {
         if self.context.is_none() {
             self.load_model().await?;
         }
@@ -349,3 +947,415 @@ impl TranscriptionService {
         Ok(())
     }
 }
+
+/// Mono samples at whisper's 16 kHz for a recording, read from its WAV file
+/// or resampled from memory. Shared by all engines.
+pub(crate) fn load_audio(
+    audio: &RecordedAudio,
+    mono_mode: MonoMode,
+    channel_weights: &[f32],
+    quality: ResampleQuality,
+) -> Result<Vec<f32>> {
+    match audio {
+        RecordedAudio::File(path) => read_wav(path, mono_mode, channel_weights, quality),
+        RecordedAudio::Memory {
+            samples,
+            sample_rate,
+        } if *sample_rate == WHISPER_SAMPLE_RATE => Ok(samples.to_vec()),
+        RecordedAudio::Memory {
+            samples,
+            sample_rate,
+        } => Ok(resample(samples, *sample_rate, WHISPER_SAMPLE_RATE, quality)),
+    }
+}
+
+/// Divisor scaling integer samples of the given width to [-1, 1], `None`
+/// for 32-bit float. Other formats are rejected rather than mis-scaled.
+fn wav_full_scale(format: hound::SampleFormat, bits: u16) -> Result<Option<f32>, TjvoxError> {
+    match (format, bits) {
+        (hound::SampleFormat::Float, 32) => Ok(None),
+        (hound::SampleFormat::Int, 8..=32) => Ok(Some((1u64 << (bits - 1)) as f32)),
+        (hound::SampleFormat::Float, _) => Err(TjvoxError::Transcription(format!(
+            "unsupported WAV sample format: {}-bit float",
+            bits
+        ))),
+        (hound::SampleFormat::Int, _) => Err(TjvoxError::Transcription(format!(
+            "unsupported WAV sample format: {}-bit integer",
+            bits
+        ))),
+    }
+}
+
+/// Samples of a WAV stream as f32 in [-1, 1] for integer formats.
+fn wav_samples<R: std::io::Read>(reader: hound::WavReader<R>) -> Result<Vec<f32>, TjvoxError> {
+    let spec = reader.spec();
+    Ok(match wav_full_scale(spec.sample_format, spec.bits_per_sample)? {
+        None => reader.into_samples::<f32>().filter_map(|s| s.ok()).collect(),
+        Some(full_scale) => reader
+            .into_samples::<i32>()
+            .filter_map(|s| s.ok())
+            .map(|s| s as f32 / full_scale)
+            .collect(),
+    })
+}
+
+/// Read a WAV file as mono samples at whisper's 16 kHz, mixing down and
+/// resampling as needed.
+fn read_wav(
+    audio_path: &Path,
+    mono_mode: MonoMode,
+    channel_weights: &[f32],
+    quality: ResampleQuality,
+) -> Result<Vec<f32>> {
+    let reader = hound::WavReader::open(audio_path).map_err(|e| {
+        TjvoxError::Transcription(format!(
+            "Failed to open WAV file {}: {}",
+            audio_path.display(),
+            e
+        ))
+    })?;
+
+    let spec = reader.spec();
+    debug!(
+        "WAV: {} Hz, {} channels, {:?}, {} bits",
+        spec.sample_rate, spec.channels, spec.sample_format, spec.bits_per_sample
+    );
+
+    let samples = wav_samples(reader).map_err(|e| {
+        TjvoxError::Transcription(format!("{}: {}", audio_path.display(), e))
+    })?;
+
+    // Convert to mono if stereo
+    let mono = if spec.channels > 1 {
+        crate::audio::downmix(
+            &samples,
+            spec.channels as usize,
+            mono_mode,
+            channel_weights,
+        )
+    } else {
+        samples
+    };
+
+    // Resample to 16kHz if needed
+    let resampled = if spec.sample_rate != WHISPER_SAMPLE_RATE {
+        let started = std::time::Instant::now();
+        let resampled = resample(&mono, spec.sample_rate, WHISPER_SAMPLE_RATE, quality);
+        match quality {
+            ResampleQuality::Fast => warn!(
+                "Audio is {} Hz, resampled {} samples to {} Hz (simple linear) in {:?}; \
+                 record at {} Hz to skip this step",
+                spec.sample_rate,
+                mono.len(),
+                WHISPER_SAMPLE_RATE,
+                started.elapsed(),
+                WHISPER_SAMPLE_RATE
+            ),
+            ResampleQuality::High => info!(
+                "Audio is {} Hz, resampled {} samples to {} Hz (windowed sinc) in {:?}",
+                spec.sample_rate,
+                mono.len(),
+                WHISPER_SAMPLE_RATE,
+                started.elapsed()
+            ),
+        }
+        resampled
+    } else {
+        mono
+    };
+
+    debug!("Audio loaded: {} samples at 16kHz", resampled.len());
+    Ok(resampled)
+}
+
+/// Zero crossings of the sinc kernel on each side of an output sample
+const SINC_ZERO_CROSSINGS: usize = 16;
+
+/// Resample between sample rates with the given quality.
+pub(crate) fn resample(
+    input: &[f32],
+    from_rate: u32,
+    to_rate: u32,
+    quality: ResampleQuality,
+) -> Vec<f32> {
+    match quality {
+        ResampleQuality::Fast => resample_linear(input, from_rate, to_rate),
+        ResampleQuality::High => resample_sinc(input, from_rate, to_rate),
+    }
+}
+
+/// Band-limited resampling with a Blackman-windowed sinc kernel. When
+/// downsampling the cutoff is lowered to the target's Nyquist frequency, so
+/// content above it is filtered out instead of folding back as aliasing.
+fn resample_sinc(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    use std::f64::consts::PI;
+
+    let ratio = from_rate as f64 / to_rate as f64;
+    let output_len = (input.len() as f64 / ratio) as usize;
+    // Cutoff relative to the input's Nyquist frequency
+    let cutoff = (1.0 / ratio).min(1.0);
+    let half_width = (SINC_ZERO_CROSSINGS as f64 / cutoff).ceil() as isize;
+
+    let mut output = Vec::with_capacity(output_len);
+    for i in 0..output_len {
+        let center = i as f64 * ratio;
+        let first = center.floor() as isize - half_width + 1;
+        let mut sum = 0.0;
+        let mut weights = 0.0;
+        for j in first..first + 2 * half_width {
+            if j < 0 || j as usize >= input.len() {
+                continue;
+            }
+            let x = center - j as f64;
+            let sinc = if x.abs() < 1e-9 {
+                1.0
+            } else {
+                (PI * cutoff * x).sin() / (PI * cutoff * x)
+            };
+            // Blackman window over [-half_width, half_width]
+            let n = (x / half_width as f64 + 1.0) / 2.0;
+            let window = 0.42 - 0.5 * (2.0 * PI * n).cos() + 0.08 * (4.0 * PI * n).cos();
+            let weight = sinc * window;
+            sum += input[j as usize] as f64 * weight;
+            weights += weight;
+        }
+        // Normalise so a constant signal keeps its level, also at the edges
+        output.push(if weights.abs() > 1e-9 { (sum / weights) as f32 } else { 0.0 });
+    }
+
+    output
+}
+
+/// Linear resampling between sample rates.
+fn resample_linear(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    let ratio = from_rate as f64 / to_rate as f64;
+    let output_len = (input.len() as f64 / ratio) as usize;
+    let mut output = Vec::with_capacity(output_len);
+
+    for i in 0..output_len {
+        let src_idx = i as f64 * ratio;
+        let idx = src_idx as usize;
+        let frac = src_idx - idx as f64;
+
+        let sample = if idx + 1 < input.len() {
+            input[idx] as f64 * (1.0 - frac) + input[idx + 1] as f64 * frac
+        } else if idx < input.len() {
+            input[idx] as f64
+        } else {
+            0.0
+        };
+
+        output.push(sample as f32);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// WAV bytes holding `samples` as mono integers of the given width
+    fn int_wav(bits: u16, samples: &[i32]) -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: WHISPER_SAMPLE_RATE,
+            bits_per_sample: bits,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut bytes, spec).unwrap();
+        for &sample in samples {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+        bytes.into_inner()
+    }
+
+    fn decode(bytes: Vec<u8>) -> Result<Vec<f32>, TjvoxError> {
+        wav_samples(hound::WavReader::new(std::io::Cursor::new(bytes)).unwrap())
+    }
+
+    #[test]
+    fn test_wav_samples_int_widths() {
+        for bits in [8u16, 16, 24, 32] {
+            let full = 1i64 << (bits - 1);
+            let samples = [(-full) as i32, (full / 2) as i32, 0, (full - 1) as i32];
+            let decoded = decode(int_wav(bits, &samples)).unwrap();
+            assert_eq!(decoded.len(), 4, "{}-bit", bits);
+            assert!(decoded.iter().all(|s| (-1.0..=1.0).contains(s)), "{}-bit", bits);
+            assert_eq!(decoded[0], -1.0, "{}-bit", bits);
+            assert!((decoded[1] - 0.5).abs() < 1e-6, "{}-bit", bits);
+            assert!(decoded[3] > 0.99, "{}-bit", bits);
+        }
+    }
+
+    #[test]
+    fn test_wav_samples_unsupported() {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: WHISPER_SAMPLE_RATE,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut bytes = std::io::Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut bytes, spec).unwrap();
+        writer.write_sample(0.25f32).unwrap();
+        writer.finalize().unwrap();
+        assert_eq!(decode(bytes.into_inner()).unwrap(), [0.25]);
+
+        let err = wav_full_scale(hound::SampleFormat::Float, 64).unwrap_err();
+        assert!(err.to_string().contains("64-bit float"), "{}", err);
+        assert!(wav_full_scale(hound::SampleFormat::Int, 4).is_err());
+    }
+
+    #[test]
+    fn test_transcription_from_text() {
+        let result = Transcription::from_text("hello".to_string(), &[0.0; 24000]);
+        assert_eq!(result.duration_ms, 1500);
+        assert_eq!(result.confidence, None);
+        assert!(result.segments.is_empty());
+        assert_eq!(
+            serde_json::to_string(&result).unwrap(),
+            r#"{"text":"hello","language":null,"segments":[],"confidence":null,"duration_ms":1500,"no_speech_probability":null}"#
+        );
+    }
+
+    #[test]
+    fn test_transcription_is_no_speech() {
+        let mut result = Transcription::from_text("Thank you.".to_string(), &[]);
+        // Engines without a no-speech probability never take this path
+        assert!(!result.is_no_speech(0.0));
+
+        result.no_speech_probability = Some(0.95);
+        assert!(result.is_no_speech(0.8));
+        assert!(!result.is_no_speech(1.0));
+
+        result.no_speech_probability = Some(0.3);
+        assert!(!result.is_no_speech(0.8));
+    }
+
+    #[test]
+    fn test_punctuated_text_from_pauses() {
+        let segment = |text: &str, start_ms: u64, end_ms: u64| Segment {
+            text: text.to_string(),
+            start_ms,
+            end_ms,
+        };
+        let result = Transcription {
+            text: "unused".to_string(),
+            segments: vec![
+                segment(" so here is the plan", 0, 1200),
+                segment(" first we test", 1300, 2000),
+                segment(" then we ship", 2400, 3000),
+                segment(" after that we rest", 4200, 5000),
+                segment(" Okay?", 6500, 7000),
+                segment(" see you", 7100, 7600),
+            ],
+            ..Transcription::default()
+        };
+        assert_eq!(
+            result.punctuated_text(300, 800).unwrap(),
+            "So here is the plan first we test, then we ship. After that we rest. Okay? See you."
+        );
+        // A long pause is a sentence break even when the comma threshold is high
+        assert_eq!(
+            result.punctuated_text(800, 800).unwrap(),
+            "So here is the plan first we test then we ship. After that we rest. Okay? See you."
+        );
+
+        let text_only = Transcription::from_text("no timing".to_string(), &[]);
+        assert_eq!(text_only.punctuated_text(300, 800), None);
+    }
+
+    #[test]
+    fn test_model_download_message() {
+        let started = ModelDownload::Started {
+            model: "base".to_string(),
+            size_mb: model_size_mb("base"),
+        };
+        assert_eq!(started.to_string(), "Downloading base (~142MB)…");
+        let custom = ModelDownload::Started {
+            model: "my-finetune".to_string(),
+            size_mb: model_size_mb("my-finetune"),
+        };
+        assert_eq!(custom.to_string(), "Downloading my-finetune…");
+        let finished = ModelDownload::Finished {
+            model: "base".to_string(),
+        };
+        assert_eq!(finished.to_string(), "Model ready");
+        assert!(VALID_MODELS.iter().all(|model| model_size_mb(model).is_some()));
+    }
+
+    #[test]
+    fn test_resolve_model_alias() {
+        assert_eq!(resolve_model_alias("turbo"), "large-v3-turbo");
+        assert_eq!(resolve_model_alias("large"), "large-v3");
+        assert_eq!(resolve_model_alias("base.en-q5_1"), "base.en-q5_1");
+        assert_eq!(resolve_model_alias("my-finetune"), "my-finetune");
+        assert!(MODEL_ALIASES
+            .iter()
+            .all(|(alias, model)| VALID_MODELS.contains(model) && !VALID_MODELS.contains(alias)));
+
+        let config = TranscriptionConfig {
+            model: "turbo".to_string(),
+            models_dir: "/nonexistent/models".to_string(),
+            ..crate::config::Config::default().transcription
+        };
+        let service = TranscriptionService::new(&config).unwrap();
+        assert_eq!(service.config.model, "large-v3-turbo");
+        assert!(service.model_path().ends_with("ggml-large-v3-turbo.bin"));
+    }
+
+    #[test]
+    fn test_download_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let writable = dir.path().join("models/ggml-base.bin");
+        assert_eq!(download_path(&writable, None).unwrap(), writable);
+
+        // A directory that cannot be created stands in for a read-only one
+        std::fs::write(dir.path().join("file"), "").unwrap();
+        let blocked = dir.path().join("file/models/ggml-base.bin");
+        let fallback = dir.path().join("cache");
+        assert_eq!(
+            download_path(&blocked, Some(&fallback)).unwrap(),
+            fallback.join("ggml-base.bin")
+        );
+
+        let err = download_path(&blocked, None).unwrap_err().to_string();
+        assert!(err.contains("file/models"));
+        assert!(err.contains("transcription.models_dir"));
+    }
+
+    fn tone(freq: f64, rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| (2.0 * std::f64::consts::PI * freq * i as f64 / rate as f64).sin() as f32)
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_resample_sinc() {
+        let dc = resample(&[0.5; 4800], 48000, 16000, ResampleQuality::High);
+        assert_eq!(dc.len(), 1600);
+        assert!(dc.iter().all(|s| (s - 0.5).abs() < 1e-3));
+
+        // Speech-band content passes at full level
+        let voice = resample(&tone(1000.0, 48000, 48000), 48000, 16000, ResampleQuality::High);
+        assert!((rms(&voice[100..15900]) - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.01);
+
+        // A 12 kHz tone is above the 8 kHz target Nyquist: linear resampling
+        // folds it back to 4 kHz, the sinc filter removes it
+        let hiss = tone(12000.0, 48000, 48000);
+        let linear = resample(&hiss, 48000, 16000, ResampleQuality::Fast);
+        let sinc = resample(&hiss, 48000, 16000, ResampleQuality::High);
+        assert!(rms(&linear[100..15900]) > 0.3);
+        assert!(rms(&sinc[100..15900]) < 0.01);
+
+        // Upsampling keeps the length ratio
+        assert_eq!(resample(&[0.0; 800], 8000, 16000, ResampleQuality::High).len(), 1600);
+    }
+}
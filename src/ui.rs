@@ -64,6 +64,32 @@ impl UiManager {
         }
     }
 
+    /// Show or update a transcription progress notification without waiting
+    /// for it. Each update replaces the previous one instead of stacking.
+    /// Only notify-send supports this; other backends are skipped.
+    pub fn show_progress(&self, title: &str, percent: i32) {
+        if !self.config.show_notifications || !self.has_notify_send {
+            debug!("[Progress] {}: {}%", title, percent);
+            return;
+        }
+
+        let spawned = Command::new("notify-send")
+            .args([
+                "--expire-time", &self.config.notification_timeout_ms.to_string(),
+                "--hint", &format!("int:value:{}", percent),
+                "--hint", "string:x-canonical-private-synchronous:tjvox-progress",
+                title,
+                &format!("Transcribing... {}%", percent),
+            ])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+
+        if let Err(e) = spawned {
+            warn!("Failed to show progress notification: {}", e);
+        }
+    }
+
     async fn show_libnotify(&self, title: &str, message: &str) -> Result<()> {
         let timeout_ms = self.config.notification_timeout_ms;
 
@@ -158,6 +184,8 @@ mod tests {
         let config = UiConfig {
             show_notifications: true,
             notification_timeout_ms: 5000,
+            verbose: false,
+            progress_notifications: false,
         };
         let manager = UiManager::with_config(&config);
         assert!(manager.config.show_notifications);
@@ -169,6 +197,8 @@ mod tests {
         let config = UiConfig {
             show_notifications: false,
             notification_timeout_ms: 3000,
+            verbose: false,
+            progress_notifications: false,
         };
         let manager = UiManager::with_config(&config);
         // Should return Ok without spawning any process
@@ -181,9 +211,18 @@ mod tests {
         let config = UiConfig {
             show_notifications: false,
             notification_timeout_ms: 3000,
+            verbose: false,
+            progress_notifications: false,
         };
         let manager = UiManager::with_config(&config);
         let result = manager.show_error("Error", "Something went wrong").await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_show_progress_disabled() {
+        let manager = UiManager::default();
+        // Notifications are off, so this must not spawn anything
+        manager.show_progress("TJvox", 50);
+    }
 }
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+use crate::config::{
+    AudioConfig, MonoMode, ResampleQuality, TranscriptionConfig, WHISPER_SAMPLE_RATE,
+};
+use crate::error::TjvoxError;
+use crate::audio::RecordedAudio;
+use crate::transcription::{
+    load_audio, resample, PartialCallback, ProgressCallback, Transcriber, Transcription,
+};
+
+/// Offline transcription with Vosk (Kaldi) models. Far cheaper than whisper
+/// on low-power CPUs, at the cost of accuracy and punctuation; replacements
+/// or the LLM pass can restore the latter.
+pub struct VoskTranscriber {
+    model_path: PathBuf,
+    model: Option<vosk::Model>,
+    mono_mode: MonoMode,
+    channel_weights: Vec<f32>,
+    resample_quality: ResampleQuality,
+    /// Vosk models are single-language; reported as configured
+    language: Option<String>,
+}
+
+impl VoskTranscriber {
+    pub fn new(config: &TranscriptionConfig, audio: &AudioConfig) -> Result<Self> {
+        let model_path = config
+            .vosk_model
+            .as_deref()
+            .filter(|path| !path.trim().is_empty())
+            .ok_or_else(|| TjvoxError::Config("transcription.vosk_model is not set".to_string()))?;
+        Ok(Self {
+            model_path: PathBuf::from(model_path),
+            model: None,
+            mono_mode: audio.mono_mode,
+            channel_weights: audio.channel_weights.clone(),
+            resample_quality: audio.resample_quality,
+            language: config.language.clone(),
+        })
+    }
+
+    fn recognize(&self, samples: &[f32]) -> Result<String> {
+        let model = self
+            .model
+            .as_ref()
+            .ok_or_else(|| TjvoxError::Transcription("Model not loaded".to_string()))?;
+        let mut recognizer =
+            vosk::Recognizer::new(model, WHISPER_SAMPLE_RATE as f32).ok_or_else(|| {
+                TjvoxError::Transcription("Failed to create Vosk recognizer".to_string())
+            })?;
+
+        let pcm: Vec<i16> = samples
+            .iter()
+            .map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .collect();
+        recognizer.accept_waveform(&pcm).map_err(|e| {
+            TjvoxError::Transcription(format!("Vosk failed to decode audio: {:?}", e))
+        })?;
+
+        let text = recognizer
+            .final_result()
+            .single()
+            .map(|result| result.text.to_string())
+            .unwrap_or_default();
+        debug!("Vosk result: {:?}", text);
+        Ok(text.trim().to_string())
+    }
+}
+
+#[async_trait]
+impl Transcriber for VoskTranscriber {
+    async fn load_model(&mut self) -> Result<()> {
+        if self.model.is_some() {
+            debug!("Model already loaded");
+            return Ok(());
+        }
+        if !self.model_path.is_dir() {
+            return Err(TjvoxError::ModelLoad(format!(
+                "Vosk model directory {} not found; download and unpack a model from \
+                 https://alphacephei.com/vosk/models",
+                self.model_path.display()
+            ))
+            .into());
+        }
+
+        info!("Loading Vosk model from {}", self.model_path.display());
+        let path = self.model_path.to_string_lossy().to_string();
+        let model = tokio::task::spawn_blocking(move || vosk::Model::new(path))
+            .await
+            .map_err(|e| TjvoxError::ModelLoad(format!("Model load task failed: {}", e)))?
+            .ok_or_else(|| {
+                TjvoxError::ModelLoad(format!(
+                    "Failed to load Vosk model from {}",
+                    self.model_path.display()
+                ))
+            })?;
+
+        self.model = Some(model);
+        info!("Vosk model loaded successfully");
+        Ok(())
+    }
+
+    fn unload_model(&mut self) {
+        if self.model.is_some() {
+            info!("Unloading Vosk model");
+            self.model = None;
+        }
+    }
+
+    fn is_loaded(&self) -> bool {
+        self.model.is_some()
+    }
+
+    async fn transcribe_detailed(
+        &mut self,
+        audio: &RecordedAudio,
+        _on_partial: Option<PartialCallback>,
+        _on_progress: Option<ProgressCallback>,
+    ) -> Result<Transcription> {
+        info!("Transcribing with Vosk: {}", audio);
+        if self.model.is_none() {
+            self.load_model().await?;
+        }
+        let samples = load_audio(
+            audio,
+            self.mono_mode,
+            &self.channel_weights,
+            self.resample_quality,
+        )?;
+        let text = self.recognize(&samples)?;
+        info!("Transcription completed: {} chars", text.len());
+        Ok(Transcription {
+            language: self.language.clone(),
+            ..Transcription::from_text(text, &samples)
+        })
+    }
+
+    fn transcribe_samples(&self, samples: &[f32], sample_rate: u32) -> Result<String> {
+        if sample_rate == WHISPER_SAMPLE_RATE {
+            self.recognize(samples)
+        } else {
+            self.recognize(&resample(
+                samples,
+                sample_rate,
+                WHISPER_SAMPLE_RATE,
+                self.resample_quality,
+            ))
+        }
+    }
+}
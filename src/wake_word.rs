@@ -0,0 +1,330 @@
+//! Hands-free dictation started by a spoken wake word.
+//!
+//! Privacy: while a wake word is configured the microphone stays open
+//! whenever the daemon runs. Audio is kept in a two-second in-memory buffer,
+//! only transcribed locally when it contains sound, and never written to disk
+//! or sent anywhere. Remove `input.wake_word` from the config (or build
+//! without the `wake-word` feature) to keep the microphone closed while idle.
+
+use anyhow::Result;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use crate::audio::{AudioRecorder, SampleChunk};
+use crate::config::Config;
+use crate::ptt::arbiter::InputEvent;
+use crate::transcription::{Transcriber, TranscriptionService};
+
+/// Audio considered when listening for the wake word
+const WINDOW: Duration = Duration::from_millis(2000);
+/// How often the window is checked while it contains sound
+const HOP: Duration = Duration::from_millis(500);
+/// RMS level below which a chunk counts as silence
+const SPEECH_RMS: f32 = 0.01;
+/// A wake-word dictation with no speech at all ends after this long
+const NO_SPEECH_TIMEOUT: Duration = Duration::from_millis(5000);
+/// Whisper threads for the listener; kept low since it runs continuously
+const LISTENER_THREADS: u32 = 2;
+
+/// Lowercase words with punctuation stripped, e.g. `"Hey, Vox!"` → `["hey", "vox"]`.
+pub fn normalize_words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric() || *c == '\'')
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Whether `phrase` (already normalized) occurs as consecutive words in `text`.
+pub fn contains_phrase(text: &str, phrase: &[String]) -> bool {
+    if phrase.is_empty() {
+        return false;
+    }
+    normalize_words(text)
+        .windows(phrase.len())
+        .any(|window| window == phrase)
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// What the listener should do after a chunk of audio.
+#[derive(Debug, PartialEq)]
+pub enum Step {
+    /// Nothing to do yet
+    Wait,
+    /// Transcribe this window and report back via `on_transcript`
+    Check(Vec<f32>),
+    /// The dictation started by the wake word has gone quiet
+    Stop,
+}
+
+/// A dictation started by the wake word, waiting for trailing silence
+#[derive(Default)]
+struct Dictation {
+    speech_seen: bool,
+    quiet: usize,
+}
+
+/// Decides when to run the keyword check and when a wake-word dictation
+/// ends. Silent audio is never transcribed, which keeps the idle cost low.
+pub struct WakeWordDetector {
+    phrase: Vec<String>,
+    window: Vec<f32>,
+    window_len: usize,
+    hop_len: usize,
+    silence_len: usize,
+    no_speech_len: usize,
+    since_check: usize,
+    sound_in_hop: bool,
+    dictation: Option<Dictation>,
+}
+
+impl WakeWordDetector {
+    pub fn new(phrase: &str, sample_rate: u32, silence: Duration) -> Self {
+        let samples = |d: Duration| (sample_rate as u128 * d.as_millis() / 1000) as usize;
+        Self {
+            phrase: normalize_words(phrase),
+            window: Vec::with_capacity(samples(WINDOW)),
+            window_len: samples(WINDOW),
+            hop_len: samples(HOP),
+            silence_len: samples(silence),
+            no_speech_len: samples(NO_SPEECH_TIMEOUT),
+            since_check: 0,
+            sound_in_hop: false,
+            dictation: None,
+        }
+    }
+
+    pub fn push(&mut self, chunk: &[f32]) -> Step {
+        let loud = rms(chunk) >= SPEECH_RMS;
+
+        if let Some(dictation) = self.dictation.as_mut() {
+            if loud {
+                dictation.speech_seen = true;
+                dictation.quiet = 0;
+            } else {
+                dictation.quiet += chunk.len();
+            }
+            let limit = if dictation.speech_seen {
+                self.silence_len
+            } else {
+                self.no_speech_len
+            };
+            if dictation.quiet >= limit {
+                self.dictation = None;
+                return Step::Stop;
+            }
+            return Step::Wait;
+        }
+
+        self.window.extend_from_slice(chunk);
+        if self.window.len() > self.window_len {
+            let excess = self.window.len() - self.window_len;
+            self.window.drain(..excess);
+        }
+        self.sound_in_hop |= loud;
+        self.since_check += chunk.len();
+        if self.since_check < self.hop_len {
+            return Step::Wait;
+        }
+        self.since_check = 0;
+        if !std::mem::take(&mut self.sound_in_hop) {
+            return Step::Wait;
+        }
+        Step::Check(self.window.clone())
+    }
+
+    /// Feed back the transcript of a `Step::Check` window. Returns true if it
+    /// contained the wake word, which starts waiting for the dictation to end.
+    pub fn on_transcript(&mut self, text: &str) -> bool {
+        if !contains_phrase(text, &self.phrase) {
+            return false;
+        }
+        self.window.clear();
+        self.since_check = 0;
+        self.sound_in_hop = false;
+        self.dictation = Some(Dictation::default());
+        true
+    }
+}
+
+/// Background microphone listener that turns the wake word into
+/// `PushStart` and the following silence into `PushStop`.
+pub struct WakeWordListener {
+    recorder: Option<AudioRecorder>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl WakeWordListener {
+    pub async fn start(
+        config: &Config,
+    ) -> Result<(Self, tokio::sync::mpsc::Receiver<InputEvent>)> {
+        let phrase = config
+            .input
+            .wake_word
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No wake word configured"))?;
+
+        let mut transcription = config.transcription.clone();
+        transcription.model = config.input.wake_word_model.clone();
+        transcription.language_models.clear();
+        transcription.single_segment = true;
+        transcription.threads = Some(LISTENER_THREADS);
+        let mut service = TranscriptionService::new(&transcription)?;
+        service.load_model().await?;
+
+        let (sample_tx, sample_rx) = std::sync::mpsc::channel::<SampleChunk>();
+        let mut recorder = AudioRecorder::new(&config.audio, None)?.with_sample_sink(sample_tx);
+        recorder.start_monitor().await?;
+
+        let sample_rate = config.audio.sample_rate;
+        let mut detector = WakeWordDetector::new(
+            &phrase,
+            sample_rate,
+            Duration::from_millis(config.input.wake_word_silence_ms),
+        );
+        let (event_tx, event_rx) = tokio::sync::mpsc::channel(8);
+
+        let thread = std::thread::spawn(move || {
+            while let Ok(chunk) = sample_rx.recv() {
+                let event = match detector.push(&chunk.into_rate(sample_rate)) {
+                    Step::Wait => continue,
+                    Step::Check(window) => match service.transcribe_samples(&window, sample_rate) {
+                        Ok(text) if detector.on_transcript(&text) => {
+                            info!("Wake word detected");
+                            InputEvent::PushStart
+                        }
+                        Ok(text) => {
+                            debug!("Wake-word check heard: {:?}", text);
+                            continue;
+                        }
+                        Err(e) => {
+                            warn!("Wake-word check failed: {}", e);
+                            continue;
+                        }
+                    },
+                    Step::Stop => {
+                        info!("Silence after wake-word dictation");
+                        InputEvent::PushStop
+                    }
+                };
+                if event_tx.blocking_send(event).is_err() {
+                    break;
+                }
+            }
+            debug!("Wake-word listener stopped");
+        });
+
+        info!("Listening for wake word {:?}", phrase);
+        Ok((
+            Self {
+                recorder: Some(recorder),
+                thread: Some(thread),
+            },
+            event_rx,
+        ))
+    }
+
+    /// Close the microphone and wait for the listener thread to finish.
+    pub fn stop(&mut self) {
+        // Dropping the recorder drops the last sample sender, ending the thread
+        if let Some(mut recorder) = self.recorder.take() {
+            recorder.stop_monitor();
+        }
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for WakeWordListener {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn loud(ms: u64) -> Vec<f32> {
+        vec![0.2; (RATE as u64 * ms / 1000) as usize]
+    }
+
+    fn quiet(ms: u64) -> Vec<f32> {
+        vec![0.0; (RATE as u64 * ms / 1000) as usize]
+    }
+
+    #[test]
+    fn test_contains_phrase() {
+        let phrase = normalize_words("Hey Vox");
+        assert_eq!(phrase, vec!["hey", "vox"]);
+        assert!(contains_phrase(" Hey, vox!", &phrase));
+        assert!(contains_phrase("okay hey vox start", &phrase));
+        assert!(!contains_phrase("hey there vox", &phrase));
+        assert!(!contains_phrase("Thank you.", &phrase));
+        assert!(!contains_phrase("anything", &[]));
+    }
+
+    #[test]
+    fn test_silence_is_never_checked() {
+        let mut detector = WakeWordDetector::new("hey vox", RATE, Duration::from_millis(1500));
+        for _ in 0..20 {
+            assert_eq!(detector.push(&quiet(100)), Step::Wait);
+        }
+    }
+
+    #[test]
+    fn test_sound_is_checked_each_hop() {
+        let mut detector = WakeWordDetector::new("hey vox", RATE, Duration::from_millis(1500));
+        for _ in 0..4 {
+            assert_eq!(detector.push(&loud(100)), Step::Wait);
+        }
+        match detector.push(&loud(100)) {
+            Step::Check(window) => assert_eq!(window.len(), 8000),
+            other => panic!("expected a check, got {:?}", other),
+        }
+        for _ in 0..30 {
+            if let Step::Check(window) = detector.push(&loud(100)) {
+                assert!(window.len() <= 32000);
+            }
+        }
+        assert!(!detector.on_transcript("something else"));
+    }
+
+    #[test]
+    fn test_dictation_ends_after_silence() {
+        let mut detector = WakeWordDetector::new("hey vox", RATE, Duration::from_millis(1000));
+        assert!(detector.on_transcript("Hey Vox."));
+
+        assert_eq!(detector.push(&loud(500)), Step::Wait);
+        assert_eq!(detector.push(&quiet(600)), Step::Wait);
+        assert_eq!(detector.push(&loud(100)), Step::Wait);
+        assert_eq!(detector.push(&quiet(600)), Step::Wait);
+        assert_eq!(detector.push(&quiet(500)), Step::Stop);
+
+        // Back to listening with an empty window
+        assert_eq!(detector.push(&quiet(500)), Step::Wait);
+    }
+
+    #[test]
+    fn test_dictation_without_speech_times_out() {
+        let mut detector = WakeWordDetector::new("hey vox", RATE, Duration::from_millis(1000));
+        assert!(detector.on_transcript("hey vox"));
+        for _ in 0..4 {
+            assert_eq!(detector.push(&quiet(1000)), Step::Wait);
+        }
+        assert_eq!(detector.push(&quiet(1000)), Step::Stop);
+    }
+}